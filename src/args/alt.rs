@@ -0,0 +1,70 @@
+use super::*;
+
+#[derive(Subcommand)]
+pub enum AltSubcommands {
+    Close(AltCloseArgs),
+    Create(AltCreateArgs),
+    Deactivate(AltDeactivateArgs),
+    Extend(AltExtendArgs),
+    Show(AltShowArgs),
+}
+
+#[derive(ClapArgs)]
+pub struct AltCreateArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Addresses to add to the new table.
+    pub addresses: Vec<Pubkey>,
+
+    /// JSON file with a list of addresses to add to the new table.
+    #[arg(short, long)]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(ClapArgs)]
+pub struct AltExtendArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Lookup table address.
+    pub lookup_table: Pubkey,
+
+    /// Addresses to add to the table.
+    pub addresses: Vec<Pubkey>,
+
+    /// JSON file with a list of addresses to add to the table.
+    #[arg(short, long)]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(ClapArgs)]
+pub struct AltDeactivateArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Lookup table address.
+    pub lookup_table: Pubkey,
+}
+
+#[derive(ClapArgs)]
+pub struct AltCloseArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Lookup table address.
+    pub lookup_table: Pubkey,
+
+    /// Recipient of the reclaimed rent, defaults to the authority.
+    #[arg(long)]
+    pub recipient: Option<Pubkey>,
+}
+
+#[derive(ClapArgs)]
+pub struct AltShowArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Lookup table address.
+    pub lookup_table: Pubkey,
+}
@@ -0,0 +1,69 @@
+use super::*;
+
+#[derive(Subcommand)]
+pub enum EscrowSubcommands {
+    Close(EscrowCloseArgs),
+    Create(EscrowCreateArgs),
+    Deposit(EscrowSolArgs),
+    List(EscrowListArgs),
+    Show(EscrowShowArgs),
+    Withdraw(EscrowSolArgs),
+}
+
+#[derive(ClapArgs)]
+pub struct EscrowCreateArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Escrow number, the lowest free one if not set.
+    #[arg(long)]
+    pub nr: Option<u16>,
+
+    /// Name of the escrow, at most 32 bytes.
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+#[derive(ClapArgs)]
+pub struct EscrowSolArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Shared escrow public key.
+    pub escrow: Pubkey,
+
+    /// Amount in SOL, e.g. 1.5.
+    pub amount: String,
+
+    /// Read the amount as lamports instead of SOL.
+    #[arg(long)]
+    pub lamports: bool,
+}
+
+#[derive(ClapArgs)]
+pub struct EscrowCloseArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Shared escrow public key.
+    pub escrow: Pubkey,
+}
+
+#[derive(ClapArgs)]
+pub struct EscrowShowArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Shared escrow public key.
+    pub escrow: Pubkey,
+}
+
+#[derive(ClapArgs)]
+pub struct EscrowListArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Owner of the escrows, defaults to the configured keypair.
+    #[arg(long)]
+    pub owner: Option<Pubkey>,
+}
@@ -17,4 +17,8 @@ pub struct FeesBalancesArgs {
 pub struct FeesFundArgs {
     #[clap(flatten)]
     pub write_options: WriteOptions,
+
+    /// Address lookup tables to compile v0 transactions with.
+    #[arg(long = "lookup-table")]
+    pub lookup_tables: Vec<Pubkey>,
 }
@@ -0,0 +1,325 @@
+use std::str::FromStr;
+
+use tensor_marketplace::types::Target;
+
+use super::*;
+
+use crate::commands::CurrencyFilter;
+
+#[derive(Subcommand)]
+pub enum MarketSubcommands {
+    Bid(MarketBidArgs),
+    Bids(MarketBidsArgs),
+    Buy(MarketBuyArgs),
+    CancelBid(MarketCancelBidArgs),
+    Cleanup(MarketCleanupArgs),
+    Delist(MarketDelistArgs),
+    EditBid(MarketEditBidArgs),
+    EditListing(MarketEditListingArgs),
+    List(MarketListArgs),
+    Listings(MarketListingsArgs),
+    TakeBid(MarketTakeBidArgs),
+}
+
+// Listing terms shared by list and edit-listing
+#[derive(ClapArgs)]
+pub struct ListingTermsArgs {
+    /// Currency the price is in: sol or a mint. New listings default to SOL, edits keep the
+    /// current currency.
+    #[arg(long, value_parser = parse_currency_filter)]
+    pub currency: Option<CurrencyFilter>,
+
+    /// Time until the listing expires, e.g. 30d or 12h.
+    #[arg(long)]
+    pub expire: Option<String>,
+
+    /// Only this wallet can buy the listing.
+    #[arg(long)]
+    pub private_taker: Option<Pubkey>,
+
+    /// Broker receiving the maker broker fees.
+    #[arg(long)]
+    pub maker_broker: Option<Pubkey>,
+}
+
+#[derive(ClapArgs)]
+pub struct MarketListArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Mint, Core asset or compressed asset id to list.
+    pub mint: Pubkey,
+
+    /// Price in SOL, e.g. 1.5 or 1500lamports, or in whole units of the currency.
+    pub price: String,
+
+    #[clap(flatten)]
+    pub terms: ListingTermsArgs,
+
+    /// Path to the keypair of a cosigner that has to approve purchases.
+    #[arg(long)]
+    pub cosigner: Option<PathBuf>,
+}
+
+#[derive(ClapArgs)]
+pub struct MarketEditListingArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Mint, Core asset or compressed asset id of the listing.
+    pub mint: Pubkey,
+
+    /// New price, in the new currency if --currency is set.
+    #[arg(long)]
+    pub price: Option<String>,
+
+    #[clap(flatten)]
+    pub terms: ListingTermsArgs,
+
+    /// Let anyone buy the listing again.
+    #[arg(long, conflicts_with = "private_taker")]
+    pub clear_private_taker: bool,
+
+    /// Remove the maker broker.
+    #[arg(long, conflicts_with = "maker_broker")]
+    pub clear_maker_broker: bool,
+}
+
+#[derive(ClapArgs)]
+pub struct MarketDelistArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Mint, Core asset or compressed asset id of the listing.
+    pub mint: Pubkey,
+}
+
+#[derive(ClapArgs)]
+pub struct MarketBuyArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Mint, Core asset or compressed asset id of the listing.
+    pub mint: Pubkey,
+
+    /// Most to pay, defaults to the listed price.
+    #[arg(long)]
+    pub max_price: Option<String>,
+
+    /// Percentage of optional royalties to pay, 0 to 100.
+    #[arg(long)]
+    pub royalty_pct: Option<u16>,
+
+    /// Broker receiving the taker broker fees.
+    #[arg(long)]
+    pub taker_broker: Option<Pubkey>,
+
+    /// Path to the keypair of the cosigner the listing requires.
+    #[arg(long)]
+    pub cosigner: Option<PathBuf>,
+}
+
+// Bid terms shared by bid and edit-bid
+#[derive(ClapArgs)]
+pub struct BidTermsArgs {
+    /// Number of assets to buy, the total including filled ones when editing.
+    #[arg(long)]
+    pub quantity: Option<u32>,
+
+    /// Time until the bid expires, e.g. 30d or 12h.
+    #[arg(long)]
+    pub expire: Option<String>,
+
+    /// Only this wallet can sell into the bid.
+    #[arg(long)]
+    pub private_taker: Option<Pubkey>,
+
+    /// Broker receiving the maker broker fees.
+    #[arg(long)]
+    pub maker_broker: Option<Pubkey>,
+
+    /// Shared escrow the bid draws SOL from instead of holding its own.
+    #[arg(long)]
+    pub margin: Option<Pubkey>,
+}
+
+#[derive(ClapArgs)]
+pub struct MarketBidArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Price per asset in SOL, e.g. 1.5 or 1500lamports.
+    pub price: String,
+
+    /// Bid on this mint, Core asset or compressed asset id.
+    #[arg(
+        long,
+        required_unless_present = "whitelist",
+        conflicts_with = "whitelist"
+    )]
+    pub mint: Option<Pubkey>,
+
+    /// Bid on any asset of this whitelist.
+    #[arg(long)]
+    pub whitelist: Option<Pubkey>,
+
+    /// Narrow a whitelist bid to a trait, checked by the cosigner.
+    #[arg(long = "trait", requires = "whitelist")]
+    pub trait_id: Option<Pubkey>,
+
+    #[clap(flatten)]
+    pub terms: BidTermsArgs,
+
+    /// Seed of the bid state, the mint for mint bids and a new one otherwise.
+    #[arg(long)]
+    pub bid_id: Option<Pubkey>,
+
+    /// Path to the keypair of a cosigner that has to approve sales into the bid.
+    #[arg(long)]
+    pub cosigner: Option<PathBuf>,
+}
+
+#[derive(ClapArgs)]
+pub struct MarketEditBidArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Bid state address.
+    pub bid_state: Pubkey,
+
+    /// New price per asset in SOL.
+    #[arg(long)]
+    pub price: Option<String>,
+
+    #[clap(flatten)]
+    pub terms: BidTermsArgs,
+
+    /// Path to the keypair of the cosigner the bid requires.
+    #[arg(long)]
+    pub cosigner: Option<PathBuf>,
+}
+
+#[derive(ClapArgs)]
+pub struct MarketCancelBidArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Bid state address.
+    pub bid_state: Pubkey,
+}
+
+#[derive(ClapArgs)]
+pub struct MarketCleanupArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Close expired orders with this cosigner, whoever owns them, instead of the signer's own.
+    #[arg(long)]
+    pub cosigned_by: Option<Pubkey>,
+
+    /// Close the orders without asking for confirmation.
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Only show the expired orders and what closing them returns.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(ClapArgs)]
+pub struct MarketTakeBidArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Bid state address.
+    pub bid_state: Pubkey,
+
+    /// Mint, Core asset or compressed asset id to sell into the bid.
+    pub mint: Pubkey,
+
+    /// Least to receive, defaults to the bid price.
+    #[arg(long)]
+    pub min_price: Option<String>,
+
+    /// Percentage of optional royalties to pay, 0 to 100.
+    #[arg(long)]
+    pub royalty_pct: Option<u16>,
+
+    /// Broker receiving the taker broker fees.
+    #[arg(long)]
+    pub taker_broker: Option<Pubkey>,
+
+    /// Path to the keypair of the cosigner the bid requires.
+    #[arg(long)]
+    pub cosigner: Option<PathBuf>,
+}
+
+#[derive(ClapArgs)]
+pub struct MarketListingsArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Only listings by this owner.
+    #[arg(long)]
+    pub owner: Option<Pubkey>,
+
+    /// Only the listing of this mint, Core asset or compressed asset id.
+    #[arg(long)]
+    pub mint: Option<Pubkey>,
+
+    /// Only listings in this currency: sol or a mint.
+    #[arg(long, value_parser = parse_currency_filter)]
+    pub currency: Option<CurrencyFilter>,
+
+    /// Only listings that haven't expired.
+    #[arg(long)]
+    pub active: bool,
+}
+
+#[derive(ClapArgs)]
+pub struct MarketBidsArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Only bids by this owner.
+    #[arg(long)]
+    pub owner: Option<Pubkey>,
+
+    /// Only bids on this kind of target: mint or whitelist.
+    #[arg(long, value_parser = parse_bid_target)]
+    pub target: Option<Target>,
+
+    /// Only bids on this mint or whitelist.
+    #[arg(long)]
+    pub target_id: Option<Pubkey>,
+
+    /// Only bids in this currency: sol or a mint.
+    #[arg(long, value_parser = parse_currency_filter)]
+    pub currency: Option<CurrencyFilter>,
+
+    /// Only bids drawing from this shared escrow.
+    #[arg(long)]
+    pub margin: Option<Pubkey>,
+
+    /// Only bids that haven't expired.
+    #[arg(long)]
+    pub active: bool,
+}
+
+pub fn parse_currency_filter(s: &str) -> Result<CurrencyFilter, String> {
+    if s.eq_ignore_ascii_case("sol") {
+        return Ok(CurrencyFilter::Sol);
+    }
+
+    Pubkey::from_str(s)
+        .map(CurrencyFilter::Spl)
+        .map_err(|_| format!("Invalid currency: {}", s))
+}
+
+pub fn parse_bid_target(s: &str) -> Result<Target, String> {
+    Ok(match s.to_lowercase().as_str() {
+        "mint" | "asset" => Target::AssetId,
+        "whitelist" => Target::Whitelist,
+        _ => return Err(format!("Invalid bid target: {}", s)),
+    })
+}
@@ -1,15 +1,25 @@
 use std::path::PathBuf;
 
+mod alt;
 mod derive;
 mod eigen;
+mod escrow;
 mod fees;
+mod market;
+mod nonce;
 mod pool;
+mod price_lock;
 mod whitelist;
 
+pub use alt::*;
 pub use derive::*;
 pub use eigen::*;
+pub use escrow::*;
 pub use fees::*;
+pub use market::*;
+pub use nonce::*;
 pub use pool::*;
+pub use price_lock::*;
 pub use whitelist::*;
 
 use clap::{Args as ClapArgs, Parser, Subcommand};
@@ -22,10 +32,17 @@ use crate::commands::Id;
 pub struct Args {
     #[clap(subcommand)]
     pub command: Commands,
+
+    /// Profile in ~/.config/eigen/profiles.yml with the RPC settings to use.
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
+    #[clap(subcommand)]
+    Alt(AltSubcommands),
+
     Decode(DecodeArgs),
 
     #[clap(subcommand)]
@@ -35,14 +52,26 @@ pub enum Commands {
 
     Error(ErrorArgs),
 
+    #[clap(subcommand)]
+    Escrow(EscrowSubcommands),
+
     #[clap(subcommand)]
     Fees(FeesSubcommands),
 
     Ids(IdArgs),
 
+    #[clap(subcommand)]
+    Market(MarketSubcommands),
+
+    #[clap(subcommand)]
+    Nonce(NonceSubcommands),
+
     #[clap(subcommand)]
     Pool(PoolSubcommands),
 
+    #[clap(subcommand)]
+    PriceLock(PriceLockSubcommands),
+
     #[clap(subcommand, name = "self")]
     Eigen(EigenSubcommands),
 
@@ -68,6 +97,18 @@ pub struct WriteOptions {
     /// RPC URL for the Solana cluster.
     #[arg(short, long)]
     pub rpc_url: Option<String>,
+
+    /// Durable nonce account to use instead of a recent blockhash.
+    #[arg(long)]
+    pub nonce: Option<Pubkey>,
+
+    /// Path to the nonce authority keypair file, defaults to the fee payer.
+    #[arg(long, requires = "nonce")]
+    pub nonce_authority: Option<PathBuf>,
+
+    /// Write the signed transaction to a file instead of sending it, to submit later.
+    #[arg(long, requires = "nonce")]
+    pub sign_only: Option<PathBuf>,
 }
 
 #[derive(ClapArgs)]
@@ -0,0 +1,54 @@
+use super::*;
+
+#[derive(Subcommand)]
+pub enum NonceSubcommands {
+    Advance(NonceAdvanceArgs),
+    Create(NonceCreateArgs),
+    Show(NonceShowArgs),
+    Submit(NonceSubmitArgs),
+}
+
+#[derive(ClapArgs)]
+pub struct NonceCreateArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Nonce authority, defaults to the fee payer.
+    #[arg(short, long)]
+    pub authority: Option<Pubkey>,
+
+    /// Path to the keypair for the new nonce account, a new one is generated if not provided.
+    #[arg(long)]
+    pub account_keypair: Option<PathBuf>,
+}
+
+#[derive(ClapArgs)]
+pub struct NonceAdvanceArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Nonce account address.
+    pub nonce_account: Pubkey,
+
+    /// Path to the nonce authority keypair file, defaults to the fee payer.
+    #[arg(short, long)]
+    pub authority: Option<PathBuf>,
+}
+
+#[derive(ClapArgs)]
+pub struct NonceShowArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Nonce account address.
+    pub nonce_account: Pubkey,
+}
+
+#[derive(ClapArgs)]
+pub struct NonceSubmitArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// File of signed transactions written with --sign-only.
+    pub path: PathBuf,
+}
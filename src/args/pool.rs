@@ -1,9 +1,48 @@
+use std::str::FromStr;
+
+use tensor_amm::types::{CurveType, PoolType};
+
 use super::*;
 
 #[derive(Subcommand)]
 pub enum PoolSubcommands {
+    Apply(PoolApplyArgs),
+    Close(PoolCloseArgs),
     Create(PoolCreateArgs),
+    DepositNft(PoolNftArgs),
+    DepositSol(PoolSolArgs),
     Edit(PoolEditArgs),
+    List(PoolListArgs),
+    Quote(PoolQuoteArgs),
+    Report(PoolReportArgs),
+    WithdrawNft(PoolNftArgs),
+    WithdrawSol(PoolSolArgs),
+}
+
+#[derive(ClapArgs)]
+pub struct PoolApplyArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Path to the YAML manifest of desired pools.
+    pub manifest_path: PathBuf,
+
+    /// Apply the plan without asking for confirmation.
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Only show the plan.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(ClapArgs)]
+pub struct PoolCloseArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Pool public key.
+    pub pool: Pubkey,
 }
 
 #[derive(ClapArgs)]
@@ -14,8 +53,61 @@ pub struct PoolCreateArgs {
     /// Whitelist public key.
     pub whitelist: Pubkey,
 
-    /// Path to the pool config file.
-    pub pool_config_path: PathBuf,
+    /// Path to the pool config file, instead of the config flags.
+    #[arg(required_unless_present = "pool_type")]
+    pub pool_config_path: Option<PathBuf>,
+
+    /// Pool type: trade, token or nft.
+    #[arg(long = "type", value_parser = parse_pool_type, conflicts_with = "pool_config_path")]
+    pub pool_type: Option<PoolType>,
+
+    /// Bonding curve: linear or exponential.
+    #[arg(long, value_parser = parse_curve_type, conflicts_with = "pool_config_path")]
+    pub curve: Option<CurveType>,
+
+    /// Starting price, e.g. 1.5SOL or 1500000lamports.
+    #[arg(long, conflicts_with = "pool_config_path")]
+    pub start: Option<String>,
+
+    /// Price change per trade: a price for linear curves, a percentage or bps for exponential ones.
+    #[arg(long, conflicts_with = "pool_config_path")]
+    pub delta: Option<String>,
+
+    /// Market making fee of trade pools, in basis points.
+    #[arg(long, conflicts_with = "pool_config_path")]
+    pub fee_bps: Option<u16>,
+
+    /// Compound market making fees into the pool instead of paying them to the owner.
+    #[arg(long, conflicts_with = "pool_config_path")]
+    pub compound_fees: bool,
+
+    /// Time until the pool expires, e.g. 30d or 12h.
+    #[arg(long, conflicts_with = "pool_config_path")]
+    pub expire: Option<String>,
+
+    /// Currency mint, SOL if not set.
+    #[arg(long, conflicts_with = "pool_config_path")]
+    pub currency: Option<Pubkey>,
+
+    /// Cosigner that has to approve trades.
+    #[arg(long, conflicts_with = "pool_config_path")]
+    pub cosigner: Option<Pubkey>,
+
+    /// Broker receiving the maker broker fees.
+    #[arg(long, conflicts_with = "pool_config_path")]
+    pub maker_broker: Option<Pubkey>,
+
+    /// Maximum number of NFTs the pool buys more than it sells.
+    #[arg(long, conflicts_with = "pool_config_path")]
+    pub max_taker_sell_count: Option<u32>,
+
+    /// Pool id as 32 bytes of hex, random if not set.
+    #[arg(long, conflicts_with = "pool_config_path")]
+    pub pool_id: Option<String>,
+
+    /// Shared escrow to trade SOL from. Pools can't be attached or detached after creation.
+    #[arg(long)]
+    pub shared_escrow: Option<Pubkey>,
 }
 
 #[derive(ClapArgs)]
@@ -26,6 +118,215 @@ pub struct PoolEditArgs {
     /// Pool public key.
     pub pool: Pubkey,
 
-    /// Path to the edit pool config file.
-    pub edit_pool_config_path: PathBuf,
+    /// Path to the edit pool config file, instead of the edit flags.
+    pub edit_pool_config_path: Option<PathBuf>,
+
+    /// New bonding curve: linear or exponential.
+    #[arg(long, value_parser = parse_curve_type, conflicts_with = "edit_pool_config_path")]
+    pub curve: Option<CurveType>,
+
+    /// New starting price, e.g. 1.5SOL or 1500000lamports.
+    #[arg(long, conflicts_with = "edit_pool_config_path")]
+    pub start: Option<String>,
+
+    /// New delta: a price for linear curves, a percentage or bps for exponential ones.
+    #[arg(long, conflicts_with = "edit_pool_config_path")]
+    pub delta: Option<String>,
+
+    /// New market making fee, in basis points.
+    #[arg(long, conflicts_with = "edit_pool_config_path")]
+    pub fee_bps: Option<u16>,
+
+    /// Remove the market making fee.
+    #[arg(long, conflicts_with_all = ["edit_pool_config_path", "fee_bps"])]
+    pub no_fee: bool,
+
+    /// Whether to compound market making fees into the pool.
+    #[arg(long, conflicts_with = "edit_pool_config_path")]
+    pub compound_fees: Option<bool>,
+
+    /// New cosigner.
+    #[arg(long, conflicts_with = "edit_pool_config_path")]
+    pub cosigner: Option<Pubkey>,
+
+    /// Time from now until the pool expires, e.g. 30d or 12h.
+    #[arg(long, conflicts_with = "edit_pool_config_path")]
+    pub expire: Option<String>,
+
+    /// New maximum number of NFTs the pool buys more than it sells.
+    #[arg(long, conflicts_with = "edit_pool_config_path")]
+    pub max_taker_sell_count: Option<u32>,
+
+    /// Reset the price back to the starting price.
+    #[arg(long, conflicts_with = "edit_pool_config_path")]
+    pub reset_price_offset: bool,
+
+    /// Apply the edit without asking for confirmation.
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(ClapArgs)]
+pub struct PoolSolArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Pool public key.
+    pub pool: Pubkey,
+
+    /// Amount in SOL, e.g. 1.5.
+    pub amount: String,
+
+    /// Read the amount as lamports instead of SOL.
+    #[arg(long)]
+    pub lamports: bool,
+}
+
+#[derive(ClapArgs)]
+pub struct PoolNftArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Pool public key.
+    pub pool: Pubkey,
+
+    /// NFT mints, or Core asset addresses.
+    pub mints: Vec<Pubkey>,
+
+    /// JSON file with a list of mints, for bulk transfers.
+    #[arg(short, long)]
+    pub file: Option<PathBuf>,
+
+    /// Address lookup tables to compile v0 transactions with.
+    #[arg(long = "lookup-table")]
+    pub lookup_tables: Vec<Pubkey>,
+}
+
+#[derive(ClapArgs)]
+pub struct PoolListArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Only pools owned by this address.
+    #[arg(long)]
+    pub owner: Option<Pubkey>,
+
+    /// Only pools using this whitelist.
+    #[arg(long)]
+    pub whitelist: Option<Pubkey>,
+
+    /// Only pools with this cosigner.
+    #[arg(long)]
+    pub cosigner: Option<Pubkey>,
+
+    /// Only pools attached to this shared escrow.
+    #[arg(long)]
+    pub shared_escrow: Option<Pubkey>,
+
+    /// Only pools of this type: trade, token or nft.
+    #[arg(long = "type", value_parser = parse_pool_type)]
+    pub pool_type: Option<PoolType>,
+
+    /// Only pools with this curve: linear or exponential.
+    #[arg(long, value_parser = parse_curve_type)]
+    pub curve: Option<CurveType>,
+
+    /// Only pools that haven't expired.
+    #[arg(long)]
+    pub active: bool,
+
+    /// Sort by address, price, amount, nfts, expiry or created.
+    #[arg(long, default_value = "address")]
+    pub sort: PoolSort,
+
+    /// Sort in descending order.
+    #[arg(long)]
+    pub desc: bool,
+}
+
+#[derive(ClapArgs)]
+pub struct PoolQuoteArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Pool public key.
+    #[arg(required_unless_present = "config", conflicts_with = "config")]
+    pub pool: Option<Pubkey>,
+
+    /// Quote a pool config file instead of a live pool.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Number of buys and sells to quote.
+    #[arg(short = 'n', long, default_value = "5")]
+    pub count: u32,
+
+    /// Royalties of the collection, in basis points.
+    #[arg(long, default_value = "0")]
+    pub royalty_bps: u16,
+
+    /// Percentage of the royalties paid, 100 for pNFTs.
+    #[arg(long, default_value = "100")]
+    pub royalty_pct: u8,
+}
+
+#[derive(ClapArgs)]
+pub struct PoolReportArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Pool public key.
+    #[arg(required_unless_present = "owner", conflicts_with = "owner")]
+    pub pool: Option<Pubkey>,
+
+    /// Report on every pool of this owner.
+    #[arg(long)]
+    pub owner: Option<Pubkey>,
+
+    /// Also export the report as CSV to this path.
+    #[arg(long)]
+    pub csv: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolSort {
+    Address,
+    Price,
+    Amount,
+    Nfts,
+    Expiry,
+    Created,
+}
+
+impl FromStr for PoolSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "address" => Self::Address,
+            "price" => Self::Price,
+            "amount" | "sol" => Self::Amount,
+            "nfts" | "nfts_held" => Self::Nfts,
+            "expiry" => Self::Expiry,
+            "created" | "created_at" => Self::Created,
+            _ => return Err(format!("Invalid sort key: {}", s)),
+        })
+    }
+}
+
+pub fn parse_pool_type(s: &str) -> Result<PoolType, String> {
+    Ok(match s.to_lowercase().as_str() {
+        "trade" => PoolType::Trade,
+        "token" => PoolType::Token,
+        "nft" => PoolType::NFT,
+        _ => return Err(format!("Invalid pool type: {}", s)),
+    })
+}
+
+pub fn parse_curve_type(s: &str) -> Result<CurveType, String> {
+    Ok(match s.to_lowercase().as_str() {
+        "linear" | "lin" => CurveType::Linear,
+        "exponential" | "exp" => CurveType::Exponential,
+        _ => return Err(format!("Invalid curve type: {}", s)),
+    })
 }
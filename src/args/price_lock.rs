@@ -0,0 +1,112 @@
+use tensor_price_lock::types::OrderType;
+
+use super::*;
+
+#[derive(Subcommand)]
+pub enum PriceLockSubcommands {
+    Close(PriceLockCloseArgs),
+    Create(PriceLockCreateArgs),
+    Edit(PriceLockEditArgs),
+    Orders(PriceLockOrdersArgs),
+    Show(PriceLockShowArgs),
+}
+
+#[derive(ClapArgs)]
+pub struct OrderTermsArgs {
+    /// Price the taker locks in, in SOL, e.g. 1.5 or 1500lamports.
+    #[arg(long)]
+    pub price: Option<String>,
+
+    /// Yearly rate of the premium the taker pays to lock the price, in basis points.
+    #[arg(long)]
+    pub apr_bps: Option<u32>,
+
+    /// How long a taker holds the price once locked, e.g. 7d or 12h.
+    #[arg(long)]
+    pub duration: Option<String>,
+
+    /// Time until the order expires, e.g. 30d or 12h.
+    #[arg(long)]
+    pub expire: Option<String>,
+
+    /// Shared escrow to draw SOL from instead of the order vault.
+    #[arg(long)]
+    pub margin: Option<Pubkey>,
+
+    /// Broker receiving the maker broker fees.
+    #[arg(long)]
+    pub maker_broker: Option<Pubkey>,
+}
+
+#[derive(ClapArgs)]
+pub struct PriceLockCreateArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Order type: token to buy NFTs at the price, nft to sell deposited NFTs at it.
+    #[arg(long = "type", value_parser = parse_order_type)]
+    pub order_type: OrderType,
+
+    /// Whitelist of the NFTs the order is for.
+    #[arg(long)]
+    pub whitelist: Pubkey,
+
+    #[clap(flatten)]
+    pub terms: OrderTermsArgs,
+}
+
+#[derive(ClapArgs)]
+pub struct PriceLockEditArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Order state address.
+    pub order: Pubkey,
+
+    #[clap(flatten)]
+    pub terms: OrderTermsArgs,
+}
+
+#[derive(ClapArgs)]
+pub struct PriceLockCloseArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Order state address.
+    pub order: Pubkey,
+}
+
+#[derive(ClapArgs)]
+pub struct PriceLockOrdersArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Only orders by this maker.
+    #[arg(long)]
+    pub maker: Option<Pubkey>,
+
+    /// Only orders locked by this taker.
+    #[arg(long)]
+    pub taker: Option<Pubkey>,
+
+    /// Only orders on this whitelist.
+    #[arg(long)]
+    pub whitelist: Option<Pubkey>,
+}
+
+#[derive(ClapArgs)]
+pub struct PriceLockShowArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Order state address.
+    pub order: Pubkey,
+}
+
+pub fn parse_order_type(s: &str) -> Result<OrderType, String> {
+    Ok(match s.to_lowercase().as_str() {
+        "token" => OrderType::Token,
+        "nft" => OrderType::NFT,
+        _ => return Err(format!("Invalid order type: {}", s)),
+    })
+}
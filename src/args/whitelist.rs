@@ -4,6 +4,11 @@ use super::*;
 pub enum WhitelistSubcommands {
     Compare(WhitelistCompareArgs),
     Create(WhitelistCreateArgs),
+    #[clap(subcommand)]
+    Merkle(WhitelistMerkleSubcommands),
+    Migrate(WhitelistMigrateArgs),
+    #[clap(subcommand)]
+    MintProof(WhitelistMintProofSubcommands),
     Update(WhitelistUpdateArgs),
 }
 
@@ -20,9 +25,22 @@ pub struct WhitelistCompareArgs {
     #[arg(short, long)]
     pub namespace: Option<Pubkey>,
 
+    /// Update authority the v2s should have, not checked when not given.
+    #[arg(long)]
+    pub expected_authority: Option<Pubkey>,
+
     /// Verbose output.
     #[arg(short, long)]
     pub verbose: bool,
+
+    /// Directory to write the comparison files and a CSV and HTML report to.
+    #[arg(long)]
+    pub report_dir: Option<PathBuf>,
+
+    /// Summary file of an earlier report to compare against, defaults to the one in the report
+    /// directory.
+    #[arg(long, requires = "report_dir")]
+    pub previous: Option<PathBuf>,
 }
 
 #[derive(ClapArgs)]
@@ -37,6 +55,122 @@ pub struct WhitelistCreateArgs {
     pub namespace_path: Option<PathBuf>,
 }
 
+#[derive(Subcommand)]
+pub enum WhitelistMerkleSubcommands {
+    Build(WhitelistMerkleBuildArgs),
+    Proof(WhitelistMerkleProofArgs),
+}
+
+#[derive(ClapArgs)]
+pub struct WhitelistMerkleBuildArgs {
+    /// JSON file with the list of mints.
+    pub mints_path: PathBuf,
+
+    /// Path to write the root and proofs to.
+    #[arg(short, long, default_value = "proofs.json")]
+    pub out: PathBuf,
+
+    /// Sort the leaves so the root doesn't depend on the order of the mints.
+    #[arg(long)]
+    pub sort_leaves: bool,
+}
+
+#[derive(ClapArgs)]
+pub struct WhitelistMerkleProofArgs {
+    /// Mint to print the proof of.
+    pub mint: Pubkey,
+
+    /// Proofs file written by merkle build.
+    #[arg(short, long, default_value = "proofs.json")]
+    pub proofs: PathBuf,
+}
+
+#[derive(ClapArgs)]
+pub struct WhitelistMigrateArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Namespace keypair to create the v2s under, defaults to the signer.
+    #[arg(short, long)]
+    pub namespace: Option<PathBuf>,
+
+    /// Missing v2s file written by compare.
+    #[arg(long)]
+    pub missing: Option<PathBuf>,
+
+    /// Mismatches file written by compare.
+    #[arg(long)]
+    pub mismatches: Option<PathBuf>,
+
+    /// List file path, to limit the comparison when no compare files are given.
+    #[arg(short, long, conflicts_with_all = ["missing", "mismatches"])]
+    pub list: Option<PathBuf>,
+
+    /// Only show the plan.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Migrate without asking for confirmation.
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Subcommand)]
+pub enum WhitelistMintProofSubcommands {
+    Init(WhitelistMintProofInitArgs),
+    Show(WhitelistMintProofShowArgs),
+}
+
+#[derive(ClapArgs)]
+pub struct WhitelistMintProofInitArgs {
+    #[clap(flatten)]
+    pub write_options: WriteOptions,
+
+    /// Whitelist v2 address.
+    pub whitelist: Pubkey,
+
+    /// Mint to create the proof of.
+    #[arg(required_unless_present = "all")]
+    pub mint: Option<Pubkey>,
+
+    /// Create the proof of every mint in the merkle tree.
+    #[arg(long, conflicts_with = "mint")]
+    pub all: bool,
+
+    /// Proofs file written by merkle build.
+    #[arg(short, long, default_value = "proofs.json")]
+    pub proofs: PathBuf,
+
+    /// JSON file with the list of mints, to recompute the proofs from instead of reading them.
+    #[arg(short, long, conflicts_with = "proofs")]
+    pub mints: Option<PathBuf>,
+
+    /// Sort the leaves of the recomputed tree, for a whitelist built with merkle build
+    /// --sort-leaves.
+    #[arg(long, requires = "mints")]
+    pub sort_leaves: bool,
+
+    /// Only show the plan.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Send without asking for confirmation.
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(ClapArgs)]
+pub struct WhitelistMintProofShowArgs {
+    #[clap(flatten)]
+    pub read_options: ReadOptions,
+
+    /// Whitelist v2 address.
+    pub whitelist: Pubkey,
+
+    /// Mint to show the proof of.
+    pub mint: Pubkey,
+}
+
 #[derive(ClapArgs)]
 pub struct WhitelistUpdateArgs {
     #[clap(flatten)]
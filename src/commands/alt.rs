@@ -0,0 +1,284 @@
+use std::{fs::File, str::FromStr};
+
+use solana_sdk::{
+    address_lookup_table::{instruction as alt_instruction, state::AddressLookupTable},
+    clock::Slot,
+    instruction::Instruction,
+    signature::Signature,
+    signer::Signer,
+};
+
+use crate::transactions::send_instructions;
+
+use super::*;
+
+/// Number of addresses added per extend instruction, the most that fits in a single transaction.
+pub const MAX_ADDRESSES_PER_EXTEND: usize = 30;
+
+pub struct AltCreateParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub addresses: Vec<Pubkey>,
+    pub file: Option<PathBuf>,
+}
+
+pub struct AltExtendParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub lookup_table: Pubkey,
+    pub addresses: Vec<Pubkey>,
+    pub file: Option<PathBuf>,
+}
+
+pub struct AltDeactivateParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub lookup_table: Pubkey,
+}
+
+pub struct AltCloseParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub lookup_table: Pubkey,
+    pub recipient: Option<Pubkey>,
+}
+
+pub struct AltShowParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub lookup_table: Pubkey,
+}
+
+/// Decoded address lookup table account.
+#[derive(Debug, Clone)]
+pub struct LookupTable {
+    pub address: Pubkey,
+    pub authority: Option<Pubkey>,
+    pub deactivation_slot: Slot,
+    pub last_extended_slot: Slot,
+    pub addresses: Vec<Pubkey>,
+}
+
+impl LookupTable {
+    pub fn is_deactivated(&self) -> bool {
+        self.deactivation_slot != Slot::MAX
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookupTableAction {
+    Created,
+    Extended,
+    Deactivated,
+    Closed,
+}
+
+/// Result of a lookup table write command.
+#[derive(Debug, Clone)]
+pub struct LookupTableChange {
+    pub action: LookupTableAction,
+    pub address: Pubkey,
+    /// Addresses added to the table, empty when none were needed.
+    pub added: Vec<Pubkey>,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn create_lookup_table(args: AltCreateParams) -> Result<LookupTableChange> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let authority = config.keypair.pubkey();
+    let addresses = collect_addresses(args.addresses, args.file)?;
+
+    let (create_ix, lookup_table) =
+        alt_instruction::create_lookup_table(authority, authority, config.recent_slot);
+
+    let mut instructions = vec![create_ix];
+    instructions.extend(extend_instructions(lookup_table, authority, &addresses));
+
+    let signatures = send_instructions(&config, instructions, &[], &[])?;
+
+    Ok(LookupTableChange {
+        action: LookupTableAction::Created,
+        address: lookup_table,
+        added: addresses,
+        signatures,
+    })
+}
+
+pub fn extend_lookup_table(args: AltExtendParams) -> Result<LookupTableChange> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let authority = config.keypair.pubkey();
+    let addresses = collect_addresses(args.addresses, args.file)?;
+
+    if addresses.is_empty() {
+        return Err(anyhow!("No addresses provided"));
+    }
+
+    // Skip addresses the table already contains so re-running a partially applied extend is safe.
+    let table = fetch_lookup_table(&config, &args.lookup_table)?;
+    let new_addresses: Vec<Pubkey> = addresses
+        .into_iter()
+        .filter(|address| !table.addresses.contains(address))
+        .collect();
+
+    let signatures = if new_addresses.is_empty() {
+        vec![]
+    } else {
+        send_instructions(
+            &config,
+            extend_instructions(args.lookup_table, authority, &new_addresses),
+            &[],
+            &[],
+        )?
+    };
+
+    Ok(LookupTableChange {
+        action: LookupTableAction::Extended,
+        address: args.lookup_table,
+        added: new_addresses,
+        signatures,
+    })
+}
+
+pub fn deactivate_lookup_table(args: AltDeactivateParams) -> Result<LookupTableChange> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let ix = alt_instruction::deactivate_lookup_table(args.lookup_table, config.keypair.pubkey());
+
+    let signatures = send_instructions(&config, vec![ix], &[], &[])?;
+
+    Ok(LookupTableChange {
+        action: LookupTableAction::Deactivated,
+        address: args.lookup_table,
+        added: vec![],
+        signatures,
+    })
+}
+
+pub fn close_lookup_table(args: AltCloseParams) -> Result<LookupTableChange> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let table = fetch_lookup_table(&config, &args.lookup_table)?;
+
+    if !table.is_deactivated() {
+        return Err(anyhow!(
+            "Lookup table {} must be deactivated before it can be closed",
+            args.lookup_table
+        ));
+    }
+
+    let authority = config.keypair.pubkey();
+    let recipient = args.recipient.unwrap_or(authority);
+
+    let ix = alt_instruction::close_lookup_table(args.lookup_table, authority, recipient);
+
+    let signatures = send_instructions(&config, vec![ix], &[], &[])?;
+
+    Ok(LookupTableChange {
+        action: LookupTableAction::Closed,
+        address: args.lookup_table,
+        added: vec![],
+        signatures,
+    })
+}
+
+pub fn show_lookup_table(args: AltShowParams) -> Result<LookupTable> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    fetch_lookup_table(&config, &args.lookup_table)
+}
+
+pub fn fetch_lookup_table(config: &CliConfig, address: &Pubkey) -> Result<LookupTable> {
+    let data = config.client.get_account_data(address)?;
+    let table = AddressLookupTable::deserialize(&data)
+        .map_err(|e| anyhow!("Invalid lookup table {}: {}", address, e))?;
+
+    Ok(LookupTable {
+        address: *address,
+        authority: table.meta.authority,
+        deactivation_slot: table.meta.deactivation_slot,
+        last_extended_slot: table.meta.last_extended_slot,
+        addresses: table.addresses.to_vec(),
+    })
+}
+
+fn extend_instructions(
+    lookup_table: Pubkey,
+    authority: Pubkey,
+    addresses: &[Pubkey],
+) -> Vec<Instruction> {
+    addresses
+        .chunks(MAX_ADDRESSES_PER_EXTEND)
+        .map(|chunk| {
+            alt_instruction::extend_lookup_table(
+                lookup_table,
+                authority,
+                Some(authority),
+                chunk.to_vec(),
+            )
+        })
+        .collect()
+}
+
+/// Combines addresses passed as arguments with ones read from a JSON file of base58 strings,
+/// such as the one written by `eigen fees shards`.
+pub fn collect_addresses(mut addresses: Vec<Pubkey>, file: Option<PathBuf>) -> Result<Vec<Pubkey>> {
+    if let Some(file) = file {
+        let list: Vec<String> = serde_json::from_reader(File::open(file)?)?;
+        for address in list {
+            addresses.push(Pubkey::from_str(&address)?);
+        }
+    }
+
+    let mut unique = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        if !unique.contains(&address) {
+            unique.push(address);
+        }
+    }
+
+    Ok(unique)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transactions::{transaction_size, MAX_TRANSACTION_SIZE};
+
+    #[test]
+    fn test_extend_fits_in_transaction() {
+        let authority = Pubkey::new_unique();
+        let addresses: Vec<Pubkey> = (0..MAX_ADDRESSES_PER_EXTEND)
+            .map(|_| Pubkey::new_unique())
+            .collect();
+
+        let ixs = extend_instructions(Pubkey::new_unique(), authority, &addresses);
+        assert_eq!(ixs.len(), 1);
+        assert!(transaction_size(&authority, &ixs, &[]).unwrap() <= MAX_TRANSACTION_SIZE);
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::Display;
+
+use crate::{
+    rpc::block_on,
+    rpc::get_multiple_accounts_batched,
+    types::metaplex::{CoreAsset, Metadata},
+};
+
+use super::*;
+
+/// Offset of the amount in an SPL token account, after the mint and owner.
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// How an NFT is minted, which decides the instructions and accounts needed to move it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetStandard {
+    /// SPL token with Token Metadata.
+    Legacy,
+    /// Programmable NFT, moved through Token Metadata with token records.
+    Programmable,
+    Token2022,
+    Core,
+}
+
+impl Display for AssetStandard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AssetStandard::Legacy => "Legacy",
+            AssetStandard::Programmable => "pNFT",
+            AssetStandard::Token2022 => "Token-2022",
+            AssetStandard::Core => "Core",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// An NFT mint, or Core asset, with what is needed to build instructions for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Asset {
+    pub mint: Pubkey,
+    pub standard: AssetStandard,
+    /// Token program of the mint, the Core program for Core assets.
+    pub token_program: Pubkey,
+    /// Authorization rules of a programmable NFT.
+    pub rule_set: Option<Pubkey>,
+    /// Collection of a Core asset.
+    pub collection: Option<Pubkey>,
+    /// Owner of a Core asset, token owners are found through their token accounts.
+    pub owner: Option<Pubkey>,
+}
+
+impl Asset {
+    /// Associated token account of the mint for `wallet`.
+    pub fn token_account(&self, wallet: &Pubkey) -> Pubkey {
+        associated_token_address(wallet, &self.mint, &self.token_program)
+    }
+}
+
+/// Fetches the mints and their metadata and determines the asset standard of each, in order.
+pub fn fetch_assets(config: &CliConfig, mints: &[Pubkey]) -> Result<Vec<Asset>> {
+    let addresses: Vec<Pubkey> = mints
+        .iter()
+        .flat_map(|mint| [*mint, Metadata::find_pda(mint).0])
+        .collect();
+
+    let accounts = block_on(get_multiple_accounts_batched(
+        config.client.clone(),
+        &addresses,
+        config.max_concurrency,
+    ))??;
+
+    mints
+        .iter()
+        .zip(accounts.chunks(2))
+        .map(|(mint, accounts)| {
+            let account = accounts[0]
+                .as_ref()
+                .ok_or_else(|| anyhow!("Mint {} not found", mint))?;
+            resolve_asset(*mint, account, accounts[1].as_ref())
+        })
+        .collect()
+}
+
+/// Determines the asset standard from the mint account owner and, for SPL tokens, the metadata.
+pub fn resolve_asset(mint: Pubkey, account: &Account, metadata: Option<&Account>) -> Result<Asset> {
+    let mut asset = Asset {
+        mint,
+        standard: AssetStandard::Legacy,
+        token_program: account.owner,
+        rule_set: None,
+        collection: None,
+        owner: None,
+    };
+
+    match account.owner {
+        o if o == MPL_CORE_ID => {
+            let core = CoreAsset::from_bytes(&account.data)
+                .map_err(|e| anyhow!("Invalid Core asset {}: {}", mint, e))?;
+            asset.standard = AssetStandard::Core;
+            asset.collection = core.collection();
+            asset.owner = Some(core.owner);
+        }
+        o if o == TOKEN_2022_ID => asset.standard = AssetStandard::Token2022,
+        o if o == TOKEN_ID => {
+            let metadata =
+                metadata.ok_or_else(|| anyhow!("Mint {} has no Token Metadata account", mint))?;
+            let metadata = Metadata::from_bytes(&metadata.data)
+                .map_err(|e| anyhow!("Invalid metadata for mint {}: {}", mint, e))?;
+
+            if metadata.is_programmable() {
+                asset.standard = AssetStandard::Programmable;
+                asset.rule_set = metadata.rule_set();
+            }
+        }
+        o => return Err(anyhow!("{} is not an NFT, it is owned by {}", mint, o)),
+    }
+
+    Ok(asset)
+}
+
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_ID,
+    )
+    .0
+}
+
+/// Amount held by an SPL token account, `None` if the data is too short to be one.
+pub fn token_account_amount(data: &[u8]) -> Option<u64> {
+    data.get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)
+        .map(|amount| u64::from_le_bytes(amount.try_into().expect("8 bytes")))
+}
@@ -6,11 +6,12 @@ use tensor_whitelist::accounts::{MintProof, MintProofV2, Whitelist, WhitelistV2}
 
 use crate::{
     discriminators::deserialize_account,
-    formatting::{AccountEntry, CustomFormat},
+    formatting::AccountEntry,
     types::{
         raydium_clmm::{PoolState as ClmmPoolState, RAYDIUM_CLMM_PROGRAM_ID},
         raydium_cp::{PoolState as CpPoolState, RAYDIUM_CPSWAP_PROGRAM_ID},
         raydium_v4::{AmmInfo, RAYDIUM_AMM_PROGRAM_ID},
+        tensor_escrow::MarginAccount,
     },
     Shard, FEE_SHARDS,
 };
@@ -19,16 +20,48 @@ use super::*;
 
 pub struct DecodeParams {
     pub rpc_url: Option<String>,
+    pub profile: Option<String>,
     pub address: Pubkey,
     pub raw: bool,
 }
 
-pub fn handle_decode(args: DecodeParams) -> Result<()> {
-    let config = CliConfig::new(None, args.rpc_url)?;
+/// An account decoded into one of the types eigen knows about.
+pub enum DecodedAccount {
+    Raw(Vec<u8>),
+    FeeShard(Shard),
+    Wallet(AccountEntry),
+    Pool(Box<Pool>),
+    NftDepositReceipt(NftDepositReceipt),
+    Whitelist(Whitelist),
+    WhitelistV2(WhitelistV2),
+    MintProof(MintProof),
+    MintProofV2(MintProofV2),
+    BidState(Box<BidState>),
+    ListState(ListState),
+    MarginAccount(MarginAccount),
+    OrderState(Box<OrderState>),
+    RaydiumAmm(Box<AmmInfo>),
+    RaydiumClmm(Box<ClmmPoolState>),
+    RaydiumCp(Box<CpPoolState>),
+    Token(AccountEntry),
+    Unknown(AccountEntry),
+}
+
+pub fn handle_decode(args: DecodeParams) -> Result<DecodedAccount> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    fetch_decoded_account(&config, &args.address, args.raw)
+}
 
+/// Fetches an account and decodes it, or returns its raw data when `raw` is set.
+pub fn fetch_decoded_account(
+    config: &CliConfig,
+    address: &Pubkey,
+    raw: bool,
+) -> Result<DecodedAccount> {
     // For `AccountNotFound` error, treat it as an unitialized system program owned wallet with 0 lamports
     // the same way explorers do.
-    let account = match config.client.get_account(&args.address) {
+    let account = match config.client.get_account(address) {
         Ok(account) => account,
         Err(e) => {
             // Check if error message contains "AccountNotFound"
@@ -41,35 +74,26 @@ pub fn handle_decode(args: DecodeParams) -> Result<()> {
                     rent_epoch: 0,
                 }
             } else {
-                return Err(e.into());
+                return Err(e);
             }
         }
     };
 
-    if args.raw {
-        println!("{:?}", account.data);
-        return Ok(());
+    if raw {
+        return Ok(DecodedAccount::Raw(account.data));
     }
 
-    if is_fee_shard(&args.address.to_string()) {
-        println!(
-            "{}",
-            Shard {
-                address: args.address,
-                account
-            }
-            .custom_format()
-        );
-        return Ok(());
+    decode_account(*address, account)
+}
+
+/// Determines the type of an account from its address, owner and discriminator and decodes it.
+pub fn decode_account(address: Pubkey, account: Account) -> Result<DecodedAccount> {
+    if is_fee_shard(&address.to_string()) {
+        return Ok(DecodedAccount::FeeShard(Shard { address, account }));
     }
 
     if is_wallet_type(&account) {
-        let account_entry = AccountEntry {
-            address: args.address,
-            account,
-        };
-        println!("{}", account_entry.custom_format());
-        return Ok(());
+        return Ok(DecodedAccount::Wallet(AccountEntry { address, account }));
     }
 
     let mut data = account.data.as_slice();
@@ -79,70 +103,57 @@ pub fn handle_decode(args: DecodeParams) -> Result<()> {
     }
     let discriminator = &data[0..8];
 
-    match discriminator {
+    let decoded = match discriminator {
         d if d == Pool::discriminator() => {
-            let pool = deserialize_account::<Pool>(data)?;
-            println!("{}", pool.custom_format());
+            DecodedAccount::Pool(Box::new(deserialize_account::<Pool>(data)?))
         }
         d if d == NftDepositReceipt::discriminator() => {
-            let nft_deposit_receipt = deserialize_account::<NftDepositReceipt>(data)?;
-            println!("{}", nft_deposit_receipt.custom_format());
+            DecodedAccount::NftDepositReceipt(deserialize_account::<NftDepositReceipt>(data)?)
         }
         d if d == Whitelist::discriminator() => {
-            let whitelist = deserialize_account::<Whitelist>(data)?;
-            println!("{}", whitelist.custom_format());
+            DecodedAccount::Whitelist(deserialize_account::<Whitelist>(data)?)
         }
         d if d == WhitelistV2::discriminator() => {
-            let whitelist = deserialize_account::<WhitelistV2>(data)?;
-            println!("{}", whitelist.custom_format());
+            DecodedAccount::WhitelistV2(deserialize_account::<WhitelistV2>(data)?)
         }
         d if d == MintProof::discriminator() => {
-            let mint_proof = deserialize_account::<MintProof>(data)?;
-            println!("{}", mint_proof.custom_format());
+            DecodedAccount::MintProof(deserialize_account::<MintProof>(data)?)
         }
         d if d == MintProofV2::discriminator() => {
-            let mint_proof = deserialize_account::<MintProofV2>(data)?;
-            println!("{}", mint_proof.custom_format());
+            DecodedAccount::MintProofV2(deserialize_account::<MintProofV2>(data)?)
         }
         d if d == BidState::discriminator() => {
-            let bid_state = deserialize_account::<BidState>(data)?;
-            println!("{}", bid_state.custom_format());
+            DecodedAccount::BidState(Box::new(deserialize_account::<BidState>(data)?))
         }
         d if d == ListState::discriminator() => {
-            let list_state = deserialize_account::<ListState>(data)?;
-            println!("{}", list_state.custom_format());
+            DecodedAccount::ListState(deserialize_account::<ListState>(data)?)
+        }
+        d if d == MarginAccount::discriminator() => {
+            DecodedAccount::MarginAccount(deserialize_account::<MarginAccount>(data)?)
         }
         d if d == OrderState::discriminator() => {
-            let order_state = deserialize_account::<OrderState>(data)?;
-            println!("{}", order_state.custom_format());
+            DecodedAccount::OrderState(Box::new(deserialize_account::<OrderState>(data)?))
         }
         _ => match account.owner {
             o if o == RAYDIUM_AMM_PROGRAM_ID && data.len() == size_of::<AmmInfo>() => {
-                let amm_info = AmmInfo::deserialize(&mut data)?;
-                println!("{}", amm_info.custom_format());
+                DecodedAccount::RaydiumAmm(Box::new(AmmInfo::deserialize(&mut data)?))
             }
             o if o == RAYDIUM_CLMM_PROGRAM_ID => {
                 // let clmm_info = ClmmPoolState::deserialize(&mut data)?;
-                let clmm_info = deserialize_account::<ClmmPoolState>(data)?;
-                println!("{}", clmm_info.custom_format());
+                DecodedAccount::RaydiumClmm(Box::new(deserialize_account::<ClmmPoolState>(data)?))
             }
             o if o == RAYDIUM_CPSWAP_PROGRAM_ID => {
                 // let cpswap_info = CpPoolState::deserialize(&mut data)?;
-                let cpswap_info = deserialize_account::<CpPoolState>(data)?;
-                println!("{}", cpswap_info.custom_format());
+                DecodedAccount::RaydiumCp(Box::new(deserialize_account::<CpPoolState>(data)?))
             }
             o if TOKEN_PROGRAM_IDS.contains(&o) => {
-                println!("Token or mint account");
-                println!("Data length: {}", data.len());
-                println!("Lamports: {}", account.lamports);
-                println!("Account owned by program: {}", account.owner);
-            }
-            _ => {
-                println!("Unknown account type");
+                DecodedAccount::Token(AccountEntry { address, account })
             }
+            _ => DecodedAccount::Unknown(AccountEntry { address, account }),
         },
-    }
-    Ok(())
+    };
+
+    Ok(decoded)
 }
 
 fn is_wallet_type(account: &Account) -> bool {
@@ -3,7 +3,11 @@ use crate::args::{AnchorDiscriminatorArgs, AnchorDiscriminatorKind};
 use anyhow::Result;
 use sha2::{Digest, Sha256};
 
-pub fn handle_anchor_discriminator(args: AnchorDiscriminatorArgs) -> Result<()> {
+/// First 8 bytes of the sha256 hash of the namespaced account or instruction name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnchorDiscriminator(pub [u8; 8]);
+
+pub fn handle_anchor_discriminator(args: AnchorDiscriminatorArgs) -> Result<AnchorDiscriminator> {
     let prefix = match args.kind {
         AnchorDiscriminatorKind::Account => "account",
         AnchorDiscriminatorKind::Instruction => "global",
@@ -15,15 +19,5 @@ pub fn handle_anchor_discriminator(args: AnchorDiscriminatorArgs) -> Result<()>
     let mut discriminator = [0u8; 8];
     discriminator.copy_from_slice(&result[..8]);
 
-    println!(
-        "Discriminator (bytes):   [{}]",
-        discriminator
-            .iter()
-            .map(|b| format!("{}", b))
-            .collect::<Vec<_>>()
-            .join(", ")
-    );
-    println!("Discriminator (hex)  :   0x{}", hex::encode(discriminator));
-
-    Ok(())
+    Ok(AnchorDiscriminator(discriminator))
 }
@@ -11,14 +11,32 @@ use crate::{commands::Discriminator, discriminators::deserialize_account, setup:
 
 pub struct DownloadParams {
     pub rpc_url: Option<String>,
+    pub profile: Option<String>,
     pub address: Pubkey,
     pub output_dir: Option<PathBuf>,
 }
 
-pub fn handle_download(args: DownloadParams) -> Result<()> {
-    let config = CliConfig::new(None, args.rpc_url)?;
+/// Account saved as JSON.
+#[derive(Debug, Clone)]
+pub struct DownloadedAccount {
+    pub address: Pubkey,
+    pub path: PathBuf,
+    pub data: serde_json::Value,
+}
+
+pub fn handle_download(args: DownloadParams) -> Result<DownloadedAccount> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
 
-    let data = config.client.get_account_data(&args.address)?;
+    download_account(&config, &args.address, args.output_dir)
+}
+
+/// Fetches a Tensor account and writes it as JSON to `output_dir`, the current directory by default.
+pub fn download_account(
+    config: &CliConfig,
+    address: &Pubkey,
+    output_dir: Option<PathBuf>,
+) -> Result<DownloadedAccount> {
+    let data = config.client.get_account_data(address)?;
     let discriminator = &data[0..8];
 
     let (account_data, file_name) = match discriminator {
@@ -60,12 +78,15 @@ pub fn handle_download(args: DownloadParams) -> Result<()> {
         _ => return Err(anyhow!("Unsupported discriminator")),
     };
 
-    let output_path = args.output_dir.unwrap_or_else(|| PathBuf::from("."));
-    let mut file = File::create(output_path.join(file_name))?;
+    let output_path = output_dir.unwrap_or_else(|| PathBuf::from("."));
+    let path = output_path.join(file_name);
+    let mut file = File::create(&path)?;
     let json_string = serde_json::to_string_pretty(&account_data)?;
     file.write_all(json_string.as_bytes())?;
 
-    println!("Account data saved to {}", output_path.display());
-
-    Ok(())
+    Ok(DownloadedAccount {
+        address: *address,
+        path,
+        data: account_data,
+    })
 }
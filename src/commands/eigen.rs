@@ -3,13 +3,20 @@ use std::env;
 use std::path::PathBuf;
 use std::process::Command;
 
-pub fn update_eigen() -> Result<()> {
+/// Binary installed by a self update.
+#[derive(Debug, Clone)]
+pub struct EigenUpdate {
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub path: PathBuf,
+}
+
+pub fn update_eigen() -> Result<EigenUpdate> {
     let (os, arch) = detect_os_and_arch()?;
     let url = get_download_url(os, arch)?;
     let cargo_bin = get_cargo_bin()?;
     let eigen_path = cargo_bin.join("eigen");
 
-    println!("Downloading Eigen binary for {}/{}", os, arch);
     let status = Command::new("curl")
         .args(["-L", "-o", eigen_path.to_str().unwrap(), &url])
         .status()?;
@@ -18,7 +25,6 @@ pub fn update_eigen() -> Result<()> {
         bail!("Failed to download Eigen binary");
     }
 
-    println!("Making Eigen binary executable");
     let status = Command::new("chmod")
         .args(["+x", eigen_path.to_str().unwrap()])
         .status()?;
@@ -27,12 +33,11 @@ pub fn update_eigen() -> Result<()> {
         bail!("Failed to make Eigen binary executable");
     }
 
-    println!(
-        "Eigen update completed successfully. Installed to: {:?}",
-        eigen_path
-    );
-
-    Ok(())
+    Ok(EigenUpdate {
+        os,
+        arch,
+        path: eigen_path,
+    })
 }
 
 fn detect_os_and_arch() -> Result<(&'static str, &'static str)> {
@@ -13,40 +13,55 @@ pub struct ErrorParams {
     pub error_code: String,
 }
 
-pub fn handle_error(args: ErrorParams) -> Result<()> {
+/// Program error matched from an error code.
+#[derive(Debug, Clone)]
+pub struct ErrorLookup {
+    pub code: u32,
+    /// Error enum the code belongs to, if it is known.
+    pub error_type: Option<String>,
+    pub name: Option<String>,
+}
+
+pub fn handle_error(args: ErrorParams) -> Result<ErrorLookup> {
     let error_code = parse_error_code(&args.error_code)?;
 
-    match error_code {
-        code if TensorAmmError::from_i32(code as i32).is_some() => print_error(
+    let lookup = match error_code {
+        code if TensorAmmError::from_i32(code as i32).is_some() => lookup(
             "TensorAmmError",
             code,
             &TensorAmmError::from_i32(code as i32).unwrap(),
         ),
-        code if TensorWhitelistError::from_i32(code as i32).is_some() => print_error(
+        code if TensorWhitelistError::from_i32(code as i32).is_some() => lookup(
             "TensorWhitelistError",
             code,
             &TensorWhitelistError::from_i32(code as i32).unwrap(),
         ),
-        code if TensorMarketplaceError::from_i32(code as i32).is_some() => print_error(
+        code if TensorMarketplaceError::from_i32(code as i32).is_some() => lookup(
             "TensorMarketplaceError",
             code,
             &TensorMarketplaceError::from_i32(code as i32).unwrap(),
         ),
-        code if AnchorErrorCode::from_u32(code).is_some() => print_error(
+        code if AnchorErrorCode::from_u32(code).is_some() => lookup(
             "Anchor ErrorCode",
             code,
             &AnchorErrorCode::from_u32(code).unwrap(),
         ),
-        _ => println!("Unknown error code: {}", error_code),
-    }
+        _ => ErrorLookup {
+            code: error_code,
+            error_type: None,
+            name: None,
+        },
+    };
 
-    Ok(())
+    Ok(lookup)
 }
 
-fn print_error<T: std::fmt::Debug>(error_type: &str, error_code: u32, error: &T) {
-    println!("{}:", error_type);
-    println!("Error Code: {}", error_code);
-    println!("Error Type: {:?}", error);
+fn lookup<T: std::fmt::Debug>(error_type: &str, error_code: u32, error: &T) -> ErrorLookup {
+    ErrorLookup {
+        code: error_code,
+        error_type: Some(error_type.to_string()),
+        name: Some(format!("{:?}", error)),
+    }
 }
 
 fn parse_error_code(input: &str) -> Result<u32> {
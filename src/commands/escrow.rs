@@ -0,0 +1,414 @@
+use solana_sdk::{native_token::lamports_to_sol, signature::Signature, signer::Signer};
+use tensor_marketplace::accounts::BidState;
+
+use crate::{
+    discriminators::deserialize_account,
+    rpc::{discriminator_filter, memcmp_filter},
+    transactions::send_instructions,
+    types::tensor_escrow::{
+        InitMarginAccountInstructionArgs, MarginAccount, MarginAccountInstruction,
+        MarginLamportsInstructionArgs,
+    },
+};
+
+use super::*;
+
+pub struct EscrowCreateParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub nr: Option<u16>,
+    pub name: Option<String>,
+}
+
+pub struct EscrowSolParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub escrow: Pubkey,
+    pub amount: String,
+    pub lamports: bool,
+}
+
+pub struct EscrowCloseParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub escrow: Pubkey,
+}
+
+pub struct EscrowShowParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub escrow: Pubkey,
+}
+
+pub struct EscrowListParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub owner: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EscrowEntry {
+    pub address: Pubkey,
+    pub account: MarginAccount,
+    /// Account balance, including the rent exemption.
+    pub lamports: u64,
+    /// SOL pools and bids can draw from, the balance above the rent exemption.
+    pub available: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct EscrowList {
+    pub owner: Pubkey,
+    pub escrows: Vec<EscrowEntry>,
+}
+
+/// A shared escrow with the pools and bids drawing from it.
+#[derive(Debug, Clone)]
+pub struct EscrowDetails {
+    pub escrow: EscrowEntry,
+    pub pools: Vec<PoolEntry>,
+    pub bids: Vec<(Pubkey, BidState)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreatedEscrow {
+    pub address: Pubkey,
+    pub nr: u16,
+    pub name: String,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EscrowSolTransfer {
+    pub escrow: Pubkey,
+    pub direction: PoolDirection,
+    pub lamports: u64,
+    /// Available balance before and after the transfer, in lamports.
+    pub balance_before: u64,
+    pub balance_after: u64,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClosedEscrow {
+    pub escrow: Pubkey,
+    /// Lamports returned to the owner, the balance and the rent.
+    pub lamports: u64,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn create_escrow(args: EscrowCreateParams) -> Result<CreatedEscrow> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    create_escrow_with_config(&config, args.nr, args.name.as_deref().unwrap_or_default())
+}
+
+/// Creates a shared escrow for the config keypair, using the lowest free number unless one is given.
+pub fn create_escrow_with_config(
+    config: &CliConfig,
+    nr: Option<u16>,
+    name: &str,
+) -> Result<CreatedEscrow> {
+    let owner = config.keypair.pubkey();
+
+    if name.len() > 32 {
+        return Err(anyhow!("Escrow name can't be longer than 32 bytes"));
+    }
+    let mut name_bytes = [0u8; 32];
+    name_bytes[..name.len()].copy_from_slice(name.as_bytes());
+
+    let taken: Vec<u16> = fetch_escrows(config, &owner)?
+        .iter()
+        .map(|entry| entry.account.nr)
+        .collect();
+
+    let nr = match nr {
+        Some(nr) if taken.contains(&nr) => {
+            return Err(anyhow!(
+                "Escrow {} already exists: {}",
+                nr,
+                MarginAccount::find_pda(&owner, nr).0
+            ))
+        }
+        Some(nr) => nr,
+        None => (0..=u16::MAX)
+            .find(|nr| !taken.contains(nr))
+            .ok_or_else(|| anyhow!("No free escrow numbers left"))?,
+    };
+
+    let address = MarginAccount::find_pda(&owner, nr).0;
+
+    let ix = MarginAccountInstruction {
+        margin_account: address,
+        owner,
+    }
+    .init(InitMarginAccountInstructionArgs {
+        margin_nr: nr,
+        name: name_bytes,
+    });
+
+    let signatures = send_instructions(config, vec![ix], &[], &[])?;
+
+    Ok(CreatedEscrow {
+        address,
+        nr,
+        name: name.to_string(),
+        signatures,
+    })
+}
+
+pub fn deposit_escrow(args: EscrowSolParams) -> Result<EscrowSolTransfer> {
+    handle_escrow_sol(args, PoolDirection::Deposit)
+}
+
+pub fn withdraw_escrow(args: EscrowSolParams) -> Result<EscrowSolTransfer> {
+    handle_escrow_sol(args, PoolDirection::Withdraw)
+}
+
+fn handle_escrow_sol(args: EscrowSolParams, direction: PoolDirection) -> Result<EscrowSolTransfer> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let lamports = parse_amount(&args.amount, args.lamports)?;
+
+    transfer_escrow_sol(&config, &args.escrow, direction, lamports)
+}
+
+/// Deposits SOL into, or withdraws SOL from, a shared escrow owned by the config keypair.
+pub fn transfer_escrow_sol(
+    config: &CliConfig,
+    address: &Pubkey,
+    direction: PoolDirection,
+    lamports: u64,
+) -> Result<EscrowSolTransfer> {
+    let owner = config.keypair.pubkey();
+    let escrow = fetch_escrow(config, address)?;
+
+    check_owner(&escrow, &owner)?;
+
+    if lamports == 0 {
+        return Err(anyhow!("Amount must be greater than zero"));
+    }
+
+    // Withdrawing into the rent exemption would leave the account unusable.
+    if direction == PoolDirection::Withdraw && lamports > escrow.available {
+        return Err(anyhow!(
+            "Escrow only holds {} SOL",
+            lamports_to_sol(escrow.available)
+        ));
+    }
+
+    let instruction = MarginAccountInstruction {
+        margin_account: *address,
+        owner,
+    };
+    let args = MarginLamportsInstructionArgs { lamports };
+    let ix = match direction {
+        PoolDirection::Deposit => instruction.deposit(args),
+        PoolDirection::Withdraw => instruction.withdraw(args),
+    };
+
+    let signatures = send_instructions(config, vec![ix], &[], &[])?;
+
+    let balance_after = fetch_escrow(config, address)?.available;
+
+    Ok(EscrowSolTransfer {
+        escrow: *address,
+        direction,
+        lamports,
+        balance_before: escrow.available,
+        balance_after,
+        signatures,
+    })
+}
+
+pub fn close_escrow(args: EscrowCloseParams) -> Result<ClosedEscrow> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    close_escrow_with_config(&config, &args.escrow)
+}
+
+/// Closes a shared escrow once no pools or bids use it, returning its balance to the owner.
+pub fn close_escrow_with_config(config: &CliConfig, address: &Pubkey) -> Result<ClosedEscrow> {
+    let owner = config.keypair.pubkey();
+    let details = fetch_escrow_details(config, address)?;
+
+    let mut blockers = vec![];
+
+    if details.escrow.account.owner != owner {
+        blockers.push(format!(
+            "it is owned by {}, sign with the owner keypair",
+            details.escrow.account.owner
+        ));
+    }
+
+    // The program only tracks the number of pools, list them so they can be closed.
+    if details.escrow.account.pools_attached > 0 {
+        blockers.push(format!(
+            "{} pools are attached, close them first: {}",
+            details.escrow.account.pools_attached,
+            details
+                .pools
+                .iter()
+                .map(|entry| entry.address.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    if !details.bids.is_empty() {
+        blockers.push(format!(
+            "{} bids draw from it, cancel them first: {}",
+            details.bids.len(),
+            details
+                .bids
+                .iter()
+                .map(|(address, _)| address.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    if !blockers.is_empty() {
+        return Err(blockers_error(
+            format!("Escrow {} can't be closed yet", address),
+            &blockers,
+        ));
+    }
+
+    let ix = MarginAccountInstruction {
+        margin_account: *address,
+        owner,
+    }
+    .close();
+
+    let signatures = send_instructions(config, vec![ix], &[], &[])?;
+
+    Ok(ClosedEscrow {
+        escrow: *address,
+        lamports: details.escrow.lamports,
+        signatures,
+    })
+}
+
+pub fn show_escrow(args: EscrowShowParams) -> Result<EscrowDetails> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    fetch_escrow_details(&config, &args.escrow)
+}
+
+pub fn list_escrows(args: EscrowListParams) -> Result<EscrowList> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+    let owner = args.owner.unwrap_or_else(|| config.keypair.pubkey());
+
+    Ok(EscrowList {
+        owner,
+        escrows: fetch_escrows(&config, &owner)?,
+    })
+}
+
+/// Fetches and decodes a shared escrow account.
+pub fn fetch_escrow(config: &CliConfig, address: &Pubkey) -> Result<EscrowEntry> {
+    let account = config.client.get_account(address)?;
+    let rent = escrow_rent(config)?;
+
+    escrow_entry(*address, account.lamports, &account.data, rent)
+}
+
+/// Fetches every shared escrow of `owner`, by number.
+pub fn fetch_escrows(config: &CliConfig, owner: &Pubkey) -> Result<Vec<EscrowEntry>> {
+    let accounts = config.client.get_program_accounts_with_config(
+        &TENSOR_ESCROW_ID,
+        discriminator_filter(
+            config.commitment,
+            MarginAccount::discriminator(),
+            vec![memcmp_filter(MarginAccount::OWNER_OFFSET, owner.as_ref())],
+        ),
+    )?;
+    let rent = escrow_rent(config)?;
+
+    let mut escrows = accounts
+        .into_iter()
+        .map(|(address, account)| escrow_entry(address, account.lamports, &account.data, rent))
+        .collect::<Result<Vec<_>>>()?;
+    escrows.sort_by_key(|entry| entry.account.nr);
+
+    Ok(escrows)
+}
+
+/// Fetches a shared escrow with the pools and bids using it.
+pub fn fetch_escrow_details(config: &CliConfig, address: &Pubkey) -> Result<EscrowDetails> {
+    let escrow = fetch_escrow(config, address)?;
+
+    let pools = fetch_pools(
+        config,
+        &PoolFilters {
+            shared_escrow: Some(*address),
+            ..Default::default()
+        },
+    )?;
+
+    let bids = fetch_bids(
+        config,
+        &BidFilters {
+            owner: Some(escrow.account.owner),
+            margin: Some(*address),
+            ..Default::default()
+        },
+    )?
+    .into_iter()
+    .map(|entry| (entry.address, entry.bid))
+    .collect();
+
+    Ok(EscrowDetails {
+        escrow,
+        pools,
+        bids,
+    })
+}
+
+fn escrow_rent(config: &CliConfig) -> Result<u64> {
+    config
+        .client
+        .get_minimum_balance_for_rent_exemption(MarginAccount::LEN)
+}
+
+fn escrow_entry(address: Pubkey, lamports: u64, data: &[u8], rent: u64) -> Result<EscrowEntry> {
+    let account = deserialize_account::<MarginAccount>(data)
+        .map_err(|e| anyhow!("Invalid escrow {}: {}", address, e))?;
+
+    Ok(EscrowEntry {
+        address,
+        account,
+        lamports,
+        available: lamports.saturating_sub(rent),
+    })
+}
+
+fn check_owner(escrow: &EscrowEntry, owner: &Pubkey) -> Result<()> {
+    if escrow.account.owner != *owner {
+        return Err(anyhow!(
+            "Escrow is owned by {}, sign with the owner keypair",
+            escrow.account.owner
+        ));
+    }
+
+    Ok(())
+}
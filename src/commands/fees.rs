@@ -1,21 +1,72 @@
-use crate::{spinner::pb_with_len, FEE_SHARDS};
+use crate::{
+    rpc::{block_on, get_balances_batched},
+    spinner::create_spinner,
+    transactions::{fetch_lookup_tables, send_instructions},
+    FEE_SHARDS,
+};
 
 use super::*;
 
 use std::{fs::File, str::FromStr};
 
 use solana_sdk::{
-    instruction::Instruction, pubkey, signer::Signer, system_instruction, transaction::Transaction,
+    instruction::Instruction, pubkey, signature::Signature, signer::Signer, system_instruction,
 };
 
 const TFEE_PROGRAM_ID: Pubkey = pubkey!("TFEEgwDP6nn1s8mMX2tTNPPz8j2VomkphLUmyxKm17A");
 
+/// Fee vault shard a marketplace or AMM state account pays fees into, picked by the last byte of
+/// the state account's address.
+pub fn find_fee_vault_pda(state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_vault", &[state.to_bytes()[31]]], &TFEE_PROGRAM_ID).0
+}
+
 pub struct FeeParams {
     pub keypair_path: Option<PathBuf>,
     pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub lookup_tables: Vec<Pubkey>,
+}
+
+/// Fee shard addresses written to a JSON file.
+#[derive(Debug, Clone)]
+pub struct GeneratedShards {
+    pub path: PathBuf,
+    pub count: usize,
+}
+
+/// Shards topped up to the rent-exempt minimum.
+#[derive(Debug, Clone)]
+pub struct FundedShards {
+    pub funded: Vec<Pubkey>,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShardBalance {
+    pub address: Pubkey,
+    pub lamports: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ShardBalances {
+    pub rent_exempt_lamports: u64,
+    pub balances: Vec<ShardBalance>,
+}
+
+impl ShardBalances {
+    pub fn funded_count(&self) -> usize {
+        self.balances
+            .iter()
+            .filter(|b| b.lamports >= self.rent_exempt_lamports)
+            .count()
+    }
 }
 
-pub fn generate_fee_shards() -> Result<()> {
+pub fn generate_fee_shards() -> Result<GeneratedShards> {
     let mut shards = vec![];
 
     for i in 0..=255 {
@@ -27,15 +78,26 @@ pub fn generate_fee_shards() -> Result<()> {
         );
     }
 
-    let file = File::create("fee_vault_shards.json")?;
+    let path = PathBuf::from("fee_vault_shards.json");
+    let file = File::create(&path)?;
     serde_json::to_writer_pretty(&file, &shards)?;
 
-    Ok(())
+    Ok(GeneratedShards {
+        path,
+        count: shards.len(),
+    })
 }
 
-pub fn fund_shards(args: FeeParams) -> Result<()> {
-    let config = CliConfig::new(args.keypair_path, args.rpc_url)?;
+pub fn fund_shards(args: FeeParams) -> Result<FundedShards> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
 
+    fund_fee_shards(&config, &args.lookup_tables)
+}
+
+/// Tops up every fee shard below the rent-exempt minimum, paid for by the config keypair.
+pub fn fund_fee_shards(config: &CliConfig, lookup_tables: &[Pubkey]) -> Result<FundedShards> {
     let rent_exempt_lamports = config.client.get_minimum_balance_for_rent_exemption(0)?;
 
     // Convert FEE_SHARDS to Pubkeys
@@ -46,46 +108,45 @@ pub fn fund_shards(args: FeeParams) -> Result<()> {
 
     // Check balances and create transfer instructions only for underfunded shards
     let mut instructions: Vec<Instruction> = Vec::new();
-    let pb = pb_with_len("shards checked", shard_pubkeys.len() as u64)?;
+    let mut funded = Vec::new();
+    let spinner = create_spinner("Checking shard balances...")?;
 
-    for pubkey in &shard_pubkeys {
-        let balance = config.client.get_balance(pubkey)?;
+    let balances = fetch_balances(config, &shard_pubkeys)?;
+
+    for (pubkey, balance) in shard_pubkeys.iter().zip(balances) {
         if balance < rent_exempt_lamports {
             instructions.push(system_instruction::transfer(
                 &config.keypair.pubkey(),
                 pubkey,
                 rent_exempt_lamports - balance,
             ));
+            funded.push(*pubkey);
         }
-        pb.inc(1);
     }
-    pb.finish_with_message("Finished checking shard balances");
+    spinner.finish_and_clear();
 
     if instructions.is_empty() {
-        println!("All shards are already funded.");
-        return Ok(());
+        return Ok(FundedShards {
+            funded,
+            signatures: vec![],
+        });
     }
 
-    // Pack instructions into transactions (15 instructions per transaction)
-    for chunk in instructions.chunks(15) {
-        let transaction = Transaction::new_signed_with_payer(
-            chunk,
-            Some(&config.keypair.pubkey()),
-            &[&config.keypair],
-            config.client.get_latest_blockhash()?,
-        );
+    // Pack instructions into as few transactions as fit, using v0 messages when lookup tables
+    // are provided.
+    let lookup_tables = fetch_lookup_tables(config, lookup_tables)?;
+    let signatures = send_instructions(config, instructions, &[], &lookup_tables)?;
 
-        config
-            .client
-            .send_and_confirm_transaction_with_spinner(&transaction)?;
-    }
-
-    Ok(())
+    Ok(FundedShards { funded, signatures })
 }
 
-pub fn get_shard_balances(args: FeeParams) -> Result<()> {
-    let config = CliConfig::new(args.keypair_path, args.rpc_url)?;
+pub fn get_shard_balances(args: FeeParams) -> Result<ShardBalances> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?;
 
+    fetch_shard_balances(&config)
+}
+
+pub fn fetch_shard_balances(config: &CliConfig) -> Result<ShardBalances> {
     let shard_pubkeys: Vec<Pubkey> = FEE_SHARDS
         .iter()
         .filter_map(|s| Pubkey::from_str(s).ok())
@@ -93,28 +154,23 @@ pub fn get_shard_balances(args: FeeParams) -> Result<()> {
 
     let rent_exempt_lamports = config.client.get_minimum_balance_for_rent_exemption(0)?;
 
-    let mut fully_funded_count = 0;
-
-    for pubkey in &shard_pubkeys {
-        let balance = config.client.get_balance(pubkey)?;
-        let status = if balance >= rent_exempt_lamports {
-            fully_funded_count += 1;
-            "✓"
-        } else {
-            "✗"
-        };
-        println!("{} {} {}", pubkey, balance, status);
-    }
+    let balances = fetch_balances(config, &shard_pubkeys)?
+        .into_iter()
+        .zip(shard_pubkeys)
+        .map(|(lamports, address)| ShardBalance { address, lamports })
+        .collect();
 
-    if fully_funded_count == shard_pubkeys.len() {
-        println!("All shards fully funded!");
-    } else {
-        println!(
-            "{}/{} shards fully funded.",
-            fully_funded_count,
-            shard_pubkeys.len()
-        );
-    }
+    Ok(ShardBalances {
+        rent_exempt_lamports,
+        balances,
+    })
+}
 
-    Ok(())
+/// Fetches balances in `getMultipleAccounts` batches rather than one request per shard.
+fn fetch_balances(config: &CliConfig, pubkeys: &[Pubkey]) -> Result<Vec<u64>> {
+    block_on(get_balances_batched(
+        config.client.clone(),
+        pubkeys,
+        config.max_concurrency,
+    ))?
 }
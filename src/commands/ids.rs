@@ -1,7 +1,7 @@
 use std::fmt::Display;
 
 use {
-    anyhow::Result,
+    anyhow::{anyhow, Result},
     solana_program::pubkey,
     solana_sdk::pubkey::Pubkey,
     strum::IntoEnumIterator,
@@ -43,18 +43,46 @@ pub enum Id {
     NativeMint,
     #[strum(serialize = "token", serialize = "spl_token")]
     Token,
+    #[strum(
+        serialize = "associated_token",
+        serialize = "associatedToken",
+        serialize = "ata"
+    )]
+    AssociatedToken,
     #[strum(
         serialize = "token_2022",
         serialize = "token2022",
         serialize = "spl_token_2022"
     )]
     Token2022,
+    #[strum(
+        serialize = "account_compression",
+        serialize = "accountCompression",
+        serialize = "spl_account_compression"
+    )]
+    AccountCompression,
+    #[strum(serialize = "noop", serialize = "spl_noop", serialize = "splNoop")]
+    Noop,
     #[strum(
         serialize = "token_metadata",
         serialize = "tokenMetadata",
         serialize = "mpl_token_metadata"
     )]
     TokenMetadata,
+    #[strum(serialize = "mpl_core", serialize = "mplCore", serialize = "core")]
+    MplCore,
+    #[strum(
+        serialize = "bubblegum",
+        serialize = "mplBubblegum",
+        serialize = "mpl_bubblegum"
+    )]
+    Bubblegum,
+    #[strum(
+        serialize = "token_auth_rules",
+        serialize = "tokenAuthRules",
+        serialize = "mpl_token_auth_rules"
+    )]
+    TokenAuthRules,
     #[strum(serialize = "amm", serialize = "tensorAmm", serialize = "tensor_amm")]
     TensorAmm,
     #[strum(
@@ -113,8 +141,14 @@ impl Id {
             Id::Rent => SYSVAR_RENT,
             Id::NativeMint => NATIVE_MINT_ID,
             Id::Token => TOKEN_ID,
+            Id::AssociatedToken => ASSOCIATED_TOKEN_ID,
             Id::Token2022 => TOKEN_2022_ID,
+            Id::AccountCompression => ACCOUNT_COMPRESSION_ID,
+            Id::Noop => NOOP_ID,
             Id::TokenMetadata => TOKEN_METADATA_ID,
+            Id::MplCore => MPL_CORE_ID,
+            Id::Bubblegum => BUBBLEGUM_ID,
+            Id::TokenAuthRules => TOKEN_AUTH_RULES_ID,
             Id::TensorAmm => TENSOR_AMM_ID,
             Id::TensorEscrow => TENSOR_ESCROW_ID,
             Id::TensorFees => TENSOR_FEES_ID,
@@ -142,9 +176,15 @@ pub const SYSVAR_RENT: Pubkey = pubkey!("SysvarRent11111111111111111111111111111
 pub const NATIVE_MINT_ID: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
 pub const TOKEN_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 pub const TOKEN_2022_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const ASSOCIATED_TOKEN_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+pub const ACCOUNT_COMPRESSION_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
 
 // Metaplex
 pub const TOKEN_METADATA_ID: Pubkey = pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+pub const MPL_CORE_ID: Pubkey = pubkey!("CoREENxT6tW1HoK8ypY1SxRMZTcVPm7R94rH4PZNhX7d");
+pub const BUBBLEGUM_ID: Pubkey = pubkey!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+pub const TOKEN_AUTH_RULES_ID: Pubkey = pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
 
 // Tensor Foundation
 pub const TENSOR_AMM_ID: Pubkey = pubkey!("TAMM6ub33ij1mbetoMyVBLeKY5iP41i4UPUJQGkhfsg");
@@ -156,14 +196,18 @@ pub const TENSOR_MERKLE_TREE_CONFIG: Pubkey =
 pub const TENSOR_PRICE_LOCK_ID: Pubkey = pubkey!("TLoCKic2wGJm7VhZKumih4Lc35fUhYqVMgA4j389Buk");
 pub const TENSOR_WHITELIST_ID: Pubkey = pubkey!("TL1ST2iRBzuGTqLn1KXnGdSnEow62BzPnGiqyRXhWtW");
 
-pub fn handle_ids(id: Option<Id>, list: bool) -> Result<()> {
+/// Either the list of known ID names or the address of a single ID.
+#[derive(Debug, Clone)]
+pub enum IdLookup {
+    List(Vec<Id>),
+    Pubkey(Pubkey),
+}
+
+pub fn handle_ids(id: Option<Id>, list: bool) -> Result<IdLookup> {
     if list {
-        println!("Available IDs:");
-        for id in Id::iter() {
-            println!("  {}", id);
-        }
+        Ok(IdLookup::List(Id::iter().collect()))
     } else {
-        println!("{}", id.unwrap().get_pubkey());
+        let id = id.ok_or_else(|| anyhow!("No ID provided"))?;
+        Ok(IdLookup::Pubkey(id.get_pubkey()))
     }
-    Ok(())
 }
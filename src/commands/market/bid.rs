@@ -0,0 +1,941 @@
+use super::*;
+
+use solana_sdk::instruction::Instruction;
+use tensor_marketplace::{
+    instructions::{
+        Bid, BidInstructionArgs, CancelBid, TakeBidCompressedMetaHash,
+        TakeBidCompressedMetaHashInstructionArgs, TakeBidCore, TakeBidCoreInstructionArgs,
+        TakeBidLegacy, TakeBidLegacyInstructionArgs, TakeBidT22, TakeBidT22InstructionArgs,
+    },
+    programs::TENSOR_MARKETPLACE_ID,
+    types::{Field, Target},
+};
+use tensor_whitelist::{
+    accounts::{MintProofV2, WhitelistV2},
+    types::Mode,
+};
+
+use crate::{
+    commands::{
+        blockers_error, fetch_escrow, find_fee_vault_pda, token_account_amount,
+        ACCOUNT_COMPRESSION_ID, ASSOCIATED_TOKEN_ID, BUBBLEGUM_ID, MPL_CORE_ID, NOOP_ID,
+        SYSVAR_INSTRUCTIONS, TENSOR_ESCROW_ID, TOKEN_AUTH_RULES_ID, TOKEN_METADATA_ID,
+    },
+    transactions::send_instructions,
+    types::metaplex::{find_edition_pda, find_token_record_pda},
+};
+
+pub struct MarketBidParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub target: BidTarget,
+    pub price: String,
+    pub terms: BidTerms,
+    /// Seed of the bid state, a new one is picked if not set.
+    pub bid_id: Option<Pubkey>,
+    pub cosigner: Option<PathBuf>,
+}
+
+pub struct MarketEditBidParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub bid_state: Pubkey,
+    pub price: Option<String>,
+    pub terms: BidTerms,
+    pub cosigner: Option<PathBuf>,
+}
+
+pub struct MarketCancelBidParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub bid_state: Pubkey,
+}
+
+pub struct MarketTakeBidParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub bid_state: Pubkey,
+    pub mint: Pubkey,
+    pub terms: TakeBidTerms,
+    pub cosigner: Option<PathBuf>,
+}
+
+/// What a bid is for: a single asset, or any asset of a whitelist, optionally narrowed to a
+/// trait whose takes the bid's cosigner checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BidTarget {
+    Mint(Pubkey),
+    Whitelist {
+        whitelist: Pubkey,
+        trait_id: Option<Pubkey>,
+    },
+}
+
+impl BidTarget {
+    fn from_state(bid: &BidState) -> Self {
+        match bid.target {
+            Target::AssetId => BidTarget::Mint(bid.target_id),
+            Target::Whitelist => BidTarget::Whitelist {
+                whitelist: bid.target_id,
+                trait_id: bid.field_id,
+            },
+        }
+    }
+
+    fn target(&self) -> (Target, Pubkey) {
+        match self {
+            BidTarget::Mint(mint) => (Target::AssetId, *mint),
+            BidTarget::Whitelist { whitelist, .. } => (Target::Whitelist, *whitelist),
+        }
+    }
+
+    fn field(&self) -> (Option<Field>, Option<Pubkey>) {
+        match self {
+            BidTarget::Whitelist {
+                trait_id: Some(trait_id),
+                ..
+            } => (Some(Field::Name), Some(*trait_id)),
+            _ => (None, None),
+        }
+    }
+}
+
+/// Optional terms of a bid, kept as typed so durations are parsed with their units. Omitted terms
+/// keep their current value when editing.
+#[derive(Debug, Clone, Default)]
+pub struct BidTerms {
+    pub quantity: Option<u32>,
+    pub expire: Option<String>,
+    pub private_taker: Option<Pubkey>,
+    pub maker_broker: Option<Pubkey>,
+    /// Shared escrow the bid draws from instead of holding its own SOL.
+    pub margin: Option<Pubkey>,
+}
+
+/// Optional terms of taking a bid. The min price defaults to the bid price, so a bid lowered in
+/// the meantime fails instead of paying less.
+#[derive(Debug, Clone, Default)]
+pub struct TakeBidTerms {
+    pub min_price: Option<String>,
+    pub royalty_pct: Option<u16>,
+    pub taker_broker: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketBid {
+    pub bid_state: Pubkey,
+    pub amount: u64,
+    pub quantity: u32,
+    pub expire_in_sec: Option<u64>,
+    pub margin: Option<Pubkey>,
+    pub edited: bool,
+    /// The bid as stored on chain after the transaction, if the RPC already returns it.
+    pub state: Option<BidState>,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketBidCancel {
+    pub bid_state: Pubkey,
+    /// SOL returned from the bid, including its rent.
+    pub refund: u64,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketBidTake {
+    pub bid_state: Pubkey,
+    pub asset: Pubkey,
+    pub standard: String,
+    pub bidder: Pubkey,
+    pub amount: u64,
+    pub min_amount: u64,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn place_bid(args: MarketBidParams) -> Result<MarketBid> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+    let cosigner = read_cosigner(args.cosigner)?;
+
+    place_bid_with_config(
+        &config,
+        &args.target,
+        &args.price,
+        &args.terms,
+        args.bid_id,
+        cosigner.as_ref(),
+    )
+}
+
+/// Places a SOL bid with the config keypair. Bids for a single mint default to the mint as the
+/// bid id so there is at most one per mint, other bids get a new id.
+pub fn place_bid_with_config(
+    config: &CliConfig,
+    target: &BidTarget,
+    price: &str,
+    terms: &BidTerms,
+    bid_id: Option<Pubkey>,
+    cosigner: Option<&Keypair>,
+) -> Result<MarketBid> {
+    let owner = config.keypair.pubkey();
+    let bid_id = bid_id.unwrap_or_else(|| match target {
+        BidTarget::Mint(mint) => *mint,
+        BidTarget::Whitelist { .. } => Keypair::new().pubkey(),
+    });
+    let bid_state = BidState::find_pda(&owner, &bid_id).0;
+
+    if config.client.get_multiple_accounts(&[bid_state])?[0].is_some() {
+        return Err(anyhow!(
+            "Bid {} already exists, use edit-bid to change it",
+            bid_state
+        ));
+    }
+
+    let (target_kind, target_id) = target.target();
+    let (field, field_id) = target.field();
+
+    let args = BidInstructionArgs {
+        bid_id,
+        target: target_kind,
+        target_id,
+        field,
+        field_id,
+        amount: Currency::SOL.parse(price)?,
+        quantity: terms.quantity.unwrap_or(1),
+        expire_in_sec: parse_expiry(terms.expire.as_deref())?,
+        currency: None,
+        private_taker: terms.private_taker,
+        maker_broker: terms.maker_broker,
+    };
+
+    if let BidTarget::Whitelist { whitelist, .. } = target {
+        let accounts = config.client.get_multiple_accounts(&[*whitelist])?;
+        accounts[0]
+            .as_ref()
+            .ok_or_else(|| anyhow!("Whitelist {} not found", whitelist))
+            .and_then(|account| {
+                deserialize_account::<WhitelistV2>(&account.data)
+                    .map_err(|e| anyhow!("Invalid whitelist {}: {}", whitelist, e))
+            })?;
+    }
+
+    if target.field().0.is_some() && cosigner.is_none() {
+        return Err(anyhow!(
+            "Trait bids need a cosigner to check the trait, pass its keypair with --cosigner"
+        ));
+    }
+
+    check_bid_funds(
+        config,
+        &args,
+        terms.margin,
+        0,
+        format!("Can't place bid {}", bid_state),
+    )?;
+
+    submit_bid(config, bid_state, args, terms.margin, cosigner, false)
+}
+
+pub fn edit_bid(args: MarketEditBidParams) -> Result<MarketBid> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+    let cosigner = read_cosigner(args.cosigner)?;
+
+    edit_bid_with_config(
+        &config,
+        &args.bid_state,
+        args.price.as_deref(),
+        &args.terms,
+        cosigner.as_ref(),
+    )
+}
+
+/// Edits a bid of the config keypair by placing it again over the existing bid state. Omitted
+/// terms keep their current value, and the quantity is the new total including filled ones.
+pub fn edit_bid_with_config(
+    config: &CliConfig,
+    bid_state: &Pubkey,
+    price: Option<&str>,
+    terms: &BidTerms,
+    cosigner: Option<&Keypair>,
+) -> Result<MarketBid> {
+    let owner = config.keypair.pubkey();
+    let (bid, account) = fetch_bid(config, bid_state)?;
+    let now = chrono::Utc::now().timestamp();
+
+    let expire_in_sec = match &terms.expire {
+        Some(expire) => parse_expiry(Some(expire))?,
+        None => (bid.expiry > now).then(|| ((bid.expiry - now) as u64).min(MAX_EXPIRY_SEC)),
+    };
+
+    let target = BidTarget::from_state(&bid);
+    let (target_kind, target_id) = target.target();
+    let args = BidInstructionArgs {
+        bid_id: bid.bid_id,
+        target: target_kind,
+        target_id,
+        field: bid.field,
+        field_id: bid.field_id,
+        amount: price
+            .map(|price| Currency::SOL.parse(price))
+            .transpose()?
+            .unwrap_or(bid.amount),
+        quantity: terms.quantity.unwrap_or(bid.quantity),
+        expire_in_sec,
+        currency: bid.currency,
+        private_taker: terms.private_taker.or(bid.private_taker),
+        maker_broker: terms.maker_broker.or(bid.maker_broker),
+    };
+    let margin = terms.margin.or(bid.margin);
+
+    let mut blockers = vec![];
+
+    if bid.owner != owner {
+        blockers.push(format!(
+            "it is owned by {}, sign with the owner keypair",
+            bid.owner
+        ));
+    }
+
+    if args.quantity <= bid.filled_quantity {
+        blockers.push(format!(
+            "{} of it are already filled, the quantity has to be more",
+            bid.filled_quantity
+        ));
+    }
+
+    if let Some(message) = cosigner_mismatch(bid.cosigner.to_option(), cosigner) {
+        blockers.push(message);
+    }
+
+    if !blockers.is_empty() {
+        return Err(blockers_error(
+            format!("Can't edit bid {}", bid_state),
+            &blockers,
+        ));
+    }
+
+    // SOL already in the bid state counts towards the new total.
+    let rent = config
+        .client
+        .get_minimum_balance_for_rent_exemption(account.data.len())?;
+    let deposited = match bid.margin {
+        Some(_) => 0,
+        None => account.lamports.saturating_sub(rent),
+    };
+
+    let unfilled = BidInstructionArgs {
+        quantity: args.quantity - bid.filled_quantity,
+        ..args.clone()
+    };
+    check_bid_funds(
+        config,
+        &unfilled,
+        margin,
+        deposited,
+        format!("Can't edit bid {}", bid_state),
+    )?;
+
+    submit_bid(config, *bid_state, args, margin, cosigner, true)
+}
+
+/// Bids without a margin hold their SOL, bids with one need their escrow to cover at least one
+/// fill.
+fn check_bid_funds(
+    config: &CliConfig,
+    args: &BidInstructionArgs,
+    margin: Option<Pubkey>,
+    deposited: u64,
+    summary: String,
+) -> Result<()> {
+    let owner = config.keypair.pubkey();
+    let mut blockers = vec![];
+
+    if args.amount == 0 {
+        blockers.push("the bid price has to be more than 0".to_string());
+    }
+
+    if args.quantity == 0 {
+        blockers.push("the quantity has to be at least 1".to_string());
+    }
+
+    match margin {
+        Some(margin) => {
+            let escrow = fetch_escrow(config, &margin)?;
+
+            if escrow.account.owner != owner {
+                blockers.push(format!(
+                    "shared escrow {} is owned by {}, not the bidder",
+                    margin, escrow.account.owner
+                ));
+            }
+
+            if escrow.available < args.amount {
+                blockers.push(format!(
+                    "shared escrow {} only holds {}, less than one fill at {}",
+                    margin,
+                    Currency::SOL.format(escrow.available),
+                    Currency::SOL.format(args.amount)
+                ));
+            }
+        }
+        None => {
+            let total = args.amount.saturating_mul(args.quantity as u64);
+            let needed = total.saturating_sub(deposited);
+            let balance = config.client.get_balance(&owner)?;
+
+            if balance < needed {
+                blockers.push(format!(
+                    "it needs {} but {} only holds {}",
+                    Currency::SOL.format(needed),
+                    owner,
+                    Currency::SOL.format(balance)
+                ));
+            }
+        }
+    }
+
+    if blockers.is_empty() {
+        Ok(())
+    } else {
+        Err(blockers_error(summary, &blockers))
+    }
+}
+
+fn submit_bid(
+    config: &CliConfig,
+    bid_state: Pubkey,
+    args: BidInstructionArgs,
+    margin: Option<Pubkey>,
+    cosigner: Option<&Keypair>,
+    edited: bool,
+) -> Result<MarketBid> {
+    let owner = config.keypair.pubkey();
+    let (amount, quantity, expire_in_sec) = (args.amount, args.quantity, args.expire_in_sec);
+
+    let ix = Bid {
+        system_program: solana_sdk::system_program::id(),
+        marketplace_program: TENSOR_MARKETPLACE_ID,
+        bid_state,
+        owner,
+        // Bids without a margin pass the owner in its place.
+        shared_escrow: margin.unwrap_or(owner),
+        cosigner: cosigner.map(|cosigner| cosigner.pubkey()),
+        rent_payer: owner,
+    }
+    .instruction(args);
+
+    let signatures = send_instructions(config, vec![ix], &cosigner_signers(cosigner), &[])?;
+
+    let state = config.client.get_multiple_accounts(&[bid_state])?[0]
+        .as_ref()
+        .and_then(|account| deserialize_account::<BidState>(&account.data).ok());
+
+    Ok(MarketBid {
+        bid_state,
+        amount,
+        quantity,
+        expire_in_sec,
+        margin,
+        edited,
+        state,
+        signatures,
+    })
+}
+
+pub fn cancel_bid(args: MarketCancelBidParams) -> Result<MarketBidCancel> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    cancel_bid_with_config(&config, &args.bid_state)
+}
+
+/// Cancels a bid of the config keypair, returning its SOL and rent.
+pub fn cancel_bid_with_config(config: &CliConfig, bid_state: &Pubkey) -> Result<MarketBidCancel> {
+    let owner = config.keypair.pubkey();
+    let (bid, account) = fetch_bid(config, bid_state)?;
+
+    if bid.owner != owner {
+        return Err(blockers_error(
+            format!("Can't cancel bid {}", bid_state),
+            &[format!(
+                "it is owned by {}, sign with the owner keypair",
+                bid.owner
+            )],
+        ));
+    }
+
+    let ix = CancelBid {
+        bid_state: *bid_state,
+        owner,
+        system_program: solana_sdk::system_program::id(),
+        marketplace_program: TENSOR_MARKETPLACE_ID,
+        rent_destination: bid.rent_payer.to_option().unwrap_or(owner),
+    }
+    .instruction();
+
+    let signatures = send_instructions(config, vec![ix], &[], &[])?;
+
+    Ok(MarketBidCancel {
+        bid_state: *bid_state,
+        refund: account.lamports,
+        signatures,
+    })
+}
+
+pub fn take_bid(args: MarketTakeBidParams) -> Result<MarketBidTake> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+    let cosigner = read_cosigner(args.cosigner)?;
+
+    take_bid_with_config(
+        &config,
+        &args.bid_state,
+        &args.mint,
+        &args.terms,
+        cosigner.as_ref(),
+    )
+}
+
+/// Sells an asset of the config keypair into a bid. Everything that would make the sale fail is
+/// checked and reported together first.
+pub fn take_bid_with_config(
+    config: &CliConfig,
+    bid_state: &Pubkey,
+    mint: &Pubkey,
+    terms: &TakeBidTerms,
+    cosigner: Option<&Keypair>,
+) -> Result<MarketBidTake> {
+    let seller = config.keypair.pubkey();
+    let (bid, _) = fetch_bid(config, bid_state)?;
+
+    if bid.currency.is_some() {
+        return Err(anyhow!(
+            "Bid {} is in an SPL currency, which the marketplace can't settle bids in",
+            bid_state
+        ));
+    }
+
+    let min_amount = match &terms.min_price {
+        Some(min_price) => Currency::SOL.parse(min_price)?,
+        None => bid.amount,
+    };
+
+    let asset = fetch_market_asset(config, mint)?;
+    let mint_proof = match bid.target {
+        Target::Whitelist => whitelist_mint_proof(config, &bid.target_id, mint)?,
+        Target::AssetId => None,
+    };
+
+    let take = TakeAccounts {
+        seller,
+        bid_state: *bid_state,
+        owner: bid.owner,
+        rent_destination: bid.rent_payer.to_option().unwrap_or(bid.owner),
+        fee_vault: find_fee_vault_pda(bid_state),
+        shared_escrow: bid.margin.unwrap_or(bid.owner),
+        whitelist: (bid.target == Target::Whitelist).then_some(bid.target_id),
+        mint_proof,
+        taker_broker: terms.taker_broker,
+        maker_broker: bid.maker_broker,
+        cosigner: cosigner.map(|cosigner| cosigner.pubkey()),
+    };
+
+    check_takeable(
+        config,
+        &bid,
+        &take,
+        &asset,
+        min_amount,
+        terms.royalty_pct,
+        cosigner,
+    )?;
+
+    let creators = creator_accounts(config, &asset, &Currency::SOL)?;
+    let ix = take.take(&asset, min_amount, terms.royalty_pct, creators);
+
+    let signatures = send_instructions(config, vec![ix], &cosigner_signers(cosigner), &[])?;
+
+    Ok(MarketBidTake {
+        bid_state: *bid_state,
+        asset: *mint,
+        standard: asset.to_string(),
+        bidder: bid.owner,
+        amount: bid.amount,
+        min_amount,
+        signatures,
+    })
+}
+
+/// Mint proof of an asset for a whitelist bid, when the whitelist verifies mints by merkle proof.
+/// The proof itself is checked before taking the bid.
+fn whitelist_mint_proof(
+    config: &CliConfig,
+    whitelist: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Option<(Pubkey, bool)>> {
+    let data = config.client.get_account_data(whitelist)?;
+    let needs_proof = deserialize_account::<WhitelistV2>(&data)
+        .map_err(|e| anyhow!("Invalid whitelist {}: {}", whitelist, e))?
+        .conditions
+        .iter()
+        .any(|condition| condition.mode == Mode::MerkleTree);
+
+    if !needs_proof {
+        return Ok(None);
+    }
+
+    let address = MintProofV2::find_pda(mint, whitelist).0;
+    let exists = config.client.get_multiple_accounts(&[address])?[0].is_some();
+
+    Ok(Some((address, exists)))
+}
+
+fn check_takeable(
+    config: &CliConfig,
+    bid: &BidState,
+    take: &TakeAccounts,
+    asset: &MarketAsset,
+    min_amount: u64,
+    royalty_pct: Option<u16>,
+    cosigner: Option<&Keypair>,
+) -> Result<()> {
+    let seller = take.seller;
+    let mut blockers = vec![];
+
+    if bid.owner == seller {
+        blockers.push("it is your own bid, cancel it instead".to_string());
+    }
+
+    if bid_expired(bid, chrono::Utc::now().timestamp()) {
+        blockers.push("the bid has expired".to_string());
+    }
+
+    if bid.filled_quantity >= bid.quantity {
+        blockers.push(format!("all {} of the bid are filled", bid.quantity));
+    }
+
+    if let Some(taker) = bid.private_taker.filter(|taker| *taker != seller) {
+        blockers.push(format!("it is a private bid for {}", taker));
+    }
+
+    if bid.target == Target::AssetId && bid.target_id != asset.id() {
+        blockers.push(format!("it is a bid for {}", bid.target_id));
+    }
+
+    if let Some((_, false)) = take.mint_proof {
+        blockers.push(format!(
+            "{} has no mint proof for whitelist {}",
+            asset.id(),
+            bid.target_id
+        ));
+    }
+
+    if bid.amount < min_amount {
+        blockers.push(format!(
+            "it bids {}, below the min price of {}",
+            Currency::SOL.format(bid.amount),
+            Currency::SOL.format(min_amount)
+        ));
+    }
+
+    if royalty_pct.is_some_and(|pct| pct > 100) {
+        blockers.push("royalty percentage can't be more than 100".to_string());
+    }
+
+    if let Some(message) = cosigner_mismatch(bid.cosigner.to_option(), cosigner) {
+        blockers.push(message);
+    }
+
+    // Margin bids draw from the shared escrow when taken, so it has to cover the price.
+    if let Some(margin) = bid.margin {
+        let escrow = fetch_escrow(config, &margin)?;
+        if escrow.available < bid.amount {
+            blockers.push(format!(
+                "its shared escrow {} only holds {}",
+                margin,
+                Currency::SOL.format(escrow.available)
+            ));
+        }
+    }
+
+    let holder = match asset {
+        MarketAsset::Compressed(compressed) => Some(compressed.owner),
+        MarketAsset::Nft(nft) if nft.standard == AssetStandard::Core => nft.owner,
+        MarketAsset::Nft(nft) => config
+            .client
+            .get_multiple_accounts(&[nft.token_account(&seller)])?[0]
+            .as_ref()
+            .and_then(|account| token_account_amount(&account.data))
+            .filter(|amount| *amount == 1)
+            .map(|_| seller),
+    };
+
+    if holder != Some(seller) {
+        blockers.push(match holder {
+            Some(holder) => format!("it is owned by {}, sign with the owner keypair", holder),
+            None => format!("it is not in the associated token account of {}", seller),
+        });
+    }
+
+    if blockers.is_empty() {
+        Ok(())
+    } else {
+        Err(blockers_error(
+            format!("Can't sell {} into bid", asset.id()),
+            &blockers,
+        ))
+    }
+}
+
+/// Blocker for a cosigner the bid requires but wasn't passed, or that doesn't match.
+fn cosigner_mismatch(required: Option<Pubkey>, cosigner: Option<&Keypair>) -> Option<String> {
+    match (required, cosigner) {
+        (Some(required), None) => Some(format!(
+            "it needs cosigner {}, pass its keypair with --cosigner",
+            required
+        )),
+        (Some(required), Some(cosigner)) if cosigner.pubkey() != required => Some(format!(
+            "it needs cosigner {}, not {}",
+            required,
+            cosigner.pubkey()
+        )),
+        _ => None,
+    }
+}
+
+/// Fetches a bid with its account, whose lamports hold the SOL of bids without a margin.
+pub fn fetch_bid(config: &CliConfig, address: &Pubkey) -> Result<(BidState, Account)> {
+    let accounts = config.client.get_multiple_accounts(&[*address])?;
+    let account = accounts[0]
+        .clone()
+        .ok_or_else(|| anyhow!("Bid {} not found", address))?;
+
+    let bid = deserialize_account::<BidState>(&account.data)
+        .map_err(|e| anyhow!("Invalid bid {}: {}", address, e))?;
+
+    Ok((bid, account))
+}
+
+/// Accounts shared by the take bid instructions of every asset standard.
+struct TakeAccounts {
+    seller: Pubkey,
+    bid_state: Pubkey,
+    owner: Pubkey,
+    rent_destination: Pubkey,
+    fee_vault: Pubkey,
+    shared_escrow: Pubkey,
+    whitelist: Option<Pubkey>,
+    /// Mint proof PDA and whether it exists, for merkle tree whitelists.
+    mint_proof: Option<(Pubkey, bool)>,
+    taker_broker: Option<Pubkey>,
+    maker_broker: Option<Pubkey>,
+    cosigner: Option<Pubkey>,
+}
+
+impl TakeAccounts {
+    fn mint_proof(&self) -> Option<Pubkey> {
+        self.mint_proof.map(|(address, _)| address)
+    }
+
+    /// Required accounts the program treats as optional take its own id when not used.
+    fn or_program(account: Option<Pubkey>) -> Pubkey {
+        account.unwrap_or(TENSOR_MARKETPLACE_ID)
+    }
+
+    fn take(
+        &self,
+        asset: &MarketAsset,
+        min_amount: u64,
+        royalty_pct: Option<u16>,
+        creators: Vec<AccountMeta>,
+    ) -> Instruction {
+        match asset {
+            MarketAsset::Compressed(asset) => {
+                self.take_compressed(asset, min_amount, royalty_pct, creators)
+            }
+            MarketAsset::Nft(asset) => match asset.standard {
+                AssetStandard::Core => self.take_core(asset, min_amount, creators),
+                AssetStandard::Token2022 => self.take_t22(asset, min_amount),
+                AssetStandard::Legacy | AssetStandard::Programmable => {
+                    self.take_legacy(asset, min_amount, royalty_pct, creators)
+                }
+            },
+        }
+    }
+
+    fn take_legacy(
+        &self,
+        asset: &Asset,
+        min_amount: u64,
+        optional_royalty_pct: Option<u16>,
+        creators: Vec<AccountMeta>,
+    ) -> Instruction {
+        let seller_ta = asset.token_account(&self.seller);
+        let owner_ta = asset.token_account(&self.owner);
+        let bid_ta = asset.token_account(&self.bid_state);
+        let token_record = |token_account: &Pubkey| {
+            (asset.standard == AssetStandard::Programmable)
+                .then(|| find_token_record_pda(&asset.mint, token_account).0)
+        };
+
+        TakeBidLegacy {
+            fee_vault: self.fee_vault,
+            seller: self.seller,
+            bid_state: self.bid_state,
+            owner: self.owner,
+            taker_broker: self.taker_broker,
+            maker_broker: self.maker_broker,
+            shared_escrow: self.shared_escrow,
+            whitelist: self.whitelist,
+            seller_ta,
+            mint: asset.mint,
+            metadata: Metadata::find_pda(&asset.mint).0,
+            owner_ta,
+            edition: find_edition_pda(&asset.mint).0,
+            seller_token_record: token_record(&seller_ta),
+            owner_token_record: token_record(&owner_ta),
+            token_metadata_program: Some(TOKEN_METADATA_ID),
+            sysvar_instructions: Some(SYSVAR_INSTRUCTIONS),
+            authorization_rules_program: asset.rule_set.map(|_| TOKEN_AUTH_RULES_ID),
+            bid_ta,
+            bid_token_record: token_record(&bid_ta),
+            authorization_rules: asset.rule_set,
+            token_program: asset.token_program,
+            associated_token_program: ASSOCIATED_TOKEN_ID,
+            system_program: solana_sdk::system_program::id(),
+            marketplace_program: TENSOR_MARKETPLACE_ID,
+            escrow_program: TENSOR_ESCROW_ID,
+            cosigner: self.cosigner,
+            mint_proof: self.mint_proof(),
+            rent_destination: self.rent_destination,
+        }
+        .instruction_with_remaining_accounts(
+            TakeBidLegacyInstructionArgs {
+                min_amount,
+                optional_royalty_pct,
+                rules_acc_present: asset.rule_set.is_some(),
+                authorization_data: None,
+            },
+            &creators,
+        )
+    }
+
+    fn take_t22(&self, asset: &Asset, min_amount: u64) -> Instruction {
+        TakeBidT22 {
+            fee_vault: self.fee_vault,
+            seller: self.seller,
+            bid_state: self.bid_state,
+            owner: self.owner,
+            taker_broker: self.taker_broker,
+            maker_broker: self.maker_broker,
+            shared_escrow: self.shared_escrow,
+            whitelist: Self::or_program(self.whitelist),
+            seller_ta: asset.token_account(&self.seller),
+            mint: asset.mint,
+            owner_ta: asset.token_account(&self.owner),
+            token_program: asset.token_program,
+            associated_token_program: ASSOCIATED_TOKEN_ID,
+            system_program: solana_sdk::system_program::id(),
+            marketplace_program: TENSOR_MARKETPLACE_ID,
+            escrow_program: TENSOR_ESCROW_ID,
+            cosigner: self.cosigner,
+            mint_proof: Self::or_program(self.mint_proof()),
+            rent_destination: self.rent_destination,
+        }
+        .instruction(TakeBidT22InstructionArgs { min_amount })
+    }
+
+    fn take_core(&self, asset: &Asset, min_amount: u64, creators: Vec<AccountMeta>) -> Instruction {
+        TakeBidCore {
+            fee_vault: self.fee_vault,
+            seller: self.seller,
+            bid_state: self.bid_state,
+            owner: self.owner,
+            taker_broker: self.taker_broker,
+            maker_broker: self.maker_broker,
+            shared_escrow: self.shared_escrow,
+            whitelist: Self::or_program(self.whitelist),
+            asset: asset.mint,
+            collection: asset.collection,
+            mpl_core_program: MPL_CORE_ID,
+            system_program: solana_sdk::system_program::id(),
+            marketplace_program: TENSOR_MARKETPLACE_ID,
+            escrow_program: TENSOR_ESCROW_ID,
+            cosigner: self.cosigner,
+            mint_proof: Self::or_program(self.mint_proof()),
+            rent_destination: self.rent_destination,
+        }
+        .instruction_with_remaining_accounts(TakeBidCoreInstructionArgs { min_amount }, &creators)
+    }
+
+    /// Compressed takes pass the metadata hash rather than the full metadata, with the creators
+    /// followed by the proof as remaining accounts.
+    fn take_compressed(
+        &self,
+        asset: &CompressedAsset,
+        min_amount: u64,
+        optional_royalty_pct: Option<u16>,
+        mut creators: Vec<AccountMeta>,
+    ) -> Instruction {
+        creators.extend(asset.proof_accounts());
+        let remaining_accounts = creators;
+
+        TakeBidCompressedMetaHash {
+            fee_vault: self.fee_vault,
+            tree_authority: asset.tree_authority(),
+            seller: (self.seller, true),
+            delegate: (asset.delegate, asset.delegate == self.seller),
+            merkle_tree: asset.merkle_tree,
+            log_wrapper: NOOP_ID,
+            compression_program: ACCOUNT_COMPRESSION_ID,
+            system_program: solana_sdk::system_program::id(),
+            bubblegum_program: BUBBLEGUM_ID,
+            marketplace_program: TENSOR_MARKETPLACE_ID,
+            tensorswap_program: TENSOR_ESCROW_ID,
+            bid_state: self.bid_state,
+            owner: self.owner,
+            taker_broker: self.taker_broker,
+            maker_broker: self.maker_broker,
+            shared_escrow: self.shared_escrow,
+            whitelist: Self::or_program(self.whitelist),
+            cosigner: self.cosigner,
+            rent_destination: self.rent_destination,
+        }
+        .instruction_with_remaining_accounts(
+            TakeBidCompressedMetaHashInstructionArgs {
+                nonce: asset.nonce(),
+                index: asset.leaf_index,
+                root: asset.root,
+                meta_hash: asset.metadata_hash,
+                creator_shares: asset.creators.iter().map(|c| c.share).collect(),
+                creator_verified: asset.creators.iter().map(|c| c.verified).collect(),
+                seller_fee_basis_points: asset.seller_fee_basis_points,
+                min_amount,
+                optional_royalty_pct,
+            },
+            &remaining_accounts,
+        )
+    }
+}
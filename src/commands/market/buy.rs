@@ -0,0 +1,538 @@
+use super::*;
+
+use solana_sdk::instruction::Instruction;
+use tensor_marketplace::{
+    instructions::{
+        BuyCompressed, BuyCompressedInstructionArgs, BuyCore, BuyCoreInstructionArgs, BuyCoreSpl,
+        BuyCoreSplInstructionArgs, BuyLegacy, BuyLegacyInstructionArgs, BuyLegacySpl,
+        BuyLegacySplInstructionArgs, BuySplCompressed, BuySplCompressedInstructionArgs, BuyT22,
+        BuyT22InstructionArgs, BuyT22Spl, BuyT22SplInstructionArgs,
+    },
+    programs::TENSOR_MARKETPLACE_ID,
+};
+
+use crate::{
+    commands::{
+        blockers_error, find_fee_vault_pda, token_account_amount, ACCOUNT_COMPRESSION_ID,
+        ASSOCIATED_TOKEN_ID, BUBBLEGUM_ID, MPL_CORE_ID, NOOP_ID, SYSVAR_INSTRUCTIONS,
+        TOKEN_AUTH_RULES_ID, TOKEN_METADATA_ID,
+    },
+    transactions::send_instructions,
+    types::metaplex::{find_edition_pda, find_token_record_pda},
+};
+
+pub struct MarketBuyParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub mint: Pubkey,
+    pub terms: BuyTerms,
+    pub cosigner: Option<PathBuf>,
+}
+
+/// Optional terms of a purchase. The max price defaults to the listed price, so a listing edited
+/// in the meantime fails instead of costing more.
+#[derive(Debug, Clone, Default)]
+pub struct BuyTerms {
+    pub max_price: Option<String>,
+    pub royalty_pct: Option<u16>,
+    pub taker_broker: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketPurchase {
+    pub list_state: Pubkey,
+    pub asset: Pubkey,
+    pub standard: String,
+    pub seller: Pubkey,
+    pub amount: u64,
+    pub max_amount: u64,
+    pub currency: Currency,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn buy_listing(args: MarketBuyParams) -> Result<MarketPurchase> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+    let cosigner = read_cosigner(args.cosigner)?;
+
+    buy_listing_with_config(&config, &args.mint, &args.terms, cosigner.as_ref())
+}
+
+/// Buys a listed asset with the config keypair, paying the listed price plus fees and royalties.
+/// Everything that would make the purchase fail is checked and reported together first.
+pub fn buy_listing_with_config(
+    config: &CliConfig,
+    mint: &Pubkey,
+    terms: &BuyTerms,
+    cosigner: Option<&Keypair>,
+) -> Result<MarketPurchase> {
+    let buyer = config.keypair.pubkey();
+    let (list_state, listing) = fetch_listing(config, mint)?;
+    let currency = fetch_currency(config, listing.currency)?;
+
+    let max_amount = match &terms.max_price {
+        Some(max_price) => currency.parse(max_price)?,
+        None => listing.amount,
+    };
+
+    check_buyable(
+        config, &listing, &currency, &buyer, max_amount, terms, cosigner,
+    )?;
+
+    let asset = fetch_market_asset(config, mint)?;
+    let creators = creator_accounts(config, &asset, &currency)?;
+
+    let accounts = BuyAccounts {
+        buyer,
+        list_state,
+        owner: listing.owner,
+        rent_destination: listing.rent_payer.to_option().unwrap_or(listing.owner),
+        fee_vault: find_fee_vault_pda(&list_state),
+        taker_broker: terms.taker_broker,
+        maker_broker: listing.maker_broker,
+        cosigner: cosigner.map(|cosigner| cosigner.pubkey()),
+        currency,
+    };
+
+    let ix = accounts.buy(&asset, max_amount, terms.royalty_pct, creators);
+
+    let signatures = send_instructions(config, vec![ix], &cosigner_signers(cosigner), &[])?;
+
+    Ok(MarketPurchase {
+        list_state,
+        asset: *mint,
+        standard: asset.to_string(),
+        seller: listing.owner,
+        amount: listing.amount,
+        max_amount,
+        currency,
+        signatures,
+    })
+}
+
+fn check_buyable(
+    config: &CliConfig,
+    listing: &ListState,
+    currency: &Currency,
+    buyer: &Pubkey,
+    max_amount: u64,
+    terms: &BuyTerms,
+    cosigner: Option<&Keypair>,
+) -> Result<()> {
+    let mut blockers = vec![];
+
+    if listing.owner == *buyer {
+        blockers.push("it is your own listing, delist it instead".to_string());
+    }
+
+    if listing_expired(listing, chrono::Utc::now().timestamp()) {
+        blockers.push("the listing has expired".to_string());
+    }
+
+    if let Some(taker) = listing.private_taker.filter(|taker| taker != buyer) {
+        blockers.push(format!("it is a private listing for {}", taker));
+    }
+
+    if listing.amount > max_amount {
+        blockers.push(format!(
+            "it is listed at {}, above the max price of {}",
+            currency.format(listing.amount),
+            currency.format(max_amount)
+        ));
+    }
+
+    if terms.royalty_pct.is_some_and(|pct| pct > 100) {
+        blockers.push("royalty percentage can't be more than 100".to_string());
+    }
+
+    match (listing.cosigner.to_option(), cosigner) {
+        (Some(required), None) => blockers.push(format!(
+            "it needs cosigner {}, pass its keypair with --cosigner",
+            required
+        )),
+        (Some(required), Some(cosigner)) if cosigner.pubkey() != required => blockers.push(
+            format!("it needs cosigner {}, not {}", required, cosigner.pubkey()),
+        ),
+        _ => {}
+    }
+
+    let balance = match currency.token_account(buyer) {
+        None => config.client.get_balance(buyer)?,
+        Some(token_account) => config.client.get_multiple_accounts(&[token_account])?[0]
+            .as_ref()
+            .and_then(|account| token_account_amount(&account.data))
+            .unwrap_or(0),
+    };
+
+    if balance < listing.amount {
+        blockers.push(format!(
+            "it costs {} but {} only holds {}",
+            currency.format(listing.amount),
+            buyer,
+            currency.format(balance)
+        ));
+    }
+
+    if blockers.is_empty() {
+        Ok(())
+    } else {
+        Err(blockers_error(
+            format!("Can't buy {}", listing.asset_id),
+            &blockers,
+        ))
+    }
+}
+
+/// Accounts shared by the buy instructions of every asset standard and currency.
+struct BuyAccounts {
+    buyer: Pubkey,
+    list_state: Pubkey,
+    owner: Pubkey,
+    rent_destination: Pubkey,
+    fee_vault: Pubkey,
+    taker_broker: Option<Pubkey>,
+    maker_broker: Option<Pubkey>,
+    cosigner: Option<Pubkey>,
+    currency: Currency,
+}
+
+impl BuyAccounts {
+    /// Currency token account of `wallet`, for SPL purchases only.
+    fn currency_ta(&self, wallet: &Pubkey) -> Pubkey {
+        self.currency
+            .token_account(wallet)
+            .expect("SPL currency has a mint")
+    }
+
+    fn broker_tas(&self) -> (Option<Pubkey>, Option<Pubkey>) {
+        (
+            self.taker_broker.map(|broker| self.currency_ta(&broker)),
+            self.maker_broker.map(|broker| self.currency_ta(&broker)),
+        )
+    }
+
+    fn buy(
+        &self,
+        asset: &MarketAsset,
+        max_amount: u64,
+        royalty_pct: Option<u16>,
+        creators: Vec<AccountMeta>,
+    ) -> Instruction {
+        match asset {
+            MarketAsset::Compressed(asset) => {
+                self.buy_compressed(asset, max_amount, royalty_pct, creators)
+            }
+            MarketAsset::Nft(asset) => match asset.standard {
+                AssetStandard::Core => self.buy_core(asset, max_amount, creators),
+                AssetStandard::Token2022 => self.buy_t22(asset, max_amount),
+                AssetStandard::Legacy | AssetStandard::Programmable => {
+                    self.buy_legacy(asset, max_amount, royalty_pct, creators)
+                }
+            },
+        }
+    }
+
+    fn buy_legacy(
+        &self,
+        asset: &Asset,
+        max_amount: u64,
+        optional_royalty_pct: Option<u16>,
+        creators: Vec<AccountMeta>,
+    ) -> Instruction {
+        let buyer_ta = asset.token_account(&self.buyer);
+        let list_ta = asset.token_account(&self.list_state);
+        let (buyer_token_record, list_token_record) =
+            if asset.standard == AssetStandard::Programmable {
+                (
+                    Some(find_token_record_pda(&asset.mint, &buyer_ta).0),
+                    Some(find_token_record_pda(&asset.mint, &list_ta).0),
+                )
+            } else {
+                (None, None)
+            };
+
+        match self.currency.mint {
+            None => BuyLegacy {
+                fee_vault: self.fee_vault,
+                buyer: self.buyer,
+                buyer_ta,
+                list_ta,
+                list_state: self.list_state,
+                mint: asset.mint,
+                owner: self.owner,
+                payer: self.buyer,
+                taker_broker: self.taker_broker,
+                maker_broker: self.maker_broker,
+                rent_destination: self.rent_destination,
+                token_program: asset.token_program,
+                associated_token_program: ASSOCIATED_TOKEN_ID,
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                system_program: solana_sdk::system_program::id(),
+                metadata: Metadata::find_pda(&asset.mint).0,
+                edition: find_edition_pda(&asset.mint).0,
+                buyer_token_record,
+                list_token_record,
+                authorization_rules: asset.rule_set,
+                authorization_rules_program: asset.rule_set.map(|_| TOKEN_AUTH_RULES_ID),
+                token_metadata_program: Some(TOKEN_METADATA_ID),
+                sysvar_instructions: Some(SYSVAR_INSTRUCTIONS),
+                cosigner: self.cosigner,
+            }
+            .instruction_with_remaining_accounts(
+                BuyLegacyInstructionArgs {
+                    max_amount,
+                    optional_royalty_pct,
+                    authorization_data: None,
+                },
+                &creators,
+            ),
+            Some(currency) => BuyLegacySpl {
+                fee_vault: self.fee_vault,
+                fee_vault_currency_ta: self.currency_ta(&self.fee_vault),
+                buyer: self.buyer,
+                buyer_ta,
+                list_ta,
+                list_state: self.list_state,
+                mint: asset.mint,
+                currency,
+                owner: self.owner,
+                owner_currency_ta: self.currency_ta(&self.owner),
+                payer: self.buyer,
+                payer_currency_ta: self.currency_ta(&self.buyer),
+                maker_broker: self.maker_broker,
+                taker_broker: self.taker_broker,
+                rent_destination: self.rent_destination,
+                token_program: asset.token_program,
+                currency_token_program: self.currency.token_program,
+                associated_token_program: ASSOCIATED_TOKEN_ID,
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                system_program: solana_sdk::system_program::id(),
+                metadata: Metadata::find_pda(&asset.mint).0,
+                edition: find_edition_pda(&asset.mint).0,
+                buyer_token_record,
+                list_token_record,
+                authorization_rules: asset.rule_set,
+                authorization_rules_program: asset.rule_set.map(|_| TOKEN_AUTH_RULES_ID),
+                token_metadata_program: Some(TOKEN_METADATA_ID),
+                sysvar_instructions: Some(SYSVAR_INSTRUCTIONS),
+                cosigner: self.cosigner,
+            }
+            .instruction_with_remaining_accounts(
+                BuyLegacySplInstructionArgs {
+                    max_amount,
+                    optional_royalty_pct,
+                    authorization_data: None,
+                },
+                &creators,
+            ),
+        }
+    }
+
+    fn buy_t22(&self, asset: &Asset, max_amount: u64) -> Instruction {
+        match self.currency.mint {
+            None => BuyT22 {
+                fee_vault: self.fee_vault,
+                buyer: self.buyer,
+                buyer_ta: asset.token_account(&self.buyer),
+                list_state: self.list_state,
+                list_ta: asset.token_account(&self.list_state),
+                mint: asset.mint,
+                owner: self.owner,
+                payer: self.buyer,
+                taker_broker: self.taker_broker,
+                maker_broker: self.maker_broker,
+                rent_destination: self.rent_destination,
+                token_program: asset.token_program,
+                associated_token_program: ASSOCIATED_TOKEN_ID,
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                system_program: solana_sdk::system_program::id(),
+                cosigner: self.cosigner,
+            }
+            .instruction(BuyT22InstructionArgs { max_amount }),
+            Some(currency) => {
+                let (taker_broker_currency_ta, maker_broker_currency_ta) = self.broker_tas();
+
+                BuyT22Spl {
+                    fee_vault: self.fee_vault,
+                    fee_vault_currency_ta: self.currency_ta(&self.fee_vault),
+                    buyer: self.buyer,
+                    buyer_ta: asset.token_account(&self.buyer),
+                    list_state: self.list_state,
+                    list_ta: asset.token_account(&self.list_state),
+                    mint: asset.mint,
+                    currency,
+                    owner: self.owner,
+                    owner_currency_ta: self.currency_ta(&self.owner),
+                    payer: self.buyer,
+                    payer_currency_ta: self.currency_ta(&self.buyer),
+                    taker_broker: self.taker_broker,
+                    taker_broker_currency_ta,
+                    maker_broker: self.maker_broker,
+                    maker_broker_currency_ta,
+                    rent_destination: self.rent_destination,
+                    token_program: asset.token_program,
+                    currency_token_program: self.currency.token_program,
+                    associated_token_program: ASSOCIATED_TOKEN_ID,
+                    marketplace_program: TENSOR_MARKETPLACE_ID,
+                    system_program: solana_sdk::system_program::id(),
+                    cosigner: self.cosigner,
+                }
+                .instruction(BuyT22SplInstructionArgs { max_amount })
+            }
+        }
+    }
+
+    fn buy_core(&self, asset: &Asset, max_amount: u64, creators: Vec<AccountMeta>) -> Instruction {
+        match self.currency.mint {
+            None => BuyCore {
+                fee_vault: self.fee_vault,
+                list_state: self.list_state,
+                asset: asset.mint,
+                collection: asset.collection,
+                buyer: self.buyer,
+                payer: self.buyer,
+                owner: self.owner,
+                taker_broker: self.taker_broker,
+                maker_broker: self.maker_broker,
+                rent_destination: self.rent_destination,
+                mpl_core_program: MPL_CORE_ID,
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                system_program: solana_sdk::system_program::id(),
+                cosigner: self.cosigner,
+            }
+            .instruction_with_remaining_accounts(BuyCoreInstructionArgs { max_amount }, &creators),
+            Some(currency) => {
+                let (taker_broker_ta, maker_broker_ta) = self.broker_tas();
+
+                BuyCoreSpl {
+                    fee_vault: self.fee_vault,
+                    fee_vault_currency_ta: self.currency_ta(&self.fee_vault),
+                    buyer: self.buyer,
+                    list_state: self.list_state,
+                    asset: asset.mint,
+                    collection: asset.collection,
+                    currency,
+                    owner: self.owner,
+                    owner_currency_ta: self.currency_ta(&self.owner),
+                    payer: self.buyer,
+                    payer_currency_ta: self.currency_ta(&self.buyer),
+                    taker_broker: self.taker_broker,
+                    taker_broker_ta,
+                    maker_broker: self.maker_broker,
+                    maker_broker_ta,
+                    rent_destination: self.rent_destination,
+                    token_program: self.currency.token_program,
+                    associated_token_program: ASSOCIATED_TOKEN_ID,
+                    mpl_core_program: MPL_CORE_ID,
+                    marketplace_program: TENSOR_MARKETPLACE_ID,
+                    system_program: solana_sdk::system_program::id(),
+                    cosigner: self.cosigner,
+                }
+                .instruction_with_remaining_accounts(
+                    BuyCoreSplInstructionArgs { max_amount },
+                    &creators,
+                )
+            }
+        }
+    }
+
+    /// Compressed purchases take the creators followed by the proof as remaining accounts.
+    fn buy_compressed(
+        &self,
+        asset: &CompressedAsset,
+        max_amount: u64,
+        optional_royalty_pct: Option<u16>,
+        mut creators: Vec<AccountMeta>,
+    ) -> Instruction {
+        creators.extend(asset.proof_accounts());
+        let remaining_accounts = creators;
+
+        let creator_shares = asset.creators.iter().map(|c| c.share).collect();
+        let creator_verified = asset.creators.iter().map(|c| c.verified).collect();
+
+        match self.currency.mint {
+            None => BuyCompressed {
+                fee_vault: self.fee_vault,
+                tree_authority: asset.tree_authority(),
+                merkle_tree: asset.merkle_tree,
+                log_wrapper: NOOP_ID,
+                compression_program: ACCOUNT_COMPRESSION_ID,
+                system_program: solana_sdk::system_program::id(),
+                bubblegum_program: BUBBLEGUM_ID,
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                list_state: self.list_state,
+                buyer: self.buyer,
+                payer: self.buyer,
+                owner: self.owner,
+                taker_broker: self.taker_broker,
+                maker_broker: self.maker_broker,
+                rent_destination: self.rent_destination,
+                cosigner: self.cosigner,
+            }
+            .instruction_with_remaining_accounts(
+                BuyCompressedInstructionArgs {
+                    nonce: asset.nonce(),
+                    index: asset.leaf_index,
+                    root: asset.root,
+                    meta_hash: asset.metadata_hash,
+                    creator_shares,
+                    creator_verified,
+                    seller_fee_basis_points: asset.seller_fee_basis_points,
+                    max_amount,
+                    optional_royalty_pct,
+                },
+                &remaining_accounts,
+            ),
+            Some(currency) => {
+                let (taker_broker_currency_ta, maker_broker_currency_ta) = self.broker_tas();
+
+                BuySplCompressed {
+                    fee_vault: self.fee_vault,
+                    fee_vault_ta: self.currency_ta(&self.fee_vault),
+                    tree_authority: asset.tree_authority(),
+                    merkle_tree: asset.merkle_tree,
+                    log_wrapper: NOOP_ID,
+                    compression_program: ACCOUNT_COMPRESSION_ID,
+                    system_program: solana_sdk::system_program::id(),
+                    bubblegum_program: BUBBLEGUM_ID,
+                    marketplace_program: TENSOR_MARKETPLACE_ID,
+                    token_program: self.currency.token_program,
+                    associated_token_program: ASSOCIATED_TOKEN_ID,
+                    list_state: self.list_state,
+                    buyer: self.buyer,
+                    payer: self.buyer,
+                    payer_source: self.currency_ta(&self.buyer),
+                    owner: self.owner,
+                    owner_destination: self.currency_ta(&self.owner),
+                    currency,
+                    taker_broker: self.taker_broker,
+                    taker_broker_currency_ta,
+                    maker_broker: self.maker_broker,
+                    maker_broker_currency_ta,
+                    rent_destination: self.rent_destination,
+                    rent_payer: self.buyer,
+                    cosigner: self.cosigner,
+                }
+                .instruction_with_remaining_accounts(
+                    BuySplCompressedInstructionArgs {
+                        nonce: asset.nonce(),
+                        index: asset.leaf_index,
+                        root: asset.root,
+                        meta_hash: asset.metadata_hash,
+                        creator_shares,
+                        creator_verified,
+                        seller_fee_basis_points: asset.seller_fee_basis_points,
+                        max_amount,
+                        optional_royalty_pct,
+                    },
+                    &remaining_accounts,
+                )
+            }
+        }
+    }
+}
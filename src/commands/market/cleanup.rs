@@ -0,0 +1,366 @@
+use super::*;
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use solana_sdk::instruction::Instruction;
+use tensor_marketplace::{
+    instructions::{
+        CloseExpiredBid, CloseExpiredListingCompressed,
+        CloseExpiredListingCompressedInstructionArgs, CloseExpiredListingCore,
+        CloseExpiredListingLegacy, CloseExpiredListingLegacyInstructionArgs,
+        CloseExpiredListingT22,
+    },
+    programs::TENSOR_MARKETPLACE_ID,
+};
+
+use crate::{
+    commands::{
+        ACCOUNT_COMPRESSION_ID, ASSOCIATED_TOKEN_ID, BUBBLEGUM_ID, MPL_CORE_ID, NOOP_ID,
+        SYSVAR_INSTRUCTIONS, TOKEN_AUTH_RULES_ID, TOKEN_METADATA_ID,
+    },
+    spinner::pb_with_len,
+    transactions::{pack_instructions, send_instructions},
+    types::metaplex::{find_edition_pda, find_token_record_pda},
+};
+
+pub struct MarketCleanupParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub cosigned_by: Option<Pubkey>,
+    pub dry_run: bool,
+}
+
+/// What an expired order is, with what its close instruction needs.
+#[derive(Debug, Clone)]
+pub enum ExpiredOrderKind {
+    Listing(ListState),
+    Bid(BidState),
+}
+
+/// An expired listing or bid and what closing it returns.
+#[derive(Debug, Clone)]
+pub struct ExpiredOrder {
+    pub address: Pubkey,
+    pub kind: ExpiredOrderKind,
+    /// Rent of the account, returned to whoever paid it.
+    pub rent: u64,
+    /// SOL a bid without a margin account still holds, returned to its owner.
+    pub escrowed: u64,
+}
+
+impl ExpiredOrder {
+    pub fn owner(&self) -> Pubkey {
+        match &self.kind {
+            ExpiredOrderKind::Listing(listing) => listing.owner,
+            ExpiredOrderKind::Bid(bid) => bid.owner,
+        }
+    }
+
+    pub fn expiry(&self) -> i64 {
+        match &self.kind {
+            ExpiredOrderKind::Listing(listing) => listing.expiry,
+            ExpiredOrderKind::Bid(bid) => bid.expiry,
+        }
+    }
+}
+
+/// Expired orders found for cleanup, listings first.
+#[derive(Debug, Clone, Default)]
+pub struct CleanupPlan {
+    pub orders: Vec<ExpiredOrder>,
+}
+
+impl CleanupPlan {
+    pub fn rent(&self) -> u64 {
+        self.orders.iter().map(|order| order.rent).sum()
+    }
+
+    pub fn escrowed(&self) -> u64 {
+        self.orders.iter().map(|order| order.escrowed).sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketCleanup {
+    pub plan: CleanupPlan,
+    pub applied: bool,
+    pub closed: Vec<Pubkey>,
+    /// Orders left open and why. They are found again on the next run, so rerunning the cleanup
+    /// picks up where this one stopped.
+    pub failed: Vec<(Pubkey, String)>,
+    pub signatures: Vec<Signature>,
+}
+
+/// Finds the expired orders and closes them once `confirm` agrees to the plan. Leaves showing
+/// the plan and asking to the caller.
+pub fn market_cleanup(
+    args: MarketCleanupParams,
+    confirm: impl FnOnce(&CleanupPlan) -> Result<bool>,
+) -> Result<MarketCleanup> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let plan = find_expired_orders(&config, args.cosigned_by)?;
+
+    if plan.orders.is_empty() || args.dry_run {
+        return Ok(MarketCleanup::skipped(plan));
+    }
+
+    if !confirm(&plan)? {
+        return Ok(MarketCleanup::skipped(plan));
+    }
+
+    close_expired_orders(&config, plan)
+}
+
+impl MarketCleanup {
+    fn skipped(plan: CleanupPlan) -> Self {
+        MarketCleanup {
+            plan,
+            applied: false,
+            closed: vec![],
+            failed: vec![],
+            signatures: vec![],
+        }
+    }
+}
+
+/// Finds the expired listings and bids of the config keypair. Closing an expired order doesn't
+/// need its owner to sign, so with `cosigned_by` every expired order with that cosigner is
+/// found instead, whoever owns it.
+pub fn find_expired_orders(config: &CliConfig, cosigned_by: Option<Pubkey>) -> Result<CleanupPlan> {
+    let owner = cosigned_by.is_none().then(|| config.keypair.pubkey());
+    let in_scope = |cosigner: Option<Pubkey>| cosigned_by.is_none() || cosigner == cosigned_by;
+    let now = chrono::Utc::now().timestamp();
+
+    let mut orders = vec![];
+
+    let mut listings = fetch_listings(
+        config,
+        &ListingFilters {
+            owner,
+            ..Default::default()
+        },
+    )?;
+    listings.sort_by_key(|entry| entry.address.to_string());
+
+    for entry in listings {
+        if listing_expired(&entry.listing, now) && in_scope(entry.listing.cosigner.to_option()) {
+            orders.push(ExpiredOrder {
+                address: entry.address,
+                kind: ExpiredOrderKind::Listing(entry.listing),
+                rent: entry.lamports,
+                escrowed: 0,
+            });
+        }
+    }
+
+    let mut bids = fetch_bids(
+        config,
+        &BidFilters {
+            owner,
+            ..Default::default()
+        },
+    )?;
+    bids.sort_by_key(|entry| entry.address.to_string());
+
+    let mut rents = HashMap::new();
+
+    for entry in bids {
+        if !bid_expired(&entry.bid, now) || !in_scope(entry.bid.cosigner.to_option()) {
+            continue;
+        }
+
+        let rent = match rents.entry(entry.space) {
+            Entry::Occupied(rent) => *rent.get(),
+            Entry::Vacant(rent) => *rent.insert(
+                config
+                    .client
+                    .get_minimum_balance_for_rent_exemption(entry.space)?,
+            ),
+        };
+
+        orders.push(ExpiredOrder {
+            address: entry.address,
+            kind: ExpiredOrderKind::Bid(entry.bid),
+            rent: rent.min(entry.lamports),
+            escrowed: entry.lamports.saturating_sub(rent),
+        });
+    }
+
+    Ok(CleanupPlan { orders })
+}
+
+/// Closes the orders in as few transactions as fit, sending them one at a time. A listing whose
+/// asset can't be read or a transaction that fails leaves its orders open without stopping the
+/// rest, so a rerun only has those left to close.
+pub fn close_expired_orders(config: &CliConfig, plan: CleanupPlan) -> Result<MarketCleanup> {
+    let payer = config.keypair.pubkey();
+
+    let mut failed = vec![];
+    let mut closable = vec![];
+
+    for order in &plan.orders {
+        match close_instruction(config, &payer, order) {
+            Ok(ix) => closable.push((order.address, ix)),
+            Err(e) => failed.push((order.address, e.to_string())),
+        }
+    }
+
+    let (addresses, instructions): (Vec<Pubkey>, Vec<Instruction>) = closable.into_iter().unzip();
+    let batches = pack_instructions(&payer, instructions, &[])?;
+
+    let progress = pb_with_len("Closing expired orders", addresses.len() as u64)?;
+
+    let mut closed = vec![];
+    let mut signatures = vec![];
+    let mut remaining = addresses.as_slice();
+
+    for batch in batches {
+        let (batch_addresses, rest) = remaining.split_at(batch.len());
+        remaining = rest;
+
+        match send_instructions(config, batch, &[], &[]) {
+            Ok(batch_signatures) => {
+                closed.extend_from_slice(batch_addresses);
+                signatures.extend(batch_signatures);
+            }
+            Err(e) => failed.extend(
+                batch_addresses
+                    .iter()
+                    .map(|address| (*address, e.to_string())),
+            ),
+        }
+
+        progress.inc(batch_addresses.len() as u64);
+    }
+
+    progress.finish_and_clear();
+
+    Ok(MarketCleanup {
+        plan,
+        applied: true,
+        closed,
+        failed,
+        signatures,
+    })
+}
+
+/// Builds the close instruction of an expired order. The rent goes back to whoever paid it and
+/// a listed asset back to its owner.
+fn close_instruction(
+    config: &CliConfig,
+    payer: &Pubkey,
+    order: &ExpiredOrder,
+) -> Result<Instruction> {
+    let listing = match &order.kind {
+        ExpiredOrderKind::Bid(bid) => {
+            return Ok(CloseExpiredBid {
+                bid_state: order.address,
+                owner: bid.owner,
+                system_program: solana_sdk::system_program::id(),
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                rent_destination: bid.rent_payer.to_option().unwrap_or(bid.owner),
+            }
+            .instruction());
+        }
+        ExpiredOrderKind::Listing(listing) => listing,
+    };
+
+    let owner = listing.owner;
+    let list_state = order.address;
+    let rent_destination = listing.rent_payer.to_option().unwrap_or(owner);
+
+    let asset = match fetch_market_asset(config, &listing.asset_id)? {
+        MarketAsset::Compressed(asset) => {
+            return Ok(CloseExpiredListingCompressed {
+                list_state,
+                owner,
+                system_program: solana_sdk::system_program::id(),
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                tree_authority: asset.tree_authority(),
+                merkle_tree: asset.merkle_tree,
+                log_wrapper: NOOP_ID,
+                compression_program: ACCOUNT_COMPRESSION_ID,
+                bubblegum_program: BUBBLEGUM_ID,
+                rent_destination,
+            }
+            .instruction_with_remaining_accounts(
+                CloseExpiredListingCompressedInstructionArgs {
+                    nonce: asset.nonce(),
+                    index: asset.leaf_index,
+                    root: asset.root,
+                    data_hash: asset.data_hash,
+                    creator_hash: asset.creator_hash,
+                },
+                &asset.proof_accounts(),
+            ));
+        }
+        MarketAsset::Nft(asset) => asset,
+    };
+
+    Ok(match asset.standard {
+        AssetStandard::Core => CloseExpiredListingCore {
+            list_state,
+            asset: asset.mint,
+            collection: asset.collection,
+            owner,
+            mpl_core_program: MPL_CORE_ID,
+            system_program: solana_sdk::system_program::id(),
+            marketplace_program: TENSOR_MARKETPLACE_ID,
+            rent_destination,
+        }
+        .instruction(),
+        AssetStandard::Token2022 => CloseExpiredListingT22 {
+            owner,
+            owner_ta: asset.token_account(&owner),
+            list_state,
+            list_ta: asset.token_account(&list_state),
+            mint: asset.mint,
+            rent_destination,
+            payer: *payer,
+            token_program: asset.token_program,
+            associated_token_program: ASSOCIATED_TOKEN_ID,
+            system_program: solana_sdk::system_program::id(),
+            marketplace_program: TENSOR_MARKETPLACE_ID,
+        }
+        .instruction(),
+        AssetStandard::Legacy | AssetStandard::Programmable => {
+            let programmable = asset.standard == AssetStandard::Programmable;
+            let token_record = |wallet: &Pubkey| {
+                programmable
+                    .then(|| find_token_record_pda(&asset.mint, &asset.token_account(wallet)).0)
+            };
+
+            CloseExpiredListingLegacy {
+                owner,
+                owner_ta: asset.token_account(&owner),
+                list_state,
+                list_ta: asset.token_account(&list_state),
+                mint: asset.mint,
+                payer: *payer,
+                rent_destination,
+                token_program: asset.token_program,
+                associated_token_program: ASSOCIATED_TOKEN_ID,
+                system_program: solana_sdk::system_program::id(),
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                metadata: Metadata::find_pda(&asset.mint).0,
+                edition: find_edition_pda(&asset.mint).0,
+                owner_token_record: token_record(&owner),
+                list_token_record: token_record(&list_state),
+                authorization_rules: asset.rule_set,
+                authorization_rules_program: asset.rule_set.map(|_| TOKEN_AUTH_RULES_ID),
+                token_metadata_program: Some(TOKEN_METADATA_ID),
+                sysvar_instructions: Some(SYSVAR_INSTRUCTIONS),
+            }
+            .instruction(CloseExpiredListingLegacyInstructionArgs {
+                authorization_data: None,
+            })
+        }
+    })
+}
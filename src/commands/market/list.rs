@@ -0,0 +1,562 @@
+use super::*;
+
+use solana_sdk::instruction::Instruction;
+use tensor_marketplace::{
+    instructions::{
+        DelistCompressed, DelistCompressedInstructionArgs, DelistCore, DelistLegacy,
+        DelistLegacyInstructionArgs, DelistT22, Edit, EditInstructionArgs, ListCompressed,
+        ListCompressedInstructionArgs, ListCore, ListCoreInstructionArgs, ListLegacy,
+        ListLegacyInstructionArgs, ListT22, ListT22InstructionArgs,
+    },
+    programs::TENSOR_MARKETPLACE_ID,
+};
+
+use crate::{
+    commands::{
+        blockers_error, token_account_amount, ACCOUNT_COMPRESSION_ID, ASSOCIATED_TOKEN_ID,
+        BUBBLEGUM_ID, MPL_CORE_ID, NOOP_ID, SYSVAR_INSTRUCTIONS, TOKEN_AUTH_RULES_ID,
+        TOKEN_METADATA_ID,
+    },
+    transactions::send_instructions,
+    types::metaplex::{find_edition_pda, find_token_record_pda},
+};
+
+pub struct MarketListParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub mint: Pubkey,
+    pub price: String,
+    pub terms: ListingTerms,
+    pub cosigner: Option<PathBuf>,
+}
+
+pub struct MarketEditListingParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub mint: Pubkey,
+    pub price: Option<String>,
+    pub terms: ListingTerms,
+}
+
+pub struct MarketDelistParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub mint: Pubkey,
+}
+
+/// A listing created or edited.
+#[derive(Debug, Clone)]
+pub struct MarketListing {
+    pub list_state: Pubkey,
+    pub asset: Pubkey,
+    /// Asset standard, not looked up when editing.
+    pub standard: Option<String>,
+    pub amount: u64,
+    pub currency: Currency,
+    pub expire_in_sec: Option<u64>,
+    pub private_taker: Option<Pubkey>,
+    pub maker_broker: Option<Pubkey>,
+    pub edited: bool,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketDelisting {
+    pub list_state: Pubkey,
+    pub asset: Pubkey,
+    pub standard: String,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn list_nft(args: MarketListParams) -> Result<MarketListing> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+    let cosigner = read_cosigner(args.cosigner)?;
+
+    list_nft_with_config(
+        &config,
+        &args.mint,
+        &args.price,
+        &args.terms,
+        cosigner.as_ref(),
+    )
+}
+
+/// Lists an asset owned by the config keypair. The asset standard is detected to build the
+/// matching instruction, and ownership is checked up front so the error names the problem.
+pub fn list_nft_with_config(
+    config: &CliConfig,
+    mint: &Pubkey,
+    price: &str,
+    terms: &ListingTerms,
+    cosigner: Option<&Keypair>,
+) -> Result<MarketListing> {
+    let owner = config.keypair.pubkey();
+    let asset = fetch_market_asset(config, mint)?;
+    let currency = fetch_currency(config, terms.currency.and_then(|currency| currency.mint()))?;
+    let amount = currency.parse(price)?;
+    let expire_in_sec = parse_expiry(terms.expire.as_deref())?;
+
+    let list_state = ListState::find_pda(mint).0;
+
+    check_listable(config, &asset, &owner, &list_state)?;
+
+    let accounts = ListingAccounts {
+        owner,
+        list_state,
+        rent_destination: owner,
+    };
+    let ix = accounts.list(
+        &asset,
+        ListTerms {
+            amount,
+            expire_in_sec,
+            currency: currency.mint,
+            private_taker: terms.private_taker,
+            maker_broker: terms.maker_broker,
+        },
+        cosigner.map(|cosigner| cosigner.pubkey()),
+    );
+
+    let signatures = send_instructions(config, vec![ix], &cosigner_signers(cosigner), &[])?;
+
+    Ok(MarketListing {
+        list_state,
+        asset: *mint,
+        standard: Some(asset.to_string()),
+        amount,
+        currency,
+        expire_in_sec,
+        private_taker: terms.private_taker,
+        maker_broker: terms.maker_broker,
+        edited: false,
+        signatures,
+    })
+}
+
+/// Checks the signer holds the asset and that it isn't listed already.
+fn check_listable(
+    config: &CliConfig,
+    asset: &MarketAsset,
+    owner: &Pubkey,
+    list_state: &Pubkey,
+) -> Result<()> {
+    let mut addresses = vec![*list_state];
+    if let MarketAsset::Nft(nft) = asset {
+        addresses.push(nft.token_account(owner));
+    }
+    let accounts = config.client.get_multiple_accounts(&addresses)?;
+
+    let mut blockers = vec![];
+
+    if accounts[0].is_some() {
+        blockers.push("it is already listed, use edit-listing to change the listing".to_string());
+    }
+
+    let holder = match asset {
+        MarketAsset::Compressed(compressed) => Some(compressed.owner),
+        MarketAsset::Nft(nft) if nft.standard == AssetStandard::Core => nft.owner,
+        MarketAsset::Nft(_) => accounts[1]
+            .as_ref()
+            .and_then(|account| token_account_amount(&account.data))
+            .filter(|amount| *amount == 1)
+            .map(|_| *owner),
+    };
+
+    if holder != Some(*owner) {
+        blockers.push(match holder {
+            Some(holder) => format!("it is owned by {}, sign with the owner keypair", holder),
+            None => format!("it is not in the associated token account of {}", owner),
+        });
+    }
+
+    if blockers.is_empty() {
+        Ok(())
+    } else {
+        Err(blockers_error(
+            format!("Can't list {}", asset.id()),
+            &blockers,
+        ))
+    }
+}
+
+pub fn edit_listing(args: MarketEditListingParams) -> Result<MarketListing> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    edit_listing_with_config(&config, &args.mint, args.price.as_deref(), &args.terms)
+}
+
+/// Edits a listing owned by the config keypair. Terms that aren't given keep their current
+/// value unless they are cleared, and the expiry keeps the time left on the listing. An expired
+/// listing needs a new expiry.
+pub fn edit_listing_with_config(
+    config: &CliConfig,
+    mint: &Pubkey,
+    price: Option<&str>,
+    terms: &ListingTerms,
+) -> Result<MarketListing> {
+    let owner = config.keypair.pubkey();
+    let (list_state, listing) = fetch_listing(config, mint)?;
+
+    let mut blockers = vec![];
+
+    if listing.owner != owner {
+        blockers.push(format!(
+            "it is listed by {}, sign with the owner keypair",
+            listing.owner
+        ));
+    }
+
+    let currency_mint = match terms.currency {
+        Some(currency) => currency.mint(),
+        None => listing.currency,
+    };
+    if currency_mint != listing.currency && price.is_none() {
+        blockers.push("changing the currency needs a new --price in that currency".to_string());
+    }
+
+    // Without a new expiry the program would pick its default, so an expired listing needs one.
+    let now = chrono::Utc::now().timestamp();
+    if listing_expired(&listing, now) && terms.expire.is_none() {
+        blockers.push("it has expired, give a new --expire".to_string());
+    }
+
+    if !blockers.is_empty() {
+        return Err(blockers_error(
+            format!("Can't edit the listing of {}", mint),
+            &blockers,
+        ));
+    }
+
+    let currency = fetch_currency(config, currency_mint)?;
+    let amount = match price {
+        Some(price) => currency.parse(price)?,
+        None => listing.amount,
+    };
+
+    let expire_in_sec = match parse_expiry(terms.expire.as_deref())? {
+        Some(expire_in_sec) => Some(expire_in_sec),
+        None => Some(((listing.expiry - now) as u64).min(MAX_EXPIRY_SEC)),
+    };
+
+    let private_taker = match terms.clear_private_taker {
+        true => None,
+        false => terms.private_taker.or(listing.private_taker),
+    };
+    let maker_broker = match terms.clear_maker_broker {
+        true => None,
+        false => terms.maker_broker.or(listing.maker_broker),
+    };
+
+    let ix = Edit {
+        list_state,
+        owner,
+        marketplace_program: TENSOR_MARKETPLACE_ID,
+    }
+    .instruction(EditInstructionArgs {
+        amount,
+        expire_in_sec,
+        currency: currency.mint,
+        private_taker,
+        maker_broker,
+    });
+
+    let signatures = send_instructions(config, vec![ix], &[], &[])?;
+
+    Ok(MarketListing {
+        list_state,
+        asset: *mint,
+        standard: None,
+        amount,
+        currency,
+        expire_in_sec,
+        private_taker,
+        maker_broker,
+        edited: true,
+        signatures,
+    })
+}
+
+pub fn delist_nft(args: MarketDelistParams) -> Result<MarketDelisting> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    delist_nft_with_config(&config, &args.mint)
+}
+
+/// Delists an asset listed by the config keypair, returning it and the listing rent.
+pub fn delist_nft_with_config(config: &CliConfig, mint: &Pubkey) -> Result<MarketDelisting> {
+    let owner = config.keypair.pubkey();
+    let (list_state, listing) = fetch_listing(config, mint)?;
+
+    if listing.owner != owner {
+        return Err(blockers_error(
+            format!("Can't delist {}", mint),
+            &[format!(
+                "it is listed by {}, sign with the owner keypair",
+                listing.owner
+            )],
+        ));
+    }
+
+    let asset = fetch_market_asset(config, mint)?;
+
+    let accounts = ListingAccounts {
+        owner,
+        list_state,
+        rent_destination: listing.rent_payer.to_option().unwrap_or(listing.owner),
+    };
+
+    let signatures = send_instructions(config, vec![accounts.delist(&asset)], &[], &[])?;
+
+    Ok(MarketDelisting {
+        list_state,
+        asset: *mint,
+        standard: asset.to_string(),
+        signatures,
+    })
+}
+
+/// Price and terms shared by every list instruction.
+#[derive(Debug, Clone, Copy)]
+struct ListTerms {
+    amount: u64,
+    expire_in_sec: Option<u64>,
+    currency: Option<Pubkey>,
+    private_taker: Option<Pubkey>,
+    maker_broker: Option<Pubkey>,
+}
+
+/// Accounts shared by the list and delist instructions of every asset standard.
+struct ListingAccounts {
+    owner: Pubkey,
+    list_state: Pubkey,
+    rent_destination: Pubkey,
+}
+
+impl ListingAccounts {
+    /// Token records of the owner and listing token accounts, for programmable NFTs only.
+    fn token_records(&self, asset: &Asset) -> (Option<Pubkey>, Option<Pubkey>) {
+        if asset.standard != AssetStandard::Programmable {
+            return (None, None);
+        }
+
+        (
+            Some(find_token_record_pda(&asset.mint, &asset.token_account(&self.owner)).0),
+            Some(find_token_record_pda(&asset.mint, &asset.token_account(&self.list_state)).0),
+        )
+    }
+
+    fn list(&self, asset: &MarketAsset, terms: ListTerms, cosigner: Option<Pubkey>) -> Instruction {
+        let asset = match asset {
+            MarketAsset::Compressed(asset) => {
+                return ListCompressed {
+                    tree_authority: asset.tree_authority(),
+                    owner: (self.owner, true),
+                    delegate: (asset.delegate, false),
+                    merkle_tree: asset.merkle_tree,
+                    log_wrapper: NOOP_ID,
+                    compression_program: ACCOUNT_COMPRESSION_ID,
+                    system_program: solana_sdk::system_program::id(),
+                    bubblegum_program: BUBBLEGUM_ID,
+                    marketplace_program: TENSOR_MARKETPLACE_ID,
+                    list_state: self.list_state,
+                    rent_payer: self.owner,
+                    cosigner,
+                }
+                .instruction_with_remaining_accounts(
+                    ListCompressedInstructionArgs {
+                        nonce: asset.nonce(),
+                        index: asset.leaf_index,
+                        root: asset.root,
+                        data_hash: asset.data_hash,
+                        creator_hash: asset.creator_hash,
+                        amount: terms.amount,
+                        expire_in_sec: terms.expire_in_sec,
+                        currency: terms.currency,
+                        private_taker: terms.private_taker,
+                        maker_broker: terms.maker_broker,
+                    },
+                    &asset.proof_accounts(),
+                );
+            }
+            MarketAsset::Nft(asset) => asset,
+        };
+
+        match asset.standard {
+            AssetStandard::Core => ListCore {
+                asset: asset.mint,
+                collection: asset.collection,
+                list_state: self.list_state,
+                owner: self.owner,
+                mpl_core_program: MPL_CORE_ID,
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                system_program: solana_sdk::system_program::id(),
+                payer: self.owner,
+                cosigner,
+            }
+            .instruction(ListCoreInstructionArgs {
+                amount: terms.amount,
+                expire_in_sec: terms.expire_in_sec,
+                currency: terms.currency,
+                private_taker: terms.private_taker,
+                maker_broker: terms.maker_broker,
+            }),
+            AssetStandard::Token2022 => ListT22 {
+                owner: self.owner,
+                owner_ta: asset.token_account(&self.owner),
+                list_state: self.list_state,
+                list_ta: asset.token_account(&self.list_state),
+                mint: asset.mint,
+                payer: self.owner,
+                token_program: asset.token_program,
+                associated_token_program: ASSOCIATED_TOKEN_ID,
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                system_program: solana_sdk::system_program::id(),
+                cosigner,
+            }
+            .instruction(ListT22InstructionArgs {
+                amount: terms.amount,
+                expire_in_sec: terms.expire_in_sec,
+                currency: terms.currency,
+                private_taker: terms.private_taker,
+                maker_broker: terms.maker_broker,
+            }),
+            AssetStandard::Legacy | AssetStandard::Programmable => {
+                let (owner_token_record, list_token_record) = self.token_records(asset);
+
+                ListLegacy {
+                    owner: self.owner,
+                    owner_ta: asset.token_account(&self.owner),
+                    list_state: self.list_state,
+                    list_ta: asset.token_account(&self.list_state),
+                    mint: asset.mint,
+                    payer: self.owner,
+                    token_program: asset.token_program,
+                    associated_token_program: ASSOCIATED_TOKEN_ID,
+                    marketplace_program: TENSOR_MARKETPLACE_ID,
+                    system_program: solana_sdk::system_program::id(),
+                    metadata: Metadata::find_pda(&asset.mint).0,
+                    edition: find_edition_pda(&asset.mint).0,
+                    owner_token_record,
+                    list_token_record,
+                    authorization_rules: asset.rule_set,
+                    authorization_rules_program: asset.rule_set.map(|_| TOKEN_AUTH_RULES_ID),
+                    token_metadata_program: Some(TOKEN_METADATA_ID),
+                    sysvar_instructions: Some(SYSVAR_INSTRUCTIONS),
+                    cosigner,
+                }
+                .instruction(ListLegacyInstructionArgs {
+                    amount: terms.amount,
+                    expire_in_sec: terms.expire_in_sec,
+                    currency: terms.currency,
+                    private_taker: terms.private_taker,
+                    maker_broker: terms.maker_broker,
+                    authorization_data: None,
+                })
+            }
+        }
+    }
+
+    fn delist(&self, asset: &MarketAsset) -> Instruction {
+        let asset = match asset {
+            MarketAsset::Compressed(asset) => {
+                return DelistCompressed {
+                    tree_authority: asset.tree_authority(),
+                    merkle_tree: asset.merkle_tree,
+                    log_wrapper: NOOP_ID,
+                    compression_program: ACCOUNT_COMPRESSION_ID,
+                    system_program: solana_sdk::system_program::id(),
+                    bubblegum_program: BUBBLEGUM_ID,
+                    list_state: self.list_state,
+                    owner: self.owner,
+                    marketplace_program: TENSOR_MARKETPLACE_ID,
+                    rent_destination: self.rent_destination,
+                }
+                .instruction_with_remaining_accounts(
+                    DelistCompressedInstructionArgs {
+                        nonce: asset.nonce(),
+                        index: asset.leaf_index,
+                        root: asset.root,
+                        data_hash: asset.data_hash,
+                        creator_hash: asset.creator_hash,
+                    },
+                    &asset.proof_accounts(),
+                );
+            }
+            MarketAsset::Nft(asset) => asset,
+        };
+
+        match asset.standard {
+            AssetStandard::Core => DelistCore {
+                asset: asset.mint,
+                collection: asset.collection,
+                owner: self.owner,
+                list_state: self.list_state,
+                mpl_core_program: MPL_CORE_ID,
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                system_program: solana_sdk::system_program::id(),
+                rent_destination: self.rent_destination,
+            }
+            .instruction(),
+            AssetStandard::Token2022 => DelistT22 {
+                owner: self.owner,
+                owner_ta: asset.token_account(&self.owner),
+                list_state: self.list_state,
+                list_ta: asset.token_account(&self.list_state),
+                mint: asset.mint,
+                rent_destination: self.rent_destination,
+                payer: self.owner,
+                token_program: asset.token_program,
+                associated_token_program: ASSOCIATED_TOKEN_ID,
+                marketplace_program: TENSOR_MARKETPLACE_ID,
+                system_program: solana_sdk::system_program::id(),
+            }
+            .instruction(),
+            AssetStandard::Legacy | AssetStandard::Programmable => {
+                let (owner_token_record, list_token_record) = self.token_records(asset);
+
+                DelistLegacy {
+                    owner: self.owner,
+                    owner_ta: asset.token_account(&self.owner),
+                    list_state: self.list_state,
+                    list_ta: asset.token_account(&self.list_state),
+                    mint: asset.mint,
+                    rent_destination: self.rent_destination,
+                    payer: self.owner,
+                    token_program: asset.token_program,
+                    associated_token_program: ASSOCIATED_TOKEN_ID,
+                    marketplace_program: TENSOR_MARKETPLACE_ID,
+                    system_program: solana_sdk::system_program::id(),
+                    metadata: Metadata::find_pda(&asset.mint).0,
+                    edition: find_edition_pda(&asset.mint).0,
+                    owner_token_record,
+                    list_token_record,
+                    authorization_rules: asset.rule_set,
+                    authorization_rules_program: asset.rule_set.map(|_| TOKEN_AUTH_RULES_ID),
+                    token_metadata_program: Some(TOKEN_METADATA_ID),
+                    sysvar_instructions: Some(SYSVAR_INSTRUCTIONS),
+                }
+                .instruction(DelistLegacyInstructionArgs {
+                    authorization_data: None,
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,447 @@
+mod bid;
+mod buy;
+mod cleanup;
+mod list;
+mod orders;
+
+pub use bid::*;
+pub use buy::*;
+pub use cleanup::*;
+pub use list::*;
+pub use orders::*;
+
+use std::{fmt::Display, path::PathBuf, str::FromStr};
+
+use {
+    anyhow::{anyhow, Result},
+    solana_sdk::{
+        account::Account,
+        instruction::AccountMeta,
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair, Signature},
+        signer::Signer,
+    },
+    tensor_marketplace::accounts::{BidState, ListState},
+};
+
+use crate::{
+    commands::{
+        associated_token_address, parse_duration, parse_price, parse_token_amount, resolve_asset,
+        Asset, AssetStandard, MAX_EXPIRY_SEC, TOKEN_ID, TOKEN_PROGRAM_IDS,
+    },
+    discriminators::deserialize_account,
+    rpc::DasAsset,
+    setup::CliConfig,
+    types::{
+        bubblegum::{canopy_depth, data_hash, find_tree_authority_pda, MetadataArgs},
+        metaplex::{core_royalties, Collection, Creator, Metadata, TokenStandard},
+    },
+};
+
+/// Offset of the decimals in an SPL mint, after the mint authority and supply.
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// An asset that can be traded on the marketplace: an NFT or Core asset with an account of its
+/// own, or a compressed NFT read through the DAS API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarketAsset {
+    Nft(Asset),
+    Compressed(Box<CompressedAsset>),
+}
+
+impl MarketAsset {
+    pub fn id(&self) -> Pubkey {
+        match self {
+            MarketAsset::Nft(asset) => asset.mint,
+            MarketAsset::Compressed(asset) => asset.id,
+        }
+    }
+}
+
+impl Display for MarketAsset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarketAsset::Nft(asset) => write!(f, "{}", asset.standard),
+            MarketAsset::Compressed(_) => write!(f, "Compressed"),
+        }
+    }
+}
+
+/// A compressed NFT leaf with the proof needed to transfer it. The proof is trimmed to the nodes
+/// the tree's canopy doesn't already hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressedAsset {
+    pub id: Pubkey,
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub leaf_index: u32,
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub metadata_hash: [u8; 32],
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<Creator>,
+    pub proof: Vec<Pubkey>,
+}
+
+impl CompressedAsset {
+    pub fn tree_authority(&self) -> Pubkey {
+        find_tree_authority_pda(&self.merkle_tree).0
+    }
+
+    /// Bubblegum nonces are the leaf index.
+    pub fn nonce(&self) -> u64 {
+        self.leaf_index as u64
+    }
+
+    pub fn proof_accounts(&self) -> Vec<AccountMeta> {
+        self.proof
+            .iter()
+            .map(|node| AccountMeta::new_readonly(*node, false))
+            .collect()
+    }
+}
+
+/// Currency a listing or bid is priced in: SOL, or an SPL token with its decimals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Currency {
+    pub mint: Option<Pubkey>,
+    pub decimals: u8,
+    /// Token program of the currency mint.
+    pub token_program: Pubkey,
+}
+
+impl Currency {
+    pub const SOL: Currency = Currency {
+        mint: None,
+        decimals: 9,
+        token_program: TOKEN_ID,
+    };
+
+    /// Parses a price in this currency. SOL prices take the same units as pool prices.
+    pub fn parse(&self, price: &str) -> Result<u64> {
+        match self.mint {
+            None => parse_price(price),
+            Some(_) => parse_token_amount(price.trim(), self.decimals),
+        }
+    }
+
+    /// Amount in whole units followed by the currency, e.g. `1.5 SOL`.
+    pub fn format(&self, amount: u64) -> String {
+        let unit = 10u128.pow(self.decimals as u32);
+        let whole = amount as u128 / unit;
+        let fraction = format!(
+            "{:0>width$}",
+            amount as u128 % unit,
+            width = self.decimals as usize
+        );
+        let fraction = fraction.trim_end_matches('0');
+
+        let value = if fraction.is_empty() {
+            whole.to_string()
+        } else {
+            format!("{}.{}", whole, fraction)
+        };
+
+        match self.mint {
+            None => format!("{} SOL", value),
+            Some(mint) => format!("{} {}", value, mint),
+        }
+    }
+
+    /// Token account of `wallet` for this currency, `None` for SOL.
+    pub fn token_account(&self, wallet: &Pubkey) -> Option<Pubkey> {
+        self.mint
+            .map(|mint| associated_token_address(wallet, &mint, &self.token_program))
+    }
+}
+
+/// Optional terms of a listing, kept as typed so prices and durations are parsed with their
+/// units.
+#[derive(Debug, Clone, Default)]
+pub struct ListingTerms {
+    pub currency: Option<CurrencyFilter>,
+    pub expire: Option<String>,
+    pub private_taker: Option<Pubkey>,
+    pub maker_broker: Option<Pubkey>,
+    /// Removes the private taker of a listing being edited.
+    pub clear_private_taker: bool,
+    /// Removes the maker broker of a listing being edited.
+    pub clear_maker_broker: bool,
+}
+
+/// Fetches the asset and determines its standard, falling back to the DAS API when there is no
+/// account for it since compressed NFTs have none.
+pub fn fetch_market_asset(config: &CliConfig, id: &Pubkey) -> Result<MarketAsset> {
+    let accounts = config
+        .client
+        .get_multiple_accounts(&[*id, Metadata::find_pda(id).0])?;
+
+    match &accounts[0] {
+        Some(account) => Ok(MarketAsset::Nft(resolve_asset(
+            *id,
+            account,
+            accounts[1].as_ref(),
+        )?)),
+        None => Ok(MarketAsset::Compressed(Box::new(fetch_compressed_asset(
+            config, id,
+        )?))),
+    }
+}
+
+/// Fetches a compressed NFT and its proof from the DAS API. The metadata hash the marketplace
+/// needs is rebuilt from the DAS metadata and checked against the leaf's data hash, so a stale
+/// indexer fails here rather than on chain.
+pub fn fetch_compressed_asset(config: &CliConfig, id: &Pubkey) -> Result<CompressedAsset> {
+    let asset = config
+        .client
+        .get_asset(id)
+        .map_err(|e| anyhow!("Mint {} not found: {}", id, e))?;
+
+    if !asset.compression.compressed {
+        return Err(anyhow!("Mint {} not found", id));
+    }
+
+    let proof = config.client.get_asset_proof(id)?;
+    let tree = config.client.get_account_data(&proof.tree_id)?;
+    let canopy_depth = canopy_depth(&tree)
+        .ok_or_else(|| anyhow!("Invalid merkle tree {}", proof.tree_id))?
+        as usize;
+
+    let metadata = das_metadata_args(&asset)?;
+    let metadata_hash = metadata.hash();
+    let leaf_data_hash = decode_hash(&asset.compression.data_hash)?;
+
+    if data_hash(&metadata_hash, metadata.seller_fee_basis_points) != leaf_data_hash {
+        return Err(anyhow!(
+            "Metadata of compressed asset {} from the DAS API doesn't match its leaf, the indexer may be behind",
+            id
+        ));
+    }
+
+    let proof_len = proof.proof.len().saturating_sub(canopy_depth);
+
+    Ok(CompressedAsset {
+        id: *id,
+        owner: asset.ownership.owner,
+        delegate: asset.ownership.delegate.unwrap_or(asset.ownership.owner),
+        merkle_tree: proof.tree_id,
+        leaf_index: u32::try_from(asset.compression.leaf_id)
+            .map_err(|_| anyhow!("Invalid leaf index of compressed asset {}", id))?,
+        root: proof.root.to_bytes(),
+        data_hash: leaf_data_hash,
+        creator_hash: decode_hash(&asset.compression.creator_hash)?,
+        metadata_hash,
+        seller_fee_basis_points: metadata.seller_fee_basis_points,
+        creators: metadata.creators,
+        proof: proof.proof[..proof_len].to_vec(),
+    })
+}
+
+/// Rebuilds the Bubblegum metadata of a compressed NFT from its DAS response.
+fn das_metadata_args(asset: &DasAsset) -> Result<MetadataArgs> {
+    let token_standard = match asset.content.metadata.token_standard.as_deref() {
+        Some("NonFungible") => Some(TokenStandard::NonFungible),
+        Some("FungibleAsset") => Some(TokenStandard::FungibleAsset),
+        Some("Fungible") => Some(TokenStandard::Fungible),
+        Some("NonFungibleEdition") => Some(TokenStandard::NonFungibleEdition),
+        _ => None,
+    };
+
+    let collection = asset
+        .grouping
+        .iter()
+        .find(|group| group.group_key == "collection")
+        .map(|group| {
+            Ok::<_, anyhow::Error>(Collection {
+                verified: group.verified.unwrap_or(true),
+                key: Pubkey::from_str(&group.group_value)?,
+            })
+        })
+        .transpose()?;
+
+    Ok(MetadataArgs {
+        name: asset.content.metadata.name.clone(),
+        symbol: asset.content.metadata.symbol.clone(),
+        uri: asset.content.json_uri.clone(),
+        seller_fee_basis_points: asset.royalty.basis_points,
+        primary_sale_happened: asset.royalty.primary_sale_happened,
+        is_mutable: asset.mutable,
+        edition_nonce: asset
+            .supply
+            .as_ref()
+            .and_then(|supply| supply.edition_nonce),
+        token_standard,
+        collection,
+        uses: None,
+        token_program_version: 0,
+        creators: asset
+            .creators
+            .iter()
+            .map(|creator| Creator {
+                address: creator.address,
+                verified: creator.verified,
+                share: creator.share,
+            })
+            .collect(),
+    })
+}
+
+/// DAS returns 32 byte hashes as base58, the same encoding as addresses.
+fn decode_hash(hash: &str) -> Result<[u8; 32]> {
+    Pubkey::from_str(hash)
+        .map(|hash| hash.to_bytes())
+        .map_err(|_| anyhow!("Invalid hash from the DAS API: {}", hash))
+}
+
+/// Fetches the currency mint to get its decimals and token program, SOL if there is none.
+pub fn fetch_currency(config: &CliConfig, mint: Option<Pubkey>) -> Result<Currency> {
+    let Some(mint) = mint else {
+        return Ok(Currency::SOL);
+    };
+
+    let account = config
+        .client
+        .get_account(&mint)
+        .map_err(|_| anyhow!("Currency mint {} not found", mint))?;
+
+    if !TOKEN_PROGRAM_IDS.contains(&account.owner) {
+        return Err(anyhow!(
+            "Currency {} is not an SPL token mint, it is owned by {}",
+            mint,
+            account.owner
+        ));
+    }
+
+    let decimals = *account
+        .data
+        .get(MINT_DECIMALS_OFFSET)
+        .ok_or_else(|| anyhow!("Invalid currency mint {}", mint))?;
+
+    Ok(Currency {
+        mint: Some(mint),
+        decimals,
+        token_program: account.owner,
+    })
+}
+
+/// Fetches the listing of an asset, erroring if it isn't listed.
+pub fn fetch_listing(config: &CliConfig, id: &Pubkey) -> Result<(Pubkey, ListState)> {
+    let address = ListState::find_pda(id).0;
+
+    let accounts = config.client.get_multiple_accounts(&[address])?;
+    let account = accounts[0]
+        .as_ref()
+        .ok_or_else(|| anyhow!("{} is not listed", id))?;
+
+    let listing = deserialize_account::<ListState>(&account.data)
+        .map_err(|e| anyhow!("Invalid listing {}: {}", address, e))?;
+
+    Ok((address, listing))
+}
+
+/// Parses an expiry flag, rejecting the ones the program would reject.
+pub fn parse_expiry(expire: Option<&str>) -> Result<Option<u64>> {
+    let expire_in_sec = expire.map(parse_duration).transpose()?;
+
+    if expire_in_sec.is_some_and(|expiry| expiry > MAX_EXPIRY_SEC) {
+        return Err(anyhow!(
+            "Expiry can't be more than {} days",
+            MAX_EXPIRY_SEC / 86_400
+        ));
+    }
+
+    Ok(expire_in_sec)
+}
+
+/// Royalty creators the marketplace pays on a sale, writable and in order. SPL sales also pay
+/// into each creator's currency token account, which follows its creator.
+pub fn creator_accounts(
+    config: &CliConfig,
+    asset: &MarketAsset,
+    currency: &Currency,
+) -> Result<Vec<AccountMeta>> {
+    let creators: Vec<Pubkey> = match asset {
+        MarketAsset::Compressed(asset) => asset
+            .creators
+            .iter()
+            .map(|creator| creator.address)
+            .collect(),
+        MarketAsset::Nft(asset) => match asset.standard {
+            AssetStandard::Legacy | AssetStandard::Programmable => {
+                let address = Metadata::find_pda(&asset.mint).0;
+                let metadata = Metadata::from_bytes(&config.client.get_account_data(&address)?)
+                    .map_err(|e| anyhow!("Invalid metadata for mint {}: {}", asset.mint, e))?;
+
+                metadata
+                    .data
+                    .creators
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|creator| creator.address)
+                    .collect()
+            }
+            AssetStandard::Core => core_creators(config, asset)?,
+            AssetStandard::Token2022 => vec![],
+        },
+    };
+
+    Ok(creators
+        .iter()
+        .flat_map(|creator| {
+            let mut accounts = vec![AccountMeta::new(*creator, false)];
+            if let Some(token_account) = currency.token_account(creator) {
+                accounts.push(AccountMeta::new(token_account, false));
+            }
+            accounts
+        })
+        .collect())
+}
+
+/// Creators of the royalties plugin on a Core asset, or on its collection if the asset has none.
+fn core_creators(config: &CliConfig, asset: &Asset) -> Result<Vec<Pubkey>> {
+    let mut addresses = vec![asset.mint];
+    addresses.extend(asset.collection);
+
+    for (address, account) in addresses
+        .iter()
+        .zip(config.client.get_multiple_accounts(&addresses)?)
+    {
+        let Some(account) = account else {
+            continue;
+        };
+
+        let royalties = core_royalties(&account.data)
+            .map_err(|e| anyhow!("Invalid Core account {}: {}", address, e))?;
+
+        if let Some(royalties) = royalties {
+            return Ok(royalties
+                .creators
+                .iter()
+                .map(|creator| creator.address)
+                .collect());
+        }
+    }
+
+    Ok(vec![])
+}
+
+/// Reads the keypair of a cosigner, which has to sign alongside the owner.
+pub fn read_cosigner(path: Option<PathBuf>) -> Result<Option<Keypair>> {
+    path.map(|path| {
+        read_keypair_file(&path)
+            .map_err(|_| anyhow!("Unable to read cosigner keypair file {}", path.display()))
+    })
+    .transpose()
+}
+
+/// Extra signers for a marketplace instruction: the cosigner, when there is one.
+fn cosigner_signers(cosigner: Option<&Keypair>) -> Vec<&Keypair> {
+    cosigner.into_iter().collect()
+}
+
+/// Whether the listing can no longer be bought.
+pub fn listing_expired(listing: &ListState, now: i64) -> bool {
+    listing.expiry <= now
+}
@@ -0,0 +1,267 @@
+use super::*;
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use tensor_marketplace::{programs::TENSOR_MARKETPLACE_ID, types::Target};
+
+use crate::{
+    discriminators::Discriminator,
+    rpc::{discriminator_filter, memcmp_filter},
+};
+
+// Offsets of the `ListState` fields that can be filtered on with memcmp.
+pub const LIST_OWNER_OFFSET: usize = 10;
+pub const LIST_ASSET_ID_OFFSET: usize = 42;
+pub const LIST_CURRENCY_OFFSET: usize = 82;
+
+// Offsets of the `BidState` fields that can be filtered on with memcmp. Everything after the
+// target id follows optional fields, so those are filtered on locally.
+pub const BID_OWNER_OFFSET: usize = 10;
+pub const BID_TARGET_OFFSET: usize = 74;
+pub const BID_TARGET_ID_OFFSET: usize = 75;
+
+pub struct MarketListingsParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub filters: ListingFilters,
+}
+
+pub struct MarketBidsParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub filters: BidFilters,
+}
+
+/// Currency to filter listings or bids on, or to move a listing to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurrencyFilter {
+    Sol,
+    Spl(Pubkey),
+}
+
+impl CurrencyFilter {
+    /// The currency mint, `None` for SOL.
+    pub fn mint(&self) -> Option<Pubkey> {
+        match self {
+            CurrencyFilter::Sol => None,
+            CurrencyFilter::Spl(mint) => Some(*mint),
+        }
+    }
+
+    fn matches(&self, currency: Option<Pubkey>) -> bool {
+        match self {
+            CurrencyFilter::Sol => currency.is_none(),
+            CurrencyFilter::Spl(mint) => currency == Some(*mint),
+        }
+    }
+
+    /// Borsh encoding of the currency option, to memcmp on.
+    fn bytes(&self) -> Vec<u8> {
+        match self {
+            CurrencyFilter::Sol => vec![0],
+            CurrencyFilter::Spl(mint) => [&[1], mint.as_ref()].concat(),
+        }
+    }
+}
+
+/// Which listings to fetch. All of them run on the RPC node except `active`.
+#[derive(Debug, Clone, Default)]
+pub struct ListingFilters {
+    pub owner: Option<Pubkey>,
+    pub mint: Option<Pubkey>,
+    pub currency: Option<CurrencyFilter>,
+    /// Only listings that haven't expired.
+    pub active: bool,
+}
+
+/// Which bids to fetch. Owner and target filters run on the RPC node, the others locally.
+#[derive(Debug, Clone, Default)]
+pub struct BidFilters {
+    pub owner: Option<Pubkey>,
+    pub target: Option<Target>,
+    pub target_id: Option<Pubkey>,
+    pub currency: Option<CurrencyFilter>,
+    pub margin: Option<Pubkey>,
+    /// Only bids that haven't expired.
+    pub active: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ListingEntry {
+    pub address: Pubkey,
+    pub listing: ListState,
+    pub currency: Currency,
+    pub lamports: u64,
+    /// Size of the account data, which sets its rent.
+    pub space: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct BidEntry {
+    pub address: Pubkey,
+    pub bid: BidState,
+    pub currency: Currency,
+    /// Rent, plus the SOL of bids without a margin account.
+    pub lamports: u64,
+    /// Size of the account data, which sets its rent.
+    pub space: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketListings {
+    pub listings: Vec<ListingEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MarketBids {
+    pub bids: Vec<BidEntry>,
+}
+
+pub fn handle_market_listings(args: MarketListingsParams) -> Result<MarketListings> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    let mut listings = fetch_listings(&config, &args.filters)?;
+    // Cheapest first.
+    listings.sort_by_key(|entry| (entry.listing.amount, entry.address.to_string()));
+
+    Ok(MarketListings { listings })
+}
+
+pub fn handle_market_bids(args: MarketBidsParams) -> Result<MarketBids> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    let mut bids = fetch_bids(&config, &args.filters)?;
+    // Highest first.
+    bids.sort_by_key(|entry| {
+        (
+            std::cmp::Reverse(entry.bid.amount),
+            entry.address.to_string(),
+        )
+    });
+
+    Ok(MarketBids { bids })
+}
+
+/// Fetches every listing matching the filters.
+pub fn fetch_listings(config: &CliConfig, filters: &ListingFilters) -> Result<Vec<ListingEntry>> {
+    let mut memcmps: Vec<_> = [
+        (LIST_OWNER_OFFSET, filters.owner),
+        (LIST_ASSET_ID_OFFSET, filters.mint),
+    ]
+    .into_iter()
+    .filter_map(|(offset, address)| address.map(|address| memcmp_filter(offset, address.as_ref())))
+    .collect();
+
+    if let Some(currency) = &filters.currency {
+        memcmps.push(memcmp_filter(LIST_CURRENCY_OFFSET, &currency.bytes()));
+    }
+
+    let accounts = config.client.get_program_accounts_with_config(
+        &TENSOR_MARKETPLACE_ID,
+        discriminator_filter(config.commitment, ListState::discriminator(), memcmps),
+    )?;
+
+    let now = chrono::Utc::now().timestamp();
+
+    let mut listings = vec![];
+
+    for (address, account) in accounts {
+        let listing = deserialize_account::<ListState>(&account.data)
+            .map_err(|e| anyhow!("Invalid listing {}: {}", address, e))?;
+
+        if filters.active && listing_expired(&listing, now) {
+            continue;
+        }
+
+        listings.push((address, listing, account));
+    }
+
+    let currencies = fetch_currencies(config, listings.iter().map(|(_, l, _)| l.currency))?;
+
+    Ok(listings
+        .into_iter()
+        .map(|(address, listing, account)| ListingEntry {
+            address,
+            currency: currencies[&listing.currency],
+            listing,
+            lamports: account.lamports,
+            space: account.data.len(),
+        })
+        .collect())
+}
+
+/// Fetches every bid matching the filters.
+pub fn fetch_bids(config: &CliConfig, filters: &BidFilters) -> Result<Vec<BidEntry>> {
+    let mut memcmps: Vec<_> = [
+        (BID_OWNER_OFFSET, filters.owner),
+        (BID_TARGET_ID_OFFSET, filters.target_id),
+    ]
+    .into_iter()
+    .filter_map(|(offset, address)| address.map(|address| memcmp_filter(offset, address.as_ref())))
+    .collect();
+
+    if let Some(target) = filters.target {
+        memcmps.push(memcmp_filter(BID_TARGET_OFFSET, &[target as u8]));
+    }
+
+    let accounts = config.client.get_program_accounts_with_config(
+        &TENSOR_MARKETPLACE_ID,
+        discriminator_filter(config.commitment, BidState::discriminator(), memcmps),
+    )?;
+
+    let now = chrono::Utc::now().timestamp();
+
+    let mut bids = vec![];
+
+    for (address, account) in accounts {
+        let bid = deserialize_account::<BidState>(&account.data)
+            .map_err(|e| anyhow!("Invalid bid {}: {}", address, e))?;
+
+        if filters
+            .currency
+            .is_some_and(|currency| !currency.matches(bid.currency))
+            || filters
+                .margin
+                .is_some_and(|margin| bid.margin != Some(margin))
+            || (filters.active && bid_expired(&bid, now))
+        {
+            continue;
+        }
+
+        bids.push((address, bid, account));
+    }
+
+    let currencies = fetch_currencies(config, bids.iter().map(|(_, b, _)| b.currency))?;
+
+    Ok(bids
+        .into_iter()
+        .map(|(address, bid, account)| BidEntry {
+            address,
+            currency: currencies[&bid.currency],
+            bid,
+            lamports: account.lamports,
+            space: account.data.len(),
+        })
+        .collect())
+}
+
+/// Fetches each distinct currency once.
+fn fetch_currencies(
+    config: &CliConfig,
+    mints: impl Iterator<Item = Option<Pubkey>>,
+) -> Result<HashMap<Option<Pubkey>, Currency>> {
+    let mut currencies = HashMap::new();
+
+    for mint in mints {
+        if let Entry::Vacant(entry) = currencies.entry(mint) {
+            entry.insert(fetch_currency(config, mint)?);
+        }
+    }
+
+    Ok(currencies)
+}
+
+/// Whether the bid can no longer be taken because it expired.
+pub fn bid_expired(bid: &BidState, now: i64) -> bool {
+    bid.expiry <= now
+}
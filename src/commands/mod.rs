@@ -1,21 +1,33 @@
+mod alt;
+mod asset;
 mod decode;
 mod derive;
 mod download;
 mod eigen;
 mod error;
+mod escrow;
 mod fees;
 mod ids;
+mod market;
+mod nonce;
 mod pool;
+mod price_lock;
 mod whitelist;
 
+pub use alt::*;
+pub use asset::*;
 pub use decode::*;
 pub use derive::*;
 pub use download::*;
 pub use eigen::*;
 pub use error::*;
+pub use escrow::*;
 pub use fees::*;
 pub use ids::*;
+pub use market::*;
+pub use nonce::*;
 pub use pool::*;
+pub use price_lock::*;
 pub use whitelist::*;
 
 pub use crate::{discriminators::Discriminator, setup::CliConfig, transaction};
@@ -0,0 +1,189 @@
+use std::path::Path;
+
+use solana_sdk::{
+    hash::Hash,
+    nonce::State as NonceState,
+    signature::{read_keypair_file, Keypair, Signature},
+    signer::Signer,
+    system_instruction,
+};
+
+use crate::transactions::{
+    fetch_nonce, missing_signers, read_signed_transactions, send_instructions,
+};
+
+use super::*;
+
+pub struct NonceCreateParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub authority: Option<Pubkey>,
+    pub account_keypair: Option<PathBuf>,
+}
+
+pub struct NonceAdvanceParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub nonce_account: Pubkey,
+    pub authority: Option<PathBuf>,
+}
+
+pub struct NonceSubmitParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    /// File written with `--sign-only`.
+    pub path: PathBuf,
+}
+
+pub struct NonceShowParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce_account: Pubkey,
+}
+
+/// Decoded durable nonce account.
+#[derive(Debug, Clone)]
+pub struct NonceAccount {
+    pub address: Pubkey,
+    pub lamports: u64,
+    pub authority: Pubkey,
+    pub blockhash: Hash,
+    pub lamports_per_signature: u64,
+}
+
+/// Transactions signed ahead of time and sent.
+#[derive(Debug, Clone)]
+pub struct SubmittedTransactions {
+    pub path: PathBuf,
+    pub signatures: Vec<Signature>,
+}
+
+/// Newly created durable nonce account.
+#[derive(Debug, Clone)]
+pub struct CreatedNonce {
+    pub address: Pubkey,
+    pub authority: Pubkey,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn create_nonce(args: NonceCreateParams) -> Result<CreatedNonce> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let payer = config.keypair.pubkey();
+    let authority = args.authority.unwrap_or(payer);
+
+    let nonce_keypair = if let Some(path) = args.account_keypair {
+        read_keypair_file(path).map_err(|_| anyhow!("Unable to read keypair file"))?
+    } else {
+        Keypair::new()
+    };
+
+    let lamports = config
+        .client
+        .get_minimum_balance_for_rent_exemption(NonceState::size())?;
+
+    let ixs = system_instruction::create_nonce_account(
+        &payer,
+        &nonce_keypair.pubkey(),
+        &authority,
+        lamports,
+    );
+
+    let signatures = send_instructions(&config, ixs, &[&nonce_keypair], &[])?;
+
+    Ok(CreatedNonce {
+        address: nonce_keypair.pubkey(),
+        authority,
+        signatures,
+    })
+}
+
+pub fn advance_nonce(args: NonceAdvanceParams) -> Result<NonceAccount> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let authority = args
+        .authority
+        .map(|path| {
+            read_keypair_file(path).map_err(|_| anyhow!("Unable to read nonce authority file"))
+        })
+        .transpose()?;
+    let authority = authority.as_ref().unwrap_or(&config.keypair);
+
+    let ix = system_instruction::advance_nonce_account(&args.nonce_account, &authority.pubkey());
+
+    send_instructions(&config, vec![ix], &[authority], &[])?;
+
+    fetch_nonce_account(&config, &args.nonce_account)
+}
+
+pub fn show_nonce(args: NonceShowParams) -> Result<NonceAccount> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    fetch_nonce_account(&config, &args.nonce_account)
+}
+
+pub fn submit_nonce_transactions(args: NonceSubmitParams) -> Result<SubmittedTransactions> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    submit_signed_transactions(&config, &args.path)
+}
+
+/// Sends the transactions written with `--sign-only`, checking first that every signer has signed
+/// them.
+pub fn submit_signed_transactions(
+    config: &CliConfig,
+    path: &Path,
+) -> Result<SubmittedTransactions> {
+    let transactions = read_signed_transactions(path)?;
+
+    for tx in &transactions {
+        let missing = missing_signers(tx);
+
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "Transaction {} is missing signatures from {}",
+                tx.signatures[0],
+                missing
+                    .iter()
+                    .map(|signer| signer.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
+
+    let signatures = transactions
+        .iter()
+        .map(|tx| config.client.send_and_confirm_transaction(tx))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(SubmittedTransactions {
+        path: path.to_path_buf(),
+        signatures,
+    })
+}
+
+fn fetch_nonce_account(config: &CliConfig, address: &Pubkey) -> Result<NonceAccount> {
+    let lamports = config.client.get_balance(address)?;
+    let data = fetch_nonce(config, address)?;
+
+    Ok(NonceAccount {
+        address: *address,
+        lamports,
+        authority: data.authority,
+        blockhash: data.blockhash(),
+        lamports_per_signature: data.fee_calculator.lamports_per_signature,
+    })
+}
@@ -0,0 +1,491 @@
+use super::*;
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_with::{serde_as, DisplayFromStr};
+use solana_sdk::{instruction::Instruction, native_token::lamports_to_sol};
+use tensor_amm::{
+    accounts::NftDepositReceipt,
+    instructions::{
+        ClosePool, CreatePool, CreatePoolInstructionArgs, DepositSol, DepositSolInstructionArgs,
+        EditPool, EditPoolInstructionArgs,
+    },
+    Currency,
+};
+
+use crate::{
+    args::{parse_curve_type, parse_pool_type},
+    rpc::{block_on, get_multiple_accounts_batched},
+    transactions::send_instructions,
+};
+
+pub struct PoolApplyParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub manifest_path: PathBuf,
+    /// Only show the plan.
+    pub dry_run: bool,
+}
+
+/// Desired state of an owner's pools.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PoolManifest {
+    pub pools: Vec<ManifestPool>,
+    /// Close the owner's pools that aren't in the manifest.
+    #[serde(default)]
+    pub close_unlisted: bool,
+}
+
+/// A pool in the manifest. Config values take the same formats as the `pool create` flags, and
+/// the pool id is required so the pool can be found again on later runs.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestPool {
+    pub pool_id: String,
+    #[serde_as(as = "DisplayFromStr")]
+    pub whitelist: Pubkey,
+    #[serde(rename = "type")]
+    pub pool_type: String,
+    pub curve: String,
+    pub start: String,
+    pub delta: Option<String>,
+    pub fee_bps: Option<u16>,
+    #[serde(default)]
+    pub compound_fees: bool,
+    /// Only used when creating the pool.
+    pub expire: Option<String>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub currency: Option<Pubkey>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub cosigner: Option<Pubkey>,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub maker_broker: Option<Pubkey>,
+    pub max_taker_sell_count: Option<u32>,
+    /// SOL the pool should hold at least, topped up with a deposit.
+    pub sol: Option<String>,
+    /// NFTs the pool should hold, deposited if they aren't yet.
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[serde(default)]
+    pub nfts: Vec<Pubkey>,
+}
+
+impl ManifestPool {
+    fn create_pool_args(&self) -> Result<CreatePoolInstructionArgs> {
+        create_pool_args(&PoolConfigFlags {
+            pool_id: Some(self.pool_id.clone()),
+            pool_type: Some(parse_pool_type(&self.pool_type).map_err(|e| anyhow!(e))?),
+            curve_type: Some(parse_curve_type(&self.curve).map_err(|e| anyhow!(e))?),
+            start: Some(self.start.clone()),
+            delta: self.delta.clone(),
+            fee_bps: self.fee_bps,
+            compound_fees: self.compound_fees,
+            expire: self.expire.clone(),
+            currency: self.currency,
+            cosigner: self.cosigner,
+            maker_broker: self.maker_broker,
+            max_taker_sell_count: self.max_taker_sell_count,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum PlanAction {
+    Create {
+        pool: Pubkey,
+        whitelist: Pubkey,
+        args: CreatePoolInstructionArgs,
+    },
+    Edit {
+        pool: Pubkey,
+        /// Human readable `field: before -> after` lines.
+        changes: Vec<String>,
+        args: EditPoolInstructionArgs,
+    },
+    DepositSol {
+        pool: Pubkey,
+        lamports: u64,
+    },
+    DepositNfts {
+        pool: Pubkey,
+        mints: Vec<Pubkey>,
+    },
+    Close {
+        pool: Pubkey,
+        rent_payer: Pubkey,
+    },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PoolPlan {
+    pub actions: Vec<PlanAction>,
+    /// Pools in the manifest that already match it.
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AppliedPlan {
+    pub plan: PoolPlan,
+    pub applied: bool,
+    pub signatures: Vec<Signature>,
+}
+
+/// Plans the manifest and applies it once `confirm` agrees to the plan. Leaves showing the plan
+/// and asking to the caller.
+pub fn handle_pool_apply(
+    args: PoolApplyParams,
+    confirm: impl FnOnce(&PoolPlan) -> Result<bool>,
+) -> Result<AppliedPlan> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let manifest: PoolManifest = serde_yaml::from_reader(std::fs::File::open(&args.manifest_path)?)
+        .map_err(|e| anyhow!("Invalid manifest: {}", e))?;
+
+    let plan = plan_pools(&config, &manifest)?;
+
+    if plan.actions.is_empty() || args.dry_run {
+        return Ok(AppliedPlan {
+            plan,
+            applied: false,
+            signatures: vec![],
+        });
+    }
+
+    if !confirm(&plan)? {
+        return Ok(AppliedPlan {
+            plan,
+            applied: false,
+            signatures: vec![],
+        });
+    }
+
+    let signatures = apply_plan(&config, &plan)?;
+
+    Ok(AppliedPlan {
+        plan,
+        applied: true,
+        signatures,
+    })
+}
+
+/// Diffs the manifest against the signer's pools on chain. Changes the program doesn't allow,
+/// such as a different pool type, are reported together instead of planned.
+pub fn plan_pools(config: &CliConfig, manifest: &PoolManifest) -> Result<PoolPlan> {
+    let owner = config.keypair.pubkey();
+
+    let existing: HashMap<Pubkey, Pool> = fetch_pools(
+        config,
+        &PoolFilters {
+            owner: Some(owner),
+            ..Default::default()
+        },
+    )?
+    .into_iter()
+    .map(|entry| (entry.address, entry.pool))
+    .collect();
+
+    let mut plan = PoolPlan::default();
+    let mut blockers = vec![];
+    let mut listed = vec![];
+    let mut nft_deposits = vec![];
+
+    for desired in &manifest.pools {
+        let args = desired
+            .create_pool_args()
+            .map_err(|e| anyhow!("Pool {}: {}", desired.pool_id, e))?;
+        validate_create_pool_args(&args).map_err(|e| anyhow!("Pool {}: {}", desired.pool_id, e))?;
+
+        let address = Pool::find_pda(&owner, args.pool_id).0;
+        if listed.contains(&address) {
+            return Err(anyhow!("Pool {} is listed twice", desired.pool_id));
+        }
+        listed.push(address);
+
+        let sol = desired.sol.as_deref().map(parse_price).transpose()?;
+
+        let Some(pool) = existing.get(&address) else {
+            plan.actions.push(PlanAction::Create {
+                pool: address,
+                whitelist: desired.whitelist,
+                args,
+            });
+            if let Some(lamports) = sol.filter(|lamports| *lamports > 0) {
+                plan.actions.push(PlanAction::DepositSol {
+                    pool: address,
+                    lamports,
+                });
+            }
+            if !desired.nfts.is_empty() {
+                nft_deposits.push((address, desired.nfts.clone()));
+            }
+            continue;
+        };
+
+        if pool.whitelist != desired.whitelist {
+            blockers.push(format!(
+                "{}: whitelist can't change from {} to {}",
+                address, pool.whitelist, desired.whitelist
+            ));
+        }
+        if pool.config.pool_type != args.config.pool_type {
+            blockers.push(format!(
+                "{}: pool type can't change from {} to {}",
+                address, pool.config.pool_type, args.config.pool_type
+            ));
+        }
+        if pool.currency
+            != args
+                .currency
+                .map(Currency::new)
+                .unwrap_or_else(Currency::sol)
+        {
+            blockers.push(format!("{}: currency can't change", address));
+        }
+
+        if let Some(edit) = edit_action(&address, pool, &args)? {
+            plan.actions.push(edit);
+        }
+
+        if let Some(target) = sol.filter(|target| *target > pool.amount) {
+            if pool.shared_escrow.to_option().is_some() {
+                blockers.push(format!(
+                    "{}: SOL of shared escrow pools is held by the escrow",
+                    address
+                ));
+            } else {
+                plan.actions.push(PlanAction::DepositSol {
+                    pool: address,
+                    lamports: target - pool.amount,
+                });
+            }
+        }
+
+        if !desired.nfts.is_empty() {
+            nft_deposits.push((address, desired.nfts.clone()));
+        }
+    }
+
+    // NFTs already in a pool have a deposit receipt.
+    let receipts: Vec<Pubkey> = nft_deposits
+        .iter()
+        .flat_map(|(pool, mints)| {
+            mints
+                .iter()
+                .map(|mint| NftDepositReceipt::find_pda(mint, pool).0)
+        })
+        .collect();
+    let mut receipts = block_on(get_multiple_accounts_batched(
+        config.client.clone(),
+        &receipts,
+        config.max_concurrency,
+    ))??
+    .into_iter();
+
+    for (pool, mints) in nft_deposits {
+        let missing: Vec<Pubkey> = mints
+            .into_iter()
+            .filter(|_| receipts.next().flatten().is_none())
+            .collect();
+
+        if !missing.is_empty() {
+            plan.actions.push(PlanAction::DepositNfts {
+                pool,
+                mints: missing,
+            });
+        }
+    }
+
+    plan.unchanged = listed
+        .iter()
+        .filter(|address| {
+            existing.contains_key(address)
+                && !plan.actions.iter().any(|action| action.pool() == **address)
+        })
+        .count();
+
+    if manifest.close_unlisted {
+        let mut unlisted: Vec<_> = existing
+            .iter()
+            .filter(|(address, _)| !listed.contains(address))
+            .collect();
+        unlisted.sort_by_key(|(address, _)| address.to_string());
+
+        for (address, pool) in unlisted {
+            let close = close_blockers(pool, &owner);
+            if close.is_empty() {
+                plan.actions.push(PlanAction::Close {
+                    pool: *address,
+                    rent_payer: pool.rent_payer,
+                });
+            } else {
+                blockers.extend(
+                    close
+                        .into_iter()
+                        .map(|blocker| format!("{}: {}", address, blocker)),
+                );
+            }
+        }
+    }
+
+    if !blockers.is_empty() {
+        return Err(blockers_error(
+            "Manifest can't be applied".to_string(),
+            &blockers,
+        ));
+    }
+
+    Ok(plan)
+}
+
+/// Edit needed to bring the pool in line with the manifest, if any. Expiry isn't compared since
+/// the manifest sets it relative to creation, and a cosigner or max taker sell count left out of
+/// the manifest keeps its current value.
+fn edit_action(
+    address: &Pubkey,
+    pool: &Pool,
+    desired: &CreatePoolInstructionArgs,
+) -> Result<Option<PlanAction>> {
+    let config = &desired.config;
+
+    let edit = diff_pool_edit(
+        pool,
+        &EditPoolArgs {
+            new_config: Some(EditPoolConfig {
+                curve_type: Some(config.curve_type),
+                starting_price: Some(config.starting_price),
+                delta: Some(config.delta),
+                mm_compound_fees: Some(config.mm_compound_fees),
+                mm_fee_bps: Some(config.mm_fee_bps.clone()),
+            }),
+            cosigner: desired.cosigner,
+            expire_in_sec: None,
+            max_taker_sell_count: desired.max_taker_sell_count,
+            reset_price_offset: false,
+        },
+    )?;
+
+    Ok((!edit.changes.is_empty()).then_some(PlanAction::Edit {
+        pool: *address,
+        changes: edit.changes,
+        args: edit.args,
+    }))
+}
+
+/// Sends the plan: pool creations, edits, SOL deposits and closes are packed into as few
+/// transactions as fit, then NFTs are deposited pool by pool.
+pub fn apply_plan(config: &CliConfig, plan: &PoolPlan) -> Result<Vec<Signature>> {
+    let owner = config.keypair.pubkey();
+
+    let instructions: Vec<Instruction> = plan
+        .actions
+        .iter()
+        .filter_map(|action| match action {
+            PlanAction::Create {
+                pool,
+                whitelist,
+                args,
+            } => Some(
+                CreatePool {
+                    rent_payer: owner,
+                    owner,
+                    pool: *pool,
+                    shared_escrow: None,
+                    whitelist: *whitelist,
+                    system_program: solana_sdk::system_program::id(),
+                }
+                .instruction(args.clone()),
+            ),
+            PlanAction::Edit { pool, args, .. } => Some(
+                EditPool {
+                    owner,
+                    pool: *pool,
+                    system_program: solana_sdk::system_program::id(),
+                }
+                .instruction(args.clone()),
+            ),
+            PlanAction::DepositSol { pool, lamports } => Some(
+                DepositSol {
+                    owner,
+                    pool: *pool,
+                    system_program: solana_sdk::system_program::id(),
+                }
+                .instruction(DepositSolInstructionArgs {
+                    lamports: *lamports,
+                }),
+            ),
+            PlanAction::Close { pool, rent_payer } => Some(
+                ClosePool {
+                    rent_payer: *rent_payer,
+                    owner,
+                    pool: *pool,
+                    system_program: solana_sdk::system_program::id(),
+                }
+                .instruction(),
+            ),
+            PlanAction::DepositNfts { .. } => None,
+        })
+        .collect();
+
+    let mut signatures = if instructions.is_empty() {
+        vec![]
+    } else {
+        send_instructions(config, instructions, &[], &[])?
+    };
+
+    for action in &plan.actions {
+        if let PlanAction::DepositNfts { pool, mints } = action {
+            let transfer = transfer_pool_nfts(config, pool, PoolDirection::Deposit, mints, &[])?;
+            signatures.extend(transfer.signatures);
+        }
+    }
+
+    Ok(signatures)
+}
+
+impl PlanAction {
+    pub fn pool(&self) -> Pubkey {
+        match self {
+            PlanAction::Create { pool, .. }
+            | PlanAction::Edit { pool, .. }
+            | PlanAction::DepositSol { pool, .. }
+            | PlanAction::DepositNfts { pool, .. }
+            | PlanAction::Close { pool, .. } => *pool,
+        }
+    }
+
+    /// One line summary, with details indented below it.
+    pub fn describe(&self) -> String {
+        match self {
+            PlanAction::Create { pool, args, .. } => format!(
+                "create {} ({} {}, starting at {} SOL)",
+                pool,
+                args.config.pool_type,
+                args.config.curve_type,
+                lamports_to_sol(args.config.starting_price)
+            ),
+            PlanAction::Edit { pool, changes, .. } => format!(
+                "edit {}{}",
+                pool,
+                changes
+                    .iter()
+                    .map(|change| format!("\n      {}", change))
+                    .collect::<String>()
+            ),
+            PlanAction::DepositSol { pool, lamports } => {
+                format!("deposit {} SOL into {}", lamports_to_sol(*lamports), pool)
+            }
+            PlanAction::DepositNfts { pool, mints } => {
+                format!("deposit {} NFTs into {}", mints.len(), pool)
+            }
+            PlanAction::Close { pool, .. } => format!("close {}", pool),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use super::*;
+
+use solana_sdk::native_token::lamports_to_sol;
+use tensor_amm::instructions::ClosePool;
+
+use crate::{commands::fetch_escrow, transactions::send_instructions};
+
+pub struct ClosePoolParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub pool: Pubkey,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClosedPool {
+    pub pool: Pubkey,
+    pub rent_payer: Pubkey,
+    /// Lamports returned to the rent payer.
+    pub rent_reclaimed: u64,
+    /// Shared escrow the pool was attached to. Its balance is not affected by closing the pool.
+    pub shared_escrow: Option<Pubkey>,
+    /// SOL left in the shared escrow, `None` when it couldn't be read.
+    pub escrow_available: Option<u64>,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn close_pool(args: ClosePoolParams) -> Result<ClosedPool> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    close_pool_with_config(&config, &args.pool)
+}
+
+/// Closes a pool owned by the config keypair once nothing is left in it.
+pub fn close_pool_with_config(config: &CliConfig, address: &Pubkey) -> Result<ClosedPool> {
+    let owner = config.keypair.pubkey();
+
+    let account = config.client.get_account(address)?;
+    let pool = deserialize_account::<Pool>(&account.data)?;
+
+    let blockers = close_blockers(&pool, &owner);
+    if !blockers.is_empty() {
+        return Err(blockers_error(
+            format!("Pool {} can't be closed yet", address),
+            &blockers,
+        ));
+    }
+
+    let ix = ClosePool {
+        rent_payer: pool.rent_payer,
+        owner,
+        pool: *address,
+        system_program: solana_sdk::system_program::id(),
+    }
+    .instruction();
+
+    // Only read to point at what is left in it, so an escrow that can't be read doesn't stop
+    // the close.
+    let shared_escrow = pool.shared_escrow.to_option();
+    let escrow_available = shared_escrow
+        .and_then(|escrow| fetch_escrow(config, &escrow).ok())
+        .map(|escrow| escrow.available);
+
+    let signatures = send_instructions(config, vec![ix], &[], &[])?;
+
+    Ok(ClosedPool {
+        pool: *address,
+        rent_payer: pool.rent_payer,
+        rent_reclaimed: account.lamports,
+        shared_escrow,
+        escrow_available,
+        signatures,
+    })
+}
+
+/// Everything that has to be done before the pool can be closed, empty when it can be closed now.
+pub fn close_blockers(pool: &Pool, signer: &Pubkey) -> Vec<String> {
+    let mut blockers = vec![];
+
+    if pool.owner != *signer {
+        blockers.push(format!(
+            "it is owned by {}, sign with the owner keypair",
+            pool.owner
+        ));
+    }
+
+    if pool.nfts_held > 0 {
+        blockers.push(format!(
+            "it holds {} NFTs, withdraw them first",
+            pool.nfts_held
+        ));
+    }
+
+    // Shared escrow pools keep their SOL in the escrow, so `amount` only counts for the others.
+    if pool.shared_escrow.to_option().is_none() && pool.amount > 0 {
+        blockers.push(format!(
+            "it holds {} SOL, withdraw it first",
+            lamports_to_sol(pool.amount)
+        ));
+    }
+
+    blockers
+}
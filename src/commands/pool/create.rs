@@ -1,22 +1,91 @@
 use super::*;
 
-use tensor_amm::instructions::{CreatePool, CreatePoolInstructionArgs};
+use tensor_amm::{
+    instructions::{CreatePool, CreatePoolInstructionArgs},
+    types::{CurveType, PoolConfig, PoolType},
+    NullableU16, HUNDRED_PCT_BPS,
+};
+use uuid::Uuid;
+
+use crate::{commands::fetch_escrow, transactions::send_instructions};
+
+// Limits the program enforces on new pools, which the client crate doesn't export.
+pub const MAX_MM_FEE_BPS: u16 = 9_999;
+pub const MAX_DELTA_BPS: u64 = 9_999;
+pub const MAX_EXPIRY_SEC: u64 = 365 * 86_400;
 
 pub struct CreatePoolParams {
     pub keypair_path: Option<PathBuf>,
     pub rpc_url: Option<String>,
-    pub pool_config_path: PathBuf,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub pool_config_path: Option<PathBuf>,
+    pub flags: PoolConfigFlags,
     pub whitelist: Pubkey,
+    /// Shared escrow the pool trades SOL from, instead of holding its own.
+    pub shared_escrow: Option<Pubkey>,
+}
+
+/// Pool config given on the command line instead of a config file. Prices and durations are
+/// kept as typed so they can be parsed with their units.
+#[derive(Debug, Clone, Default)]
+pub struct PoolConfigFlags {
+    pub pool_id: Option<String>,
+    pub pool_type: Option<PoolType>,
+    pub curve_type: Option<CurveType>,
+    pub start: Option<String>,
+    pub delta: Option<String>,
+    pub fee_bps: Option<u16>,
+    pub compound_fees: bool,
+    pub expire: Option<String>,
+    pub currency: Option<Pubkey>,
+    pub cosigner: Option<Pubkey>,
+    pub maker_broker: Option<Pubkey>,
+    pub max_taker_sell_count: Option<u32>,
 }
 
-pub fn create_pool(args: CreatePoolParams) -> Result<()> {
-    let config = CliConfig::new(args.keypair_path, args.rpc_url)?;
+#[derive(Debug, Clone)]
+pub struct CreatedPool {
+    pub pool: Pubkey,
+    pub config: CreatePoolInstructionArgs,
+    pub shared_escrow: Option<Pubkey>,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn create_pool(args: CreatePoolParams) -> Result<CreatedPool> {
+    let create_pool_args = match args.pool_config_path {
+        Some(path) => serde_json::from_reader(std::fs::File::open(path)?)?,
+        None => create_pool_args(&args.flags)?,
+    };
+
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    create_pool_with_config(
+        &config,
+        &args.whitelist,
+        args.shared_escrow,
+        create_pool_args,
+    )
+}
+
+pub fn create_pool_with_config(
+    config: &CliConfig,
+    whitelist: &Pubkey,
+    shared_escrow: Option<Pubkey>,
+    create_pool_args: CreatePoolInstructionArgs,
+) -> Result<CreatedPool> {
+    validate_create_pool_args(&create_pool_args)?;
 
     let payer = config.keypair.pubkey();
     let owner = config.keypair.pubkey();
 
-    let create_pool_args: CreatePoolInstructionArgs =
-        serde_json::from_reader(std::fs::File::open(args.pool_config_path)?)?;
+    if let Some(escrow) = shared_escrow {
+        check_shared_escrow(config, &escrow, &create_pool_args)?;
+    }
 
     let pool = Pool::find_pda(&owner, create_pool_args.pool_id).0;
 
@@ -24,17 +93,229 @@ pub fn create_pool(args: CreatePoolParams) -> Result<()> {
         rent_payer: payer,
         owner,
         pool,
-        shared_escrow: None,
-        whitelist: args.whitelist,
+        shared_escrow,
+        whitelist: *whitelist,
         system_program: solana_sdk::system_program::id(),
     }
-    .instruction(create_pool_args);
+    .instruction(create_pool_args.clone());
+
+    let signatures = send_instructions(config, vec![ix], &[], &[])?;
+
+    Ok(CreatedPool {
+        pool,
+        config: create_pool_args,
+        shared_escrow,
+        signatures,
+    })
+}
+
+/// Pools can only be attached to a shared escrow when they're created, so check it up front.
+fn check_shared_escrow(
+    config: &CliConfig,
+    address: &Pubkey,
+    args: &CreatePoolInstructionArgs,
+) -> Result<()> {
+    let escrow = fetch_escrow(config, address)?;
+    let mut blockers = vec![];
+
+    if escrow.account.owner != config.keypair.pubkey() {
+        blockers.push(format!(
+            "Shared escrow is owned by {}, not the pool owner",
+            escrow.account.owner
+        ));
+    }
+
+    if args.config.pool_type == PoolType::NFT {
+        blockers.push("NFT pools don't hold SOL, so can't use a shared escrow".to_string());
+    }
+
+    if args.currency.is_some() {
+        blockers.push("Shared escrows only hold SOL, not other currencies".to_string());
+    }
+
+    if blockers.is_empty() {
+        Ok(())
+    } else {
+        Err(blockers_error(
+            format!("Pool can't use shared escrow {}", address),
+            &blockers,
+        ))
+    }
+}
+
+/// Builds the create pool arguments from flags, with a random pool id unless one is given.
+pub fn create_pool_args(flags: &PoolConfigFlags) -> Result<CreatePoolInstructionArgs> {
+    let pool_type = flags
+        .pool_type
+        .ok_or_else(|| anyhow!("--type is required without a pool config file"))?;
+    let curve_type = flags
+        .curve_type
+        .ok_or_else(|| anyhow!("--curve is required without a pool config file"))?;
+    let starting_price = parse_price(
+        flags
+            .start
+            .as_deref()
+            .ok_or_else(|| anyhow!("--start is required without a pool config file"))?,
+    )?;
+    let delta = match &flags.delta {
+        Some(delta) => parse_delta(delta, curve_type)?,
+        None => 0,
+    };
+
+    let pool_id = match &flags.pool_id {
+        Some(pool_id) => parse_pool_id(pool_id)?,
+        None => random_pool_id(),
+    };
+
+    Ok(CreatePoolInstructionArgs {
+        pool_id,
+        config: PoolConfig {
+            pool_type,
+            curve_type,
+            starting_price,
+            delta,
+            mm_compound_fees: flags.compound_fees,
+            mm_fee_bps: flags
+                .fee_bps
+                .map(NullableU16::new)
+                .unwrap_or_else(NullableU16::none),
+        },
+        currency: flags.currency,
+        cosigner: flags.cosigner,
+        maker_broker: flags.maker_broker,
+        max_taker_sell_count: flags.max_taker_sell_count,
+        expire_in_sec: flags.expire.as_deref().map(parse_duration).transpose()?,
+    })
+}
+
+/// Rejects the configs the program would reject, listing every problem at once.
+pub fn validate_create_pool_args(args: &CreatePoolInstructionArgs) -> Result<()> {
+    let mut blockers = pool_config_blockers(&args.config);
+
+    if args
+        .expire_in_sec
+        .is_some_and(|expiry| expiry > MAX_EXPIRY_SEC)
+    {
+        blockers.push(format!(
+            "Expiry can't be more than {} days",
+            MAX_EXPIRY_SEC / 86_400
+        ));
+    }
+
+    if blockers.is_empty() {
+        Ok(())
+    } else {
+        Err(blockers_error(
+            "Pool config would be rejected".to_string(),
+            &blockers,
+        ))
+    }
+}
+
+/// Reasons the program would reject a pool config, when creating or editing a pool.
+pub fn pool_config_blockers(config: &PoolConfig) -> Vec<String> {
+    let mut blockers = vec![];
+
+    if config.starting_price < 1 {
+        blockers.push("Starting price can't be smaller than 1 lamport".to_string());
+    }
+
+    match (config.pool_type, config.mm_fee_bps.to_option()) {
+        (PoolType::Trade, Some(bps)) if *bps > MAX_MM_FEE_BPS => blockers.push(format!(
+            "MM fee of {} bps is above the maximum of {} bps",
+            bps, MAX_MM_FEE_BPS
+        )),
+        (PoolType::Token | PoolType::NFT, Some(_)) => blockers.push(format!(
+            "MM fees are only allowed on trade pools, not {} pools",
+            config.pool_type
+        )),
+        _ => {}
+    }
+
+    if config.curve_type == CurveType::Exponential && config.delta > MAX_DELTA_BPS {
+        blockers.push(format!(
+            "Exponential delta of {} bps is above the maximum of {} bps",
+            config.delta, MAX_DELTA_BPS
+        ));
+    }
+
+    blockers
+}
+
+fn random_pool_id() -> [u8; 32] {
+    let mut pool_id = [0u8; 32];
+    pool_id[..16].copy_from_slice(Uuid::new_v4().as_bytes());
+    pool_id[16..].copy_from_slice(Uuid::new_v4().as_bytes());
+    pool_id
+}
+
+/// Parses a 32 byte pool id given as hex.
+pub fn parse_pool_id(pool_id: &str) -> Result<[u8; 32]> {
+    hex::decode(pool_id.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| anyhow!("Invalid pool id, expected 32 bytes of hex: {}", pool_id))
+}
+
+/// Parses a price in SOL, e.g. `1.5` or `1.5SOL`, or in lamports, e.g. `1500lamports`.
+pub fn parse_price(price: &str) -> Result<u64> {
+    let price = price.trim();
+    let lower = price.to_lowercase();
+
+    if let Some(lamports) = lower.strip_suffix("lamports") {
+        parse_amount(lamports.trim(), true)
+    } else {
+        parse_amount(lower.strip_suffix("sol").unwrap_or(&lower).trim(), false)
+    }
+}
+
+/// Parses a curve delta. Linear deltas are prices, exponential ones are a percentage, e.g. `5%`,
+/// or basis points, e.g. `500` or `500bps`.
+pub fn parse_delta(delta: &str, curve_type: CurveType) -> Result<u64> {
+    match curve_type {
+        CurveType::Linear => parse_price(delta),
+        CurveType::Exponential => {
+            let delta = delta.trim().to_lowercase();
+
+            if let Some(pct) = delta.strip_suffix('%') {
+                // Basis points are hundredths of a percent, so at most two decimals.
+                let scaled = parse_amount(pct.trim(), false)?;
+                let unit = LAMPORTS_PER_SOL / (HUNDRED_PCT_BPS as u64 / 100);
+
+                if scaled % unit != 0 {
+                    return Err(anyhow!("Delta can't be finer than 0.01%: {}%", pct));
+                }
+
+                Ok(scaled / unit)
+            } else {
+                let bps = delta.strip_suffix("bps").unwrap_or(&delta).trim();
+                parse_amount(bps, true)
+            }
+        }
+    }
+}
 
-    let tx = transaction!(&[&config.keypair], &[ix], &config.client);
+/// Parses a duration in seconds, or with a `s`, `m`, `h` or `d` unit, e.g. `30d`.
+pub fn parse_duration(duration: &str) -> Result<u64> {
+    let invalid = || anyhow!("Invalid duration: {}", duration);
 
-    config.client.send_and_confirm_transaction(&tx)?;
+    let duration = duration.trim();
+    let (value, unit) = match duration.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => duration.split_at(index),
+        None => (duration, "s"),
+    };
 
-    println!("Pool created: {}", pool);
+    let multiplier = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        _ => return Err(invalid()),
+    };
 
-    Ok(())
+    value
+        .parse::<u64>()
+        .ok()
+        .and_then(|value| value.checked_mul(multiplier))
+        .ok_or_else(invalid)
 }
@@ -4,86 +4,299 @@ use borsh::BorshDeserialize;
 use serde::{Deserialize, Serialize};
 use tensor_amm::{
     instructions::{EditPool, EditPoolInstructionArgs},
-    types::{CurveType, PoolConfig, PoolType},
+    types::{CurveType, PoolConfig},
     NullableU16,
 };
 
+use crate::{
+    formatting::{format_date, format_duration, option_formatter},
+    transactions::send_instructions,
+};
+
 pub struct EditPoolParams {
     pub keypair_path: Option<PathBuf>,
     pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
     pub pool: Pubkey,
-    pub edit_pool_config_path: PathBuf,
+    pub edit_pool_config_path: Option<PathBuf>,
+    pub flags: EditPoolFlags,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
-struct EditPoolArgs {
+/// Edits given on the command line instead of a config file, in the `pool create` formats.
+#[derive(Debug, Clone, Default)]
+pub struct EditPoolFlags {
+    pub curve_type: Option<CurveType>,
+    pub start: Option<String>,
+    pub delta: Option<String>,
+    pub fee_bps: Option<u16>,
+    pub no_fee: bool,
+    pub compound_fees: Option<bool>,
+    pub cosigner: Option<Pubkey>,
+    pub expire: Option<String>,
+    pub max_taker_sell_count: Option<u32>,
+    pub reset_price_offset: bool,
+}
+
+/// Partial edit of a pool. Fields left out keep their current value.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct EditPoolArgs {
     pub new_config: Option<EditPoolConfig>,
     pub cosigner: Option<Pubkey>,
     pub expire_in_sec: Option<u64>,
     pub max_taker_sell_count: Option<u32>,
+    #[serde(default)]
     pub reset_price_offset: bool,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
 pub struct EditPoolConfig {
-    pub curve_type: CurveType,
-    pub starting_price: u64,
-    pub delta: u64,
-    pub mm_compound_fees: bool,
-    pub mm_fee_bps: NullableU16,
+    pub curve_type: Option<CurveType>,
+    pub starting_price: Option<u64>,
+    pub delta: Option<u64>,
+    pub mm_compound_fees: Option<bool>,
+    pub mm_fee_bps: Option<NullableU16>,
 }
 
 impl EditPoolConfig {
-    fn convert(&self, pool_type: PoolType) -> PoolConfig {
+    /// The current config with the edited fields replaced.
+    pub fn merge(&self, current: &PoolConfig) -> PoolConfig {
         PoolConfig {
-            pool_type,
-            curve_type: self.curve_type,
-            starting_price: self.starting_price,
-            delta: self.delta,
-            mm_compound_fees: self.mm_compound_fees,
-            mm_fee_bps: self.mm_fee_bps.clone(),
+            pool_type: current.pool_type,
+            curve_type: self.curve_type.unwrap_or(current.curve_type),
+            starting_price: self.starting_price.unwrap_or(current.starting_price),
+            delta: self.delta.unwrap_or(current.delta),
+            mm_compound_fees: self.mm_compound_fees.unwrap_or(current.mm_compound_fees),
+            mm_fee_bps: self
+                .mm_fee_bps
+                .clone()
+                .unwrap_or_else(|| current.mm_fee_bps.clone()),
         }
     }
 }
 
 impl EditPoolArgs {
-    fn convert(&self, pool_type: PoolType) -> EditPoolInstructionArgs {
-        EditPoolInstructionArgs {
-            new_config: self.new_config.as_ref().map(|c| c.convert(pool_type)),
-            cosigner: self.cosigner,
-            expire_in_sec: self.expire_in_sec,
-            max_taker_sell_count: self.max_taker_sell_count,
-            reset_price_offset: self.reset_price_offset,
-        }
+    /// Parses the flags against the current config, which decides the unit of the delta.
+    pub fn from_flags(flags: &EditPoolFlags, current: &PoolConfig) -> Result<Self> {
+        let curve_type = flags.curve_type.unwrap_or(current.curve_type);
+
+        let mm_fee_bps = match (flags.fee_bps, flags.no_fee) {
+            (Some(_), true) => return Err(anyhow!("--fee-bps can't be used with --no-fee")),
+            (Some(bps), false) => Some(NullableU16::new(bps)),
+            (None, true) => Some(NullableU16::none()),
+            (None, false) => None,
+        };
+
+        let new_config = EditPoolConfig {
+            curve_type: flags.curve_type,
+            starting_price: flags.start.as_deref().map(parse_price).transpose()?,
+            delta: flags
+                .delta
+                .as_deref()
+                .map(|delta| parse_delta(delta, curve_type))
+                .transpose()?,
+            mm_compound_fees: flags.compound_fees,
+            mm_fee_bps,
+        };
+
+        Ok(Self {
+            new_config: (new_config != EditPoolConfig::default()).then_some(new_config),
+            cosigner: flags.cosigner,
+            expire_in_sec: flags.expire.as_deref().map(parse_duration).transpose()?,
+            max_taker_sell_count: flags.max_taker_sell_count,
+            reset_price_offset: flags.reset_price_offset,
+        })
     }
 }
 
-pub fn edit_pool(args: EditPoolParams) -> Result<()> {
-    let config = CliConfig::new(args.keypair_path, args.rpc_url)?;
+/// An edit resolved against the pool, with the `field: before -> after` changes it makes.
+#[derive(Debug, Clone)]
+pub struct PoolEdit {
+    pub changes: Vec<String>,
+    pub args: EditPoolInstructionArgs,
+}
+
+#[derive(Debug, Clone)]
+pub struct EditedPool {
+    pub pool: Pubkey,
+    /// `None` when the edit wasn't confirmed.
+    pub changes: Option<Vec<String>>,
+    pub signatures: Vec<Signature>,
+}
 
-    let owner = config.keypair.pubkey();
+/// Resolves the edit against the pool and sends it once `confirm` agrees to the changes. Leaves
+/// showing the changes and asking to the caller.
+pub fn edit_pool(
+    args: EditPoolParams,
+    confirm: impl FnOnce(&PoolEdit) -> Result<bool>,
+) -> Result<EditedPool> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
 
     // Fetch and decode pool account.
     let pool_data = config.client.get_account_data(&args.pool)?;
-    let pool_type = Pool::try_from_slice(&pool_data)?.config.pool_type;
+    let pool = Pool::try_from_slice(&pool_data)?;
 
-    let edit_pool_args: EditPoolArgs =
-        serde_json::from_reader(std::fs::File::open(args.edit_pool_config_path)?)?;
+    let edit_pool_args = match args.edit_pool_config_path {
+        Some(path) => serde_json::from_reader(std::fs::File::open(path)?)?,
+        None => EditPoolArgs::from_flags(&args.flags, &pool.config)?,
+    };
 
-    println!("{:?}", edit_pool_args);
+    let edit = diff_pool_edit(&pool, &edit_pool_args)?;
+    if edit.changes.is_empty() {
+        return Err(anyhow!(
+            "Nothing to change, the pool already matches the edit"
+        ));
+    }
 
-    let ix = EditPool {
-        owner,
+    if !confirm(&edit)? {
+        return Ok(EditedPool {
+            pool: args.pool,
+            changes: None,
+            signatures: vec![],
+        });
+    }
+
+    let signatures = edit_pool_with_config(&config, &args.pool, &edit)?;
+
+    Ok(EditedPool {
         pool: args.pool,
-        system_program: solana_sdk::system_program::id(),
+        changes: Some(edit.changes),
+        signatures,
+    })
+}
+
+/// Merges the edit into the pool's current state and lists what it changes. Fields set to their
+/// current value are left out of the instruction, so the changes are empty if nothing differs.
+pub fn diff_pool_edit(pool: &Pool, edit: &EditPoolArgs) -> Result<PoolEdit> {
+    let mut changes = vec![];
+
+    let new_config = edit
+        .new_config
+        .as_ref()
+        .map(|new_config| new_config.merge(&pool.config))
+        .filter(|new_config| *new_config != pool.config);
+
+    if let Some(new_config) = &new_config {
+        let blockers = pool_config_blockers(new_config);
+        if !blockers.is_empty() {
+            return Err(blockers_error(
+                "Pool config would be rejected".to_string(),
+                &blockers,
+            ));
+        }
+
+        let current = &pool.config;
+        diff_field(
+            &mut changes,
+            "curve_type",
+            current.curve_type,
+            new_config.curve_type,
+        );
+        diff_field(
+            &mut changes,
+            "starting_price",
+            current.starting_price,
+            new_config.starting_price,
+        );
+        diff_field(&mut changes, "delta", current.delta, new_config.delta);
+        diff_field(
+            &mut changes,
+            "mm_compound_fees",
+            current.mm_compound_fees,
+            new_config.mm_compound_fees,
+        );
+        diff_field(
+            &mut changes,
+            "mm_fee_bps",
+            option_formatter(&current.mm_fee_bps.to_option()),
+            option_formatter(&new_config.mm_fee_bps.to_option()),
+        );
     }
-    .instruction(edit_pool_args.convert(pool_type));
 
-    let tx = transaction!(&[&config.keypair], &[ix], &config.client);
+    let cosigner = edit
+        .cosigner
+        .filter(|cosigner| pool.cosigner.to_option() != Some(*cosigner));
+    if let Some(cosigner) = cosigner {
+        diff_field(
+            &mut changes,
+            "cosigner",
+            option_formatter(&pool.cosigner.to_option()),
+            cosigner,
+        );
+    }
 
-    config.client.send_and_confirm_transaction(&tx)?;
+    if let Some(expire_in_sec) = edit.expire_in_sec {
+        if expire_in_sec > MAX_EXPIRY_SEC {
+            return Err(anyhow!(
+                "Expiry can't be more than {} days",
+                MAX_EXPIRY_SEC / 86_400
+            ));
+        }
+        changes.push(format!(
+            "expiry: {} -> {} from now",
+            format_date(pool.expiry),
+            format_duration(expire_in_sec as i64)
+        ));
+    }
 
-    println!("Pool updated: {}", args.pool);
+    let max_taker_sell_count = edit
+        .max_taker_sell_count
+        .filter(|max| pool.max_taker_sell_count != *max);
+    if let Some(max) = max_taker_sell_count {
+        diff_field(
+            &mut changes,
+            "max_taker_sell_count",
+            pool.max_taker_sell_count,
+            max,
+        );
+    }
+
+    let reset_price_offset = edit.reset_price_offset && pool.price_offset != 0;
+    if reset_price_offset {
+        diff_field(&mut changes, "price_offset", pool.price_offset, 0);
+    }
 
-    Ok(())
+    Ok(PoolEdit {
+        changes,
+        args: EditPoolInstructionArgs {
+            new_config,
+            cosigner,
+            expire_in_sec: edit.expire_in_sec,
+            max_taker_sell_count,
+            reset_price_offset,
+        },
+    })
+}
+
+pub fn edit_pool_with_config(
+    config: &CliConfig,
+    address: &Pubkey,
+    edit: &PoolEdit,
+) -> Result<Vec<Signature>> {
+    let ix = EditPool {
+        owner: config.keypair.pubkey(),
+        pool: *address,
+        system_program: solana_sdk::system_program::id(),
+    }
+    .instruction(edit.args.clone());
+
+    send_instructions(config, vec![ix], &[], &[])
+}
+
+/// Adds a `field: before -> after` line if the value changes.
+pub fn diff_field(
+    changes: &mut Vec<String>,
+    field: &str,
+    before: impl ToString,
+    after: impl ToString,
+) {
+    let (before, after) = (before.to_string(), after.to_string());
+
+    if before != after {
+        changes.push(format!("{}: {} -> {}", field, before, after));
+    }
 }
@@ -0,0 +1,137 @@
+use super::*;
+
+use tensor_amm::{
+    programs::TENSOR_AMM_ID,
+    types::{CurveType, PoolType, TakerSide},
+};
+
+use crate::{
+    args::PoolSort,
+    discriminators::Discriminator,
+    rpc::{discriminator_filter, memcmp_filter},
+};
+
+// Offsets of the `Pool` fields that can be filtered on with memcmp.
+pub const POOL_OWNER_OFFSET: usize = 66;
+pub const POOL_WHITELIST_OFFSET: usize = 98;
+pub const POOL_SHARED_ESCROW_OFFSET: usize = 226;
+pub const POOL_COSIGNER_OFFSET: usize = 258;
+
+pub struct PoolListParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub filters: PoolFilters,
+    pub sort: PoolSort,
+    pub desc: bool,
+}
+
+/// Which pools to fetch. Address filters run on the RPC node, the others locally.
+#[derive(Debug, Clone, Default)]
+pub struct PoolFilters {
+    pub owner: Option<Pubkey>,
+    pub whitelist: Option<Pubkey>,
+    pub cosigner: Option<Pubkey>,
+    pub shared_escrow: Option<Pubkey>,
+    pub pool_type: Option<PoolType>,
+    pub curve_type: Option<CurveType>,
+    /// Only pools that haven't expired.
+    pub active: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolEntry {
+    pub address: Pubkey,
+    pub pool: Pool,
+    /// Price the pool currently quotes, in lamports: what it pays for an NFT for token pools and
+    /// what it sells one for otherwise.
+    pub price: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolList {
+    pub pools: Vec<PoolEntry>,
+}
+
+pub fn handle_pool_list(args: PoolListParams) -> Result<PoolList> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    let mut pools = fetch_pools(&config, &args.filters)?;
+    sort_pools(&mut pools, args.sort, args.desc);
+
+    Ok(PoolList { pools })
+}
+
+/// Fetches every pool matching the filters.
+pub fn fetch_pools(config: &CliConfig, filters: &PoolFilters) -> Result<Vec<PoolEntry>> {
+    let memcmps = [
+        (POOL_OWNER_OFFSET, filters.owner),
+        (POOL_WHITELIST_OFFSET, filters.whitelist),
+        (POOL_SHARED_ESCROW_OFFSET, filters.shared_escrow),
+        (POOL_COSIGNER_OFFSET, filters.cosigner),
+    ]
+    .into_iter()
+    .filter_map(|(offset, address)| address.map(|address| memcmp_filter(offset, address.as_ref())))
+    .collect();
+
+    let accounts = config.client.get_program_accounts_with_config(
+        &TENSOR_AMM_ID,
+        discriminator_filter(config.commitment, Pool::discriminator(), memcmps),
+    )?;
+
+    let now = chrono::Utc::now().timestamp();
+
+    let mut pools = vec![];
+
+    for (address, account) in accounts {
+        let pool = deserialize_account::<Pool>(&account.data)
+            .map_err(|e| anyhow!("Invalid pool {}: {}", address, e))?;
+
+        if filters
+            .pool_type
+            .is_some_and(|pool_type| pool.config.pool_type != pool_type)
+            || filters
+                .curve_type
+                .is_some_and(|curve_type| pool.config.curve_type != curve_type)
+            || (filters.active && pool.expiry <= now)
+        {
+            continue;
+        }
+
+        pools.push(PoolEntry {
+            address,
+            price: quoted_price(&pool),
+            pool,
+        });
+    }
+
+    Ok(pools)
+}
+
+/// Current price of the side the pool trades on.
+pub fn quoted_price(pool: &Pool) -> Option<u64> {
+    let side = match pool.config.pool_type {
+        PoolType::Token => TakerSide::Sell,
+        PoolType::NFT | PoolType::Trade => TakerSide::Buy,
+    };
+
+    pool.current_price(side).ok()
+}
+
+pub fn sort_pools(pools: &mut [PoolEntry], sort: PoolSort, desc: bool) {
+    pools.sort_by(|a, b| {
+        let ordering = match sort {
+            PoolSort::Address => a.address.to_string().cmp(&b.address.to_string()),
+            PoolSort::Price => a.price.cmp(&b.price),
+            PoolSort::Amount => a.pool.amount.cmp(&b.pool.amount),
+            PoolSort::Nfts => a.pool.nfts_held.cmp(&b.pool.nfts_held),
+            PoolSort::Expiry => a.pool.expiry.cmp(&b.pool.expiry),
+            PoolSort::Created => a.pool.created_at.cmp(&b.pool.created_at),
+        };
+
+        if desc {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
@@ -1,15 +1,163 @@
+mod apply;
+mod close;
 mod create;
 mod edit;
+mod list;
+mod nft;
+mod quote;
+mod report;
+mod sol;
 
+pub use apply::*;
+pub use close::*;
 pub use create::*;
 pub use edit::*;
+pub use list::*;
+pub use nft::*;
+pub use quote::*;
+pub use report::*;
+pub use sol::*;
 
 use std::path::PathBuf;
 
 use {
-    anyhow::Result,
-    solana_sdk::{pubkey::Pubkey, signer::Signer, transaction::Transaction},
+    anyhow::{anyhow, Result},
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL, pubkey::Pubkey, signature::Signature, signer::Signer,
+    },
     tensor_amm::accounts::Pool,
 };
 
-use crate::{setup::CliConfig, transaction};
+use crate::{discriminators::deserialize_account, setup::CliConfig};
+
+/// Whether assets are moving into or out of a pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolDirection {
+    Deposit,
+    Withdraw,
+}
+
+/// Fetches and decodes a pool account.
+pub fn fetch_pool(config: &CliConfig, address: &Pubkey) -> Result<Pool> {
+    let data = config.client.get_account_data(address)?;
+
+    deserialize_account::<Pool>(&data).map_err(|e| anyhow!("Invalid pool {}: {}", address, e))
+}
+
+/// Combines everything that stops an action on a pool into a single error.
+pub(crate) fn blockers_error(summary: String, blockers: &[String]) -> anyhow::Error {
+    anyhow!(
+        "{}:\n{}",
+        summary,
+        blockers
+            .iter()
+            .map(|blocker| format!("  - {}", blocker))
+            .collect::<Vec<_>>()
+            .join("\n")
+    )
+}
+
+/// Parses an amount of SOL such as `1.5` into lamports, or a whole number of lamports when
+/// `lamports` is set. Parsing the decimal string avoids float rounding errors.
+pub fn parse_amount(amount: &str, lamports: bool) -> Result<u64> {
+    if lamports {
+        return amount
+            .parse::<u64>()
+            .map_err(|_| anyhow!("Invalid amount: {}", amount));
+    }
+
+    parse_token_amount(amount, 9)
+}
+
+/// Parses a decimal amount such as `1.5` into base units of a token with `decimals` decimals.
+pub fn parse_token_amount(amount: &str, decimals: u8) -> Result<u64> {
+    let invalid = || anyhow!("Invalid amount: {}", amount);
+
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+
+    if (whole.is_empty() && fraction.is_empty())
+        || fraction.len() > decimals as usize
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let whole = if whole.is_empty() {
+        0
+    } else {
+        whole.parse::<u64>().map_err(|_| invalid())?
+    };
+    let fraction = if decimals == 0 {
+        0
+    } else {
+        format!("{:0<width$}", fraction, width = decimals as usize)
+            .parse::<u64>()
+            .map_err(|_| invalid())?
+    };
+
+    10u64
+        .checked_pow(decimals as u32)
+        .and_then(|unit| whole.checked_mul(unit))
+        .and_then(|units| units.checked_add(fraction))
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("1", false).unwrap(), LAMPORTS_PER_SOL);
+        assert_eq!(parse_amount("1.5", false).unwrap(), 1_500_000_000);
+        assert_eq!(parse_amount("0.3", false).unwrap(), 300_000_000);
+        assert_eq!(parse_amount(".000000001", false).unwrap(), 1);
+        assert_eq!(parse_amount("2.", false).unwrap(), 2 * LAMPORTS_PER_SOL);
+        assert_eq!(parse_amount("1500", true).unwrap(), 1500);
+
+        assert!(parse_amount("0.0000000001", false).is_err());
+        assert!(parse_amount("1.5", true).is_err());
+        assert!(parse_amount("-1", false).is_err());
+        assert!(parse_amount(".", false).is_err());
+        assert!(parse_amount("abc", false).is_err());
+        assert!(parse_amount("18446744074", false).is_err());
+
+        assert_eq!(parse_token_amount("2.5", 6).unwrap(), 2_500_000);
+        assert_eq!(parse_token_amount("7", 0).unwrap(), 7);
+        assert!(parse_token_amount("7.5", 0).is_err());
+        assert!(parse_token_amount("0.0000001", 6).is_err());
+    }
+
+    #[test]
+    fn test_parse_pool_flags() {
+        use tensor_amm::types::CurveType;
+
+        assert_eq!(parse_price("1.5SOL").unwrap(), 1_500_000_000);
+        assert_eq!(parse_price("1.5 sol").unwrap(), 1_500_000_000);
+        assert_eq!(parse_price("2").unwrap(), 2 * LAMPORTS_PER_SOL);
+        assert_eq!(parse_price("1500lamports").unwrap(), 1500);
+        assert!(parse_price("1.5lamports").is_err());
+
+        assert_eq!(parse_delta("5%", CurveType::Exponential).unwrap(), 500);
+        assert_eq!(parse_delta("2.5%", CurveType::Exponential).unwrap(), 250);
+        assert_eq!(parse_delta("500bps", CurveType::Exponential).unwrap(), 500);
+        assert_eq!(parse_delta("500", CurveType::Exponential).unwrap(), 500);
+        assert!(parse_delta("0.005%", CurveType::Exponential).is_err());
+        assert_eq!(
+            parse_delta("0.1SOL", CurveType::Linear).unwrap(),
+            100_000_000
+        );
+
+        assert_eq!(parse_duration("30d").unwrap(), 30 * 86_400);
+        assert_eq!(parse_duration("12h").unwrap(), 12 * 3_600);
+        assert_eq!(parse_duration("90").unwrap(), 90);
+        assert!(parse_duration("1w").is_err());
+        assert!(parse_duration("d").is_err());
+
+        assert_eq!(parse_pool_id(&"01".repeat(32)).unwrap(), [1; 32]);
+        assert!(parse_pool_id("0101").is_err());
+    }
+}
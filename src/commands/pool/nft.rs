@@ -0,0 +1,361 @@
+use super::*;
+
+use solana_sdk::{address_lookup_table::AddressLookupTableAccount, instruction::Instruction};
+use tensor_amm::{
+    accounts::NftDepositReceipt,
+    instructions::{
+        DepositNft, DepositNftCore, DepositNftInstructionArgs, DepositNftT22, WithdrawNft,
+        WithdrawNftCore, WithdrawNftInstructionArgs, WithdrawNftT22,
+    },
+    types::PoolType,
+};
+use tensor_whitelist::{
+    accounts::{MintProofV2, WhitelistV2},
+    types::Mode,
+};
+
+use crate::{
+    commands::{
+        collect_addresses, fetch_assets, token_account_amount, Asset, AssetStandard,
+        ASSOCIATED_TOKEN_ID, MPL_CORE_ID, SYSVAR_INSTRUCTIONS, TOKEN_AUTH_RULES_ID,
+        TOKEN_METADATA_ID,
+    },
+    rpc::{block_on, get_multiple_accounts_batched},
+    transactions::{fetch_lookup_tables, send_instructions},
+    types::metaplex::{find_edition_pda, find_token_record_pda, Metadata},
+};
+
+pub struct PoolNftParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub pool: Pubkey,
+    pub mints: Vec<Pubkey>,
+    pub file: Option<PathBuf>,
+    pub lookup_tables: Vec<Pubkey>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolNftTransfer {
+    pub pool: Pubkey,
+    pub direction: PoolDirection,
+    pub assets: Vec<Asset>,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn deposit_nft(args: PoolNftParams) -> Result<PoolNftTransfer> {
+    handle_pool_nft(args, PoolDirection::Deposit)
+}
+
+pub fn withdraw_nft(args: PoolNftParams) -> Result<PoolNftTransfer> {
+    handle_pool_nft(args, PoolDirection::Withdraw)
+}
+
+fn handle_pool_nft(args: PoolNftParams, direction: PoolDirection) -> Result<PoolNftTransfer> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let mints = collect_addresses(args.mints, args.file)?;
+    let lookup_tables = fetch_lookup_tables(&config, &args.lookup_tables)?;
+
+    transfer_pool_nfts(&config, &args.pool, direction, &mints, &lookup_tables)
+}
+
+/// Deposits NFTs into, or withdraws NFTs from, a pool owned by the config keypair. The asset
+/// standard of every mint is detected to build the matching instruction, and the instructions
+/// are packed into as few transactions as fit.
+pub fn transfer_pool_nfts(
+    config: &CliConfig,
+    address: &Pubkey,
+    direction: PoolDirection,
+    mints: &[Pubkey],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<PoolNftTransfer> {
+    if mints.is_empty() {
+        return Err(anyhow!("No mints provided"));
+    }
+
+    let owner = config.keypair.pubkey();
+    let pool = fetch_pool(config, address)?;
+
+    if pool.owner != owner {
+        return Err(anyhow!(
+            "Pool is owned by {}, sign with the owner keypair",
+            pool.owner
+        ));
+    }
+
+    if direction == PoolDirection::Deposit && pool.config.pool_type == PoolType::Token {
+        return Err(anyhow!(
+            "Token pools only buy NFTs and can't hold them, use an NFT or Trade pool"
+        ));
+    }
+
+    let assets = fetch_assets(config, mints)?;
+
+    // Mints of merkle tree whitelists are verified against a mint proof.
+    let whitelist = config.client.get_account_data(&pool.whitelist)?;
+    let needs_proof = deserialize_account::<WhitelistV2>(&whitelist)
+        .map_err(|e| anyhow!("Invalid whitelist {}: {}", pool.whitelist, e))?
+        .conditions
+        .iter()
+        .any(|condition| condition.mode == Mode::MerkleTree);
+
+    let accounts = NftAccounts::new(&owner, address, &pool, needs_proof);
+
+    check_assets(config, &accounts, &assets, direction)?;
+
+    let ixs: Vec<Instruction> = assets
+        .iter()
+        .map(|asset| match direction {
+            PoolDirection::Deposit => accounts.deposit(asset),
+            PoolDirection::Withdraw => accounts.withdraw(asset),
+        })
+        .collect();
+
+    let signatures = send_instructions(config, ixs, &[], lookup_tables)?;
+
+    Ok(PoolNftTransfer {
+        pool: *address,
+        direction,
+        assets,
+        signatures,
+    })
+}
+
+/// Checks up front that every asset can be moved, so a bulk transfer doesn't fail halfway.
+fn check_assets(
+    config: &CliConfig,
+    accounts: &NftAccounts,
+    assets: &[Asset],
+    direction: PoolDirection,
+) -> Result<()> {
+    // The owner's token account for deposits, the deposit receipt for withdrawals, then the mint
+    // proof, for every asset.
+    let addresses: Vec<Pubkey> = assets
+        .iter()
+        .flat_map(|asset| {
+            let holding = match direction {
+                PoolDirection::Deposit => asset.token_account(&accounts.owner),
+                PoolDirection::Withdraw => accounts.receipt(asset),
+            };
+            [
+                holding,
+                MintProofV2::find_pda(&asset.mint, &accounts.whitelist).0,
+            ]
+        })
+        .collect();
+
+    let fetched = block_on(get_multiple_accounts_batched(
+        config.client.clone(),
+        &addresses,
+        config.max_concurrency,
+    ))??;
+
+    let mut blockers = vec![];
+
+    for (asset, fetched) in assets.iter().zip(fetched.chunks(2)) {
+        let (holding, proof) = (&fetched[0], &fetched[1]);
+
+        match direction {
+            PoolDirection::Deposit if asset.standard == AssetStandard::Core => {
+                if asset.owner != Some(accounts.owner) {
+                    blockers.push(format!("{} is not owned by {}", asset.mint, accounts.owner));
+                }
+            }
+            PoolDirection::Deposit => {
+                let amount = holding
+                    .as_ref()
+                    .and_then(|account| token_account_amount(&account.data));
+                if amount != Some(1) {
+                    blockers.push(format!(
+                        "{} is not in the associated token account of {}",
+                        asset.mint, accounts.owner
+                    ));
+                }
+            }
+            PoolDirection::Withdraw => {
+                if holding.is_none() {
+                    blockers.push(format!("{} is not deposited in this pool", asset.mint));
+                }
+            }
+        }
+
+        if accounts.needs_proof && proof.is_none() {
+            blockers.push(format!(
+                "{} has no mint proof for whitelist {}",
+                asset.mint, accounts.whitelist
+            ));
+        }
+    }
+
+    if !blockers.is_empty() {
+        return Err(blockers_error(
+            format!("Can't move {} NFTs", assets.len()),
+            &blockers,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Accounts shared by every NFT moved in or out of a pool.
+struct NftAccounts {
+    owner: Pubkey,
+    pool: Pubkey,
+    whitelist: Pubkey,
+    needs_proof: bool,
+}
+
+impl NftAccounts {
+    fn new(owner: &Pubkey, address: &Pubkey, pool: &Pool, needs_proof: bool) -> Self {
+        Self {
+            owner: *owner,
+            pool: *address,
+            whitelist: pool.whitelist,
+            needs_proof,
+        }
+    }
+
+    fn receipt(&self, asset: &Asset) -> Pubkey {
+        NftDepositReceipt::find_pda(&asset.mint, &self.pool).0
+    }
+
+    fn mint_proof(&self, asset: &Asset) -> Option<Pubkey> {
+        self.needs_proof
+            .then(|| MintProofV2::find_pda(&asset.mint, &self.whitelist).0)
+    }
+
+    /// Token records of the owner and pool token accounts, for programmable NFTs only.
+    fn token_records(&self, asset: &Asset) -> (Option<Pubkey>, Option<Pubkey>) {
+        if asset.standard != AssetStandard::Programmable {
+            return (None, None);
+        }
+
+        (
+            Some(find_token_record_pda(&asset.mint, &asset.token_account(&self.owner)).0),
+            Some(find_token_record_pda(&asset.mint, &asset.token_account(&self.pool)).0),
+        )
+    }
+
+    fn deposit(&self, asset: &Asset) -> Instruction {
+        match asset.standard {
+            AssetStandard::Core => DepositNftCore {
+                owner: self.owner,
+                pool: self.pool,
+                whitelist: self.whitelist,
+                mint_proof: self.mint_proof(asset),
+                asset: asset.mint,
+                collection: asset.collection,
+                mpl_core_program: MPL_CORE_ID,
+                nft_receipt: self.receipt(asset),
+                system_program: solana_sdk::system_program::id(),
+            }
+            .instruction(),
+            AssetStandard::Token2022 => DepositNftT22 {
+                owner: self.owner,
+                pool: self.pool,
+                whitelist: self.whitelist,
+                mint_proof: self.mint_proof(asset),
+                mint: asset.mint,
+                nft_receipt: self.receipt(asset),
+                owner_ta: asset.token_account(&self.owner),
+                pool_ta: asset.token_account(&self.pool),
+                token_program: asset.token_program,
+                associated_token_program: ASSOCIATED_TOKEN_ID,
+                system_program: solana_sdk::system_program::id(),
+            }
+            .instruction(),
+            AssetStandard::Legacy | AssetStandard::Programmable => {
+                let (user_token_record, pool_token_record) = self.token_records(asset);
+
+                DepositNft {
+                    owner: self.owner,
+                    pool: self.pool,
+                    whitelist: self.whitelist,
+                    mint_proof: self.mint_proof(asset),
+                    mint: asset.mint,
+                    metadata: Metadata::find_pda(&asset.mint).0,
+                    edition: find_edition_pda(&asset.mint).0,
+                    user_token_record,
+                    pool_token_record,
+                    token_metadata_program: Some(TOKEN_METADATA_ID),
+                    sysvar_instructions: Some(SYSVAR_INSTRUCTIONS),
+                    authorization_rules: asset.rule_set,
+                    authorization_rules_program: asset.rule_set.map(|_| TOKEN_AUTH_RULES_ID),
+                    nft_receipt: self.receipt(asset),
+                    owner_ta: asset.token_account(&self.owner),
+                    pool_ta: asset.token_account(&self.pool),
+                    token_program: asset.token_program,
+                    associated_token_program: ASSOCIATED_TOKEN_ID,
+                    system_program: solana_sdk::system_program::id(),
+                }
+                .instruction(DepositNftInstructionArgs {
+                    authorization_data: None,
+                })
+            }
+        }
+    }
+
+    fn withdraw(&self, asset: &Asset) -> Instruction {
+        match asset.standard {
+            AssetStandard::Core => WithdrawNftCore {
+                owner: self.owner,
+                pool: self.pool,
+                whitelist: Some(self.whitelist),
+                mint_proof: self.mint_proof(asset),
+                asset: asset.mint,
+                collection: asset.collection,
+                mpl_core_program: MPL_CORE_ID,
+                nft_receipt: self.receipt(asset),
+            }
+            .instruction(),
+            AssetStandard::Token2022 => WithdrawNftT22 {
+                owner: self.owner,
+                pool: self.pool,
+                whitelist: Some(self.whitelist),
+                mint_proof: self.mint_proof(asset),
+                mint: asset.mint,
+                nft_receipt: self.receipt(asset),
+                owner_ta: asset.token_account(&self.owner),
+                pool_ta: asset.token_account(&self.pool),
+                token_program: asset.token_program,
+                associated_token_program: ASSOCIATED_TOKEN_ID,
+                system_program: solana_sdk::system_program::id(),
+            }
+            .instruction(),
+            AssetStandard::Legacy | AssetStandard::Programmable => {
+                let (user_token_record, pool_token_record) = self.token_records(asset);
+
+                WithdrawNft {
+                    owner: self.owner,
+                    pool: self.pool,
+                    whitelist: Some(self.whitelist),
+                    mint_proof: self.mint_proof(asset),
+                    mint: asset.mint,
+                    metadata: Metadata::find_pda(&asset.mint).0,
+                    edition: find_edition_pda(&asset.mint).0,
+                    user_token_record,
+                    pool_token_record,
+                    token_metadata_program: Some(TOKEN_METADATA_ID),
+                    sysvar_instructions: Some(SYSVAR_INSTRUCTIONS),
+                    authorization_rules: asset.rule_set,
+                    authorization_rules_program: asset.rule_set.map(|_| TOKEN_AUTH_RULES_ID),
+                    nft_receipt: self.receipt(asset),
+                    owner_ta: asset.token_account(&self.owner),
+                    pool_ta: asset.token_account(&self.pool),
+                    token_program: asset.token_program,
+                    associated_token_program: ASSOCIATED_TOKEN_ID,
+                    system_program: solana_sdk::system_program::id(),
+                }
+                .instruction(WithdrawNftInstructionArgs {
+                    authorization_data: None,
+                })
+            }
+        }
+    }
+}
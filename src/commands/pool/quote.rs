@@ -0,0 +1,317 @@
+use super::*;
+
+use solana_sdk::native_token::lamports_to_sol;
+use tensor_amm::{
+    calc_fees,
+    instructions::CreatePoolInstructionArgs,
+    types::{PoolStats, PoolType, TakerSide},
+    Currency, NullableAddress, HUNDRED_PCT_BPS,
+};
+
+pub struct PoolQuoteParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub pool: Option<Pubkey>,
+    pub pool_config_path: Option<PathBuf>,
+    pub count: u32,
+    pub royalty_bps: u16,
+    pub royalty_pct: u8,
+}
+
+/// Royalties a taker pays on top of the curve price: the collection's seller fee basis points,
+/// and the share of it paid (100 for pNFTs, where royalties are enforced).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Royalties {
+    pub bps: u16,
+    pub pct: u8,
+}
+
+impl Royalties {
+    pub fn none() -> Self {
+        Self { bps: 0, pct: 100 }
+    }
+
+    pub fn amount(&self, price: u64) -> Result<u64> {
+        let fee = (price as u128) * (self.bps as u128) / (HUNDRED_PCT_BPS as u128);
+        Ok(u64::try_from(fee * (self.pct as u128) / 100)?)
+    }
+}
+
+/// One trade against the pool, in lamports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteStep {
+    /// Curve price at the time of the trade.
+    pub price: u64,
+    /// Taker fee, split between the protocol and the brokers.
+    pub taker_fee: u64,
+    pub maker_broker_fee: u64,
+    pub royalty: u64,
+    /// Market making fee kept by the pool owner, trade pools only.
+    pub mm_fee: u64,
+    /// What the taker pays on a buy, or receives on a sell.
+    pub total: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolQuote {
+    /// Live pool address, `None` when quoting a local config.
+    pub address: Option<Pubkey>,
+    pub pool: Pool,
+    pub royalties: Royalties,
+    /// Next buys from the pool, cheapest first.
+    pub buys: Vec<QuoteStep>,
+    /// Next sells into the pool, most expensive first.
+    pub sells: Vec<QuoteStep>,
+    /// Why the buy ladder is shorter than requested.
+    pub buys_stopped: Option<String>,
+    /// Why the sell ladder is shorter than requested.
+    pub sells_stopped: Option<String>,
+}
+
+pub fn handle_pool_quote(args: PoolQuoteParams) -> Result<PoolQuote> {
+    if args.royalty_bps > HUNDRED_PCT_BPS {
+        return Err(anyhow!(
+            "Royalty bps can't be more than {}",
+            HUNDRED_PCT_BPS
+        ));
+    }
+    if args.royalty_pct > 100 {
+        return Err(anyhow!("Royalty percentage can't be more than 100"));
+    }
+    let royalties = Royalties {
+        bps: args.royalty_bps,
+        pct: args.royalty_pct,
+    };
+
+    match (args.pool, args.pool_config_path) {
+        (Some(address), None) => {
+            let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+            let pool = fetch_pool(&config, &address)?;
+
+            quote_pool(Some(address), pool, args.count, royalties)
+        }
+        (None, Some(path)) => {
+            let create_pool_args: CreatePoolInstructionArgs =
+                serde_json::from_reader(std::fs::File::open(path)?)?;
+
+            quote_pool(
+                None,
+                pool_from_args(&create_pool_args),
+                args.count,
+                royalties,
+            )
+        }
+        _ => Err(anyhow!("Pass either a pool address or --config")),
+    }
+}
+
+/// A freshly created pool with the given config, for quoting without an account on chain.
+pub fn pool_from_args(args: &CreatePoolInstructionArgs) -> Pool {
+    Pool {
+        discriminator: [0; 8],
+        version: 1,
+        bump: [0],
+        pool_id: args.pool_id,
+        created_at: 0,
+        updated_at: 0,
+        expiry: 0,
+        owner: Pubkey::default(),
+        whitelist: Pubkey::default(),
+        rent_payer: Pubkey::default(),
+        currency: args
+            .currency
+            .map(Currency::new)
+            .unwrap_or_else(Currency::sol),
+        amount: 0,
+        price_offset: 0,
+        nfts_held: 0,
+        stats: PoolStats::default(),
+        shared_escrow: NullableAddress::none(),
+        cosigner: args
+            .cosigner
+            .map(NullableAddress::new)
+            .unwrap_or_else(NullableAddress::none),
+        maker_broker: args
+            .maker_broker
+            .map(NullableAddress::new)
+            .unwrap_or_else(NullableAddress::none),
+        max_taker_sell_count: args.max_taker_sell_count.unwrap_or(0),
+        config: args.config.clone(),
+        reserved: [0; 100],
+    }
+}
+
+/// Quotes the next `count` buys and sells, replaying each trade on a copy of the pool the way
+/// the program does. Only live pools are bounded by their NFTs and SOL.
+pub fn quote_pool(
+    address: Option<Pubkey>,
+    pool: Pool,
+    count: u32,
+    royalties: Royalties,
+) -> Result<PoolQuote> {
+    let live = address.is_some();
+
+    let (buys, buys_stopped) = if pool.config.pool_type == PoolType::Token {
+        (vec![], Some("Token pools don't sell NFTs".to_string()))
+    } else {
+        ladder(&pool, TakerSide::Buy, count, royalties, live)?
+    };
+
+    let (sells, sells_stopped) = if pool.config.pool_type == PoolType::NFT {
+        (vec![], Some("NFT pools don't buy NFTs".to_string()))
+    } else {
+        ladder(&pool, TakerSide::Sell, count, royalties, live)?
+    };
+
+    Ok(PoolQuote {
+        address,
+        pool,
+        royalties,
+        buys,
+        sells,
+        buys_stopped,
+        sells_stopped,
+    })
+}
+
+/// Prices a single trade at the pool's current offset.
+pub fn quote_step(pool: &Pool, side: TakerSide, royalties: Royalties) -> Result<QuoteStep> {
+    let price = pool
+        .current_price(side)
+        .map_err(|e| anyhow!("Failed to price pool: {}", e))?;
+
+    let fees = calc_fees(price).map_err(|e| anyhow!("Failed to compute fees: {}", e))?;
+    let royalty = royalties.amount(price)?;
+    let mm_fee = mm_fee(pool, price);
+
+    // Buys pay fees on top of the price and the MM fee comes out of it, while sells pay
+    // everything out of the price.
+    let total = match side {
+        TakerSide::Buy => price
+            .checked_add(fees.taker_fee)
+            .and_then(|v| v.checked_add(royalty)),
+        TakerSide::Sell => price
+            .checked_sub(fees.taker_fee)
+            .and_then(|v| v.checked_sub(royalty))
+            .and_then(|v| v.checked_sub(mm_fee)),
+    }
+    .ok_or_else(|| anyhow!("Fees exceed the price of {} lamports", price))?;
+
+    Ok(QuoteStep {
+        price,
+        taker_fee: fees.taker_fee,
+        maker_broker_fee: fees.maker_broker_fee,
+        royalty,
+        mm_fee,
+        total,
+    })
+}
+
+fn mm_fee(pool: &Pool, price: u64) -> u64 {
+    match (pool.config.pool_type, pool.config.mm_fee_bps.to_option()) {
+        (PoolType::Trade, Some(bps)) => {
+            ((price as u128) * (*bps as u128) / (HUNDRED_PCT_BPS as u128)) as u64
+        }
+        _ => 0,
+    }
+}
+
+fn ladder(
+    pool: &Pool,
+    side: TakerSide,
+    count: u32,
+    royalties: Royalties,
+    live: bool,
+) -> Result<(Vec<QuoteStep>, Option<String>)> {
+    let mut pool = pool.clone();
+    let mut steps = Vec::with_capacity(count as usize);
+
+    for _ in 0..count {
+        if live {
+            if let Some(reason) = exhausted(&pool, side) {
+                return Ok((steps, Some(reason)));
+            }
+        }
+
+        let step = match quote_step(&pool, side, royalties) {
+            Ok(step) => step,
+            // Linear curves bottom out at zero, and exponential ones can overflow.
+            Err(_) if !steps.is_empty() => {
+                return Ok((steps, Some("Curve can't move further".to_string())));
+            }
+            Err(e) => return Err(e),
+        };
+
+        // Shared escrow balances live outside the pool, so they can't be checked here.
+        if live
+            && side == TakerSide::Sell
+            && pool.shared_escrow.to_option().is_none()
+            && pool.amount < step.price
+        {
+            return Ok((
+                steps,
+                Some(format!(
+                    "Pool only holds {} SOL",
+                    lamports_to_sol(pool.amount)
+                )),
+            ));
+        }
+
+        apply_trade(&mut pool, side, &step);
+        steps.push(step);
+    }
+
+    Ok((steps, None))
+}
+
+fn exhausted(pool: &Pool, side: TakerSide) -> Option<String> {
+    match side {
+        TakerSide::Buy if pool.nfts_held == 0 => Some("Pool has no NFTs left".to_string()),
+        TakerSide::Sell
+            if pool.max_taker_sell_count > 0
+                && pool
+                    .stats
+                    .taker_sell_count
+                    .saturating_sub(pool.stats.taker_buy_count)
+                    >= pool.max_taker_sell_count =>
+        {
+            Some(format!(
+                "Pool reached its max taker sell count of {}",
+                pool.max_taker_sell_count
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Updates the pool the way the program does after a trade.
+fn apply_trade(pool: &mut Pool, side: TakerSide, step: &QuoteStep) {
+    let trade = pool.config.pool_type == PoolType::Trade;
+    // Compounded MM fees stay in the pool, otherwise they go to the owner.
+    let compounded = if pool.config.mm_compound_fees {
+        step.mm_fee
+    } else {
+        0
+    };
+
+    match side {
+        TakerSide::Buy => {
+            pool.price_offset += 1;
+            pool.nfts_held = pool.nfts_held.saturating_sub(1);
+            pool.stats.taker_buy_count += 1;
+            if trade {
+                pool.amount = pool
+                    .amount
+                    .saturating_add(step.price - step.mm_fee + compounded);
+            }
+        }
+        TakerSide::Sell => {
+            pool.price_offset -= 1;
+            pool.stats.taker_sell_count += 1;
+            if trade {
+                pool.nfts_held += 1;
+            }
+            pool.amount = pool.amount.saturating_sub(step.price - compounded);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use super::*;
+
+use std::path::Path;
+
+use solana_sdk::native_token::lamports_to_sol;
+
+use crate::args::PoolSort;
+
+pub struct PoolReportParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub pool: Option<Pubkey>,
+    pub owner: Option<Pubkey>,
+    pub csv: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolReportRow {
+    pub address: Pubkey,
+    pub pool: Pool,
+    pub buys: u32,
+    pub sells: u32,
+    /// Realized market making profit, in lamports.
+    pub mm_profit: u64,
+    /// Price the pool currently quotes, see `quoted_price`.
+    pub price: Option<u64>,
+    /// NFTs held by the pool valued at the current price, in lamports.
+    pub inventory_value: u64,
+    /// Change of the current price from the starting price, in percent.
+    pub drift_pct: Option<f64>,
+    /// Seconds left before the pool expires, negative once it has.
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolReport {
+    pub rows: Vec<PoolReportRow>,
+    /// Where the CSV export was written.
+    pub csv: Option<PathBuf>,
+}
+
+impl PoolReport {
+    pub fn trades(&self) -> u64 {
+        self.rows
+            .iter()
+            .map(|row| row.buys as u64 + row.sells as u64)
+            .sum()
+    }
+
+    pub fn mm_profit(&self) -> u64 {
+        self.rows.iter().map(|row| row.mm_profit).sum()
+    }
+
+    pub fn amount(&self) -> u64 {
+        self.rows.iter().map(|row| row.pool.amount).sum()
+    }
+
+    pub fn inventory_value(&self) -> u64 {
+        self.rows.iter().map(|row| row.inventory_value).sum()
+    }
+}
+
+pub fn handle_pool_report(args: PoolReportParams) -> Result<PoolReport> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    let entries = match (args.pool, args.owner) {
+        (Some(address), None) => {
+            let pool = fetch_pool(&config, &address)?;
+            vec![PoolEntry {
+                address,
+                price: quoted_price(&pool),
+                pool,
+            }]
+        }
+        (None, Some(owner)) => {
+            let mut entries = fetch_pools(
+                &config,
+                &PoolFilters {
+                    owner: Some(owner),
+                    ..Default::default()
+                },
+            )?;
+            sort_pools(&mut entries, PoolSort::Created, false);
+            entries
+        }
+        _ => return Err(anyhow!("Pass either a pool address or --owner")),
+    };
+
+    let mut report = pool_report(entries, chrono::Utc::now().timestamp());
+
+    if let Some(path) = args.csv {
+        write_report_csv(&report, &path)?;
+        report.csv = Some(path);
+    }
+
+    Ok(report)
+}
+
+/// Summarizes the stats of each pool as of `now`.
+pub fn pool_report(entries: Vec<PoolEntry>, now: i64) -> PoolReport {
+    let rows = entries
+        .into_iter()
+        .map(|entry| {
+            let pool = entry.pool;
+            let starting_price = pool.config.starting_price;
+
+            PoolReportRow {
+                address: entry.address,
+                buys: pool.stats.taker_buy_count,
+                sells: pool.stats.taker_sell_count,
+                mm_profit: pool.stats.accumulated_mm_profit,
+                price: entry.price,
+                inventory_value: entry
+                    .price
+                    .map(|price| price.saturating_mul(pool.nfts_held as u64))
+                    .unwrap_or_default(),
+                drift_pct: entry.price.filter(|_| starting_price > 0).map(|price| {
+                    (price as f64 - starting_price as f64) / starting_price as f64 * 100.0
+                }),
+                expires_in: pool.expiry - now,
+                pool,
+            }
+        })
+        .collect();
+
+    PoolReport { rows, csv: None }
+}
+
+pub fn write_report_csv(report: &PoolReport, path: &Path) -> Result<()> {
+    let mut csv = String::from(
+        "address,type,curve,buys,sells,mm_profit_sol,amount_sol,nfts_held,price_sol,inventory_value_sol,price_offset,drift_pct,expiry,expires_in_secs\n",
+    );
+
+    for row in &report.rows {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+            row.address,
+            row.pool.config.pool_type,
+            row.pool.config.curve_type,
+            row.buys,
+            row.sells,
+            lamports_to_sol(row.mm_profit),
+            lamports_to_sol(row.pool.amount),
+            row.pool.nfts_held,
+            row.price
+                .map(|price| lamports_to_sol(price).to_string())
+                .unwrap_or_default(),
+            lamports_to_sol(row.inventory_value),
+            row.pool.price_offset,
+            row.drift_pct
+                .map(|drift| format!("{:.2}", drift))
+                .unwrap_or_default(),
+            row.pool.expiry,
+            row.expires_in,
+        ));
+    }
+
+    std::fs::write(path, csv)
+        .map_err(|e| anyhow!("Failed to write report to {}: {}", path.display(), e))
+}
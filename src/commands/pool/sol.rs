@@ -0,0 +1,133 @@
+use super::*;
+
+use solana_sdk::native_token::lamports_to_sol;
+use tensor_amm::{
+    instructions::{
+        DepositSol, DepositSolInstructionArgs, WithdrawSol, WithdrawSolInstructionArgs,
+    },
+    types::PoolType,
+};
+
+use crate::transactions::send_instructions;
+
+pub struct PoolSolParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub pool: Pubkey,
+    pub amount: String,
+    pub lamports: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolSolTransfer {
+    pub pool: Pubkey,
+    pub direction: PoolDirection,
+    pub lamports: u64,
+    /// Pool `amount` before and after the transfer, in lamports.
+    pub balance_before: u64,
+    pub balance_after: u64,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn deposit_sol(args: PoolSolParams) -> Result<PoolSolTransfer> {
+    handle_pool_sol(args, PoolDirection::Deposit)
+}
+
+pub fn withdraw_sol(args: PoolSolParams) -> Result<PoolSolTransfer> {
+    handle_pool_sol(args, PoolDirection::Withdraw)
+}
+
+fn handle_pool_sol(args: PoolSolParams, direction: PoolDirection) -> Result<PoolSolTransfer> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let lamports = parse_amount(&args.amount, args.lamports)?;
+
+    transfer_pool_sol(&config, &args.pool, direction, lamports)
+}
+
+/// Deposits SOL into, or withdraws SOL from, a pool owned by the config keypair.
+pub fn transfer_pool_sol(
+    config: &CliConfig,
+    address: &Pubkey,
+    direction: PoolDirection,
+    lamports: u64,
+) -> Result<PoolSolTransfer> {
+    let owner = config.keypair.pubkey();
+    let pool = fetch_pool(config, address)?;
+
+    check_pool_sol(&pool, &owner, direction, lamports)?;
+
+    let ix = match direction {
+        PoolDirection::Deposit => DepositSol {
+            owner,
+            pool: *address,
+            system_program: solana_sdk::system_program::id(),
+        }
+        .instruction(DepositSolInstructionArgs { lamports }),
+        PoolDirection::Withdraw => WithdrawSol {
+            owner,
+            pool: *address,
+            system_program: solana_sdk::system_program::id(),
+        }
+        .instruction(WithdrawSolInstructionArgs { lamports }),
+    };
+
+    let signatures = send_instructions(config, vec![ix], &[], &[])?;
+
+    let balance_after = fetch_pool(config, address)?.amount;
+
+    Ok(PoolSolTransfer {
+        pool: *address,
+        direction,
+        lamports,
+        balance_before: pool.amount,
+        balance_after,
+        signatures,
+    })
+}
+
+fn check_pool_sol(
+    pool: &Pool,
+    owner: &Pubkey,
+    direction: PoolDirection,
+    lamports: u64,
+) -> Result<()> {
+    if pool.owner != *owner {
+        return Err(anyhow!(
+            "Pool is owned by {}, sign with the owner keypair",
+            pool.owner
+        ));
+    }
+
+    if lamports == 0 {
+        return Err(anyhow!("Amount must be greater than zero"));
+    }
+
+    if !pool.currency.is_sol() {
+        return Err(anyhow!("Pool currency is {}, not SOL", pool.currency));
+    }
+
+    if let Some(escrow) = pool.shared_escrow.to_option() {
+        return Err(anyhow!(
+            "Pool uses shared escrow {}, move SOL through the escrow instead",
+            escrow
+        ));
+    }
+
+    match direction {
+        PoolDirection::Deposit if pool.config.pool_type == PoolType::NFT => Err(anyhow!(
+            "NFT pools only sell NFTs and can't hold SOL, use a Token or Trade pool"
+        )),
+        PoolDirection::Withdraw if lamports > pool.amount => Err(anyhow!(
+            "Pool only holds {} SOL",
+            lamports_to_sol(pool.amount)
+        )),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,82 @@
+mod order;
+mod orders;
+
+pub use order::*;
+pub use orders::*;
+
+use std::path::PathBuf;
+
+use {
+    anyhow::{anyhow, Result},
+    solana_sdk::{pubkey::Pubkey, signature::Signature, signer::Signer},
+    tensor_amm::HUNDRED_PCT_BPS,
+    tensor_price_lock::{
+        accounts::{OrderNftReceipt, OrderState},
+        types::OrderType,
+    },
+};
+
+use crate::{
+    commands::{blockers_error, TENSOR_PRICE_LOCK_ID},
+    discriminators::deserialize_account,
+    setup::CliConfig,
+};
+
+const SECONDS_PER_YEAR: u128 = 365 * 86_400;
+
+/// Order state of `maker` for an order id. The SDK seeds the order id as a remainder vector,
+/// which is just its bytes.
+pub fn find_order_state_pda(maker: &Pubkey, order_id: &[u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[OrderState::PREFIX, maker.as_ref(), order_id],
+        &TENSOR_PRICE_LOCK_ID,
+    )
+    .0
+}
+
+/// System account holding the SOL of an order.
+pub fn find_order_vault_pda(order_state: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"order_vault", order_state.as_ref()],
+        &TENSOR_PRICE_LOCK_ID,
+    )
+    .0
+}
+
+/// What locking and exercising an order moves, in lamports, worked out from its terms. The
+/// premium is the order's APR over the lock duration. The price lock SDK doesn't have the fees the
+/// program takes from the premium, only the amounts a lock charged in its `LockEvent`, so they
+/// aren't included, nor are fees on the exercise trade, which depend on the market it settles
+/// through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settlement {
+    /// Price the taker locks in, paid for an NFT order or received for a token order.
+    pub price: u64,
+    /// What the taker pays to lock the order, before the program's fees.
+    pub premium: u64,
+}
+
+/// Works out the settlement of an order with the given terms.
+pub fn simulate_settlement(price: u64, apr_bps: u32, duration_sec: u32) -> Result<Settlement> {
+    let premium = (price as u128) * (apr_bps as u128) * (duration_sec as u128)
+        / (HUNDRED_PCT_BPS as u128 * SECONDS_PER_YEAR);
+    let premium = u64::try_from(premium).map_err(|_| anyhow!("Premium overflows"))?;
+
+    Ok(Settlement { price, premium })
+}
+
+/// Fetches and decodes an order.
+pub fn fetch_order(config: &CliConfig, address: &Pubkey) -> Result<OrderState> {
+    let accounts = config.client.get_multiple_accounts(&[*address])?;
+    let account = accounts[0]
+        .as_ref()
+        .ok_or_else(|| anyhow!("Order {} not found", address))?;
+
+    deserialize_account::<OrderState>(&account.data)
+        .map_err(|e| anyhow!("Invalid order {}: {}", address, e))
+}
+
+/// Whether a taker holds the order locked at `now`.
+pub fn order_locked(order: &OrderState, now: i64) -> bool {
+    order.taker.is_some() && order.locked_until > now
+}
@@ -0,0 +1,342 @@
+use super::*;
+
+use solana_sdk::{instruction::Instruction, signature::Keypair};
+use tensor_price_lock::instructions::{CloseOrder, UpsertOrder, UpsertOrderInstructionArgs};
+
+use crate::{
+    commands::{parse_duration, parse_expiry, parse_price, ASSOCIATED_TOKEN_ID, TOKEN_ID},
+    formatting::format_date,
+    transactions::send_instructions,
+};
+
+pub struct PriceLockCreateParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub order_type: OrderType,
+    pub whitelist: Pubkey,
+    pub terms: OrderTerms,
+}
+
+pub struct PriceLockEditParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub order: Pubkey,
+    pub terms: OrderTerms,
+}
+
+pub struct PriceLockCloseParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub order: Pubkey,
+}
+
+/// Terms of an order, kept as typed so prices and durations are parsed with their units. Edits
+/// keep the current value of the terms that aren't given.
+#[derive(Debug, Clone, Default)]
+pub struct OrderTerms {
+    pub price: Option<String>,
+    pub apr_bps: Option<u32>,
+    pub duration: Option<String>,
+    pub expire: Option<String>,
+    pub margin: Option<Pubkey>,
+    pub maker_broker: Option<Pubkey>,
+}
+
+/// An order created or edited.
+#[derive(Debug, Clone)]
+pub struct PriceLockOrder {
+    pub order_state: Pubkey,
+    pub vault: Pubkey,
+    pub order_type: OrderType,
+    pub price: u64,
+    pub apr_bps: u32,
+    pub duration_sec: u32,
+    pub expire_in_sec: Option<u64>,
+    pub settlement: Settlement,
+    pub edited: bool,
+    pub signatures: Vec<Signature>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClosedPriceLockOrder {
+    pub order_state: Pubkey,
+    /// SOL held by the vault, returned to the maker along with the rent.
+    pub vault_balance: u64,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn create_price_lock_order(args: PriceLockCreateParams) -> Result<PriceLockOrder> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    create_order_with_config(&config, args.order_type, &args.whitelist, &args.terms)
+}
+
+/// Creates an order of the config keypair on a whitelist, under a new random order id. Price,
+/// APR and duration are required.
+pub fn create_order_with_config(
+    config: &CliConfig,
+    order_type: OrderType,
+    whitelist: &Pubkey,
+    terms: &OrderTerms,
+) -> Result<PriceLockOrder> {
+    let maker = config.keypair.pubkey();
+
+    let mut blockers = vec![];
+    if terms.price.is_none() {
+        blockers.push("it needs a --price".to_string());
+    }
+    if terms.apr_bps.is_none() {
+        blockers.push("it needs an --apr-bps".to_string());
+    }
+    if terms.duration.is_none() {
+        blockers.push("it needs a --duration".to_string());
+    }
+    if config.client.get_multiple_accounts(&[*whitelist])?[0].is_none() {
+        blockers.push(format!("whitelist {} doesn't exist", whitelist));
+    }
+
+    if !blockers.is_empty() {
+        return Err(blockers_error(
+            "Can't create the order".to_string(),
+            &blockers,
+        ));
+    }
+
+    let order_id = Keypair::new().pubkey().to_bytes();
+    let order_state = find_order_state_pda(&maker, &order_id);
+
+    let args = UpsertOrderInstructionArgs {
+        order_id,
+        order_type,
+        price: parse_price(terms.price.as_deref().unwrap_or_default())?,
+        expire_in_sec: parse_expiry(terms.expire.as_deref())?,
+        apr_bps: terms.apr_bps.unwrap_or_default(),
+        duration_sec: parse_lock_duration(terms.duration.as_deref().unwrap_or_default())?,
+    };
+
+    submit_order(
+        config,
+        order_state,
+        whitelist,
+        terms.margin,
+        terms.maker_broker,
+        args,
+        false,
+    )
+}
+
+pub fn edit_price_lock_order(args: PriceLockEditParams) -> Result<PriceLockOrder> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    edit_order_with_config(&config, &args.order, &args.terms)
+}
+
+/// Edits an open order of the config keypair. Terms that aren't given keep their current value,
+/// and the expiry keeps the time left on the order.
+pub fn edit_order_with_config(
+    config: &CliConfig,
+    address: &Pubkey,
+    terms: &OrderTerms,
+) -> Result<PriceLockOrder> {
+    let maker = config.keypair.pubkey();
+    let order = fetch_order(config, address)?;
+    let now = chrono::Utc::now().timestamp();
+
+    let mut blockers = vec![];
+    if order.maker != maker {
+        blockers.push(format!(
+            "it is made by {}, sign with the maker keypair",
+            order.maker
+        ));
+    }
+    if let Some(blocker) = lock_blocker(&order, now) {
+        blockers.push(blocker);
+    }
+
+    if !blockers.is_empty() {
+        return Err(blockers_error(
+            format!("Can't edit order {}", address),
+            &blockers,
+        ));
+    }
+
+    let expire_in_sec = match parse_expiry(terms.expire.as_deref())? {
+        Some(expire_in_sec) => Some(expire_in_sec),
+        None if order.expiry > now => Some((order.expiry - now) as u64),
+        None => None,
+    };
+
+    let args = UpsertOrderInstructionArgs {
+        order_id: order.order_id,
+        order_type: order.order_type,
+        price: match &terms.price {
+            Some(price) => parse_price(price)?,
+            None => order.price,
+        },
+        expire_in_sec,
+        apr_bps: terms.apr_bps.unwrap_or(order.apr_bps),
+        duration_sec: match &terms.duration {
+            Some(duration) => parse_lock_duration(duration)?,
+            None => order.duration_sec,
+        },
+    };
+
+    submit_order(
+        config,
+        *address,
+        &order.whitelist,
+        terms.margin.or(order.margin),
+        terms.maker_broker.or(order.maker_broker),
+        args,
+        true,
+    )
+}
+
+/// Sends an upsert of the order and reports the settlement its terms lead to.
+fn submit_order(
+    config: &CliConfig,
+    order_state: Pubkey,
+    whitelist: &Pubkey,
+    margin: Option<Pubkey>,
+    maker_broker: Option<Pubkey>,
+    args: UpsertOrderInstructionArgs,
+    edited: bool,
+) -> Result<PriceLockOrder> {
+    let settlement = simulate_settlement(args.price, args.apr_bps, args.duration_sec)?;
+    let vault = find_order_vault_pda(&order_state);
+
+    let ix = UpsertOrder {
+        maker: config.keypair.pubkey(),
+        order_state,
+        order_vault: vault,
+        whitelist: *whitelist,
+        margin_account: margin,
+        maker_broker,
+        currency: None,
+        maker_token: None,
+        order_ta: None,
+        token_program: TOKEN_ID,
+        system_program: solana_sdk::system_program::id(),
+        associated_token_program: ASSOCIATED_TOKEN_ID,
+        tlock_program: TENSOR_PRICE_LOCK_ID,
+    }
+    .instruction(args.clone());
+
+    let signatures = send_instructions(config, vec![ix], &[], &[])?;
+
+    Ok(PriceLockOrder {
+        order_state,
+        vault,
+        order_type: args.order_type,
+        price: args.price,
+        apr_bps: args.apr_bps,
+        duration_sec: args.duration_sec,
+        expire_in_sec: args.expire_in_sec,
+        settlement,
+        edited,
+        signatures,
+    })
+}
+
+pub fn close_price_lock_order(args: PriceLockCloseParams) -> Result<ClosedPriceLockOrder> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    close_order_with_config(&config, &args.order)
+}
+
+/// Closes an order of the config keypair once it isn't locked and holds no NFTs, returning the
+/// vault's SOL and the rent to the maker.
+pub fn close_order_with_config(
+    config: &CliConfig,
+    address: &Pubkey,
+) -> Result<ClosedPriceLockOrder> {
+    let maker = config.keypair.pubkey();
+    let order = fetch_order(config, address)?;
+    let now = chrono::Utc::now().timestamp();
+
+    let mut blockers = vec![];
+    if order.maker != maker {
+        blockers.push(format!(
+            "it is made by {}, sign with the maker keypair",
+            order.maker
+        ));
+    }
+    if let Some(blocker) = lock_blocker(&order, now) {
+        blockers.push(blocker);
+    }
+    if order.nfts_held > 0 {
+        blockers.push(format!(
+            "it holds {} NFTs, withdraw them first",
+            order.nfts_held
+        ));
+    }
+
+    if !blockers.is_empty() {
+        return Err(blockers_error(
+            format!("Can't close order {}", address),
+            &blockers,
+        ));
+    }
+
+    let vault = find_order_vault_pda(address);
+    let vault_balance = config.client.get_balance(&vault)?;
+
+    let ix: Instruction = CloseOrder {
+        signer: maker,
+        order_state: *address,
+        order_vault: vault,
+        taker: order.taker,
+        maker,
+        system_program: solana_sdk::system_program::id(),
+        tlock_program: TENSOR_PRICE_LOCK_ID,
+    }
+    .instruction();
+
+    let signatures = send_instructions(config, vec![ix], &[], &[])?;
+
+    Ok(ClosedPriceLockOrder {
+        order_state: *address,
+        vault_balance,
+        signatures,
+    })
+}
+
+/// Blocker for an order a taker still holds locked.
+fn lock_blocker(order: &OrderState, now: i64) -> Option<String> {
+    order_locked(order, now).then(|| {
+        format!(
+            "it is locked by {} until {}",
+            order.taker.unwrap_or_default(),
+            format_date(order.locked_until)
+        )
+    })
+}
+
+/// Parses the lock duration of an order, which the program stores in 32 bits.
+fn parse_lock_duration(duration: &str) -> Result<u32> {
+    let seconds = parse_duration(duration)?;
+
+    if seconds == 0 {
+        return Err(anyhow!("Duration must be greater than zero"));
+    }
+
+    u32::try_from(seconds).map_err(|_| anyhow!("Duration {} is too long", duration))
+}
@@ -0,0 +1,165 @@
+use super::*;
+
+use crate::{
+    commands::{fetch_market_asset, MarketAsset},
+    discriminators::Discriminator,
+    rpc::{discriminator_filter, memcmp_filter},
+};
+
+// Offset of the maker in `OrderState`. The whitelist and taker follow optional fields, so those
+// are filtered on locally.
+pub const ORDER_MAKER_OFFSET: usize = 47;
+
+// Offset of the order state in `OrderNftReceipt`.
+pub const RECEIPT_ORDER_STATE_OFFSET: usize = 41;
+
+pub struct PriceLockOrdersParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub filters: OrderFilters,
+}
+
+pub struct PriceLockShowParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub order: Pubkey,
+}
+
+/// Which orders to fetch. The maker filter runs on the RPC node, the others locally.
+#[derive(Debug, Clone, Default)]
+pub struct OrderFilters {
+    pub maker: Option<Pubkey>,
+    pub taker: Option<Pubkey>,
+    pub whitelist: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrderEntry {
+    pub address: Pubkey,
+    pub order: OrderState,
+}
+
+#[derive(Debug, Clone)]
+pub struct PriceLockOrders {
+    pub orders: Vec<OrderEntry>,
+}
+
+/// An NFT deposited into an order, with the asset its receipt points at.
+#[derive(Debug, Clone)]
+pub struct OrderReceipt {
+    pub address: Pubkey,
+    pub receipt: OrderNftReceipt,
+    /// Standard of the asset, `None` when it can't be found.
+    pub standard: Option<String>,
+}
+
+/// An order with its deposited NFTs and what settling it moves.
+#[derive(Debug, Clone)]
+pub struct PriceLockOrderDetails {
+    pub address: Pubkey,
+    pub order: OrderState,
+    pub vault: Pubkey,
+    pub receipts: Vec<OrderReceipt>,
+    pub settlement: Settlement,
+}
+
+pub fn handle_price_lock_orders(args: PriceLockOrdersParams) -> Result<PriceLockOrders> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    let mut orders = fetch_orders(&config, &args.filters)?;
+    // Newest first.
+    orders.sort_by_key(|entry| {
+        (
+            std::cmp::Reverse(entry.order.created_at),
+            entry.address.to_string(),
+        )
+    });
+
+    Ok(PriceLockOrders { orders })
+}
+
+pub fn show_price_lock_order(args: PriceLockShowParams) -> Result<PriceLockOrderDetails> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    fetch_order_details(&config, &args.order)
+}
+
+/// Fetches every order matching the filters.
+pub fn fetch_orders(config: &CliConfig, filters: &OrderFilters) -> Result<Vec<OrderEntry>> {
+    let memcmps = filters
+        .maker
+        .map(|maker| memcmp_filter(ORDER_MAKER_OFFSET, maker.as_ref()))
+        .into_iter()
+        .collect();
+
+    let accounts = config.client.get_program_accounts_with_config(
+        &TENSOR_PRICE_LOCK_ID,
+        discriminator_filter(config.commitment, OrderState::discriminator(), memcmps),
+    )?;
+
+    let mut orders = vec![];
+
+    for (address, account) in accounts {
+        let order = deserialize_account::<OrderState>(&account.data)
+            .map_err(|e| anyhow!("Invalid order {}: {}", address, e))?;
+
+        if filters
+            .taker
+            .is_some_and(|taker| order.taker != Some(taker))
+            || filters
+                .whitelist
+                .is_some_and(|whitelist| order.whitelist != whitelist)
+        {
+            continue;
+        }
+
+        orders.push(OrderEntry { address, order });
+    }
+
+    Ok(orders)
+}
+
+/// Fetches an order with the receipts of the NFTs deposited into it, resolving the asset of
+/// each.
+pub fn fetch_order_details(config: &CliConfig, address: &Pubkey) -> Result<PriceLockOrderDetails> {
+    let order = fetch_order(config, address)?;
+
+    let accounts = config.client.get_program_accounts_with_config(
+        &TENSOR_PRICE_LOCK_ID,
+        discriminator_filter(
+            config.commitment,
+            OrderNftReceipt::discriminator(),
+            vec![memcmp_filter(RECEIPT_ORDER_STATE_OFFSET, address.as_ref())],
+        ),
+    )?;
+
+    let mut receipts = accounts
+        .into_iter()
+        .map(|(receipt_address, account)| {
+            let receipt = deserialize_account::<OrderNftReceipt>(&account.data)
+                .map_err(|e| anyhow!("Invalid receipt {}: {}", receipt_address, e))?;
+
+            // A missing or unreadable asset is shown as such rather than failing the order.
+            let standard = fetch_market_asset(config, &receipt.asset)
+                .ok()
+                .map(|asset: MarketAsset| asset.to_string());
+
+            Ok(OrderReceipt {
+                address: receipt_address,
+                receipt,
+                standard,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    receipts.sort_by_key(|entry| entry.receipt.asset.to_string());
+
+    let settlement = simulate_settlement(order.price, order.apr_bps, order.duration_sec)?;
+
+    Ok(PriceLockOrderDetails {
+        address: *address,
+        vault: find_order_vault_pda(address),
+        order,
+        receipts,
+        settlement,
+    })
+}
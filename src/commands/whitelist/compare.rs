@@ -1,26 +1,28 @@
-use std::{collections::HashMap, fs::File, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+};
 
 use {
-    anyhow::Result,
+    anyhow::{anyhow, Result},
     serde::{Deserialize, Serialize},
     serde_with::{serde_as, DisplayFromStr},
-    solana_account_decoder::UiAccountEncoding,
-    solana_client::{
-        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
-        rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
-    },
     solana_program::{pubkey, pubkey::Pubkey},
-    solana_sdk::{account::Account, commitment_config::CommitmentConfig},
+    solana_sdk::account::Account,
     tensor_whitelist::{
         accounts::{Whitelist, WhitelistV2},
         programs::TENSOR_WHITELIST_ID,
-        types::{Condition, Mode},
+        types::{Condition, Mode, State},
     },
 };
 
+use super::{write_comparison_report, ComparisonSummary};
+
 use crate::{
     discriminators::{deserialize_account, Discriminator},
-    formatting::{write_formatted, CustomFormat},
+    formatting::write_formatted,
+    rpc::{block_on, discriminator_filter, get_multiple_accounts_batched, run_blocking},
     setup::CliConfig,
     spinner::create_spinner,
 };
@@ -59,10 +61,21 @@ pub struct ComparisonResult {
     pub whitelist_v1: Pubkey,
     #[serde_as(as = "DisplayFromStr")]
     pub whitelist_v2: Pubkey,
-    pub mismatch: Option<Mismatch>,
+    /// Every discrepancy between the two, empty when they match.
+    pub mismatches: Vec<MismatchDetail>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+impl ComparisonResult {
+    pub fn is_match(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    pub fn has(&self, kind: Mismatch) -> bool {
+        self.mismatches.iter().any(|detail| detail.kind == kind)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Mismatch {
     Uuid,
     MerkleRoot,
@@ -71,22 +84,108 @@ pub enum Mismatch {
     V2Missing,
     V2ConditionsLength,
     UnexpectedV2Conditions,
+    Frozen,
+    UpdateAuthority,
+}
+
+/// A discrepancy with what the v2 should have, derived from the v1, and what it has.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct MismatchDetail {
+    pub kind: Mismatch,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl MismatchDetail {
+    fn new(kind: Mismatch, expected: impl ToString, actual: impl ToString) -> Self {
+        MismatchDetail {
+            kind,
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        }
+    }
+}
+
+/// Outcome of comparing whitelist v1s against their v2 counterparts.
+#[derive(Debug, Clone)]
+pub struct ComparisonReport {
+    pub cluster: String,
+    pub v1_count: usize,
+    pub v2_count: usize,
+    /// Whitelist v1s with no v2 on chain.
+    pub missing: Vec<MissingWhitelistPair>,
+    /// Whitelist v1s with a v2 on chain.
+    pub pairs: Vec<WhitelistPair>,
+    /// Comparison of every whitelist v1 that has a v2 on chain.
+    pub results: Vec<ComparisonResult>,
+    /// Summary of the previous report, when one was written to a report directory.
+    pub previous: Option<ComparisonSummary>,
+    /// Report files written.
+    pub report_files: Vec<PathBuf>,
+}
+
+impl ComparisonReport {
+    pub fn mismatches(&self) -> impl Iterator<Item = &ComparisonResult> {
+        self.results.iter().filter(|result| !result.is_match())
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.mismatches().next().is_none()
+    }
 }
 
 pub struct CompareParams {
     pub keypair_path: Option<PathBuf>,
     pub rpc_url: Option<String>,
+    pub profile: Option<String>,
     pub list: Option<PathBuf>,
     pub namespace: Option<Pubkey>,
-    pub verbose: bool,
+    /// Update authority the v2s should have. Whitelist v1s have no authority of their own, so it
+    /// isn't checked when not given.
+    pub expected_authority: Option<Pubkey>,
+    /// Directory to write the files and the CSV and HTML reports to.
+    pub report_dir: Option<PathBuf>,
+    /// Summary of an earlier report to show the changes since.
+    pub previous: Option<PathBuf>,
 }
 
-pub fn handle_compare(args: CompareParams) -> Result<()> {
-    let cli_config = CliConfig::new(args.keypair_path, args.rpc_url)?;
+pub fn handle_compare(args: CompareParams) -> Result<ComparisonReport> {
+    let cli_config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?;
+
+    let mut report = compare_on_chain(
+        &cli_config,
+        args.list,
+        args.namespace,
+        args.expected_authority,
+    )?;
+
+    match &args.report_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| anyhow!("Failed to create {}: {}", dir.display(), e))?;
 
+            write_comparison_files(&report, dir)?;
+            report.report_files =
+                write_comparison_report(&mut report, dir, args.previous.as_deref())?;
+        }
+        None => write_comparison_files(&report, Path::new("."))?,
+    }
+
+    Ok(report)
+}
+
+/// Fetches whitelist v1s, either all of them or the ones whose UUIDs are listed in the `list`
+/// file, and compares each against the v2 derived from it under `namespace`. The update authority
+/// of the v2s is only checked against `expected_authority` when it is given.
+pub fn compare_on_chain(
+    cli_config: &CliConfig,
+    list: Option<PathBuf>,
+    namespace: Option<Pubkey>,
+    expected_authority: Option<Pubkey>,
+) -> Result<ComparisonReport> {
     let genesis_hash = cli_config.client.get_genesis_hash()?.to_string();
 
-    let namespace = args.namespace.unwrap_or(WHITELIST_SIGNER_PUBKEY);
+    let namespace = namespace.unwrap_or(WHITELIST_SIGNER_PUBKEY);
 
     let cluster = if genesis_hash == MAINNET_GENESIS_HASH {
         "mainnet"
@@ -96,95 +195,77 @@ pub fn handle_compare(args: CompareParams) -> Result<()> {
         "unknown"
     };
 
-    println!("Fetching whitelists from: {}", cluster);
-
-    // Spinner with empty message we populate later.
-    let spinner = create_spinner("")?;
-
     // Open the list file and decode into a vector of Pubkeys
-    let whitelists: Vec<(Pubkey, Account)> = if let Some(list) = args.list {
-        spinner.set_message("Opening specified file...");
-
-        let list: Vec<Pubkey> = serde_json::from_reader(File::open(&list)?)?;
-
-        let pubkeys: Vec<_> = list
-            .iter()
-            .map(|p| Whitelist::find_pda(p.to_bytes()).0)
-            .collect();
-
-        cli_config
-            .client
-            .get_multiple_accounts(&pubkeys)?
-            .into_iter()
-            .flatten()
-            .map(|account| {
-                (
-                    Whitelist::find_pda(account.data[8..40].try_into().unwrap()).0,
-                    account,
-                )
-            })
-            .collect()
-    } else {
-        spinner.set_message("Running gPA call to get all whitelist v1s...");
-
-        // GPA to find all whitelists v1s
-        let mut disc = Vec::with_capacity(8);
-        disc.extend(Whitelist::discriminator());
-
-        let filter = RpcFilterType::Memcmp(Memcmp::new(0, MemcmpEncodedBytes::Bytes(disc)));
-        let filters = vec![filter];
-
-        let config = RpcProgramAccountsConfig {
-            filters: Some(filters),
-            account_config: RpcAccountInfoConfig {
-                data_slice: None,
-                encoding: Some(UiAccountEncoding::Base64),
-                commitment: Some(CommitmentConfig::confirmed()),
-                min_context_slot: None,
-            },
-            with_context: None,
-        };
-
-        cli_config
-            .client
-            .get_program_accounts_with_config(&TENSOR_WHITELIST_ID, config)?
+    let list: Option<Vec<Pubkey>> = match list {
+        Some(list) => Some(serde_json::from_reader(File::open(&list)?)?),
+        None => None,
     };
-    spinner.finish_and_clear();
 
-    println!("Found {} v1 whitelists on-chain", whitelists.len());
-
-    // GPA to find all whitelists v2s
-    let mut disc = Vec::with_capacity(8);
-    disc.extend(WhitelistV2::discriminator());
+    let spinner = create_spinner("Fetching whitelist v1s and v2s...")?;
+
+    let client = cli_config.client.clone();
+    let max_concurrency = cli_config.max_concurrency;
+    let commitment = cli_config.commitment;
+
+    // Fetch the v1s and run the v2 gPA at the same time.
+    let (whitelists, on_chain_whitelist_v2s) = block_on(async {
+        let v1s = async {
+            match list {
+                Some(list) => {
+                    let pubkeys: Vec<_> = list
+                        .iter()
+                        .map(|p| Whitelist::find_pda(p.to_bytes()).0)
+                        .collect();
+
+                    let accounts =
+                        get_multiple_accounts_batched(client.clone(), &pubkeys, max_concurrency)
+                            .await?;
+
+                    Ok(accounts
+                        .into_iter()
+                        .flatten()
+                        .map(|account| {
+                            (
+                                Whitelist::find_pda(account.data[8..40].try_into().unwrap()).0,
+                                account,
+                            )
+                        })
+                        .collect::<Vec<(Pubkey, Account)>>())
+                }
+                None => {
+                    let client = client.clone();
+                    run_blocking(move || {
+                        client.get_program_accounts_with_config(
+                            &TENSOR_WHITELIST_ID,
+                            discriminator_filter(commitment, Whitelist::discriminator(), vec![]),
+                        )
+                    })
+                    .await
+                }
+            }
+        };
 
-    let filter = RpcFilterType::Memcmp(Memcmp::new(0, MemcmpEncodedBytes::Bytes(disc)));
-    let filters = vec![filter];
+        let v2s = run_blocking({
+            let client = client.clone();
+            move || {
+                client.get_program_accounts_with_config(
+                    &TENSOR_WHITELIST_ID,
+                    discriminator_filter(commitment, WhitelistV2::discriminator(), vec![]),
+                )
+            }
+        });
 
-    let config = RpcProgramAccountsConfig {
-        filters: Some(filters),
-        account_config: RpcAccountInfoConfig {
-            data_slice: None,
-            encoding: Some(UiAccountEncoding::Base64),
-            commitment: Some(CommitmentConfig::confirmed()),
-            min_context_slot: None,
-        },
-        with_context: None,
-    };
+        tokio::try_join!(v1s, v2s)
+    })??;
 
-    let spinner = create_spinner("Running gPA call to get all whitelist v2s...")?;
+    spinner.finish_and_clear();
 
-    let on_chain_whitelist_v2s: HashMap<Pubkey, Account> = cli_config
-        .client
-        .get_program_accounts_with_config(&TENSOR_WHITELIST_ID, config)?
-        .into_iter()
-        .collect();
+    let v1_count = whitelists.len();
 
-    spinner.finish_and_clear();
+    let on_chain_whitelist_v2s: HashMap<Pubkey, Account> =
+        on_chain_whitelist_v2s.into_iter().collect();
 
-    println!(
-        "Found {} v2 whitelists on-chain",
-        on_chain_whitelist_v2s.len()
-    );
+    let v2_count = on_chain_whitelist_v2s.len();
 
     let whitelist_pairs: Vec<WhitelistPair> = whitelists
         .into_iter()
@@ -209,18 +290,11 @@ pub fn handle_compare(args: CompareParams) -> Result<()> {
         })
         .collect();
 
-    println!("Built pairs");
-
     // Find missing V2s by finding all the None values in the v2 field
     let (missing_v2s, existing_v2s): (Vec<WhitelistPair>, Vec<WhitelistPair>) = whitelist_pairs
         .into_iter()
         .partition(|pair| pair.v2_data.is_none());
 
-    println!("{} whitelists have no v2 on chain", missing_v2s.len());
-
-    let no_missing_v2s = missing_v2s.is_empty();
-    let number_of_missing_v2s = missing_v2s.len();
-
     let missing_pairs: Vec<MissingWhitelistPair> = missing_v2s
         .into_iter()
         .map(|pair| MissingWhitelistPair {
@@ -229,137 +303,193 @@ pub fn handle_compare(args: CompareParams) -> Result<()> {
         })
         .collect();
 
+    // Only compare the whitelists that have a v2 on chain
+    let comparison_results = compare_whitelists(&existing_v2s, expected_authority);
+
+    Ok(ComparisonReport {
+        cluster: cluster.to_string(),
+        v1_count,
+        v2_count,
+        missing: missing_pairs,
+        pairs: existing_v2s,
+        results: comparison_results,
+        previous: None,
+        report_files: vec![],
+    })
+}
+
+/// Writes the missing v2s, successful matches and mismatches to files prefixed with the cluster.
+fn write_comparison_files(report: &ComparisonReport, dir: &Path) -> Result<()> {
+    let cluster = &report.cluster;
+
     let spinner = create_spinner("Writing missing v2s to file...")?;
     // Write v2_missing to a file
-    let file = File::create(format!("{}_v2_missing.json", cluster))?;
-    serde_json::to_writer_pretty(file, &missing_pairs)?;
+    let file = File::create(dir.join(format!("{}_v2_missing.json", cluster)))?;
+    serde_json::to_writer_pretty(file, &report.missing)?;
     spinner.finish_and_clear();
 
     let spinner = create_spinner("Writing successful matches to file...")?;
-    let file = File::create(format!("{}_successful_matches.json", cluster))?;
-    serde_json::to_writer_pretty(file, &existing_v2s)?;
+    let file = File::create(dir.join(format!("{}_successful_matches.json", cluster)))?;
+    serde_json::to_writer_pretty(file, &report.pairs)?;
 
     write_formatted(
-        &format!("{}_v2_successful_matches.txt", cluster),
-        &existing_v2s,
+        &dir.join(format!("{}_v2_successful_matches.txt", cluster))
+            .to_string_lossy(),
+        &report.pairs,
     )?;
     spinner.finish_and_clear();
 
-    // Only compare the whitelists that have a v2 on chain
-    let comparison_results = compare_whitelists(&existing_v2s);
-
     // Write any comparison results with a mismatch to a file. We need to filter out the ones with no mismatch.
-    let mismatches = comparison_results
-        .iter()
-        .filter(|result| result.mismatch.is_some())
-        .collect::<Vec<_>>();
-
-    println!(
-        "Of the {} whitelist v1s with a v2 on chain, {} have a mismatch",
-        existing_v2s.len(),
-        mismatches.len()
-    );
+    let mismatches = report.mismatches().collect::<Vec<_>>();
 
     let spinner = create_spinner("Writing mismatches to file...")?;
 
-    let file = File::create(format!("{}_mismatches.json", cluster))?;
+    let file = File::create(dir.join(format!("{}_mismatches.json", cluster)))?;
     serde_json::to_writer_pretty(file, &mismatches)?;
 
     spinner.finish_and_clear();
 
-    if args.verbose {
-        for result in comparison_results.iter() {
-            println!("{}", result.custom_format());
-            println!(); // Add a blank line between comparisons for readability
-        }
-    }
+    Ok(())
+}
 
-    if mismatches.is_empty() && no_missing_v2s {
-        println!("All good! ✅ 😎");
-    } else {
-        println!(
-            "There are {} mismatches and {} missing v2s",
-            mismatches.len(),
-            number_of_missing_v2s
-        );
+/// Condition a v2 migrated from the whitelist v1 should have, in the order `compare_whitelists`
+/// checks them: the merkle root, then the VOC, then the FVC. `None` when the v1 has none set.
+pub fn expected_condition(v1: &Whitelist) -> Option<Condition> {
+    if v1.root_hash != DEFAULT_ROOT_HASH {
+        return Some(Condition {
+            mode: Mode::MerkleTree,
+            value: Pubkey::new_from_array(v1.root_hash),
+        });
     }
 
-    Ok(())
+    v1.voc
+        .map(|voc| Condition {
+            mode: Mode::VOC,
+            value: voc,
+        })
+        .or_else(|| {
+            v1.fvc.map(|fvc| Condition {
+                mode: Mode::FVC,
+                value: fvc,
+            })
+        })
 }
 
-fn has_matching_condition(conditions: &[Condition], mode: Mode, value: &Pubkey) -> bool {
-    conditions
-        .iter()
-        .any(|condition| condition.mode == mode && condition.value == *value)
+fn format_condition(condition: &Condition) -> String {
+    format!("{:?} {}", condition.mode, condition.value)
+}
+
+fn mode_mismatch(mode: Mode) -> Mismatch {
+    match mode {
+        Mode::MerkleTree => Mismatch::MerkleRoot,
+        Mode::VOC => Mismatch::Voc,
+        Mode::FVC => Mismatch::Fvc,
+    }
 }
 
-pub fn compare_whitelists(whitelist_pairs: &[WhitelistPair]) -> Vec<ComparisonResult> {
+pub fn compare_whitelists(
+    whitelist_pairs: &[WhitelistPair],
+    expected_authority: Option<Pubkey>,
+) -> Vec<ComparisonResult> {
     whitelist_pairs
         .iter()
-        .map(|pair| {
-            let mut result = ComparisonResult {
-                whitelist_v1: pair.v1_pubkey,
-                whitelist_v2: pair.v2_pubkey,
-                mismatch: None,
-            };
-
-            let v1 = &pair.v1_data;
-            let v2 = match &pair.v2_data {
-                Some(v2) => v2,
-                None => {
-                    result.mismatch = Some(Mismatch::V2Missing);
-                    return result;
-                }
-            };
+        .map(|pair| compare_whitelist(pair, expected_authority))
+        .collect()
+}
 
-            // Check UUID
-            if v1.uuid != v2.uuid {
-                result.mismatch = Some(Mismatch::Uuid);
-                return result;
-            }
+/// Compares a pair, collecting every discrepancy. A v2 should have the v1's UUID and frozen
+/// state, `expected_authority` as its update authority when one is given, and a single
+/// condition, the one `expected_condition` derives from the v1.
+pub fn compare_whitelist(
+    pair: &WhitelistPair,
+    expected_authority: Option<Pubkey>,
+) -> ComparisonResult {
+    let mut mismatches = vec![];
+
+    let v1 = &pair.v1_data;
+
+    if let Some(v2) = &pair.v2_data {
+        if v1.uuid != v2.uuid {
+            mismatches.push(MismatchDetail::new(
+                Mismatch::Uuid,
+                hex::encode(v1.uuid),
+                hex::encode(v2.uuid),
+            ));
+        }
 
-            // Check conditions length
-            if v2.conditions.len() != 1 {
-                result.mismatch = Some(Mismatch::V2ConditionsLength);
-                return result;
+        let expected = expected_condition(v1);
+
+        if let Some(condition) = &expected {
+            if !v2.conditions.contains(condition) {
+                let actual: Vec<String> = v2
+                    .conditions
+                    .iter()
+                    .filter(|c| c.mode == condition.mode)
+                    .map(format_condition)
+                    .collect();
+
+                mismatches.push(MismatchDetail::new(
+                    mode_mismatch(condition.mode),
+                    format_condition(condition),
+                    if actual.is_empty() {
+                        "none".to_string()
+                    } else {
+                        actual.join(", ")
+                    },
+                ));
             }
+        }
 
-            // Return early if we find a matching condition
-            if v1.root_hash != DEFAULT_ROOT_HASH {
-                if has_matching_condition(
-                    &v2.conditions,
-                    Mode::MerkleTree,
-                    &Pubkey::new_from_array(v1.root_hash),
-                ) {
-                    return result;
-                } else {
-                    result.mismatch = Some(Mismatch::MerkleRoot);
-                    return result;
-                }
-            }
+        if v2.conditions.len() != 1 {
+            mismatches.push(MismatchDetail::new(
+                Mismatch::V2ConditionsLength,
+                1,
+                v2.conditions.len(),
+            ));
+        }
 
-            if let Some(voc) = v1.voc {
-                if has_matching_condition(&v2.conditions, Mode::VOC, &voc) {
-                    return result;
-                } else {
-                    result.mismatch = Some(Mismatch::Voc);
-                    return result;
-                }
+        for condition in &v2.conditions {
+            if expected.as_ref() != Some(condition) {
+                mismatches.push(MismatchDetail::new(
+                    Mismatch::UnexpectedV2Conditions,
+                    "none",
+                    format_condition(condition),
+                ));
             }
+        }
 
-            if let Some(fvc) = v1.fvc {
-                if has_matching_condition(&v2.conditions, Mode::FVC, &fvc) {
-                    return result;
-                } else {
-                    result.mismatch = Some(Mismatch::Fvc);
-                    return result;
-                }
+        let frozen = |frozen: bool| if frozen { "frozen" } else { "unfrozen" };
+        let v2_frozen = v2.state == State::Frozen;
+        if v1.frozen != v2_frozen {
+            mismatches.push(MismatchDetail::new(
+                Mismatch::Frozen,
+                frozen(v1.frozen),
+                frozen(v2_frozen),
+            ));
+        }
+
+        if let Some(authority) = expected_authority {
+            if v2.update_authority != authority {
+                mismatches.push(MismatchDetail::new(
+                    Mismatch::UpdateAuthority,
+                    authority,
+                    v2.update_authority,
+                ));
             }
+        }
+    } else {
+        mismatches.push(MismatchDetail::new(
+            Mismatch::V2Missing,
+            pair.v2_pubkey,
+            "none",
+        ));
+    }
 
-            result.mismatch = Some(Mismatch::UnexpectedV2Conditions);
-            result
-        })
-        .collect()
+    ComparisonResult {
+        whitelist_v1: pair.v1_pubkey,
+        whitelist_v2: pair.v2_pubkey,
+        mismatches,
+    }
 }
 
 #[cfg(test)]
@@ -368,6 +498,10 @@ mod tests {
     use solana_program::pubkey::Pubkey;
     use tensor_whitelist::types::{Condition, Mode, State};
 
+    fn kinds(result: &ComparisonResult) -> Vec<Mismatch> {
+        result.mismatches.iter().map(|detail| detail.kind).collect()
+    }
+
     fn create_whitelist(
         uuid: [u8; 32],
         root_hash: [u8; 32],
@@ -433,10 +567,10 @@ mod tests {
             v2_data: Some(v2_data),
         };
 
-        let results = compare_whitelists(&[pair]);
+        let results = compare_whitelists(&[pair], None);
 
         assert_eq!(results.len(), 1);
-        assert!(results[0].mismatch.is_none());
+        assert!(results[0].is_match());
     }
 
     #[test]
@@ -463,10 +597,10 @@ mod tests {
             v2_data: Some(v2_data),
         };
 
-        let results = compare_whitelists(&[pair]);
+        let results = compare_whitelists(&[pair], None);
 
         assert_eq!(results.len(), 1);
-        assert!(results[0].mismatch.is_none());
+        assert!(results[0].is_match());
     }
 
     #[test]
@@ -493,10 +627,10 @@ mod tests {
             v2_data: Some(v2_data),
         };
 
-        let results = compare_whitelists(&[pair]);
+        let results = compare_whitelists(&[pair], None);
 
         assert_eq!(results.len(), 1);
-        assert!(results[0].mismatch.is_none());
+        assert!(results[0].is_match());
     }
 
     #[test]
@@ -525,10 +659,10 @@ mod tests {
             v2_data: Some(v2_data_voc),
         };
 
-        let results_voc = compare_whitelists(&[pair_voc]);
+        let results_voc = compare_whitelists(&[pair_voc], None);
 
         assert_eq!(results_voc.len(), 1);
-        assert!(results_voc[0].mismatch.is_none());
+        assert!(results_voc[0].is_match());
 
         // Test with v2 condition matching FVC
         let condition_fvc = Condition {
@@ -544,12 +678,15 @@ mod tests {
             v2_data: Some(v2_data_fvc),
         };
 
-        let results_fvc = compare_whitelists(&[pair_fvc]);
+        let results_fvc = compare_whitelists(&[pair_fvc], None);
 
         assert_eq!(results_fvc.len(), 1);
         // Even though v2 has a condition matching FVC, it should still mismatch Voc is set on v1
-        // and takes priority over FVC.
-        assert_eq!(results_fvc[0].mismatch, Some(Mismatch::Voc));
+        // and takes priority over FVC, which is then unexpected.
+        assert_eq!(
+            kinds(&results_fvc[0]),
+            vec![Mismatch::Voc, Mismatch::UnexpectedV2Conditions]
+        );
     }
 
     #[test]
@@ -578,10 +715,10 @@ mod tests {
             v2_data: Some(v2_data_merkle),
         };
 
-        let results_merkle = compare_whitelists(&[pair_merkle]);
+        let results_merkle = compare_whitelists(&[pair_merkle], None);
 
         assert_eq!(results_merkle.len(), 1);
-        assert!(results_merkle[0].mismatch.is_none());
+        assert!(results_merkle[0].is_match());
 
         // Test with v2 condition matching FVC
         let condition_fvc: Condition = Condition {
@@ -597,12 +734,15 @@ mod tests {
             v2_data: Some(v2_data_fvc),
         };
 
-        let results_fvc = compare_whitelists(&[pair_fvc]);
+        let results_fvc = compare_whitelists(&[pair_fvc], None);
 
         assert_eq!(results_fvc.len(), 1);
         // Even though v2 has a condition matching FVC, it should still mismatch Merklet is set
         // on v1 and takes priority.
-        assert_eq!(results_fvc[0].mismatch, Some(Mismatch::MerkleRoot));
+        assert_eq!(
+            kinds(&results_fvc[0]),
+            vec![Mismatch::MerkleRoot, Mismatch::UnexpectedV2Conditions]
+        );
     }
 
     #[test]
@@ -638,11 +778,18 @@ mod tests {
             v2_data: Some(v2_data),
         };
 
-        let results = compare_whitelists(&[pair]);
+        let results = compare_whitelists(&[pair], None);
 
         assert_eq!(results.len(), 1);
-        // Expect a V2ConditionsLength mismatch because v2 has more than one condition
-        assert_eq!(results[0].mismatch, Some(Mismatch::V2ConditionsLength));
+        // Expect a V2ConditionsLength mismatch because v2 has more than one condition, and the
+        // extra FVC one reported on its own
+        assert_eq!(
+            kinds(&results[0]),
+            vec![
+                Mismatch::V2ConditionsLength,
+                Mismatch::UnexpectedV2Conditions
+            ]
+        );
     }
 
     #[test]
@@ -671,11 +818,11 @@ mod tests {
             v2_data: Some(v2_data),
         };
 
-        let results = compare_whitelists(&[pair]);
+        let results = compare_whitelists(&[pair], None);
 
         assert_eq!(results.len(), 1);
         // Expect no mismatch since v2 matches one of the v1 conditions
-        assert!(results[0].mismatch.is_none());
+        assert!(results[0].is_match());
     }
 
     #[test]
@@ -711,11 +858,18 @@ mod tests {
             v2_data: Some(v2_data),
         };
 
-        let results = compare_whitelists(&[pair]);
+        let results = compare_whitelists(&[pair], None);
 
         assert_eq!(results.len(), 1);
-        // Expect a V2ConditionsLength mismatch because v2 has more than one condition
-        assert_eq!(results[0].mismatch, Some(Mismatch::V2ConditionsLength));
+        // Expect a V2ConditionsLength mismatch because v2 has more than one condition, and the
+        // extra FVC one reported on its own
+        assert_eq!(
+            kinds(&results[0]),
+            vec![
+                Mismatch::V2ConditionsLength,
+                Mismatch::UnexpectedV2Conditions
+            ]
+        );
     }
 
     #[test]
@@ -743,9 +897,122 @@ mod tests {
             v2_data: Some(v2_data),
         };
 
-        let results = compare_whitelists(&[pair]);
+        let results = compare_whitelists(&[pair], None);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(kinds(&results[0]), vec![Mismatch::UnexpectedV2Conditions]);
+    }
+
+    #[test]
+    fn test_collects_every_mismatch() {
+        // v2 has another uuid, the wrong condition, is frozen and has another update authority
+        // Assert every one of them is reported with what was expected
+
+        let uuid = [10u8; 32];
+        let namespace = Pubkey::new_unique();
+
+        let voc = Pubkey::new_unique();
+        let fvc = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+
+        let v1_data = create_whitelist(uuid, DEFAULT_ROOT_HASH, Some(voc), None);
+
+        let mut v2_data = create_whitelist_v2(
+            [11u8; 32],
+            vec![Condition {
+                mode: Mode::FVC,
+                value: fvc,
+            }],
+            namespace,
+        );
+        v2_data.state = State::Frozen;
+        v2_data.update_authority = authority;
+
+        let pair = WhitelistPair {
+            v1_pubkey: Whitelist::find_pda(v1_data.uuid).0,
+            v1_data,
+            v2_pubkey: WhitelistV2::find_pda(&namespace, uuid).0,
+            v2_data: Some(v2_data),
+        };
+
+        let results = compare_whitelists(&[pair], Some(namespace));
 
         assert_eq!(results.len(), 1);
-        assert_eq!(results[0].mismatch, Some(Mismatch::UnexpectedV2Conditions));
+        assert_eq!(
+            results[0].mismatches,
+            vec![
+                MismatchDetail::new(Mismatch::Uuid, hex::encode(uuid), hex::encode([11u8; 32])),
+                MismatchDetail::new(Mismatch::Voc, format!("VOC {}", voc), "none"),
+                MismatchDetail::new(
+                    Mismatch::UnexpectedV2Conditions,
+                    "none",
+                    format!("FVC {}", fvc)
+                ),
+                MismatchDetail::new(Mismatch::Frozen, "unfrozen", "frozen"),
+                MismatchDetail::new(Mismatch::UpdateAuthority, namespace, authority),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update_authority() {
+        // v2 matching the v1 apart from its update authority
+        // Assert the authority is only checked against the expected one when it is given
+
+        let uuid = [13u8; 32];
+        let namespace = Pubkey::new_unique();
+        let voc = Pubkey::new_unique();
+
+        let v1_data = create_whitelist(uuid, DEFAULT_ROOT_HASH, Some(voc), None);
+        let v2_data = create_whitelist_v2(
+            uuid,
+            vec![Condition {
+                mode: Mode::VOC,
+                value: voc,
+            }],
+            namespace,
+        );
+        let authority = v2_data.update_authority;
+
+        let pair = WhitelistPair {
+            v1_pubkey: Whitelist::find_pda(uuid).0,
+            v1_data,
+            v2_pubkey: WhitelistV2::find_pda(&namespace, uuid).0,
+            v2_data: Some(v2_data),
+        };
+
+        assert!(compare_whitelist(&pair, None).is_match());
+        assert!(compare_whitelist(&pair, Some(authority)).is_match());
+
+        let expected = Pubkey::new_unique();
+        assert_eq!(
+            compare_whitelist(&pair, Some(expected)).mismatches,
+            vec![MismatchDetail::new(
+                Mismatch::UpdateAuthority,
+                expected,
+                authority
+            )]
+        );
+    }
+
+    #[test]
+    fn test_missing_v2() {
+        let uuid = [12u8; 32];
+        let v1_data = create_whitelist(uuid, DEFAULT_ROOT_HASH, Some(Pubkey::new_unique()), None);
+        let v2_pubkey = WhitelistV2::find_pda(&WHITELIST_SIGNER_PUBKEY, uuid).0;
+
+        let pair = WhitelistPair {
+            v1_pubkey: Whitelist::find_pda(uuid).0,
+            v1_data,
+            v2_pubkey,
+            v2_data: None,
+        };
+
+        let results = compare_whitelists(&[pair], None);
+
+        assert_eq!(
+            results[0].mismatches,
+            vec![MismatchDetail::new(Mismatch::V2Missing, v2_pubkey, "none")]
+        );
     }
 }
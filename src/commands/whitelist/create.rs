@@ -9,6 +9,10 @@ use uuid::Uuid;
 pub struct CreateWhitelistV2Params {
     pub keypair_path: Option<PathBuf>,
     pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
     pub whitelist_config_path: PathBuf,
     pub namespace_path: Option<PathBuf>,
 }
@@ -22,8 +26,17 @@ struct CreateWhitelistV2Config {
     conditions: Vec<Condition>,
 }
 
-pub fn create_whitelist_v2(args: CreateWhitelistV2Params) -> Result<()> {
-    let config = CliConfig::new(args.keypair_path, args.rpc_url)?;
+#[derive(Debug, Clone)]
+pub struct CreatedWhitelist {
+    pub whitelist: Pubkey,
+    pub namespace: Pubkey,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn create_whitelist_v2(args: CreateWhitelistV2Params) -> Result<CreatedWhitelist> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
 
     let payer = config.keypair.pubkey();
     let owner = config.keypair.pubkey();
@@ -63,12 +76,11 @@ pub fn create_whitelist_v2(args: CreateWhitelistV2Params) -> Result<()> {
     }
     .instruction(args);
 
-    let tx = transaction!(&[&config.keypair, &namespace_signer], &[ix], &config.client);
-
-    config.client.send_and_confirm_transaction(&tx)?;
+    let signatures = send_instructions(&config, vec![ix], &[&namespace_signer], &[])?;
 
-    println!("Whitelist created: {}", whitelist);
-    println!("Namespace: {}", namespace_signer.pubkey());
-
-    Ok(())
+    Ok(CreatedWhitelist {
+        whitelist,
+        namespace: namespace_signer.pubkey(),
+        signatures,
+    })
 }
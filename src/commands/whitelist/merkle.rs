@@ -0,0 +1,378 @@
+use super::*;
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::File,
+    path::Path,
+};
+
+use solana_sdk::keccak;
+
+/// Proofs are stored in `MintProofV2` accounts, which hold at most this many nodes.
+pub const MAX_PROOF_LEN: usize = 28;
+
+pub struct MerkleBuildParams {
+    pub mints_path: PathBuf,
+    pub out: PathBuf,
+    /// Sort the leaves instead of keeping the order of the mints file.
+    pub sort_leaves: bool,
+}
+
+pub struct MerkleProofParams {
+    pub proofs_path: PathBuf,
+    pub mint: Pubkey,
+}
+
+/// Root of a whitelist merkle tree and the proof of every mint in it. Nodes are stored as
+/// addresses, the type `Condition::value` uses for the root.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProofs {
+    #[serde_as(as = "DisplayFromStr")]
+    pub root: Pubkey,
+    #[serde_as(as = "BTreeMap<DisplayFromStr, Vec<DisplayFromStr>>")]
+    pub proofs: BTreeMap<Pubkey, Vec<Pubkey>>,
+}
+
+impl MerkleProofs {
+    pub fn proof(&self, mint: &Pubkey) -> Option<Vec<[u8; 32]>> {
+        self.proofs
+            .get(mint)
+            .map(|proof| proof.iter().map(|node| node.to_bytes()).collect())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BuiltMerkleTree {
+    pub proofs: MerkleProofs,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct MerkleProof {
+    pub mint: Pubkey,
+    pub root: Pubkey,
+    pub proof: Vec<[u8; 32]>,
+}
+
+pub fn handle_merkle_build(args: MerkleBuildParams) -> Result<BuiltMerkleTree> {
+    let mints = read_mints(&args.mints_path)?;
+    let proofs = build_merkle_proofs(&mints, args.sort_leaves)?;
+
+    let file = File::create(&args.out)
+        .map_err(|e| anyhow!("Failed to create {}: {}", args.out.display(), e))?;
+    serde_json::to_writer_pretty(file, &proofs)?;
+
+    Ok(BuiltMerkleTree {
+        proofs,
+        path: args.out,
+    })
+}
+
+pub fn handle_merkle_proof(args: MerkleProofParams) -> Result<MerkleProof> {
+    let proofs = read_proofs(&args.proofs_path)?;
+
+    let proof = proofs.proof(&args.mint).ok_or_else(|| {
+        anyhow!(
+            "Mint {} is not in {}",
+            args.mint,
+            args.proofs_path.display()
+        )
+    })?;
+
+    if !verify_merkle_proof(&args.mint, &proof, &proofs.root.to_bytes()) {
+        return Err(anyhow!(
+            "The proof of {} doesn't lead to root {}",
+            args.mint,
+            proofs.root
+        ));
+    }
+
+    Ok(MerkleProof {
+        mint: args.mint,
+        root: proofs.root,
+        proof,
+    })
+}
+
+/// Reads a JSON array of mint addresses.
+pub fn read_mints(path: &Path) -> Result<Vec<Pubkey>> {
+    #[serde_as]
+    #[derive(Deserialize)]
+    struct Mints(#[serde_as(as = "Vec<DisplayFromStr>")] Vec<Pubkey>);
+
+    let file = File::open(path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let Mints(mints) = serde_json::from_reader(file)
+        .map_err(|e| anyhow!("Invalid mints file {}: {}", path.display(), e))?;
+
+    Ok(mints)
+}
+
+pub fn read_proofs(path: &Path) -> Result<MerkleProofs> {
+    let file = File::open(path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+
+    serde_json::from_reader(file)
+        .map_err(|e| anyhow!("Invalid proofs file {}: {}", path.display(), e))
+}
+
+/// Leaf of a mint, the keccak hash of its address.
+pub fn merkle_leaf(mint: &Pubkey) -> [u8; 32] {
+    keccak::hash(mint.as_ref()).to_bytes()
+}
+
+/// Parent of two nodes. The program sorts each pair before hashing it, so proofs don't need to
+/// say which side a node is on.
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        keccak::hashv(&[a, b]).to_bytes()
+    } else {
+        keccak::hashv(&[b, a]).to_bytes()
+    }
+}
+
+/// Builds the tree of the mints the way Tensor's builder does and the whitelist program verifies
+/// it: keccak leaves in the order of the mints, with sorted pairs hashed into their parent and an
+/// odd node carried up a level as is. A single mint is paired with the hash of an empty node so
+/// its proof isn't empty. Repeated mints are only kept the first time.
+///
+/// The root depends on the order of the leaves, so it only matches a whitelist's root when the
+/// mints are in the order its tree was built from. `sort_leaves` sorts them first, for a root that
+/// doesn't depend on the order of the mints.
+pub fn build_merkle_proofs(mints: &[Pubkey], sort_leaves: bool) -> Result<MerkleProofs> {
+    let mut seen = HashSet::new();
+    let mut leaves: Vec<([u8; 32], Option<Pubkey>)> = mints
+        .iter()
+        .filter(|mint| seen.insert(**mint))
+        .map(|mint| (merkle_leaf(mint), Some(*mint)))
+        .collect();
+
+    if sort_leaves {
+        leaves.sort();
+    }
+
+    match leaves.len() {
+        0 => return Err(anyhow!("No mints to build a merkle tree from")),
+        1 => leaves.push((keccak::hash(&[0; 32]).to_bytes(), None)),
+        _ => {}
+    }
+
+    // Index of each node's position in its level, so the proofs can be collected while the
+    // levels are hashed up.
+    let mut positions: Vec<usize> = (0..leaves.len()).collect();
+    let mut proofs: Vec<Vec<[u8; 32]>> = vec![vec![]; leaves.len()];
+    let mut level: Vec<[u8; 32]> = leaves.iter().map(|(leaf, _)| *leaf).collect();
+
+    while level.len() > 1 {
+        for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+            let sibling = *position ^ 1;
+            if sibling < level.len() {
+                proof.push(level[sibling]);
+            }
+            *position /= 2;
+        }
+
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+
+    if proofs.iter().any(|proof| proof.len() > MAX_PROOF_LEN) {
+        return Err(anyhow!(
+            "Too many mints, proofs can't be longer than {} nodes",
+            MAX_PROOF_LEN
+        ));
+    }
+
+    Ok(MerkleProofs {
+        root: Pubkey::new_from_array(level[0]),
+        proofs: leaves
+            .iter()
+            .zip(proofs)
+            .filter_map(|((_, mint), proof)| {
+                mint.map(|mint| {
+                    (
+                        mint,
+                        proof.into_iter().map(Pubkey::new_from_array).collect(),
+                    )
+                })
+            })
+            .collect(),
+    })
+}
+
+/// Checks a proof the way the whitelist program does.
+pub fn verify_merkle_proof(mint: &Pubkey, proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    proof
+        .iter()
+        .fold(merkle_leaf(mint), |node, sibling| hash_pair(&node, sibling))
+        == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn mints(addresses: &[&str]) -> Vec<Pubkey> {
+        addresses
+            .iter()
+            .map(|address| Pubkey::from_str(address).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_single_mint_is_paired_with_empty_node() {
+        // The whitelist program's own tests build a single mint tree from the mint's leaf and the
+        // hash of an empty node, sorted.
+
+        let mint = Pubkey::new_unique();
+        let empty = keccak::hash(&[0; 32]).to_bytes();
+        let mut leaves = [merkle_leaf(&mint), empty];
+        leaves.sort();
+
+        let proofs = build_merkle_proofs(&[mint], false).unwrap();
+
+        assert_eq!(
+            proofs.root.to_bytes(),
+            keccak::hashv(&[&leaves[0], &leaves[1]]).to_bytes()
+        );
+        assert_eq!(proofs.proof(&mint).unwrap(), vec![empty]);
+        assert!(verify_merkle_proof(
+            &mint,
+            &proofs.proof(&mint).unwrap(),
+            &proofs.root.to_bytes()
+        ));
+    }
+
+    // These roots were computed with a separate implementation of the same tree (keccak256 leaves
+    // in input order, sorted pairs, odd node carried up) rather than taken from this builder.
+    #[test]
+    fn test_known_roots() {
+        let vectors = [
+            (
+                vec![
+                    "So11111111111111111111111111111111111111112",
+                    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+                ],
+                "Dc5FKvaLCktjKFTrFcXkBNoJjZHKLenEjeiHP3cNQbpr",
+            ),
+            (
+                vec![
+                    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+                    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "So11111111111111111111111111111111111111112",
+                ],
+                "CyDQpHDwL7p9z18EDV53PXpF3ugZT8KLPznebjun57tu",
+            ),
+            (
+                vec![
+                    "So11111111111111111111111111111111111111112",
+                    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                    "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+                    "SysvarRent111111111111111111111111111111111",
+                    "11111111111111111111111111111111",
+                ],
+                "4phagfnPYegaiyUUGNxZbjrzEorpy3g3TqVEn9sDSg6f",
+            ),
+            (
+                vec![
+                    "So11111111111111111111111111111111111111112",
+                    "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                ],
+                "BnvdcLhA9ADykqsfVGJyAg8tyAngSbnRCkfZZj1FJtM5",
+            ),
+            (
+                vec!["So11111111111111111111111111111111111111112"],
+                "7j3AJ4VQSqfCDJ8WYeaN5xXJt2GgefEDrZaM1p6zif6B",
+            ),
+        ];
+
+        for (addresses, root) in vectors {
+            let proofs = build_merkle_proofs(&mints(&addresses), false).unwrap();
+
+            assert_eq!(proofs.root.to_string(), root);
+        }
+    }
+
+    #[test]
+    fn test_sort_leaves() {
+        let mut mints = mints(&[
+            "So11111111111111111111111111111111111111112",
+            "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+            "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+        ]);
+        let sorted = build_merkle_proofs(&mints, true).unwrap();
+
+        // These leaves sort into the reverse of the order above.
+        assert_eq!(
+            sorted.root.to_string(),
+            "CyDQpHDwL7p9z18EDV53PXpF3ugZT8KLPznebjun57tu"
+        );
+        assert_ne!(build_merkle_proofs(&mints, false).unwrap(), sorted);
+
+        mints.rotate_left(1);
+        assert_eq!(build_merkle_proofs(&mints, true).unwrap(), sorted);
+    }
+
+    #[test]
+    fn test_two_mints() {
+        let mints = mints(&[
+            "So11111111111111111111111111111111111111112",
+            "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+        ]);
+        let (a, b) = (merkle_leaf(&mints[0]), merkle_leaf(&mints[1]));
+        let (low, high) = if a <= b { (a, b) } else { (b, a) };
+
+        let proofs = build_merkle_proofs(&mints, false).unwrap();
+
+        assert_eq!(
+            proofs.root.to_bytes(),
+            keccak::hashv(&[&low, &high]).to_bytes()
+        );
+        assert_eq!(proofs.proof(&mints[0]).unwrap(), vec![b]);
+        assert_eq!(proofs.proof(&mints[1]).unwrap(), vec![a]);
+    }
+
+    #[test]
+    fn test_every_proof_verifies() {
+        // Odd counts carry a node up a level, so some proofs are shorter than others.
+        for count in [3, 5, 8, 13, 100] {
+            let mints: Vec<Pubkey> = (0..count).map(|_| Pubkey::new_unique()).collect();
+            let proofs = build_merkle_proofs(&mints, false).unwrap();
+            let root = proofs.root.to_bytes();
+
+            assert_eq!(proofs.proofs.len(), count);
+
+            for mint in &mints {
+                let proof = proofs.proof(mint).unwrap();
+                assert!(proof.len() <= (count as f64).log2().ceil() as usize);
+                assert!(verify_merkle_proof(mint, &proof, &root));
+            }
+
+            let outsider = Pubkey::new_unique();
+            assert!(!verify_merkle_proof(
+                &outsider,
+                &proofs.proof(&mints[0]).unwrap(),
+                &root
+            ));
+        }
+    }
+
+    #[test]
+    fn test_duplicates_and_empty() {
+        let mint = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+
+        assert_eq!(
+            build_merkle_proofs(&[mint, other, mint], false).unwrap(),
+            build_merkle_proofs(&[mint, other], false).unwrap()
+        );
+        assert!(build_merkle_proofs(&[], false).is_err());
+    }
+}
@@ -0,0 +1,423 @@
+use super::*;
+
+use std::{collections::HashSet, fs::File};
+
+use solana_sdk::instruction::Instruction;
+use tensor_whitelist::{
+    accounts::Whitelist,
+    instructions::{
+        CreateWhitelistV2, CreateWhitelistV2InstructionArgs, UpdateWhitelistV2,
+        UpdateWhitelistV2InstructionArgs,
+    },
+    types::{Condition, Operation},
+};
+
+use crate::{
+    discriminators::deserialize_account,
+    rpc::{block_on, get_multiple_accounts_batched},
+    spinner::pb_with_len,
+    transactions::pack_instructions,
+};
+
+pub struct MigrateParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    /// Namespace keypair the v2s are created under and updated by, the signer's keypair when not
+    /// given.
+    pub namespace_path: Option<PathBuf>,
+    /// Compare output of the v1s without a v2.
+    pub missing_path: Option<PathBuf>,
+    /// Compare output of the v1s whose v2 doesn't match.
+    pub mismatches_path: Option<PathBuf>,
+    /// UUID list to limit the comparison to, when it is recomputed.
+    pub list: Option<PathBuf>,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum MigrationAction {
+    Create {
+        v1: Pubkey,
+        v2: Pubkey,
+        uuid: [u8; 32],
+        condition: Condition,
+    },
+    Update {
+        v1: Pubkey,
+        v2: Pubkey,
+        /// Condition mismatches the update fixes.
+        mismatches: Vec<Mismatch>,
+        condition: Condition,
+    },
+}
+
+impl MigrationAction {
+    pub fn v2(&self) -> Pubkey {
+        match self {
+            MigrationAction::Create { v2, .. } | MigrationAction::Update { v2, .. } => *v2,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            MigrationAction::Create {
+                v1, v2, condition, ..
+            } => format!(
+                "create {} from {} with {:?} {}",
+                v2, v1, condition.mode, condition.value
+            ),
+            MigrationAction::Update {
+                v2,
+                mismatches,
+                condition,
+                ..
+            } => format!(
+                "update {} ({:?} mismatches) to {:?} {}",
+                v2, mismatches, condition.mode, condition.value
+            ),
+        }
+    }
+}
+
+/// What migrating the whitelists takes.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationPlan {
+    pub actions: Vec<MigrationAction>,
+    /// Whitelist v1s that can't be migrated and why.
+    pub skipped: Vec<(Pubkey, String)>,
+    /// Whitelist v1s whose v2 already matches.
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct WhitelistMigration {
+    pub plan: MigrationPlan,
+    pub applied: bool,
+    pub migrated: Vec<Pubkey>,
+    /// v2s left as they were and why. The plan is worked out from the chain on every run, so
+    /// rerunning the migration only picks these up again.
+    pub failed: Vec<(Pubkey, String)>,
+    pub signatures: Vec<Signature>,
+    /// Comparison run once the migration is applied.
+    pub report: Option<ComparisonReport>,
+}
+
+impl WhitelistMigration {
+    fn skipped(plan: MigrationPlan) -> Self {
+        WhitelistMigration {
+            plan,
+            applied: false,
+            migrated: vec![],
+            failed: vec![],
+            signatures: vec![],
+            report: None,
+        }
+    }
+}
+
+/// Works out the migration of the whitelists compare reports as missing or mismatched and applies
+/// it once `confirm` agrees to the plan, comparing again afterwards. Leaves showing the plan and
+/// asking to the caller.
+pub fn handle_migrate(
+    args: MigrateParams,
+    confirm: impl FnOnce(&MigrationPlan) -> Result<bool>,
+) -> Result<WhitelistMigration> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let namespace_signer = match args.namespace_path {
+        Some(path) => {
+            read_keypair_file(path).map_err(|_| anyhow!("Unable to read keypair file"))?
+        }
+        None => config.keypair.insecure_clone(),
+    };
+    let namespace = namespace_signer.pubkey();
+
+    let v1s = if args.missing_path.is_some() || args.mismatches_path.is_some() {
+        read_comparison_files(args.missing_path, args.mismatches_path)?
+    } else {
+        let report =
+            compare_on_chain(&config, args.list.clone(), Some(namespace), Some(namespace))?;
+
+        report
+            .missing
+            .iter()
+            .map(|pair| pair.v1_pubkey)
+            .chain(report.mismatches().map(|result| result.whitelist_v1))
+            .collect()
+    };
+
+    let pairs = fetch_whitelist_pairs(&config, &v1s, &namespace)?;
+    // The namespace is the update authority compare expects.
+    let plan = plan_migration(&pairs, &namespace);
+
+    if plan.actions.is_empty() || args.dry_run || !confirm(&plan)? {
+        return Ok(WhitelistMigration::skipped(plan));
+    }
+
+    let mut migration = apply_migration(&config, &namespace_signer, plan)?;
+    migration.report = Some(compare_on_chain(
+        &config,
+        args.list,
+        Some(namespace),
+        Some(namespace),
+    )?);
+
+    Ok(migration)
+}
+
+/// Reads the whitelist v1s out of the `_v2_missing.json` and `_mismatches.json` files written by
+/// compare, without duplicates.
+pub fn read_comparison_files(
+    missing_path: Option<PathBuf>,
+    mismatches_path: Option<PathBuf>,
+) -> Result<Vec<Pubkey>> {
+    let missing: Vec<MissingWhitelistPair> = match missing_path {
+        Some(path) => serde_json::from_reader(File::open(path)?)?,
+        None => vec![],
+    };
+    let mismatches: Vec<ComparisonResult> = match mismatches_path {
+        Some(path) => serde_json::from_reader(File::open(path)?)?,
+        None => vec![],
+    };
+
+    let mut seen = HashSet::new();
+
+    Ok(missing
+        .iter()
+        .map(|pair| pair.v1_pubkey)
+        .chain(mismatches.iter().map(|result| result.whitelist_v1))
+        .filter(|v1| seen.insert(*v1))
+        .collect())
+}
+
+/// Fetches the whitelist v1s and the v2s derived from them under `namespace`. v1s that don't
+/// exist are left out.
+pub fn fetch_whitelist_pairs(
+    config: &CliConfig,
+    v1s: &[Pubkey],
+    namespace: &Pubkey,
+) -> Result<Vec<WhitelistPair>> {
+    let client = config.client.clone();
+    let accounts = block_on(get_multiple_accounts_batched(
+        client.clone(),
+        v1s,
+        config.max_concurrency,
+    ))??;
+
+    let v1_data = v1s
+        .iter()
+        .zip(accounts)
+        .filter_map(|(v1, account)| account.map(|account| (*v1, account)))
+        .map(|(v1, account)| {
+            deserialize_account::<Whitelist>(&account.data)
+                .map(|data| (v1, data))
+                .map_err(|e| anyhow!("Invalid whitelist v1 {}: {}", v1, e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let v2s: Vec<Pubkey> = v1_data
+        .iter()
+        .map(|(_, data)| WhitelistV2::find_pda(namespace, data.uuid).0)
+        .collect();
+    let v2_accounts = block_on(get_multiple_accounts_batched(
+        client,
+        &v2s,
+        config.max_concurrency,
+    ))??;
+
+    Ok(v1_data
+        .into_iter()
+        .zip(v2s.into_iter().zip(v2_accounts))
+        .map(
+            |((v1_pubkey, v1_data), (v2_pubkey, account))| WhitelistPair {
+                v1_pubkey,
+                v1_data,
+                v2_pubkey,
+                v2_data: account
+                    .and_then(|account| deserialize_account::<WhitelistV2>(&account.data).ok()),
+            },
+        )
+        .collect())
+}
+
+/// Works out the create or update each pair needs for its v2 to match the v1. Only conditions are
+/// migrated, so a v2 with another UUID or that `update_authority` can't update is skipped, as are
+/// frozen state and update authority differences.
+pub fn plan_migration(pairs: &[WhitelistPair], update_authority: &Pubkey) -> MigrationPlan {
+    let mut plan = MigrationPlan::default();
+
+    for (pair, result) in pairs
+        .iter()
+        .zip(compare_whitelists(pairs, Some(*update_authority)))
+    {
+        if result.is_match() {
+            plan.unchanged += 1;
+            continue;
+        }
+
+        let condition = expected_condition(&pair.v1_data);
+        let no_condition = || {
+            (
+                pair.v1_pubkey,
+                "the v1 has no merkle root, VOC or FVC to migrate".to_string(),
+            )
+        };
+
+        let v2 = match (&pair.v2_data, &condition) {
+            (Some(v2), _) => v2,
+            (None, Some(condition)) => {
+                plan.actions.push(MigrationAction::Create {
+                    v1: pair.v1_pubkey,
+                    v2: pair.v2_pubkey,
+                    uuid: pair.v1_data.uuid,
+                    condition: condition.clone(),
+                });
+                continue;
+            }
+            (None, None) => {
+                plan.skipped.push(no_condition());
+                continue;
+            }
+        };
+
+        if result.has(Mismatch::Uuid) {
+            plan.skipped.push((
+                pair.v1_pubkey,
+                "the v2 has a different uuid, which can't be updated".to_string(),
+            ));
+            continue;
+        }
+
+        let (fixable, manual): (Vec<Mismatch>, Vec<Mismatch>) = result
+            .mismatches
+            .iter()
+            .map(|detail| detail.kind)
+            .partition(|kind| !matches!(kind, Mismatch::Frozen | Mismatch::UpdateAuthority));
+
+        let mut reasons = vec![];
+
+        if !fixable.is_empty() {
+            match condition {
+                None => reasons.push(no_condition().1),
+                Some(_) if v2.update_authority != *update_authority => reasons.push(format!(
+                    "the v2 update authority is {}",
+                    v2.update_authority
+                )),
+                Some(condition) => plan.actions.push(MigrationAction::Update {
+                    v1: pair.v1_pubkey,
+                    v2: pair.v2_pubkey,
+                    mismatches: fixable,
+                    condition,
+                }),
+            }
+        }
+
+        if !manual.is_empty() {
+            reasons.push(format!("{:?} mismatches are left to fix by hand", manual));
+        }
+
+        if !reasons.is_empty() {
+            plan.skipped.push((pair.v1_pubkey, reasons.join(", ")));
+        }
+    }
+
+    plan
+}
+
+/// Sends the plan in as few transactions as fit, one at a time. A transaction that fails leaves
+/// its whitelists as they were without stopping the rest.
+pub fn apply_migration(
+    config: &CliConfig,
+    namespace_signer: &Keypair,
+    plan: MigrationPlan,
+) -> Result<WhitelistMigration> {
+    let payer = config.keypair.pubkey();
+
+    let (v2s, instructions): (Vec<Pubkey>, Vec<Instruction>) = plan
+        .actions
+        .iter()
+        .map(|action| {
+            (
+                action.v2(),
+                migration_instruction(&payer, &namespace_signer.pubkey(), action),
+            )
+        })
+        .unzip();
+    let batches = pack_instructions(&payer, instructions, &[])?;
+
+    let progress = pb_with_len("Migrating whitelists", v2s.len() as u64)?;
+
+    let mut migrated = vec![];
+    let mut failed = vec![];
+    let mut signatures = vec![];
+    let mut remaining = v2s.as_slice();
+
+    for batch in batches {
+        let (batch_v2s, rest) = remaining.split_at(batch.len());
+        remaining = rest;
+
+        match send_instructions(config, batch, &[namespace_signer], &[]) {
+            Ok(batch_signatures) => {
+                migrated.extend_from_slice(batch_v2s);
+                signatures.extend(batch_signatures);
+            }
+            Err(e) => failed.extend(batch_v2s.iter().map(|v2| (*v2, e.to_string()))),
+        }
+
+        progress.inc(batch_v2s.len() as u64);
+    }
+
+    progress.finish_and_clear();
+
+    Ok(WhitelistMigration {
+        plan,
+        applied: true,
+        migrated,
+        failed,
+        signatures,
+        report: None,
+    })
+}
+
+fn migration_instruction(
+    payer: &Pubkey,
+    namespace: &Pubkey,
+    action: &MigrationAction,
+) -> Instruction {
+    match action {
+        MigrationAction::Create {
+            v2,
+            uuid,
+            condition,
+            ..
+        } => CreateWhitelistV2 {
+            payer: *payer,
+            update_authority: *namespace,
+            namespace: *namespace,
+            whitelist: *v2,
+            system_program: solana_sdk::system_program::id(),
+        }
+        .instruction(CreateWhitelistV2InstructionArgs {
+            uuid: *uuid,
+            freeze_authority: None,
+            conditions: vec![condition.clone()],
+        }),
+        MigrationAction::Update { v2, condition, .. } => UpdateWhitelistV2 {
+            payer: *payer,
+            update_authority: *namespace,
+            whitelist: *v2,
+            new_update_authority: None,
+            system_program: solana_sdk::system_program::id(),
+        }
+        .instruction(UpdateWhitelistV2InstructionArgs {
+            freeze_authority: Operation::Noop,
+            conditions: Some(vec![condition.clone()]),
+        }),
+    }
+}
@@ -0,0 +1,331 @@
+use super::*;
+
+use solana_sdk::instruction::Instruction;
+use tensor_whitelist::{
+    accounts::MintProofV2,
+    instructions::{InitUpdateMintProofV2, InitUpdateMintProofV2InstructionArgs},
+    types::Mode,
+};
+
+use crate::{
+    discriminators::deserialize_account,
+    rpc::{block_on, get_multiple_accounts_batched},
+    spinner::pb_with_len,
+    transactions::pack_instructions,
+};
+
+pub struct InitMintProofParams {
+    pub keypair_path: Option<PathBuf>,
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
+    pub whitelist: Pubkey,
+    /// Mint to create the proof of, every mint in the proofs when not given.
+    pub mint: Option<Pubkey>,
+    /// Proofs file written by merkle build.
+    pub proofs_path: PathBuf,
+    /// Mints file to recompute the proofs from instead of reading them.
+    pub mints_path: Option<PathBuf>,
+    /// Sort the leaves of the recomputed tree instead of keeping the order of the mints file.
+    pub sort_leaves: bool,
+    pub dry_run: bool,
+}
+
+pub struct ShowMintProofParams {
+    pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub whitelist: Pubkey,
+    pub mint: Pubkey,
+}
+
+#[derive(Debug, Clone)]
+pub struct MintProofAction {
+    pub mint: Pubkey,
+    pub address: Pubkey,
+    pub proof: Vec<[u8; 32]>,
+    /// Whether a mint proof with another proof is already on chain.
+    pub exists: bool,
+}
+
+/// Mint proofs to create or update for a whitelist.
+#[derive(Debug, Clone)]
+pub struct MintProofPlan {
+    pub whitelist: Pubkey,
+    pub root: Pubkey,
+    pub actions: Vec<MintProofAction>,
+    /// Mints whose proof is already on chain.
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct InitializedMintProofs {
+    pub plan: MintProofPlan,
+    pub applied: bool,
+    pub initialized: Vec<Pubkey>,
+    /// Mints left without a proof and why. The plan skips proofs already on chain, so running the
+    /// command again only picks these up.
+    pub failed: Vec<(Pubkey, String)>,
+    pub signatures: Vec<Signature>,
+}
+
+impl InitializedMintProofs {
+    fn skipped(plan: MintProofPlan) -> Self {
+        InitializedMintProofs {
+            plan,
+            applied: false,
+            initialized: vec![],
+            failed: vec![],
+            signatures: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MintProofDetails {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    pub whitelist: Pubkey,
+    pub proof: Vec<[u8; 32]>,
+    pub creation_slot: u64,
+    pub payer: Pubkey,
+    /// Root of the whitelist's merkle condition, if it has one.
+    pub root: Option<Pubkey>,
+    /// Whether the stored proof leads from the mint to the root.
+    pub valid: bool,
+}
+
+/// Plans the mint proofs and sends them once `confirm` agrees to the plan. Leaves showing the
+/// plan and asking to the caller.
+pub fn handle_init_mint_proof(
+    args: InitMintProofParams,
+    confirm: impl FnOnce(&MintProofPlan) -> Result<bool>,
+) -> Result<InitializedMintProofs> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
+
+    let proofs = match args.mints_path {
+        Some(path) => build_merkle_proofs(&read_mints(&path)?, args.sort_leaves)?,
+        None => read_proofs(&args.proofs_path)?,
+    };
+
+    let mints: Vec<Pubkey> = match args.mint {
+        Some(mint) => vec![mint],
+        None => proofs.proofs.keys().copied().collect(),
+    };
+
+    let plan = plan_mint_proofs(&config, &args.whitelist, &proofs, &mints)?;
+
+    if plan.actions.is_empty() || args.dry_run {
+        return Ok(InitializedMintProofs::skipped(plan));
+    }
+
+    if !confirm(&plan)? {
+        return Ok(InitializedMintProofs::skipped(plan));
+    }
+
+    init_mint_proofs(&config, plan)
+}
+
+pub fn handle_show_mint_proof(args: ShowMintProofParams) -> Result<MintProofDetails> {
+    let config = CliConfig::new(None, args.rpc_url, args.profile)?;
+
+    fetch_mint_proof(&config, &args.whitelist, &args.mint)
+}
+
+/// Root of the whitelist's merkle condition, `None` when it has none.
+pub fn fetch_merkle_root(config: &CliConfig, whitelist: &Pubkey) -> Result<Option<Pubkey>> {
+    let accounts = config.client.get_multiple_accounts(&[*whitelist])?;
+    let account = accounts[0]
+        .as_ref()
+        .ok_or_else(|| anyhow!("Whitelist {} not found", whitelist))?;
+
+    let data = deserialize_account::<WhitelistV2>(&account.data)
+        .map_err(|e| anyhow!("Invalid whitelist v2 {}: {}", whitelist, e))?;
+
+    Ok(data
+        .conditions
+        .iter()
+        .find(|condition| condition.mode == Mode::MerkleTree)
+        .map(|condition| condition.value))
+}
+
+/// Works out the mint proofs to send for `mints`. The proofs must be of the whitelist's merkle
+/// root, and mints whose proof is already on chain are left out.
+pub fn plan_mint_proofs(
+    config: &CliConfig,
+    whitelist: &Pubkey,
+    proofs: &MerkleProofs,
+    mints: &[Pubkey],
+) -> Result<MintProofPlan> {
+    let root = fetch_merkle_root(config, whitelist)?
+        .ok_or_else(|| anyhow!("Whitelist {} has no merkle tree condition", whitelist))?;
+
+    if root != proofs.root {
+        return Err(anyhow!(
+            "The proofs are of root {}, but whitelist {} has root {}. Check that the mints are in \
+             the order the tree was built from, and whether it was built with --sort-leaves",
+            proofs.root,
+            whitelist,
+            root
+        ));
+    }
+
+    let actions = mints
+        .iter()
+        .map(|mint| {
+            let proof = proofs
+                .proof(mint)
+                .ok_or_else(|| anyhow!("Mint {} is not in the merkle tree", mint))?;
+
+            Ok(MintProofAction {
+                mint: *mint,
+                address: MintProofV2::find_pda(mint, whitelist).0,
+                proof,
+                exists: false,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let addresses: Vec<Pubkey> = actions.iter().map(|action| action.address).collect();
+    let accounts = block_on(get_multiple_accounts_batched(
+        config.client.clone(),
+        &addresses,
+        config.max_concurrency,
+    ))??;
+
+    let mut plan = MintProofPlan {
+        whitelist: *whitelist,
+        root,
+        actions: vec![],
+        unchanged: 0,
+    };
+
+    for (mut action, account) in actions.into_iter().zip(accounts) {
+        if let Some(account) = account {
+            // An unreadable account is sent again, which the program overwrites.
+            let stored = deserialize_account::<MintProofV2>(&account.data).ok();
+            if stored.is_some_and(|stored| stored_proof(&stored) == action.proof) {
+                plan.unchanged += 1;
+                continue;
+            }
+            action.exists = true;
+        }
+
+        plan.actions.push(action);
+    }
+
+    Ok(plan)
+}
+
+/// Sends the mint proofs in as few transactions as fit, one at a time. A transaction that fails
+/// leaves its mints without a proof without stopping the rest.
+pub fn init_mint_proofs(config: &CliConfig, plan: MintProofPlan) -> Result<InitializedMintProofs> {
+    let payer = config.keypair.pubkey();
+
+    let instructions: Vec<Instruction> = plan
+        .actions
+        .iter()
+        .map(|action| mint_proof_instruction(&payer, &plan.whitelist, action))
+        .collect();
+    let batches = pack_instructions(&payer, instructions, &[])?;
+
+    let progress = pb_with_len("Creating mint proofs", plan.actions.len() as u64)?;
+
+    let mut initialized = vec![];
+    let mut failed = vec![];
+    let mut signatures = vec![];
+    let mut remaining = plan.actions.as_slice();
+
+    for batch in batches {
+        let (batch_actions, rest) = remaining.split_at(batch.len());
+        remaining = rest;
+
+        match send_instructions(config, batch, &[], &[]) {
+            Ok(batch_signatures) => {
+                initialized.extend(batch_actions.iter().map(|action| action.mint));
+                signatures.extend(batch_signatures);
+            }
+            Err(e) => failed.extend(
+                batch_actions
+                    .iter()
+                    .map(|action| (action.mint, e.to_string())),
+            ),
+        }
+
+        progress.inc(batch_actions.len() as u64);
+    }
+
+    progress.finish_and_clear();
+
+    Ok(InitializedMintProofs {
+        plan,
+        applied: true,
+        initialized,
+        failed,
+        signatures,
+    })
+}
+
+fn mint_proof_instruction(
+    payer: &Pubkey,
+    whitelist: &Pubkey,
+    action: &MintProofAction,
+) -> Instruction {
+    InitUpdateMintProofV2 {
+        payer: *payer,
+        mint: action.mint,
+        whitelist: *whitelist,
+        mint_proof: action.address,
+        system_program: solana_sdk::system_program::id(),
+    }
+    .instruction(InitUpdateMintProofV2InstructionArgs {
+        proof: action.proof.clone(),
+    })
+}
+
+/// The proof of a mint proof account, without its padding.
+pub fn stored_proof(mint_proof: &MintProofV2) -> Vec<[u8; 32]> {
+    let len = (mint_proof.proof_len as usize).min(MAX_PROOF_LEN);
+
+    mint_proof.proof[..len].to_vec()
+}
+
+/// Fetches the mint proof of `mint` for the whitelist and checks it against the whitelist's merkle
+/// root.
+pub fn fetch_mint_proof(
+    config: &CliConfig,
+    whitelist: &Pubkey,
+    mint: &Pubkey,
+) -> Result<MintProofDetails> {
+    let address = MintProofV2::find_pda(mint, whitelist).0;
+
+    let accounts = config.client.get_multiple_accounts(&[address])?;
+    let account = accounts[0].as_ref().ok_or_else(|| {
+        anyhow!(
+            "Mint proof {} of {} for whitelist {} not found",
+            address,
+            mint,
+            whitelist
+        )
+    })?;
+    let mint_proof = deserialize_account::<MintProofV2>(&account.data)
+        .map_err(|e| anyhow!("Invalid mint proof {}: {}", address, e))?;
+
+    let proof = stored_proof(&mint_proof);
+    let root = fetch_merkle_root(config, whitelist)?;
+    let valid = root.is_some_and(|root| verify_merkle_proof(mint, &proof, &root.to_bytes()));
+
+    Ok(MintProofDetails {
+        address,
+        mint: *mint,
+        whitelist: *whitelist,
+        proof,
+        creation_slot: mint_proof.creation_slot,
+        payer: mint_proof.payer,
+        root,
+        valid,
+    })
+}
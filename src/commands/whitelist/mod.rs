@@ -1,22 +1,29 @@
 mod compare;
 mod create;
+mod merkle;
+mod migrate;
+mod mint_proof;
+mod report;
 mod update;
 
 pub use compare::*;
 pub use create::*;
+pub use merkle::*;
+pub use migrate::*;
+pub use mint_proof::*;
+pub use report::*;
 pub use update::*;
 
 use std::path::PathBuf;
 
-use crate::{setup::CliConfig, transaction};
+use crate::{setup::CliConfig, transactions::send_instructions};
 
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 use solana_sdk::{
     pubkey::Pubkey,
-    signature::{read_keypair_file, Keypair},
+    signature::{read_keypair_file, Keypair, Signature},
     signer::Signer,
-    transaction::Transaction,
 };
 use tensor_whitelist::accounts::WhitelistV2;
@@ -0,0 +1,320 @@
+use super::*;
+
+use std::{collections::BTreeMap, fs::File, path::Path};
+
+use anyhow::Context;
+
+use crate::formatting::format_date;
+
+/// Counts of a comparison, kept next to the reports so the next one can show the progress made.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComparisonSummary {
+    pub cluster: String,
+    pub generated_at: i64,
+    pub v1_count: usize,
+    pub v2_count: usize,
+    /// Whitelist v1s with no v2 on chain.
+    pub missing: usize,
+    /// Whitelist v1s whose v2 matches.
+    pub matched: usize,
+    /// Whitelist v1s whose v2 has at least one mismatch.
+    pub mismatched: usize,
+    /// Whitelists with each kind of mismatch, missing v2s included.
+    pub by_kind: BTreeMap<Mismatch, usize>,
+}
+
+impl ComparisonReport {
+    pub fn summary(&self) -> ComparisonSummary {
+        let mut by_kind = BTreeMap::new();
+
+        if !self.missing.is_empty() {
+            by_kind.insert(Mismatch::V2Missing, self.missing.len());
+        }
+
+        for result in &self.results {
+            let mut kinds: Vec<Mismatch> =
+                result.mismatches.iter().map(|detail| detail.kind).collect();
+            kinds.sort();
+            kinds.dedup();
+
+            for kind in kinds {
+                *by_kind.entry(kind).or_default() += 1;
+            }
+        }
+
+        let mismatched = self.mismatches().count();
+
+        ComparisonSummary {
+            cluster: self.cluster.clone(),
+            generated_at: chrono::Utc::now().timestamp(),
+            v1_count: self.v1_count,
+            v2_count: self.v2_count,
+            missing: self.missing.len(),
+            matched: self.results.len() - mismatched,
+            mismatched,
+            by_kind,
+        }
+    }
+}
+
+fn summary_path(dir: &Path, cluster: &str) -> PathBuf {
+    dir.join(format!("{}_summary.json", cluster))
+}
+
+/// Reads a summary written with an earlier report.
+pub fn read_summary(path: &Path) -> Result<ComparisonSummary> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open previous report {}", path.display()))?;
+
+    serde_json::from_reader(file)
+        .with_context(|| format!("Invalid previous report {}", path.display()))
+}
+
+/// Writes the CSV and HTML reports and the summary to `dir`. The delta is against `previous`, or
+/// the summary already in `dir` when not given, read before it is replaced. Returns the files
+/// written.
+pub fn write_comparison_report(
+    report: &mut ComparisonReport,
+    dir: &Path,
+    previous: Option<&Path>,
+) -> Result<Vec<PathBuf>> {
+    let summary_file = summary_path(dir, &report.cluster);
+
+    report.previous = match previous {
+        Some(path) => Some(read_summary(path)?),
+        None if summary_file.exists() => Some(read_summary(&summary_file)?),
+        None => None,
+    };
+
+    let summary = report.summary();
+
+    let csv_file = dir.join(format!("{}_report.csv", report.cluster));
+    write_comparison_csv(report, &csv_file)?;
+
+    let html_file = dir.join(format!("{}_report.html", report.cluster));
+    std::fs::write(&html_file, comparison_html(report, &summary))
+        .map_err(|e| anyhow!("Failed to write report to {}: {}", html_file.display(), e))?;
+
+    serde_json::to_writer_pretty(File::create(&summary_file)?, &summary)?;
+
+    Ok(vec![csv_file, html_file, summary_file])
+}
+
+/// Status and mismatches of every whitelist v1, missing v2s first, then mismatches, then matches.
+fn report_rows(report: &ComparisonReport) -> Vec<(Pubkey, Pubkey, &'static str, Vec<String>)> {
+    let missing = report.missing.iter().map(|pair| {
+        (
+            pair.v1_pubkey,
+            pair.v2_pubkey,
+            "missing",
+            vec![format!("{:?}", Mismatch::V2Missing)],
+        )
+    });
+
+    let mut compared: Vec<_> = report
+        .results
+        .iter()
+        .map(|result| {
+            (
+                result.whitelist_v1,
+                result.whitelist_v2,
+                if result.is_match() {
+                    "match"
+                } else {
+                    "mismatch"
+                },
+                result
+                    .mismatches
+                    .iter()
+                    .map(|detail| {
+                        format!(
+                            "{:?}: expected {}, found {}",
+                            detail.kind, detail.expected, detail.actual
+                        )
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    compared.sort_by_key(|(v1, _, status, _)| (*status == "match", v1.to_string()));
+
+    missing.chain(compared).collect()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn write_comparison_csv(report: &ComparisonReport, path: &Path) -> Result<()> {
+    let mut csv = String::from("whitelist_v1,whitelist_v2,status,mismatch_count,mismatches\n");
+
+    for (v1, v2, status, mismatches) in report_rows(report) {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            v1,
+            v2,
+            status,
+            mismatches.len(),
+            csv_field(&mismatches.join("; "))
+        ));
+    }
+
+    std::fs::write(path, csv)
+        .map_err(|e| anyhow!("Failed to write report to {}: {}", path.display(), e))
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn explorer_link(address: &Pubkey, cluster: &str) -> String {
+    let query = if cluster == "devnet" {
+        "?cluster=devnet"
+    } else {
+        ""
+    };
+
+    format!(
+        "<a href=\"https://explorer.solana.com/address/{0}{1}\"><code>{0}</code></a>",
+        address, query
+    )
+}
+
+/// Change since the previous report, e.g. `4 (-2)`.
+fn with_delta(current: usize, previous: Option<usize>) -> String {
+    match previous {
+        Some(previous) if previous != current => {
+            format!("{} ({:+})", current, current as i64 - previous as i64)
+        }
+        _ => current.to_string(),
+    }
+}
+
+/// A report page with no external assets, so it can be shared as a single file.
+pub fn comparison_html(report: &ComparisonReport, summary: &ComparisonSummary) -> String {
+    let previous = report.previous.as_ref();
+
+    let counts = [
+        (
+            "Whitelist v1s",
+            summary.v1_count,
+            previous.map(|p| p.v1_count),
+        ),
+        (
+            "Whitelist v2s",
+            summary.v2_count,
+            previous.map(|p| p.v2_count),
+        ),
+        ("Missing v2s", summary.missing, previous.map(|p| p.missing)),
+        ("Matching", summary.matched, previous.map(|p| p.matched)),
+        (
+            "Mismatched",
+            summary.mismatched,
+            previous.map(|p| p.mismatched),
+        ),
+    ]
+    .iter()
+    .map(|(label, current, previous)| {
+        format!(
+            "<tr><th>{}</th><td>{}</td></tr>\n",
+            label,
+            with_delta(*current, *previous)
+        )
+    })
+    .collect::<String>();
+
+    let mut kinds: Vec<Mismatch> = summary.by_kind.keys().copied().collect();
+    if let Some(previous) = previous {
+        kinds.extend(previous.by_kind.keys().copied());
+        kinds.sort();
+        kinds.dedup();
+    }
+
+    let by_kind = kinds
+        .iter()
+        .map(|kind| {
+            format!(
+                "<tr><th>{:?}</th><td>{}</td></tr>\n",
+                kind,
+                with_delta(
+                    summary.by_kind.get(kind).copied().unwrap_or_default(),
+                    previous.map(|p| p.by_kind.get(kind).copied().unwrap_or_default())
+                )
+            )
+        })
+        .collect::<String>();
+
+    let rows = report_rows(report)
+        .into_iter()
+        .map(|(v1, v2, status, mismatches)| {
+            format!(
+                "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                status,
+                explorer_link(&v1, &report.cluster),
+                explorer_link(&v2, &report.cluster),
+                status,
+                mismatches
+                    .iter()
+                    .map(|mismatch| escape_html(mismatch))
+                    .collect::<Vec<_>>()
+                    .join("<br>")
+            )
+        })
+        .collect::<String>();
+
+    let compared_to = previous
+        .map(|p| {
+            format!(
+                "<p>Changes are since the report of {}.</p>\n",
+                format_date(p.generated_at)
+            )
+        })
+        .unwrap_or_default();
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Whitelist comparison: {cluster}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; margin-bottom: 2em; }}
+th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }}
+tr.missing td {{ background: #fde2e2; }}
+tr.mismatch td {{ background: #fff4d6; }}
+code {{ font-size: 0.9em; }}
+</style>
+</head>
+<body>
+<h1>Whitelist comparison: {cluster}</h1>
+<p>Generated on {date}.</p>
+{compared_to}<h2>Summary</h2>
+<table>
+{counts}</table>
+<h2>Mismatches by category</h2>
+<table>
+{by_kind}</table>
+<h2>Whitelists</h2>
+<table>
+<tr><th>Whitelist v1</th><th>Whitelist v2</th><th>Status</th><th>Mismatches</th></tr>
+{rows}</table>
+</body>
+</html>
+"#,
+        cluster = escape_html(&report.cluster),
+        date = format_date(summary.generated_at),
+        compared_to = compared_to,
+        counts = counts,
+        by_kind = by_kind,
+        rows = rows,
+    )
+}
@@ -9,14 +9,26 @@ use tensor_whitelist::{
 pub struct UpdateWhitelistV2Params {
     pub keypair_path: Option<PathBuf>,
     pub rpc_url: Option<String>,
+    pub profile: Option<String>,
+    pub nonce: Option<Pubkey>,
+    pub nonce_authority: Option<PathBuf>,
+    pub sign_only: Option<PathBuf>,
     pub whitelist_address: Pubkey,
     pub new_conditions_path: Option<PathBuf>,
     pub new_update_authority_path: Option<PathBuf>,
     pub new_freeze_authority: Option<Pubkey>,
 }
 
-pub fn update_whitelist_v2(args: UpdateWhitelistV2Params) -> Result<()> {
-    let config = CliConfig::new(args.keypair_path, args.rpc_url)?;
+#[derive(Debug, Clone)]
+pub struct UpdatedWhitelist {
+    pub whitelist: Pubkey,
+    pub signatures: Vec<Signature>,
+}
+
+pub fn update_whitelist_v2(args: UpdateWhitelistV2Params) -> Result<UpdatedWhitelist> {
+    let config = CliConfig::new(args.keypair_path, args.rpc_url, args.profile)?
+        .with_nonce(args.nonce, args.nonce_authority)?
+        .with_sign_only(args.sign_only)?;
 
     let payer = config.keypair.pubkey();
     let owner = config.keypair.pubkey();
@@ -51,16 +63,11 @@ pub fn update_whitelist_v2(args: UpdateWhitelistV2Params) -> Result<()> {
     }
     .instruction(update_args);
 
-    let signers = if let Some(ref new_auth) = new_update_authority {
-        vec![&config.keypair, new_auth]
-    } else {
-        vec![&config.keypair]
-    };
-    let tx = transaction!(&signers, &[ix], &config.client);
-
-    config.client.send_and_confirm_transaction(&tx)?;
-
-    println!("Whitelist updated: {}", args.whitelist_address);
+    let signers: Vec<&Keypair> = new_update_authority.iter().collect();
+    let signatures = send_instructions(&config, vec![ix], &signers, &[])?;
 
-    Ok(())
+    Ok(UpdatedWhitelist {
+        whitelist: args.whitelist_address,
+        signatures,
+    })
 }
@@ -3,7 +3,7 @@ use borsh::BorshDeserialize;
 use sha2::{Digest, Sha256};
 use tensor_amm::accounts::{NftDepositReceipt, Pool};
 use tensor_marketplace::accounts::{BidState, ListState};
-use tensor_price_lock::accounts::OrderState;
+use tensor_price_lock::accounts::{OrderNftReceipt, OrderState};
 use tensor_whitelist::accounts::{MintProof, MintProofV2, Whitelist, WhitelistV2};
 
 use crate::args::AnchorDiscriminatorKind;
@@ -56,3 +56,4 @@ impl Discriminator for BidState {}
 impl Discriminator for ListState {}
 
 impl Discriminator for OrderState {}
+impl Discriminator for OrderNftReceipt {}
@@ -0,0 +1,36 @@
+use crate::commands::DecodedAccount;
+
+use super::CustomFormat;
+
+impl CustomFormat for DecodedAccount {
+    fn custom_format(&self) -> String {
+        match self {
+            DecodedAccount::Raw(data) => format!("{:?}", data),
+            DecodedAccount::FeeShard(shard) => shard.custom_format(),
+            DecodedAccount::Wallet(entry) => entry.custom_format(),
+            DecodedAccount::Pool(pool) => pool.custom_format(),
+            DecodedAccount::NftDepositReceipt(receipt) => receipt.custom_format(),
+            DecodedAccount::Whitelist(whitelist) => whitelist.custom_format(),
+            DecodedAccount::WhitelistV2(whitelist) => whitelist.custom_format(),
+            DecodedAccount::MintProof(mint_proof) => mint_proof.custom_format(),
+            DecodedAccount::MintProofV2(mint_proof) => mint_proof.custom_format(),
+            DecodedAccount::BidState(bid_state) => bid_state.custom_format(),
+            DecodedAccount::ListState(list_state) => list_state.custom_format(),
+            DecodedAccount::MarginAccount(margin_account) => margin_account.custom_format(),
+            DecodedAccount::OrderState(order_state) => order_state.custom_format(),
+            DecodedAccount::RaydiumAmm(amm_info) => amm_info.custom_format(),
+            DecodedAccount::RaydiumClmm(clmm_info) => clmm_info.custom_format(),
+            DecodedAccount::RaydiumCp(cpswap_info) => cpswap_info.custom_format(),
+            DecodedAccount::Token(entry) => format!(
+                "Token or mint account
+Data length: {}
+Lamports: {}
+Account owned by program: {}",
+                entry.account.data.len(),
+                entry.account.lamports,
+                entry.account.owner
+            ),
+            DecodedAccount::Unknown(_) => "Unknown account type".to_string(),
+        }
+    }
+}
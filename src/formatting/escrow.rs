@@ -0,0 +1,167 @@
+use console::Style;
+use solana_sdk::native_token::lamports_to_sol;
+
+use crate::{
+    commands::{
+        ClosedEscrow, CreatedEscrow, EscrowDetails, EscrowEntry, EscrowList, EscrowSolTransfer,
+        PoolDirection, PoolList,
+    },
+    formatting::{format_date, format_signatures, pad_label},
+    types::tensor_escrow::MarginAccount,
+};
+
+use super::CustomFormat;
+
+const LABEL_LENGTH: usize = 16;
+
+impl CustomFormat for MarginAccount {
+    fn custom_format(&self) -> String {
+        // Use the default text color but set this up for future use.
+        let color = Style::new();
+
+        format!(
+            "{}
+{}: {}
+{}: {}
+{}: {}
+{}: {}
+{}: {}
+{}: {}",
+            color.apply_to("MarginAccount---"),
+            pad_label("discriminator", LABEL_LENGTH),
+            color.apply_to(hex::encode(self.discriminator)),
+            pad_label("owner", LABEL_LENGTH),
+            color.apply_to(self.owner),
+            pad_label("name", LABEL_LENGTH),
+            color.apply_to(self.name()),
+            pad_label("nr", LABEL_LENGTH),
+            color.apply_to(self.nr),
+            pad_label("bump", LABEL_LENGTH),
+            color.apply_to(self.bump[0]),
+            pad_label("pools_attached", LABEL_LENGTH),
+            color.apply_to(self.pools_attached),
+        )
+    }
+}
+
+impl CustomFormat for EscrowEntry {
+    fn custom_format(&self) -> String {
+        format!(
+            "Escrow: {}\nOwner: {}\nName: {}\nNr: {}\nBalance: {} SOL ({} SOL available)\nPools attached: {}",
+            self.address,
+            self.account.owner,
+            self.account.name(),
+            self.account.nr,
+            lamports_to_sol(self.lamports),
+            lamports_to_sol(self.available),
+            self.account.pools_attached,
+        )
+    }
+}
+
+impl CustomFormat for EscrowDetails {
+    fn custom_format(&self) -> String {
+        let mut output = format!(
+            "{}\n\nPools:\n{}\n\nBids:\n",
+            self.escrow.custom_format(),
+            PoolList {
+                pools: self.pools.clone(),
+            }
+            .custom_format()
+        );
+
+        if self.bids.is_empty() {
+            output.push_str("No bids found.");
+            return output;
+        }
+
+        output.push_str(&format!(
+            "{:<44}  {:>14}  {:>8}  {}\n",
+            "Address", "Amount (SOL)", "Quantity", "Expiry"
+        ));
+
+        for (address, bid) in &self.bids {
+            output.push_str(&format!(
+                "{:<44}  {:>14}  {:>8}  {}\n",
+                address,
+                lamports_to_sol(bid.amount),
+                bid.quantity.saturating_sub(bid.filled_quantity),
+                format_date(bid.expiry)
+            ));
+        }
+
+        output.push_str(&format!("{} bids", self.bids.len()));
+
+        output
+    }
+}
+
+impl CustomFormat for EscrowList {
+    fn custom_format(&self) -> String {
+        if self.escrows.is_empty() {
+            return format!("No escrows found for {}.", self.owner);
+        }
+
+        let mut output = format!(
+            "{:<44}  {:>5}  {:<32}  {:>14}  {}\n",
+            "Address", "Nr", "Name", "Balance (SOL)", "Pools"
+        );
+
+        for entry in &self.escrows {
+            output.push_str(&format!(
+                "{:<44}  {:>5}  {:<32}  {:>14}  {}\n",
+                entry.address,
+                entry.account.nr,
+                entry.account.name(),
+                lamports_to_sol(entry.available),
+                entry.account.pools_attached,
+            ));
+        }
+
+        output.push_str(&format!("{} escrows", self.escrows.len()));
+
+        output
+    }
+}
+
+impl CustomFormat for CreatedEscrow {
+    fn custom_format(&self) -> String {
+        format!(
+            "Escrow created: {}\nNr: {}\nName: {}{}",
+            self.address,
+            self.nr,
+            self.name,
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for EscrowSolTransfer {
+    fn custom_format(&self) -> String {
+        let action = match self.direction {
+            PoolDirection::Deposit => "Deposited",
+            PoolDirection::Withdraw => "Withdrew",
+        };
+
+        format!(
+            "{} {} SOL for escrow {}\nBalance before: {} SOL\nBalance after : {} SOL{}",
+            action,
+            lamports_to_sol(self.lamports),
+            self.escrow,
+            lamports_to_sol(self.balance_before),
+            lamports_to_sol(self.balance_after),
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for ClosedEscrow {
+    fn custom_format(&self) -> String {
+        format!(
+            "Escrow closed: {}\nReturned {} SOL to the owner{}",
+            self.escrow,
+            lamports_to_sol(self.lamports),
+            format_signatures(&self.signatures)
+        )
+    }
+}
@@ -0,0 +1,76 @@
+use console::Style;
+
+use crate::{
+    commands::{LookupTable, LookupTableAction, LookupTableChange},
+    formatting::{format_signatures, pad_label},
+};
+
+use super::{option_formatter, CustomFormat};
+
+const LABEL_LENGTH: usize = 20;
+
+impl CustomFormat for LookupTable {
+    fn custom_format(&self) -> String {
+        // Use the default text color but set this up for future use.
+        let color = Style::new();
+
+        format!(
+            "{}
+{}: {}
+{}: {}
+{}: {}
+{}: {}
+{}: {}{}",
+            color.apply_to("AddressLookupTable--"),
+            pad_label("address", LABEL_LENGTH),
+            color.apply_to(self.address),
+            pad_label("authority", LABEL_LENGTH),
+            color.apply_to(option_formatter(&self.authority)),
+            pad_label("deactivation_slot", LABEL_LENGTH),
+            color.apply_to(if self.is_deactivated() {
+                self.deactivation_slot.to_string()
+            } else {
+                "Active".to_string()
+            }),
+            pad_label("last_extended_slot", LABEL_LENGTH),
+            color.apply_to(self.last_extended_slot),
+            pad_label("addresses", LABEL_LENGTH),
+            color.apply_to(self.addresses.len()),
+            self.addresses
+                .iter()
+                .enumerate()
+                .map(|(i, address)| format!("\n  {:>3}: {}", i, color.apply_to(address)))
+                .collect::<String>()
+        )
+    }
+}
+
+impl CustomFormat for LookupTableChange {
+    fn custom_format(&self) -> String {
+        let message = match self.action {
+            LookupTableAction::Created if self.added.is_empty() => {
+                format!("Lookup table created: {}", self.address)
+            }
+            LookupTableAction::Created => format!(
+                "Lookup table created: {}\nAdded {} addresses",
+                self.address,
+                self.added.len()
+            ),
+            LookupTableAction::Extended if self.added.is_empty() => {
+                "All addresses are already in the lookup table.".to_string()
+            }
+            LookupTableAction::Extended => format!(
+                "Added {} addresses to lookup table: {}",
+                self.added.len(),
+                self.address
+            ),
+            LookupTableAction::Deactivated => format!(
+                "Lookup table deactivated: {}\nIt can be closed once the deactivation slot is no longer a recent slot.",
+                self.address
+            ),
+            LookupTableAction::Closed => format!("Lookup table closed: {}", self.address),
+        };
+
+        format!("{}{}", message, format_signatures(&self.signatures))
+    }
+}
@@ -1,7 +1,19 @@
 use console::Style;
-use tensor_marketplace::accounts::{BidState, ListState};
+use tensor_marketplace::{
+    accounts::{BidState, ListState},
+    types::Target,
+};
 
-use crate::formatting::{format_timestamp, option_formatter, pad_label};
+use crate::{
+    commands::{
+        CleanupPlan, Currency, ExpiredOrderKind, MarketBid, MarketBidCancel, MarketBidTake,
+        MarketBids, MarketCleanup, MarketDelisting, MarketListing, MarketListings, MarketPurchase,
+    },
+    formatting::{
+        format_date, format_duration, format_signatures, format_timestamp, option_formatter,
+        pad_label,
+    },
+};
 
 use super::CustomFormat;
 
@@ -154,3 +166,280 @@ impl CustomFormat for ListState {
         )
     }
 }
+
+impl CustomFormat for MarketListing {
+    fn custom_format(&self) -> String {
+        let header = match (&self.standard, self.edited) {
+            (_, true) => format!("Listing edited: {}", self.asset),
+            (Some(standard), false) => format!("Listed {} asset: {}", standard, self.asset),
+            (None, false) => format!("Listed: {}", self.asset),
+        };
+
+        format!(
+            "{}
+List state: {}
+Price: {}
+Expires in: {}
+Private taker: {}
+Maker broker: {}{}",
+            header,
+            self.list_state,
+            self.currency.format(self.amount),
+            self.expire_in_sec
+                .map(|seconds| format_duration(seconds as i64))
+                .unwrap_or_else(|| "None".to_string()),
+            option_formatter(&self.private_taker),
+            option_formatter(&self.maker_broker),
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for MarketDelisting {
+    fn custom_format(&self) -> String {
+        format!(
+            "Delisted {} asset: {}
+List state closed: {}{}",
+            self.standard,
+            self.asset,
+            self.list_state,
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for MarketPurchase {
+    fn custom_format(&self) -> String {
+        format!(
+            "Bought {} asset: {}
+Seller: {}
+Price: {} (max {}), plus fees and royalties{}",
+            self.standard,
+            self.asset,
+            self.seller,
+            self.currency.format(self.amount),
+            self.currency.format(self.max_amount),
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for MarketBid {
+    fn custom_format(&self) -> String {
+        let header = if self.edited {
+            "Bid edited"
+        } else {
+            "Bid placed"
+        };
+        let state = match &self.state {
+            Some(state) => format!("\n\n{}", state.custom_format()),
+            None => "\n\nBid state not returned by the RPC yet".to_string(),
+        };
+
+        format!(
+            "{}: {}
+Price: {} x {}
+Expires in: {}
+Margin: {}{}{}",
+            header,
+            self.bid_state,
+            Currency::SOL.format(self.amount),
+            self.quantity,
+            self.expire_in_sec
+                .map(|seconds| format_duration(seconds as i64))
+                .unwrap_or_else(|| "None".to_string()),
+            option_formatter(&self.margin),
+            format_signatures(&self.signatures),
+            state
+        )
+    }
+}
+
+impl CustomFormat for MarketBidCancel {
+    fn custom_format(&self) -> String {
+        format!(
+            "Bid cancelled: {}
+Refunded: {}{}",
+            self.bid_state,
+            Currency::SOL.format(self.refund),
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for MarketBidTake {
+    fn custom_format(&self) -> String {
+        format!(
+            "Sold {} asset: {}
+Bid: {}
+Bidder: {}
+Price: {} (min {}), less fees and royalties{}",
+            self.standard,
+            self.asset,
+            self.bid_state,
+            self.bidder,
+            Currency::SOL.format(self.amount),
+            Currency::SOL.format(self.min_amount),
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+/// Expiry date, flagged when it has passed.
+fn format_expiry(expiry: i64, now: i64) -> String {
+    if expiry <= now {
+        format!("{} (expired)", format_date(expiry))
+    } else {
+        format_date(expiry)
+    }
+}
+
+impl CustomFormat for MarketListings {
+    fn custom_format(&self) -> String {
+        if self.listings.is_empty() {
+            return "No listings found.".to_string();
+        }
+
+        let now = chrono::Utc::now().timestamp();
+
+        let mut output = format!(
+            "{:<44}  {:<44}  {:<44}  {:>14}  {}\n",
+            "Address", "Asset", "Owner", "Price", "Expiry"
+        );
+
+        for entry in &self.listings {
+            let listing = &entry.listing;
+
+            output.push_str(&format!(
+                "{:<44}  {:<44}  {:<44}  {:>14}  {}\n",
+                entry.address,
+                listing.asset_id,
+                listing.owner,
+                entry.currency.format(listing.amount),
+                format_expiry(listing.expiry, now)
+            ));
+        }
+
+        output.push_str(&format!("{} listings", self.listings.len()));
+
+        output
+    }
+}
+
+impl CustomFormat for MarketBids {
+    fn custom_format(&self) -> String {
+        if self.bids.is_empty() {
+            return "No bids found.".to_string();
+        }
+
+        let now = chrono::Utc::now().timestamp();
+
+        let mut output = format!(
+            "{:<44}  {:<44}  {:<9}  {:<44}  {:>14}  {:>9}  {:<6}  {}\n",
+            "Address", "Owner", "Target", "Target ID", "Price", "Filled", "Margin", "Expiry"
+        );
+
+        for entry in &self.bids {
+            let bid = &entry.bid;
+            let target = match (bid.target, bid.field) {
+                (Target::AssetId, _) => "Mint",
+                (Target::Whitelist, Some(_)) => "Trait",
+                (Target::Whitelist, None) => "Whitelist",
+            };
+
+            output.push_str(&format!(
+                "{:<44}  {:<44}  {:<9}  {:<44}  {:>14}  {:>9}  {:<6}  {}\n",
+                entry.address,
+                bid.owner,
+                target,
+                bid.target_id,
+                entry.currency.format(bid.amount),
+                format!("{}/{}", bid.filled_quantity, bid.quantity),
+                if bid.margin.is_some() { "yes" } else { "no" },
+                format_expiry(bid.expiry, now)
+            ));
+        }
+
+        output.push_str(&format!("{} bids", self.bids.len()));
+
+        output
+    }
+}
+
+impl CustomFormat for CleanupPlan {
+    fn custom_format(&self) -> String {
+        if self.orders.is_empty() {
+            return "No expired listings or bids found.".to_string();
+        }
+
+        let mut output = format!(
+            "{:<7}  {:<44}  {:<44}  {:>14}  {:>14}  {}\n",
+            "Type", "Address", "Owner", "Rent", "Escrowed", "Expired"
+        );
+
+        for order in &self.orders {
+            output.push_str(&format!(
+                "{:<7}  {:<44}  {:<44}  {:>14}  {:>14}  {}\n",
+                match order.kind {
+                    ExpiredOrderKind::Listing(_) => "Listing",
+                    ExpiredOrderKind::Bid(_) => "Bid",
+                },
+                order.address,
+                order.owner(),
+                Currency::SOL.format(order.rent),
+                Currency::SOL.format(order.escrowed),
+                format_date(order.expiry())
+            ));
+        }
+
+        output.push_str(&format!(
+            "{} expired orders, {} rent and {} escrowed to reclaim",
+            self.orders.len(),
+            Currency::SOL.format(self.rent()),
+            Currency::SOL.format(self.escrowed())
+        ));
+
+        output
+    }
+}
+
+impl CustomFormat for MarketCleanup {
+    fn custom_format(&self) -> String {
+        if self.plan.orders.is_empty() {
+            return self.plan.custom_format();
+        }
+
+        if !self.applied {
+            return format!("{}\nNothing closed.", self.plan.custom_format());
+        }
+
+        let (rent, escrowed) = self
+            .plan
+            .orders
+            .iter()
+            .filter(|order| self.closed.contains(&order.address))
+            .fold((0, 0), |(rent, escrowed), order| {
+                (rent + order.rent, escrowed + order.escrowed)
+            });
+
+        let mut output = format!(
+            "Closed {} of {} expired orders in {} transactions
+Reclaimed: {} rent, {} escrowed{}",
+            self.closed.len(),
+            self.plan.orders.len(),
+            self.signatures.len(),
+            Currency::SOL.format(rent),
+            Currency::SOL.format(escrowed),
+            format_signatures(&self.signatures)
+        );
+
+        if !self.failed.is_empty() {
+            output.push_str("\nLeft open, run cleanup again to retry:");
+            for (address, error) in &self.failed {
+                output.push_str(&format!("\n  {}: {}", address, error));
+            }
+        }
+
+        output
+    }
+}
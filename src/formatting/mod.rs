@@ -3,13 +3,18 @@ use std::{fmt::Display, fs::File, io::Write};
 use {
     anyhow::Result,
     chrono::DateTime,
-    solana_sdk::{account::Account, pubkey::Pubkey},
+    solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature},
 };
 
 pub mod amm;
+pub mod decode;
+pub mod escrow;
+pub mod lookup_table;
 pub mod marketplace;
+pub mod nonce;
 pub mod price_lock;
 pub mod raydium;
+pub mod results;
 pub mod wallet;
 pub mod whitelist;
 
@@ -28,12 +33,44 @@ pub fn pad_label(label: &str, max_length: usize) -> String {
     format!("{:<width$}", label, width = max_length)
 }
 
+/// One line per transaction signature, with a leading newline so it can be appended to a message.
+pub fn format_signatures(signatures: &[Signature]) -> String {
+    signatures
+        .iter()
+        .map(|signature| format!("\nSignature: {}", signature))
+        .collect()
+}
+
 pub fn format_timestamp(timestamp: i64) -> String {
     DateTime::from_timestamp(timestamp, 0)
         .unwrap_or_default()
         .to_rfc3339()
 }
 
+/// Rough length of a span of seconds, e.g. `3d 4h` or `12m`.
+pub fn format_duration(seconds: i64) -> String {
+    let seconds = seconds.unsigned_abs();
+    let (days, hours, minutes) = (
+        seconds / 86_400,
+        seconds % 86_400 / 3_600,
+        seconds % 3_600 / 60,
+    );
+
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+/// Date part of a timestamp, for compact tables.
+pub fn format_date(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
 pub struct AccountEntry {
     pub address: Pubkey,
     pub account: Account,
@@ -0,0 +1,59 @@
+use console::Style;
+
+use crate::{
+    commands::{CreatedNonce, NonceAccount, SubmittedTransactions},
+    formatting::{format_signatures, pad_label},
+};
+
+use super::CustomFormat;
+
+const LABEL_LENGTH: usize = 22;
+
+impl CustomFormat for NonceAccount {
+    fn custom_format(&self) -> String {
+        // Use the default text color but set this up for future use.
+        let color = Style::new();
+
+        format!(
+            "{}
+{}: {}
+{}: {}
+{}: {}
+{}: {}
+{}: {}",
+            color.apply_to("NonceAccount----------"),
+            pad_label("address", LABEL_LENGTH),
+            color.apply_to(self.address),
+            pad_label("lamports", LABEL_LENGTH),
+            color.apply_to(self.lamports),
+            pad_label("authority", LABEL_LENGTH),
+            color.apply_to(self.authority),
+            pad_label("blockhash", LABEL_LENGTH),
+            color.apply_to(self.blockhash),
+            pad_label("lamports_per_signature", LABEL_LENGTH),
+            color.apply_to(self.lamports_per_signature),
+        )
+    }
+}
+
+impl CustomFormat for CreatedNonce {
+    fn custom_format(&self) -> String {
+        format!(
+            "Nonce account created: {}\nAuthority: {}{}",
+            self.address,
+            self.authority,
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for SubmittedTransactions {
+    fn custom_format(&self) -> String {
+        format!(
+            "Submitted {} transactions from {}{}",
+            self.signatures.len(),
+            self.path.display(),
+            format_signatures(&self.signatures)
+        )
+    }
+}
@@ -1,7 +1,19 @@
 use console::Style;
-use tensor_price_lock::accounts::{OrderNftReceipt, OrderState};
+use tensor_price_lock::{
+    accounts::{OrderNftReceipt, OrderState},
+    types::OrderType,
+};
 
-use crate::formatting::{format_timestamp, option_formatter, pad_label};
+use crate::{
+    commands::{
+        order_locked, ClosedPriceLockOrder, Currency, PriceLockOrder, PriceLockOrderDetails,
+        PriceLockOrders, Settlement,
+    },
+    formatting::{
+        format_date, format_duration, format_signatures, format_timestamp, option_formatter,
+        pad_label,
+    },
+};
 
 use super::CustomFormat;
 
@@ -150,3 +162,157 @@ impl CustomFormat for OrderNftReceipt {
         )
     }
 }
+
+/// Where an order stands: open to takers, locked by one, or expired.
+fn order_status(order: &OrderState, now: i64) -> &'static str {
+    if order_locked(order, now) {
+        "Locked"
+    } else if order.taker.is_some() {
+        "Lock ended"
+    } else if order.expiry <= now {
+        "Expired"
+    } else {
+        "Open"
+    }
+}
+
+fn order_type_name(order_type: OrderType) -> &'static str {
+    match order_type {
+        OrderType::Token => "Token",
+        OrderType::NFT => "NFT",
+    }
+}
+
+fn format_settlement(settlement: &Settlement, order_type: OrderType) -> String {
+    let exercise = match order_type {
+        OrderType::Token => "Taker sells an NFT for",
+        OrderType::NFT => "Taker buys an NFT for",
+    };
+
+    format!(
+        "Settlement:
+  Premium paid to lock: {} before the program's fees
+  {}: {}",
+        Currency::SOL.format(settlement.premium),
+        exercise,
+        Currency::SOL.format(settlement.price)
+    )
+}
+
+impl CustomFormat for PriceLockOrders {
+    fn custom_format(&self) -> String {
+        if self.orders.is_empty() {
+            return "No orders found.".to_string();
+        }
+
+        let now = chrono::Utc::now().timestamp();
+
+        let mut output = format!(
+            "{:<44}  {:<44}  {:<5}  {:>14}  {:>8}  {:>8}  {:<10}  {}\n",
+            "Address", "Maker", "Type", "Price", "APR", "Duration", "Status", "Expiry"
+        );
+
+        for entry in &self.orders {
+            let order = &entry.order;
+
+            output.push_str(&format!(
+                "{:<44}  {:<44}  {:<5}  {:>14}  {:>8}  {:>8}  {:<10}  {}\n",
+                entry.address,
+                order.maker,
+                order_type_name(order.order_type),
+                Currency::SOL.format(order.price),
+                format!("{:.2}%", order.apr_bps as f64 / 100.0),
+                format_duration(order.duration_sec as i64),
+                order_status(order, now),
+                format_date(order.expiry)
+            ));
+        }
+
+        output.push_str(&format!("{} orders", self.orders.len()));
+
+        output
+    }
+}
+
+impl CustomFormat for PriceLockOrderDetails {
+    fn custom_format(&self) -> String {
+        let now = chrono::Utc::now().timestamp();
+
+        let mut output = format!(
+            "Order: {}
+Status: {}
+Vault: {}
+
+{}
+
+{}
+
+Receipts:",
+            self.address,
+            order_status(&self.order, now),
+            self.vault,
+            self.order.custom_format(),
+            format_settlement(&self.settlement, self.order.order_type)
+        );
+
+        if self.receipts.is_empty() {
+            output.push_str("\nNo NFTs deposited.");
+        }
+
+        for entry in &self.receipts {
+            output.push_str(&format!(
+                "\n  {}  {}  (receipt {})",
+                entry.receipt.asset,
+                entry.standard.as_deref().unwrap_or("Not found"),
+                entry.address
+            ));
+        }
+
+        output
+    }
+}
+
+impl CustomFormat for PriceLockOrder {
+    fn custom_format(&self) -> String {
+        let header = if self.edited {
+            "Order edited"
+        } else {
+            "Order created"
+        };
+
+        format!(
+            "{}: {}
+Vault: {}
+Type: {}
+Price: {}
+APR: {:.2}%
+Lock duration: {}
+Expires in: {}
+{}{}",
+            header,
+            self.order_state,
+            self.vault,
+            order_type_name(self.order_type),
+            Currency::SOL.format(self.price),
+            self.apr_bps as f64 / 100.0,
+            format_duration(self.duration_sec as i64),
+            self.expire_in_sec
+                .map(|seconds| format_duration(seconds as i64))
+                .unwrap_or_else(|| "None".to_string()),
+            format_settlement(&self.settlement, self.order_type),
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for ClosedPriceLockOrder {
+    fn custom_format(&self) -> String {
+        format!(
+            "Order closed: {}
+Returned from the vault: {}{}",
+            self.order_state,
+            Currency::SOL.format(self.vault_balance),
+            format_signatures(&self.signatures)
+        )
+    }
+}
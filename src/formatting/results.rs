@@ -0,0 +1,682 @@
+use solana_sdk::{native_token::lamports_to_sol, pubkey::Pubkey};
+use tensor_amm::types::CurveType;
+
+use crate::commands::{
+    AnchorDiscriminator, AppliedPlan, BuiltMerkleTree, ClosedPool, ComparisonReport, CreatedPool,
+    CreatedWhitelist, DownloadedAccount, EditedPool, EigenUpdate, ErrorLookup, FundedShards,
+    GeneratedShards, IdLookup, InitializedMintProofs, MerkleProof, MigrationPlan, MintProofDetails,
+    MintProofPlan, PoolDirection, PoolEdit, PoolList, PoolNftTransfer, PoolPlan, PoolQuote,
+    PoolReport, PoolSolTransfer, ShardBalances, UpdatedWhitelist, WhitelistMigration,
+};
+
+use super::{format_date, format_duration, format_signatures, option_formatter, CustomFormat};
+
+impl CustomFormat for DownloadedAccount {
+    fn custom_format(&self) -> String {
+        format!("Account data saved to {}", self.path.display())
+    }
+}
+
+impl CustomFormat for ErrorLookup {
+    fn custom_format(&self) -> String {
+        match (&self.error_type, &self.name) {
+            (Some(error_type), Some(name)) => format!(
+                "{}:\nError Code: {}\nError Type: {}",
+                error_type, self.code, name
+            ),
+            _ => format!("Unknown error code: {}", self.code),
+        }
+    }
+}
+
+impl CustomFormat for IdLookup {
+    fn custom_format(&self) -> String {
+        match self {
+            IdLookup::List(ids) => format!(
+                "Available IDs:{}",
+                ids.iter()
+                    .map(|id| format!("\n  {}", id))
+                    .collect::<String>()
+            ),
+            IdLookup::Pubkey(pubkey) => pubkey.to_string(),
+        }
+    }
+}
+
+impl CustomFormat for AnchorDiscriminator {
+    fn custom_format(&self) -> String {
+        format!(
+            "Discriminator (bytes):   [{}]\nDiscriminator (hex)  :   0x{}",
+            self.0
+                .iter()
+                .map(|b| format!("{}", b))
+                .collect::<Vec<_>>()
+                .join(", "),
+            hex::encode(self.0)
+        )
+    }
+}
+
+impl CustomFormat for EigenUpdate {
+    fn custom_format(&self) -> String {
+        format!(
+            "Installed Eigen binary for {}/{}\nEigen update completed successfully. Installed to: {:?}",
+            self.os, self.arch, self.path
+        )
+    }
+}
+
+impl CustomFormat for GeneratedShards {
+    fn custom_format(&self) -> String {
+        format!("Wrote {} fee shards to {}", self.count, self.path.display())
+    }
+}
+
+impl CustomFormat for FundedShards {
+    fn custom_format(&self) -> String {
+        if self.funded.is_empty() {
+            return "All shards are already funded.".to_string();
+        }
+
+        format!(
+            "Funded {} shards in {} transactions.{}",
+            self.funded.len(),
+            self.signatures.len(),
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for ShardBalances {
+    fn custom_format(&self) -> String {
+        let mut output: String = self
+            .balances
+            .iter()
+            .map(|shard| {
+                let status = if shard.lamports >= self.rent_exempt_lamports {
+                    "✓"
+                } else {
+                    "✗"
+                };
+                format!("{} {} {}\n", shard.address, shard.lamports, status)
+            })
+            .collect();
+
+        let funded = self.funded_count();
+
+        if funded == self.balances.len() {
+            output.push_str("All shards fully funded!");
+        } else {
+            output.push_str(&format!(
+                "{}/{} shards fully funded.",
+                funded,
+                self.balances.len()
+            ));
+        }
+
+        output
+    }
+}
+
+impl CustomFormat for CreatedPool {
+    fn custom_format(&self) -> String {
+        let args = &self.config;
+
+        format!(
+            "Pool created: {}\nPool id: {}\n{}\nCurrency: {}\nShared escrow: {}\nCosigner: {}\nMaker broker: {}\nMax taker sell count: {}\nExpires in: {}{}",
+            self.pool,
+            hex::encode(args.pool_id),
+            args.config.custom_format().trim_end(),
+            args.currency
+                .map(|currency| currency.to_string())
+                .unwrap_or_else(|| "SOL".to_string()),
+            option_formatter(&self.shared_escrow),
+            option_formatter(&args.cosigner),
+            option_formatter(&args.maker_broker),
+            option_formatter(&args.max_taker_sell_count),
+            args.expire_in_sec
+                .map(|seconds| format_duration(seconds as i64))
+                .unwrap_or_else(|| "None".to_string()),
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for ClosedPool {
+    fn custom_format(&self) -> String {
+        let escrow_note = self
+            .shared_escrow
+            .map(|escrow| {
+                let balance = self
+                    .escrow_available
+                    .map(|available| format!(", it still holds {} SOL", lamports_to_sol(available)))
+                    .unwrap_or_default();
+
+                format!(
+                    "\nThe pool's SOL stays in shared escrow {}{}. Withdraw it with:\n  eigen escrow withdraw {} <AMOUNT>",
+                    escrow, balance, escrow
+                )
+            })
+            .unwrap_or_default();
+
+        format!(
+            "Pool closed: {}\nReclaimed {} SOL of rent to {}{}{}",
+            self.pool,
+            lamports_to_sol(self.rent_reclaimed),
+            self.rent_payer,
+            escrow_note,
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for PoolSolTransfer {
+    fn custom_format(&self) -> String {
+        let action = match self.direction {
+            PoolDirection::Deposit => "Deposited",
+            PoolDirection::Withdraw => "Withdrew",
+        };
+
+        format!(
+            "{} {} SOL for pool {}\nBalance before: {} SOL\nBalance after : {} SOL{}",
+            action,
+            lamports_to_sol(self.lamports),
+            self.pool,
+            lamports_to_sol(self.balance_before),
+            lamports_to_sol(self.balance_after),
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for PoolNftTransfer {
+    fn custom_format(&self) -> String {
+        let action = match self.direction {
+            PoolDirection::Deposit => "Deposited",
+            PoolDirection::Withdraw => "Withdrew",
+        };
+
+        format!(
+            "{} {} NFTs for pool {} in {} transactions{}{}",
+            action,
+            self.assets.len(),
+            self.pool,
+            self.signatures.len(),
+            self.assets
+                .iter()
+                .map(|asset| format!("\n  {} ({})", asset.mint, asset.standard))
+                .collect::<String>(),
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for PoolList {
+    fn custom_format(&self) -> String {
+        if self.pools.is_empty() {
+            return "No pools found.".to_string();
+        }
+
+        let now = chrono::Utc::now().timestamp();
+
+        let mut output = format!(
+            "{:<44}  {:<5}  {:<11}  {:>14}  {:>14}  {:>5}  {}\n",
+            "Address", "Type", "Curve", "Price (SOL)", "Amount (SOL)", "NFTs", "Expiry"
+        );
+
+        for entry in &self.pools {
+            let pool = &entry.pool;
+            let expiry = if pool.expiry <= now {
+                format!("{} (expired)", format_date(pool.expiry))
+            } else {
+                format_date(pool.expiry)
+            };
+
+            output.push_str(&format!(
+                "{:<44}  {:<5}  {:<11}  {:>14}  {:>14}  {:>5}  {}\n",
+                entry.address,
+                pool.config.pool_type,
+                pool.config.curve_type,
+                option_formatter(&entry.price.map(lamports_to_sol)),
+                lamports_to_sol(pool.amount),
+                pool.nfts_held,
+                expiry
+            ));
+        }
+
+        output.push_str(&format!("{} pools", self.pools.len()));
+
+        output
+    }
+}
+
+impl CustomFormat for PoolQuote {
+    fn custom_format(&self) -> String {
+        let config = &self.pool.config;
+
+        let delta = match config.curve_type {
+            CurveType::Linear => format!("{} SOL", lamports_to_sol(config.delta)),
+            CurveType::Exponential => format!("{} bps", config.delta),
+        };
+
+        let mut output = format!(
+            "Pool: {}\nType: {}, Curve: {}\nStarting price: {} SOL, Delta: {}, Price offset: {}\nMM fee: {}, Royalties: {} bps at {}%\n",
+            self.address
+                .map(|address| address.to_string())
+                .unwrap_or_else(|| "(local config)".to_string()),
+            config.pool_type,
+            config.curve_type,
+            lamports_to_sol(config.starting_price),
+            delta,
+            self.pool.price_offset,
+            config
+                .mm_fee_bps
+                .to_option()
+                .map(|bps| format!("{} bps", bps))
+                .unwrap_or_else(|| "None".to_string()),
+            self.royalties.bps,
+            self.royalties.pct,
+        );
+
+        for (title, steps, stopped) in [
+            ("Buys (taker pays)", &self.buys, &self.buys_stopped),
+            ("Sells (taker receives)", &self.sells, &self.sells_stopped),
+        ] {
+            output.push_str(&format!("\n{}:\n", title));
+
+            if !steps.is_empty() {
+                output.push_str(&format!(
+                    "  {:>3}  {:>14}  {:>12}  {:>12}  {:>12}  {:>14}\n",
+                    "#", "Price (SOL)", "Taker fee", "Royalty", "MM fee", "Total (SOL)"
+                ));
+            }
+            for (i, step) in steps.iter().enumerate() {
+                output.push_str(&format!(
+                    "  {:>3}  {:>14}  {:>12}  {:>12}  {:>12}  {:>14}\n",
+                    i + 1,
+                    lamports_to_sol(step.price),
+                    lamports_to_sol(step.taker_fee),
+                    lamports_to_sol(step.royalty),
+                    lamports_to_sol(step.mm_fee),
+                    lamports_to_sol(step.total),
+                ));
+            }
+            if let Some(reason) = stopped {
+                output.push_str(&format!("  {}\n", reason));
+            }
+        }
+
+        output.trim_end().to_string()
+    }
+}
+
+impl CustomFormat for PoolReport {
+    fn custom_format(&self) -> String {
+        if self.rows.is_empty() {
+            return "No pools found.".to_string();
+        }
+
+        let mut output = format!(
+            "{:<44}  {:<5}  {:>6}  {:>6}  {:>14}  {:>14}  {:>14}  {:>8}  {}\n",
+            "Address",
+            "Type",
+            "Buys",
+            "Sells",
+            "MM profit",
+            "Amount (SOL)",
+            "Inventory",
+            "Drift",
+            "Expires"
+        );
+
+        for row in &self.rows {
+            let drift = row
+                .drift_pct
+                .map(|drift| format!("{:+.2}%", drift))
+                .unwrap_or_else(|| "-".to_string());
+            let expires = if row.expires_in <= 0 {
+                format!("expired {} ago", format_duration(row.expires_in))
+            } else {
+                format!("in {}", format_duration(row.expires_in))
+            };
+
+            output.push_str(&format!(
+                "{:<44}  {:<5}  {:>6}  {:>6}  {:>14}  {:>14}  {:>14}  {:>8}  {}\n",
+                row.address,
+                row.pool.config.pool_type,
+                row.buys,
+                row.sells,
+                lamports_to_sol(row.mm_profit),
+                lamports_to_sol(row.pool.amount),
+                lamports_to_sol(row.inventory_value),
+                drift,
+                expires
+            ));
+        }
+
+        output.push_str(&format!(
+            "\n{} pools, {} trades\nRealized MM profit: {} SOL\nSOL held          : {} SOL\nInventory value   : {} SOL",
+            self.rows.len(),
+            self.trades(),
+            lamports_to_sol(self.mm_profit()),
+            lamports_to_sol(self.amount()),
+            lamports_to_sol(self.inventory_value()),
+        ));
+
+        if let Some(path) = &self.csv {
+            output.push_str(&format!("\nReport saved to {}", path.display()));
+        }
+
+        output
+    }
+}
+
+impl CustomFormat for PoolPlan {
+    fn custom_format(&self) -> String {
+        if self.actions.is_empty() {
+            return format!("Nothing to do, {} pools up to date.", self.unchanged);
+        }
+
+        format!(
+            "Plan: {} actions, {} pools unchanged{}",
+            self.actions.len(),
+            self.unchanged,
+            self.actions
+                .iter()
+                .map(|action| format!("\n  {}", action.describe()))
+                .collect::<String>()
+        )
+    }
+}
+
+impl CustomFormat for AppliedPlan {
+    fn custom_format(&self) -> String {
+        if self.plan.actions.is_empty() {
+            self.plan.custom_format()
+        } else if self.applied {
+            format!(
+                "Applied {} actions in {} transactions{}",
+                self.plan.actions.len(),
+                self.signatures.len(),
+                format_signatures(&self.signatures)
+            )
+        } else {
+            format!("{}\nNothing applied.", self.plan.custom_format())
+        }
+    }
+}
+
+impl CustomFormat for PoolEdit {
+    fn custom_format(&self) -> String {
+        format!(
+            "Changes:{}",
+            self.changes
+                .iter()
+                .map(|change| format!("\n  {}", change))
+                .collect::<String>()
+        )
+    }
+}
+
+impl CustomFormat for EditedPool {
+    fn custom_format(&self) -> String {
+        match &self.changes {
+            Some(changes) => format!(
+                "Pool updated: {}{}{}",
+                self.pool,
+                changes
+                    .iter()
+                    .map(|change| format!("\n  {}", change))
+                    .collect::<String>(),
+                format_signatures(&self.signatures)
+            ),
+            None => format!("Pool {} left unchanged.", self.pool),
+        }
+    }
+}
+
+impl CustomFormat for CreatedWhitelist {
+    fn custom_format(&self) -> String {
+        format!(
+            "Whitelist created: {}\nNamespace: {}{}",
+            self.whitelist,
+            self.namespace,
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for UpdatedWhitelist {
+    fn custom_format(&self) -> String {
+        format!(
+            "Whitelist updated: {}{}",
+            self.whitelist,
+            format_signatures(&self.signatures)
+        )
+    }
+}
+
+impl CustomFormat for ComparisonReport {
+    fn custom_format(&self) -> String {
+        let mismatches = self.mismatches().count();
+
+        let summary = if self.is_clean() {
+            "All good! ✅ 😎".to_string()
+        } else {
+            format!(
+                "There are {} mismatches and {} missing v2s",
+                mismatches,
+                self.missing.len()
+            )
+        };
+
+        let mut output = format!(
+            "Fetched whitelists from: {}
+Found {} v1 whitelists on-chain
+Found {} v2 whitelists on-chain
+{} whitelists have no v2 on chain
+Of the {} whitelist v1s with a v2 on chain, {} have a mismatch
+{}",
+            self.cluster,
+            self.v1_count,
+            self.v2_count,
+            self.missing.len(),
+            self.results.len(),
+            mismatches,
+            summary
+        );
+
+        if let Some(previous) = &self.previous {
+            output.push_str(&format!(
+                "\nSince the report of {}: {} missing v2s ({:+}), {} mismatches ({:+})",
+                format_date(previous.generated_at),
+                self.missing.len(),
+                self.missing.len() as i64 - previous.missing as i64,
+                mismatches,
+                mismatches as i64 - previous.mismatched as i64
+            ));
+        }
+
+        if !self.report_files.is_empty() {
+            output.push_str("\nReport written to:");
+            for path in &self.report_files {
+                output.push_str(&format!("\n  {}", path.display()));
+            }
+        }
+
+        output
+    }
+}
+
+impl CustomFormat for MigrationPlan {
+    fn custom_format(&self) -> String {
+        let mut output = if self.actions.is_empty() {
+            format!(
+                "Nothing to migrate, {} whitelists up to date.",
+                self.unchanged
+            )
+        } else {
+            format!(
+                "Plan: {} actions, {} whitelists unchanged{}",
+                self.actions.len(),
+                self.unchanged,
+                self.actions
+                    .iter()
+                    .map(|action| format!("\n  {}", action.describe()))
+                    .collect::<String>()
+            )
+        };
+
+        if !self.skipped.is_empty() {
+            output.push_str(&format!("\nSkipped {} whitelists:", self.skipped.len()));
+            for (v1, reason) in &self.skipped {
+                output.push_str(&format!("\n  {}: {}", v1, reason));
+            }
+        }
+
+        output
+    }
+}
+
+impl CustomFormat for WhitelistMigration {
+    fn custom_format(&self) -> String {
+        if self.plan.actions.is_empty() {
+            return self.plan.custom_format();
+        }
+
+        if !self.applied {
+            return format!("{}\nNothing migrated.", self.plan.custom_format());
+        }
+
+        let mut output = format!(
+            "Migrated {} of {} whitelists in {} transactions{}",
+            self.migrated.len(),
+            self.plan.actions.len(),
+            self.signatures.len(),
+            format_signatures(&self.signatures)
+        );
+
+        if !self.failed.is_empty() {
+            output.push_str("\nNot migrated, run migrate again to retry:");
+            for (v2, error) in &self.failed {
+                output.push_str(&format!("\n  {}: {}", v2, error));
+            }
+        }
+
+        if let Some(report) = &self.report {
+            output.push_str(&format!("\n\n{}", report.custom_format()));
+        }
+
+        output
+    }
+}
+
+impl CustomFormat for BuiltMerkleTree {
+    fn custom_format(&self) -> String {
+        format!(
+            "Merkle root: {}\nBuilt from {} mints, proofs written to {}\nCondition: {{ \"mode\": \"MerkleTree\", \"value\": \"{}\" }}",
+            self.proofs.root,
+            self.proofs.proofs.len(),
+            self.path.display(),
+            self.proofs.root
+        )
+    }
+}
+
+impl CustomFormat for MerkleProof {
+    fn custom_format(&self) -> String {
+        format!(
+            "Mint: {}\nMerkle root: {}\nProof ({} nodes):{}",
+            self.mint,
+            self.root,
+            self.proof.len(),
+            self.proof
+                .iter()
+                .map(|node| format!("\n  {}", Pubkey::new_from_array(*node)))
+                .collect::<String>()
+        )
+    }
+}
+
+impl CustomFormat for MintProofPlan {
+    fn custom_format(&self) -> String {
+        if self.actions.is_empty() {
+            return format!(
+                "Nothing to send, {} mint proofs for whitelist {} up to date.",
+                self.unchanged, self.whitelist
+            );
+        }
+
+        format!(
+            "Whitelist: {}\nMerkle root: {}\nPlan: {} mint proofs to send, {} up to date{}",
+            self.whitelist,
+            self.root,
+            self.actions.len(),
+            self.unchanged,
+            self.actions
+                .iter()
+                .map(|action| format!(
+                    "\n  {} {} for {} ({} nodes)",
+                    if action.exists { "update" } else { "create" },
+                    action.address,
+                    action.mint,
+                    action.proof.len()
+                ))
+                .collect::<String>()
+        )
+    }
+}
+
+impl CustomFormat for InitializedMintProofs {
+    fn custom_format(&self) -> String {
+        if self.plan.actions.is_empty() {
+            return self.plan.custom_format();
+        }
+
+        if !self.applied {
+            return format!("{}\nNothing sent.", self.plan.custom_format());
+        }
+
+        let mut output = format!(
+            "Sent {} of {} mint proofs in {} transactions{}",
+            self.initialized.len(),
+            self.plan.actions.len(),
+            self.signatures.len(),
+            format_signatures(&self.signatures)
+        );
+
+        if !self.failed.is_empty() {
+            output.push_str("\nNot sent, run init again to retry:");
+            for (mint, error) in &self.failed {
+                output.push_str(&format!("\n  {}: {}", mint, error));
+            }
+        }
+
+        output
+    }
+}
+
+impl CustomFormat for MintProofDetails {
+    fn custom_format(&self) -> String {
+        let status = match (&self.root, self.valid) {
+            (None, _) => "Whitelist has no merkle tree condition".to_string(),
+            (Some(root), true) => format!("Valid for merkle root {}", root),
+            (Some(root), false) => format!("Invalid for merkle root {}", root),
+        };
+
+        format!(
+            "Mint proof: {}\nMint: {}\nWhitelist: {}\nPayer: {}\nCreation slot: {}\nStatus: {}\nProof ({} nodes):{}",
+            self.address,
+            self.mint,
+            self.whitelist,
+            self.payer,
+            self.creation_slot,
+            status,
+            self.proof.len(),
+            self.proof
+                .iter()
+                .map(|node| format!("\n  {}", Pubkey::new_from_array(*node)))
+                .collect::<String>()
+        )
+    }
+}
@@ -186,18 +186,21 @@ impl CustomFormat for ComparisonResult {
             "{}
 {}: {}
 {}: {}
-{}: {}",
+{}: {}{}",
             color.apply_to("Whitelist Comparison----"),
             pad_label("Whitelist V1", LABEL_LENGTH),
             color.apply_to(self.whitelist_v1),
             pad_label("Whitelist V2", LABEL_LENGTH),
             color.apply_to(self.whitelist_v2),
             pad_label("Whitelists match", LABEL_LENGTH),
-            color.apply_to(if self.mismatch.is_none() {
-                check
-            } else {
-                cross
-            }),
+            color.apply_to(if self.is_match() { check } else { cross }),
+            self.mismatches
+                .iter()
+                .map(|detail| format!(
+                    "\n  {:?}: expected {}, found {}",
+                    detail.kind, detail.expected, detail.actual
+                ))
+                .collect::<String>()
         )
     }
 }
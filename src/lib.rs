@@ -4,8 +4,11 @@ pub mod args;
 pub mod commands;
 pub mod discriminators;
 pub mod formatting;
+pub mod prompt;
+pub mod rpc;
 pub mod setup;
 pub mod spinner;
+pub mod transactions;
 pub mod types;
 
 pub struct Shard {
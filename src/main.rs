@@ -3,91 +3,748 @@ use clap::Parser;
 
 use tensor_eigen::{
     args::{
-        Args, Commands, DeriveSubcommands, EigenSubcommands, FeesSubcommands, PoolSubcommands,
-        WhitelistSubcommands,
+        AltSubcommands, Args, BidTermsArgs, Commands, DeriveSubcommands, EigenSubcommands,
+        EscrowSubcommands, FeesSubcommands, ListingTermsArgs, MarketSubcommands, NonceSubcommands,
+        OrderTermsArgs, PoolSubcommands, PriceLockSubcommands, WhitelistMerkleSubcommands,
+        WhitelistMintProofSubcommands, WhitelistSubcommands,
     },
     commands::{
-        create_pool, create_whitelist_v2, edit_pool, fund_shards, generate_fee_shards,
+        advance_nonce, buy_listing, cancel_bid, close_escrow, close_lookup_table, close_pool,
+        close_price_lock_order, create_escrow, create_lookup_table, create_nonce, create_pool,
+        create_price_lock_order, create_whitelist_v2, deactivate_lookup_table, delist_nft,
+        deposit_escrow, deposit_nft, deposit_sol, edit_bid, edit_listing, edit_pool,
+        edit_price_lock_order, extend_lookup_table, fund_shards, generate_fee_shards,
         get_shard_balances, handle_anchor_discriminator, handle_compare, handle_decode,
-        handle_download, handle_error, handle_ids, update_eigen, update_whitelist_v2,
+        handle_download, handle_error, handle_ids, handle_init_mint_proof, handle_market_bids,
+        handle_market_listings, handle_merkle_build, handle_merkle_proof, handle_migrate,
+        handle_pool_apply, handle_pool_list, handle_pool_quote, handle_pool_report,
+        handle_price_lock_orders, handle_show_mint_proof, list_escrows, list_nft, market_cleanup,
+        place_bid, show_escrow, show_lookup_table, show_nonce, show_price_lock_order,
+        submit_nonce_transactions, take_bid, update_eigen, update_whitelist_v2, withdraw_escrow,
+        withdraw_nft, withdraw_sol, AltCloseParams, AltCreateParams, AltDeactivateParams,
+        AltExtendParams, AltShowParams, BidFilters, BidTarget, BidTerms, BuyTerms, ClosePoolParams,
         CompareParams, CreatePoolParams, CreateWhitelistV2Params, DecodeParams, DownloadParams,
-        EditPoolParams, ErrorParams, FeeParams, UpdateWhitelistV2Params,
+        EditPoolFlags, EditPoolParams, ErrorParams, EscrowCloseParams, EscrowCreateParams,
+        EscrowListParams, EscrowShowParams, EscrowSolParams, FeeParams, InitMintProofParams,
+        ListingFilters, ListingTerms, MarketBidParams, MarketBidsParams, MarketBuyParams,
+        MarketCancelBidParams, MarketCleanupParams, MarketDelistParams, MarketEditBidParams,
+        MarketEditListingParams, MarketListParams, MarketListingsParams, MarketTakeBidParams,
+        MerkleBuildParams, MerkleProofParams, MigrateParams, NonceAdvanceParams, NonceCreateParams,
+        NonceShowParams, NonceSubmitParams, OrderFilters, OrderTerms, PoolApplyParams,
+        PoolConfigFlags, PoolFilters, PoolListParams, PoolNftParams, PoolQuoteParams,
+        PoolReportParams, PoolSolParams, PriceLockCloseParams, PriceLockCreateParams,
+        PriceLockEditParams, PriceLockOrdersParams, PriceLockShowParams, ShowMintProofParams,
+        TakeBidTerms, UpdateWhitelistV2Params,
     },
+    formatting::CustomFormat,
+    prompt::confirm,
 };
 
+/// Prints the result of a command. Commands return structured results so they can also be
+/// used as a library, leaving the output to the binary.
+fn render<T: CustomFormat>(output: Result<T>) -> Result<()> {
+    println!("{}", output?.custom_format());
+
+    Ok(())
+}
+
+/// Confirms a plan before a command applies it, by showing it and asking on the terminal. With
+/// `yes` it goes ahead without showing the plan, the command's result reports what was done.
+fn confirm_plan<P: CustomFormat>(yes: bool, prompt: &str) -> impl FnOnce(&P) -> Result<bool> + '_ {
+    move |plan| {
+        if yes {
+            return Ok(true);
+        }
+
+        println!("{}\n", plan.custom_format());
+
+        confirm(prompt)
+    }
+}
+
+fn listing_terms(args: ListingTermsArgs) -> ListingTerms {
+    ListingTerms {
+        currency: args.currency,
+        expire: args.expire,
+        private_taker: args.private_taker,
+        maker_broker: args.maker_broker,
+        clear_private_taker: false,
+        clear_maker_broker: false,
+    }
+}
+
+fn bid_terms(args: BidTermsArgs) -> BidTerms {
+    BidTerms {
+        quantity: args.quantity,
+        expire: args.expire,
+        private_taker: args.private_taker,
+        maker_broker: args.maker_broker,
+        margin: args.margin,
+    }
+}
+
+fn order_terms(args: OrderTermsArgs) -> OrderTerms {
+    OrderTerms {
+        price: args.price,
+        apr_bps: args.apr_bps,
+        duration: args.duration,
+        expire: args.expire,
+        margin: args.margin,
+        maker_broker: args.maker_broker,
+    }
+}
+
 fn main() -> Result<()> {
     solana_logger::setup_with_default("solana=info");
 
     let args = Args::parse();
+    let profile = args.profile;
 
     match args.command {
-        Commands::Decode(args) => handle_decode(DecodeParams {
+        Commands::Alt(subcommand) => match subcommand {
+            AltSubcommands::Close(args) => render(close_lookup_table(AltCloseParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                lookup_table: args.lookup_table,
+                recipient: args.recipient,
+            })),
+            AltSubcommands::Create(args) => render(create_lookup_table(AltCreateParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                addresses: args.addresses,
+                file: args.file,
+            })),
+            AltSubcommands::Deactivate(args) => {
+                render(deactivate_lookup_table(AltDeactivateParams {
+                    keypair_path: args.write_options.keypair_path,
+                    rpc_url: args.write_options.rpc_url,
+                    profile: profile.clone(),
+                    nonce: args.write_options.nonce,
+                    nonce_authority: args.write_options.nonce_authority,
+                    sign_only: args.write_options.sign_only,
+                    lookup_table: args.lookup_table,
+                }))
+            }
+            AltSubcommands::Extend(args) => render(extend_lookup_table(AltExtendParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                lookup_table: args.lookup_table,
+                addresses: args.addresses,
+                file: args.file,
+            })),
+            AltSubcommands::Show(args) => render(show_lookup_table(AltShowParams {
+                rpc_url: args.read_options.rpc_url,
+                profile: profile.clone(),
+                lookup_table: args.lookup_table,
+            })),
+        },
+        Commands::Decode(args) => render(handle_decode(DecodeParams {
             rpc_url: args.read_options.rpc_url,
+            profile: profile.clone(),
             address: args.address,
             raw: args.raw,
-        }),
+        })),
         Commands::Derive(subcommand) => match subcommand {
-            DeriveSubcommands::AnchorDisc(args) => handle_anchor_discriminator(args),
+            DeriveSubcommands::AnchorDisc(args) => render(handle_anchor_discriminator(args)),
         },
-        Commands::Download(args) => handle_download(DownloadParams {
+        Commands::Download(args) => render(handle_download(DownloadParams {
             rpc_url: args.read_options.rpc_url,
+            profile: profile.clone(),
             address: args.address,
             output_dir: args.output_dir,
-        }),
-        Commands::Error(args) => handle_error(ErrorParams {
+        })),
+        Commands::Error(args) => render(handle_error(ErrorParams {
             error_code: args.error_code,
-        }),
+        })),
+        Commands::Escrow(subcommand) => match subcommand {
+            EscrowSubcommands::Close(args) => render(close_escrow(EscrowCloseParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                escrow: args.escrow,
+            })),
+            EscrowSubcommands::Create(args) => render(create_escrow(EscrowCreateParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                nr: args.nr,
+                name: args.name,
+            })),
+            EscrowSubcommands::Deposit(args) => render(deposit_escrow(EscrowSolParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                escrow: args.escrow,
+                amount: args.amount,
+                lamports: args.lamports,
+            })),
+            EscrowSubcommands::List(args) => render(list_escrows(EscrowListParams {
+                rpc_url: args.read_options.rpc_url,
+                profile: profile.clone(),
+                owner: args.owner,
+            })),
+            EscrowSubcommands::Show(args) => render(show_escrow(EscrowShowParams {
+                rpc_url: args.read_options.rpc_url,
+                profile: profile.clone(),
+                escrow: args.escrow,
+            })),
+            EscrowSubcommands::Withdraw(args) => render(withdraw_escrow(EscrowSolParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                escrow: args.escrow,
+                amount: args.amount,
+                lamports: args.lamports,
+            })),
+        },
         Commands::Eigen(subcommand) => match subcommand {
-            EigenSubcommands::Update => update_eigen(),
+            EigenSubcommands::Update => render(update_eigen()),
         },
         Commands::Fees(subcommand) => match subcommand {
-            FeesSubcommands::Shards => generate_fee_shards(),
-            FeesSubcommands::Fund(args) => fund_shards(FeeParams {
+            FeesSubcommands::Shards => render(generate_fee_shards()),
+            FeesSubcommands::Fund(args) => render(fund_shards(FeeParams {
                 keypair_path: args.write_options.keypair_path,
                 rpc_url: args.write_options.rpc_url,
-            }),
-            FeesSubcommands::Balances(args) => get_shard_balances(FeeParams {
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                lookup_tables: args.lookup_tables,
+            })),
+            FeesSubcommands::Balances(args) => render(get_shard_balances(FeeParams {
                 keypair_path: None,
                 rpc_url: args.read_options.rpc_url,
-            }),
+                profile: profile.clone(),
+                nonce: None,
+                nonce_authority: None,
+                sign_only: None,
+                lookup_tables: vec![],
+            })),
+        },
+        Commands::Ids(args) => render(handle_ids(args.id, args.list)),
+        Commands::Market(subcommand) => match subcommand {
+            MarketSubcommands::Bid(args) => render(place_bid(MarketBidParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                target: match (args.mint, args.whitelist) {
+                    (Some(mint), _) => BidTarget::Mint(mint),
+                    (None, whitelist) => BidTarget::Whitelist {
+                        whitelist: whitelist.expect("clap requires a mint or whitelist"),
+                        trait_id: args.trait_id,
+                    },
+                },
+                price: args.price,
+                terms: bid_terms(args.terms),
+                bid_id: args.bid_id,
+                cosigner: args.cosigner,
+            })),
+            MarketSubcommands::Bids(args) => render(handle_market_bids(MarketBidsParams {
+                rpc_url: args.read_options.rpc_url,
+                profile: profile.clone(),
+                filters: BidFilters {
+                    owner: args.owner,
+                    target: args.target,
+                    target_id: args.target_id,
+                    currency: args.currency,
+                    margin: args.margin,
+                    active: args.active,
+                },
+            })),
+            MarketSubcommands::Buy(args) => render(buy_listing(MarketBuyParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                mint: args.mint,
+                terms: BuyTerms {
+                    max_price: args.max_price,
+                    royalty_pct: args.royalty_pct,
+                    taker_broker: args.taker_broker,
+                },
+                cosigner: args.cosigner,
+            })),
+            MarketSubcommands::CancelBid(args) => render(cancel_bid(MarketCancelBidParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                bid_state: args.bid_state,
+            })),
+            MarketSubcommands::Cleanup(args) => render(market_cleanup(
+                MarketCleanupParams {
+                    keypair_path: args.write_options.keypair_path,
+                    rpc_url: args.write_options.rpc_url,
+                    profile: profile.clone(),
+                    nonce: args.write_options.nonce,
+                    nonce_authority: args.write_options.nonce_authority,
+                    sign_only: args.write_options.sign_only,
+                    cosigned_by: args.cosigned_by,
+                    dry_run: args.dry_run,
+                },
+                confirm_plan(args.yes, "Close these orders?"),
+            )),
+            MarketSubcommands::Delist(args) => render(delist_nft(MarketDelistParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                mint: args.mint,
+            })),
+            MarketSubcommands::EditBid(args) => render(edit_bid(MarketEditBidParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                bid_state: args.bid_state,
+                price: args.price,
+                terms: bid_terms(args.terms),
+                cosigner: args.cosigner,
+            })),
+            MarketSubcommands::EditListing(args) => render(edit_listing(MarketEditListingParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                mint: args.mint,
+                price: args.price,
+                terms: ListingTerms {
+                    clear_private_taker: args.clear_private_taker,
+                    clear_maker_broker: args.clear_maker_broker,
+                    ..listing_terms(args.terms)
+                },
+            })),
+            MarketSubcommands::List(args) => render(list_nft(MarketListParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                mint: args.mint,
+                price: args.price,
+                terms: listing_terms(args.terms),
+                cosigner: args.cosigner,
+            })),
+            MarketSubcommands::Listings(args) => {
+                render(handle_market_listings(MarketListingsParams {
+                    rpc_url: args.read_options.rpc_url,
+                    profile: profile.clone(),
+                    filters: ListingFilters {
+                        owner: args.owner,
+                        mint: args.mint,
+                        currency: args.currency,
+                        active: args.active,
+                    },
+                }))
+            }
+            MarketSubcommands::TakeBid(args) => render(take_bid(MarketTakeBidParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                bid_state: args.bid_state,
+                mint: args.mint,
+                terms: TakeBidTerms {
+                    min_price: args.min_price,
+                    royalty_pct: args.royalty_pct,
+                    taker_broker: args.taker_broker,
+                },
+                cosigner: args.cosigner,
+            })),
+        },
+        Commands::Nonce(subcommand) => match subcommand {
+            NonceSubcommands::Advance(args) => render(advance_nonce(NonceAdvanceParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                nonce_account: args.nonce_account,
+                authority: args.authority,
+            })),
+            NonceSubcommands::Create(args) => render(create_nonce(NonceCreateParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                authority: args.authority,
+                account_keypair: args.account_keypair,
+            })),
+            NonceSubcommands::Show(args) => render(show_nonce(NonceShowParams {
+                rpc_url: args.read_options.rpc_url,
+                profile: profile.clone(),
+                nonce_account: args.nonce_account,
+            })),
+            NonceSubcommands::Submit(args) => {
+                render(submit_nonce_transactions(NonceSubmitParams {
+                    rpc_url: args.read_options.rpc_url,
+                    profile: profile.clone(),
+                    path: args.path,
+                }))
+            }
         },
-        Commands::Ids(args) => handle_ids(args.id, args.list),
         Commands::Pool(subcommand) => match subcommand {
-            PoolSubcommands::Create(args) => create_pool(CreatePoolParams {
+            PoolSubcommands::Apply(args) => render(handle_pool_apply(
+                PoolApplyParams {
+                    keypair_path: args.write_options.keypair_path,
+                    rpc_url: args.write_options.rpc_url,
+                    profile: profile.clone(),
+                    nonce: args.write_options.nonce,
+                    nonce_authority: args.write_options.nonce_authority,
+                    sign_only: args.write_options.sign_only,
+                    manifest_path: args.manifest_path,
+                    dry_run: args.dry_run,
+                },
+                confirm_plan(args.yes, "Apply this plan?"),
+            )),
+            PoolSubcommands::Close(args) => render(close_pool(ClosePoolParams {
                 keypair_path: args.write_options.keypair_path,
                 rpc_url: args.write_options.rpc_url,
-                whitelist: args.whitelist,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                pool: args.pool,
+            })),
+            PoolSubcommands::Create(args) => render(create_pool(CreatePoolParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
                 pool_config_path: args.pool_config_path,
-            }),
-            PoolSubcommands::Edit(args) => edit_pool(EditPoolParams {
+                flags: PoolConfigFlags {
+                    pool_id: args.pool_id,
+                    pool_type: args.pool_type,
+                    curve_type: args.curve,
+                    start: args.start,
+                    delta: args.delta,
+                    fee_bps: args.fee_bps,
+                    compound_fees: args.compound_fees,
+                    expire: args.expire,
+                    currency: args.currency,
+                    cosigner: args.cosigner,
+                    maker_broker: args.maker_broker,
+                    max_taker_sell_count: args.max_taker_sell_count,
+                },
+                whitelist: args.whitelist,
+                shared_escrow: args.shared_escrow,
+            })),
+            PoolSubcommands::DepositNft(args) => render(deposit_nft(PoolNftParams {
                 keypair_path: args.write_options.keypair_path,
                 rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
                 pool: args.pool,
-                edit_pool_config_path: args.edit_pool_config_path,
-            }),
-        },
-        Commands::Whitelist(subcommand) => match subcommand {
-            WhitelistSubcommands::Compare(args) => handle_compare(CompareParams {
-                keypair_path: None,
+                mints: args.mints,
+                file: args.file,
+                lookup_tables: args.lookup_tables,
+            })),
+            PoolSubcommands::DepositSol(args) => render(deposit_sol(PoolSolParams {
+                keypair_path: args.write_options.keypair_path,
+                rpc_url: args.write_options.rpc_url,
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                pool: args.pool,
+                amount: args.amount,
+                lamports: args.lamports,
+            })),
+            PoolSubcommands::Edit(args) => render(edit_pool(
+                EditPoolParams {
+                    keypair_path: args.write_options.keypair_path,
+                    rpc_url: args.write_options.rpc_url,
+                    profile: profile.clone(),
+                    nonce: args.write_options.nonce,
+                    nonce_authority: args.write_options.nonce_authority,
+                    sign_only: args.write_options.sign_only,
+                    pool: args.pool,
+                    edit_pool_config_path: args.edit_pool_config_path,
+                    flags: EditPoolFlags {
+                        curve_type: args.curve,
+                        start: args.start,
+                        delta: args.delta,
+                        fee_bps: args.fee_bps,
+                        no_fee: args.no_fee,
+                        compound_fees: args.compound_fees,
+                        cosigner: args.cosigner,
+                        expire: args.expire,
+                        max_taker_sell_count: args.max_taker_sell_count,
+                        reset_price_offset: args.reset_price_offset,
+                    },
+                },
+                confirm_plan(args.yes, "Apply this edit?"),
+            )),
+            PoolSubcommands::List(args) => render(handle_pool_list(PoolListParams {
+                rpc_url: args.read_options.rpc_url,
+                profile: profile.clone(),
+                filters: PoolFilters {
+                    owner: args.owner,
+                    whitelist: args.whitelist,
+                    cosigner: args.cosigner,
+                    shared_escrow: args.shared_escrow,
+                    pool_type: args.pool_type,
+                    curve_type: args.curve,
+                    active: args.active,
+                },
+                sort: args.sort,
+                desc: args.desc,
+            })),
+            PoolSubcommands::Quote(args) => render(handle_pool_quote(PoolQuoteParams {
+                rpc_url: args.read_options.rpc_url,
+                profile: profile.clone(),
+                pool: args.pool,
+                pool_config_path: args.config,
+                count: args.count,
+                royalty_bps: args.royalty_bps,
+                royalty_pct: args.royalty_pct,
+            })),
+            PoolSubcommands::Report(args) => render(handle_pool_report(PoolReportParams {
                 rpc_url: args.read_options.rpc_url,
-                list: args.list,
-                namespace: args.namespace,
-                verbose: args.verbose,
-            }),
-            WhitelistSubcommands::Create(args) => create_whitelist_v2(CreateWhitelistV2Params {
+                profile: profile.clone(),
+                pool: args.pool,
+                owner: args.owner,
+                csv: args.csv,
+            })),
+            PoolSubcommands::WithdrawNft(args) => render(withdraw_nft(PoolNftParams {
                 keypair_path: args.write_options.keypair_path,
                 rpc_url: args.write_options.rpc_url,
-                namespace_path: args.namespace_path,
-                whitelist_config_path: args.whitelist_config_path,
-            }),
-            WhitelistSubcommands::Update(args) => update_whitelist_v2(UpdateWhitelistV2Params {
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                pool: args.pool,
+                mints: args.mints,
+                file: args.file,
+                lookup_tables: args.lookup_tables,
+            })),
+            PoolSubcommands::WithdrawSol(args) => render(withdraw_sol(PoolSolParams {
                 keypair_path: args.write_options.keypair_path,
                 rpc_url: args.write_options.rpc_url,
-                whitelist_address: args.whitelist_address,
-                new_conditions_path: args.new_conditions_path,
-                new_update_authority_path: args.new_update_authority_path,
-                new_freeze_authority: args.new_freeze_authority,
-            }),
+                profile: profile.clone(),
+                nonce: args.write_options.nonce,
+                nonce_authority: args.write_options.nonce_authority,
+                sign_only: args.write_options.sign_only,
+                pool: args.pool,
+                amount: args.amount,
+                lamports: args.lamports,
+            })),
+        },
+        Commands::PriceLock(subcommand) => match subcommand {
+            PriceLockSubcommands::Close(args) => {
+                render(close_price_lock_order(PriceLockCloseParams {
+                    keypair_path: args.write_options.keypair_path,
+                    rpc_url: args.write_options.rpc_url,
+                    profile: profile.clone(),
+                    nonce: args.write_options.nonce,
+                    nonce_authority: args.write_options.nonce_authority,
+                    sign_only: args.write_options.sign_only,
+                    order: args.order,
+                }))
+            }
+            PriceLockSubcommands::Create(args) => {
+                render(create_price_lock_order(PriceLockCreateParams {
+                    keypair_path: args.write_options.keypair_path,
+                    rpc_url: args.write_options.rpc_url,
+                    profile: profile.clone(),
+                    nonce: args.write_options.nonce,
+                    nonce_authority: args.write_options.nonce_authority,
+                    sign_only: args.write_options.sign_only,
+                    order_type: args.order_type,
+                    whitelist: args.whitelist,
+                    terms: order_terms(args.terms),
+                }))
+            }
+            PriceLockSubcommands::Edit(args) => {
+                render(edit_price_lock_order(PriceLockEditParams {
+                    keypair_path: args.write_options.keypair_path,
+                    rpc_url: args.write_options.rpc_url,
+                    profile: profile.clone(),
+                    nonce: args.write_options.nonce,
+                    nonce_authority: args.write_options.nonce_authority,
+                    sign_only: args.write_options.sign_only,
+                    order: args.order,
+                    terms: order_terms(args.terms),
+                }))
+            }
+            PriceLockSubcommands::Orders(args) => {
+                render(handle_price_lock_orders(PriceLockOrdersParams {
+                    rpc_url: args.read_options.rpc_url,
+                    profile: profile.clone(),
+                    filters: OrderFilters {
+                        maker: args.maker,
+                        taker: args.taker,
+                        whitelist: args.whitelist,
+                    },
+                }))
+            }
+            PriceLockSubcommands::Show(args) => {
+                render(show_price_lock_order(PriceLockShowParams {
+                    rpc_url: args.read_options.rpc_url,
+                    profile: profile.clone(),
+                    order: args.order,
+                }))
+            }
+        },
+        Commands::Whitelist(subcommand) => match subcommand {
+            WhitelistSubcommands::Compare(args) => {
+                let report = handle_compare(CompareParams {
+                    keypair_path: None,
+                    rpc_url: args.read_options.rpc_url,
+                    profile: profile.clone(),
+                    list: args.list,
+                    namespace: args.namespace,
+                    expected_authority: args.expected_authority,
+                    report_dir: args.report_dir,
+                    previous: args.previous,
+                })?;
+
+                if args.verbose {
+                    for result in report.results.iter() {
+                        println!("{}", result.custom_format());
+                        println!(); // Add a blank line between comparisons for readability
+                    }
+                }
+
+                render(Ok(report))
+            }
+            WhitelistSubcommands::Create(args) => {
+                render(create_whitelist_v2(CreateWhitelistV2Params {
+                    keypair_path: args.write_options.keypair_path,
+                    rpc_url: args.write_options.rpc_url,
+                    profile: profile.clone(),
+                    nonce: args.write_options.nonce,
+                    nonce_authority: args.write_options.nonce_authority,
+                    sign_only: args.write_options.sign_only,
+                    namespace_path: args.namespace_path,
+                    whitelist_config_path: args.whitelist_config_path,
+                }))
+            }
+            WhitelistSubcommands::Merkle(subcommand) => match subcommand {
+                WhitelistMerkleSubcommands::Build(args) => {
+                    render(handle_merkle_build(MerkleBuildParams {
+                        mints_path: args.mints_path,
+                        out: args.out,
+                        sort_leaves: args.sort_leaves,
+                    }))
+                }
+                WhitelistMerkleSubcommands::Proof(args) => {
+                    render(handle_merkle_proof(MerkleProofParams {
+                        proofs_path: args.proofs,
+                        mint: args.mint,
+                    }))
+                }
+            },
+            WhitelistSubcommands::Migrate(args) => render(handle_migrate(
+                MigrateParams {
+                    keypair_path: args.write_options.keypair_path,
+                    rpc_url: args.write_options.rpc_url,
+                    profile: profile.clone(),
+                    nonce: args.write_options.nonce,
+                    nonce_authority: args.write_options.nonce_authority,
+                    sign_only: args.write_options.sign_only,
+                    namespace_path: args.namespace,
+                    missing_path: args.missing,
+                    mismatches_path: args.mismatches,
+                    list: args.list,
+                    dry_run: args.dry_run,
+                },
+                confirm_plan(args.yes, "Migrate these whitelists?"),
+            )),
+            WhitelistSubcommands::MintProof(subcommand) => match subcommand {
+                WhitelistMintProofSubcommands::Init(args) => {
+                    // A single proof is sent as asked, a bulk run shows what it's about to pay
+                    // for first.
+                    let yes = args.yes || args.mint.is_some();
+
+                    render(handle_init_mint_proof(
+                        InitMintProofParams {
+                            keypair_path: args.write_options.keypair_path,
+                            rpc_url: args.write_options.rpc_url,
+                            profile: profile.clone(),
+                            nonce: args.write_options.nonce,
+                            nonce_authority: args.write_options.nonce_authority,
+                            sign_only: args.write_options.sign_only,
+                            whitelist: args.whitelist,
+                            mint: args.mint,
+                            proofs_path: args.proofs,
+                            mints_path: args.mints,
+                            sort_leaves: args.sort_leaves,
+                            dry_run: args.dry_run,
+                        },
+                        confirm_plan(yes, "Create these mint proofs?"),
+                    ))
+                }
+                WhitelistMintProofSubcommands::Show(args) => {
+                    render(handle_show_mint_proof(ShowMintProofParams {
+                        rpc_url: args.read_options.rpc_url,
+                        profile: profile.clone(),
+                        whitelist: args.whitelist,
+                        mint: args.mint,
+                    }))
+                }
+            },
+            WhitelistSubcommands::Update(args) => {
+                render(update_whitelist_v2(UpdateWhitelistV2Params {
+                    keypair_path: args.write_options.keypair_path,
+                    rpc_url: args.write_options.rpc_url,
+                    profile: profile.clone(),
+                    nonce: args.write_options.nonce,
+                    nonce_authority: args.write_options.nonce_authority,
+                    sign_only: args.write_options.sign_only,
+                    whitelist_address: args.whitelist_address,
+                    new_conditions_path: args.new_conditions_path,
+                    new_update_authority_path: args.new_update_authority_path,
+                    new_freeze_authority: args.new_freeze_authority,
+                }))
+            }
         },
     }
 }
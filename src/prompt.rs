@@ -0,0 +1,14 @@
+use std::io::{self, BufRead, Write};
+
+use anyhow::Result;
+
+/// Asks a yes or no question on the terminal, defaulting to no.
+pub fn confirm(prompt: &str) -> Result<bool> {
+    print!("{} [y/N] ", prompt);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
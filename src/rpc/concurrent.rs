@@ -0,0 +1,130 @@
+use std::{future::Future, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use tokio::{runtime::Builder, sync::Semaphore, task};
+
+use super::Rpc;
+
+/// Most accounts a single `getMultipleAccounts` request can return.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Runs a future to completion on a new multi-threaded runtime, for use from synchronous commands.
+pub fn block_on<F: Future>(future: F) -> Result<F::Output> {
+    let runtime = Builder::new_multi_thread().enable_all().build()?;
+
+    Ok(runtime.block_on(future))
+}
+
+/// Runs a single blocking RPC call on the blocking thread pool so it can be awaited alongside
+/// others.
+pub async fn run_blocking<T, F>(call: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    task::spawn_blocking(call)
+        .await
+        .map_err(|e| anyhow!("RPC task failed: {}", e))?
+}
+
+/// Runs blocking RPC calls on the blocking thread pool, at most `max_concurrency` at a time.
+/// Results are returned in the order of the calls, failing with the first error.
+pub async fn run_concurrently<T, F>(calls: Vec<F>, max_concurrency: usize) -> Result<Vec<T>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(max_concurrency.max(1)));
+
+    let handles: Vec<_> = calls
+        .into_iter()
+        .map(|call| {
+            let semaphore = semaphore.clone();
+            task::spawn(async move {
+                let _permit = semaphore.acquire_owned().await?;
+                run_blocking(call).await
+            })
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        results.push(
+            handle
+                .await
+                .map_err(|e| anyhow!("RPC task failed: {}", e))??,
+        );
+    }
+
+    Ok(results)
+}
+
+/// Fetches accounts in `getMultipleAccounts` batches, running up to `max_concurrency` batches at
+/// once. Missing accounts are `None`, in the same position as their address.
+pub async fn get_multiple_accounts_batched(
+    client: Arc<dyn Rpc>,
+    pubkeys: &[Pubkey],
+    max_concurrency: usize,
+) -> Result<Vec<Option<Account>>> {
+    let calls: Vec<_> = pubkeys
+        .chunks(MAX_MULTIPLE_ACCOUNTS)
+        .map(|chunk| {
+            let client = client.clone();
+            let chunk = chunk.to_vec();
+            move || client.get_multiple_accounts(&chunk)
+        })
+        .collect();
+
+    Ok(run_concurrently(calls, max_concurrency)
+        .await?
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+/// Lamport balances of the addresses, zero for accounts that don't exist.
+pub async fn get_balances_batched(
+    client: Arc<dyn Rpc>,
+    pubkeys: &[Pubkey],
+    max_concurrency: usize,
+) -> Result<Vec<u64>> {
+    Ok(
+        get_multiple_accounts_batched(client, pubkeys, max_concurrency)
+            .await?
+            .into_iter()
+            .map(|account| account.map(|a| a.lamports).unwrap_or(0))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::FixtureRpc;
+
+    #[test]
+    fn test_batched_accounts_keep_order() {
+        let pubkeys: Vec<Pubkey> = (0..250).map(|_| Pubkey::new_unique()).collect();
+
+        let rpc = FixtureRpc::new();
+        for (i, pubkey) in pubkeys.iter().enumerate().filter(|(i, _)| i % 3 == 0) {
+            rpc.insert(
+                *pubkey,
+                Account {
+                    lamports: i as u64,
+                    ..Account::default()
+                },
+            );
+        }
+
+        let balances = block_on(get_balances_batched(Arc::new(rpc), &pubkeys, 2))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(balances.len(), pubkeys.len());
+        for (i, balance) in balances.into_iter().enumerate() {
+            assert_eq!(balance, if i % 3 == 0 { i as u64 } else { 0 });
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Response types of the Digital Asset Standard (DAS) API, which is the only way to read
+//! compressed NFTs since they have no accounts of their own.
+
+use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
+use solana_sdk::pubkey::Pubkey;
+
+/// `getAsset` response, trimmed to the fields eigen uses.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DasAsset {
+    #[serde_as(as = "DisplayFromStr")]
+    pub id: Pubkey,
+    pub content: DasContent,
+    pub compression: DasCompression,
+    pub royalty: DasRoyalty,
+    #[serde(default)]
+    pub creators: Vec<DasCreator>,
+    #[serde(default)]
+    pub grouping: Vec<DasGroup>,
+    pub ownership: DasOwnership,
+    #[serde(default)]
+    pub supply: Option<DasSupply>,
+    pub mutable: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DasContent {
+    pub json_uri: String,
+    pub metadata: DasMetadata,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DasMetadata {
+    pub name: String,
+    #[serde(default)]
+    pub symbol: String,
+    #[serde(default)]
+    pub token_standard: Option<String>,
+}
+
+/// Leaf data of a compressed asset; the hashes are base58 and empty for uncompressed assets.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DasCompression {
+    pub compressed: bool,
+    pub data_hash: String,
+    pub creator_hash: String,
+    pub tree: String,
+    pub leaf_id: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DasRoyalty {
+    pub basis_points: u16,
+    pub primary_sale_happened: bool,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DasCreator {
+    #[serde_as(as = "DisplayFromStr")]
+    pub address: Pubkey,
+    pub share: u8,
+    pub verified: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DasGroup {
+    pub group_key: String,
+    pub group_value: String,
+    #[serde(default)]
+    pub verified: Option<bool>,
+}
+
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DasOwnership {
+    #[serde_as(as = "DisplayFromStr")]
+    pub owner: Pubkey,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    #[serde(default)]
+    pub delegate: Option<Pubkey>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DasSupply {
+    #[serde(default)]
+    pub edition_nonce: Option<u8>,
+}
+
+/// `getAssetProof` response: the full proof, before trimming it to the tree's canopy.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DasAssetProof {
+    #[serde_as(as = "DisplayFromStr")]
+    pub root: Pubkey,
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    pub proof: Vec<Pubkey>,
+    pub node_index: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub tree_id: Pubkey,
+}
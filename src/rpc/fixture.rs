@@ -0,0 +1,237 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    path::Path,
+    str::FromStr,
+    sync::{Mutex, RwLock},
+};
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use solana_client::{
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_response::{RpcKeyedAccount, RpcSimulateTransactionResult},
+};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    clock::Slot,
+    hash::Hash,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+
+use super::{DasAsset, DasAssetProof, Rpc};
+
+/// In-memory [`Rpc`] backed by a fixed set of accounts.
+///
+/// Accounts can be inserted directly or loaded from JSON files in the format written by
+/// `solana account <ADDRESS> --output json`. Transactions are recorded rather than executed, so
+/// account state never changes unless a test changes it. Compressed assets are served through the
+/// DAS methods once inserted with [`FixtureRpc::insert_asset`].
+pub struct FixtureRpc {
+    accounts: RwLock<HashMap<Pubkey, Account>>,
+    assets: RwLock<HashMap<Pubkey, (DasAsset, DasAssetProof)>>,
+    sent: Mutex<Vec<VersionedTransaction>>,
+    genesis_hash: Hash,
+    blockhash: Hash,
+    slot: Slot,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FixtureFile {
+    One(RpcKeyedAccount),
+    Many(Vec<RpcKeyedAccount>),
+}
+
+impl Default for FixtureRpc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FixtureRpc {
+    pub fn new() -> Self {
+        Self {
+            accounts: RwLock::new(HashMap::new()),
+            assets: RwLock::new(HashMap::new()),
+            sent: Mutex::new(vec![]),
+            genesis_hash: Hash::new_unique(),
+            blockhash: Hash::new_unique(),
+            slot: 1,
+        }
+    }
+
+    /// Sets the genesis hash, which is how commands tell which cluster they are talking to.
+    pub fn with_genesis_hash(mut self, genesis_hash: Hash) -> Self {
+        self.genesis_hash = genesis_hash;
+        self
+    }
+
+    pub fn with_account(self, pubkey: Pubkey, account: Account) -> Self {
+        self.insert(pubkey, account);
+        self
+    }
+
+    pub fn insert(&self, pubkey: Pubkey, account: Account) {
+        self.accounts.write().unwrap().insert(pubkey, account);
+    }
+
+    pub fn insert_asset(&self, asset: DasAsset, proof: DasAssetProof) {
+        self.assets
+            .write()
+            .unwrap()
+            .insert(asset.id, (asset, proof));
+    }
+
+    /// Loads a JSON file holding a single keyed account or a list of them.
+    pub fn load_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file: FixtureFile = serde_json::from_reader(File::open(path)?)
+            .map_err(|e| anyhow!("Invalid fixture {}: {}", path.display(), e))?;
+
+        let keyed_accounts = match file {
+            FixtureFile::One(keyed_account) => vec![keyed_account],
+            FixtureFile::Many(keyed_accounts) => keyed_accounts,
+        };
+
+        for keyed_account in keyed_accounts {
+            let pubkey = Pubkey::from_str(&keyed_account.pubkey)?;
+            let account: Account = keyed_account
+                .account
+                .decode()
+                .ok_or_else(|| anyhow!("Unable to decode fixture account {}", pubkey))?;
+
+            self.insert(pubkey, account);
+        }
+
+        Ok(())
+    }
+
+    /// Loads every `.json` file in a directory.
+    pub fn load_dir(&self, dir: impl AsRef<Path>) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                self.load_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Transactions passed to `send_and_confirm_transaction`, in order.
+    pub fn sent_transactions(&self) -> Vec<VersionedTransaction> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl Rpc for FixtureRpc {
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        self.accounts
+            .read()
+            .unwrap()
+            .get(pubkey)
+            .cloned()
+            // Same wording as the RPC error so callers can handle both the same way.
+            .ok_or_else(|| anyhow!("AccountNotFound: pubkey={}", pubkey))
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let accounts = self.accounts.read().unwrap();
+
+        Ok(pubkeys
+            .iter()
+            .map(|pubkey| accounts.get(pubkey).cloned())
+            .collect())
+    }
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let filters = config.filters.unwrap_or_default();
+
+        Ok(self
+            .accounts
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, account)| account.owner == *program_id)
+            .filter(|(_, account)| {
+                let shared = AccountSharedData::from((*account).clone());
+                filters.iter().all(|filter| filter.allows(&shared))
+            })
+            .map(|(pubkey, account)| (*pubkey, account.clone()))
+            .collect())
+    }
+
+    fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
+        Ok(self
+            .accounts
+            .read()
+            .unwrap()
+            .get(pubkey)
+            .map(|account| account.lamports)
+            .unwrap_or(0))
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        Ok(Rent::default().minimum_balance(data_len))
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(self.blockhash)
+    }
+
+    fn get_genesis_hash(&self) -> Result<Hash> {
+        Ok(self.genesis_hash)
+    }
+
+    fn get_slot(&self) -> Result<Slot> {
+        Ok(self.slot)
+    }
+
+    fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Signature> {
+        self.sent.lock().unwrap().push(transaction.clone());
+
+        Ok(transaction.signatures[0])
+    }
+
+    fn simulate_transaction(
+        &self,
+        _transaction: &VersionedTransaction,
+    ) -> Result<RpcSimulateTransactionResult> {
+        Ok(RpcSimulateTransactionResult {
+            err: None,
+            logs: Some(vec![]),
+            accounts: None,
+            units_consumed: Some(0),
+            return_data: None,
+        })
+    }
+
+    fn get_asset(&self, id: &Pubkey) -> Result<DasAsset> {
+        self.assets
+            .read()
+            .unwrap()
+            .get(id)
+            .map(|(asset, _)| asset.clone())
+            .ok_or_else(|| anyhow!("Asset Not Found: {}", id))
+    }
+
+    fn get_asset_proof(&self, id: &Pubkey) -> Result<DasAssetProof> {
+        self.assets
+            .read()
+            .unwrap()
+            .get(id)
+            .map(|(_, proof)| proof.clone())
+            .ok_or_else(|| anyhow!("Asset Proof Not Found: {}", id))
+    }
+}
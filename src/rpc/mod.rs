@@ -0,0 +1,198 @@
+//! RPC calls used by eigen, behind a trait so commands can run against something other than a
+//! live cluster.
+
+mod concurrent;
+mod das;
+mod fixture;
+mod rate_limit;
+
+pub use concurrent::*;
+pub use das::*;
+pub use fixture::*;
+pub use rate_limit::*;
+
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+    rpc_request::RpcRequest,
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_sdk::{
+    account::Account, clock::Slot, commitment_config::CommitmentConfig, hash::Hash, pubkey::Pubkey,
+    signature::Signature, transaction::VersionedTransaction,
+};
+
+pub trait Rpc: Send + Sync {
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Account>;
+
+    fn get_account_data(&self, pubkey: &Pubkey) -> Result<Vec<u8>> {
+        Ok(self.get_account(pubkey)?.data)
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>>;
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>>;
+
+    /// Balance in lamports, zero for accounts that don't exist.
+    fn get_balance(&self, pubkey: &Pubkey) -> Result<u64>;
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64>;
+
+    fn get_latest_blockhash(&self) -> Result<Hash>;
+
+    fn get_genesis_hash(&self) -> Result<Hash>;
+
+    fn get_slot(&self) -> Result<Slot>;
+
+    fn send_and_confirm_transaction(&self, transaction: &VersionedTransaction)
+        -> Result<Signature>;
+
+    fn simulate_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<RpcSimulateTransactionResult>;
+
+    /// DAS `getAsset`, which only some RPC providers serve.
+    fn get_asset(&self, id: &Pubkey) -> Result<DasAsset> {
+        Err(anyhow!(
+            "RPC does not support the DAS API, unable to fetch asset {}",
+            id
+        ))
+    }
+
+    /// DAS `getAssetProof`, which only some RPC providers serve.
+    fn get_asset_proof(&self, id: &Pubkey) -> Result<DasAssetProof> {
+        Err(anyhow!(
+            "RPC does not support the DAS API, unable to fetch proof of asset {}",
+            id
+        ))
+    }
+}
+
+impl Rpc for RpcClient {
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        Ok(RpcClient::get_account(self, pubkey)?)
+    }
+
+    fn get_account_data(&self, pubkey: &Pubkey) -> Result<Vec<u8>> {
+        Ok(RpcClient::get_account_data(self, pubkey)?)
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        Ok(RpcClient::get_multiple_accounts(self, pubkeys)?)
+    }
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(RpcClient::get_program_accounts_with_config(
+            self, program_id, config,
+        )?)
+    }
+
+    fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
+        Ok(RpcClient::get_balance(self, pubkey)?)
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        Ok(RpcClient::get_minimum_balance_for_rent_exemption(
+            self, data_len,
+        )?)
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        Ok(RpcClient::get_latest_blockhash(self)?)
+    }
+
+    fn get_genesis_hash(&self) -> Result<Hash> {
+        Ok(RpcClient::get_genesis_hash(self)?)
+    }
+
+    fn get_slot(&self) -> Result<Slot> {
+        Ok(RpcClient::get_slot(self)?)
+    }
+
+    fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Signature> {
+        Ok(RpcClient::send_and_confirm_transaction_with_spinner(
+            self,
+            transaction,
+        )?)
+    }
+
+    fn simulate_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<RpcSimulateTransactionResult> {
+        Ok(RpcClient::simulate_transaction(self, transaction)?.value)
+    }
+
+    fn get_asset(&self, id: &Pubkey) -> Result<DasAsset> {
+        self.send(
+            RpcRequest::Custom { method: "getAsset" },
+            json!({ "id": id.to_string() }),
+        )
+        .map_err(|e| {
+            let message = format!("Unable to fetch asset {} from the DAS API: {}", id, e);
+            anyhow::Error::new(e).context(message)
+        })
+    }
+
+    fn get_asset_proof(&self, id: &Pubkey) -> Result<DasAssetProof> {
+        self.send(
+            RpcRequest::Custom {
+                method: "getAssetProof",
+            },
+            json!({ "id": id.to_string() }),
+        )
+        .map_err(|e| {
+            let message = format!(
+                "Unable to fetch proof of asset {} from the DAS API: {}",
+                id, e
+            );
+            anyhow::Error::new(e).context(message)
+        })
+    }
+}
+
+/// gPA config matching accounts by their discriminator and any extra filters, read at
+/// `commitment`.
+pub fn discriminator_filter(
+    commitment: CommitmentConfig,
+    discriminator: [u8; 8],
+    filters: Vec<RpcFilterType>,
+) -> RpcProgramAccountsConfig {
+    let mut all = vec![memcmp_filter(0, &discriminator)];
+    all.extend(filters);
+
+    RpcProgramAccountsConfig {
+        filters: Some(all),
+        account_config: RpcAccountInfoConfig {
+            data_slice: None,
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment),
+            min_context_slot: None,
+        },
+        with_context: None,
+    }
+}
+
+/// Matches accounts with `bytes` at `offset` in their data.
+pub fn memcmp_filter(offset: usize, bytes: &[u8]) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new(
+        offset,
+        MemcmpEncodedBytes::Bytes(bytes.to_vec()),
+    ))
+}
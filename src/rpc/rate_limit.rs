@@ -0,0 +1,354 @@
+use std::{
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Error, Result};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_response::RpcSimulateTransactionResult,
+};
+use solana_sdk::{
+    account::Account, clock::Slot, hash::Hash, pubkey::Pubkey, signature::Signature,
+    transaction::VersionedTransaction,
+};
+
+use super::{DasAsset, DasAssetProof, Rpc};
+
+/// Delay before the first retry of a throttled request, doubled on every further retry.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Token bucket holding up to `burst` requests, refilled at `requests_per_second`.
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: u32, burst: u32) -> Self {
+        let burst = burst.max(1) as f64;
+
+        Self {
+            requests_per_second: requests_per_second.max(1) as f64,
+            burst,
+            state: Mutex::new(BucketState {
+                tokens: burst,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token, or returns how long to wait until one is available.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.requests_per_second).min(self.burst);
+        state.last_refill = now;
+
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - state.tokens) / self.requests_per_second,
+            ))
+        }
+    }
+
+    /// Blocks until a token is available.
+    pub fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            thread::sleep(wait);
+        }
+    }
+}
+
+/// [`Rpc`] wrapper that rate limits requests and retries ones the node throttled.
+pub struct RateLimitedRpc<R> {
+    inner: R,
+    limiter: RateLimiter,
+    max_retries: u32,
+}
+
+impl<R: Rpc> RateLimitedRpc<R> {
+    pub fn new(inner: R, limiter: RateLimiter, max_retries: u32) -> Self {
+        Self {
+            inner,
+            limiter,
+            max_retries,
+        }
+    }
+
+    fn call<T>(&self, f: impl Fn(&R) -> Result<T>) -> Result<T> {
+        let mut backoff = INITIAL_BACKOFF;
+        let mut retries = 0;
+
+        loop {
+            self.limiter.acquire();
+
+            match f(&self.inner) {
+                Err(e) if is_rate_limited(&e) && retries < self.max_retries => {
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                    retries += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Whether the node rejected the request with HTTP 429. Only the status of the HTTP response is
+/// checked, as error messages can have "429" in an address or signature.
+pub fn is_rate_limited(error: &Error) -> bool {
+    match error.downcast_ref::<ClientError>().map(|e| e.kind()) {
+        Some(ClientErrorKind::Reqwest(e)) => e.status().map(|status| status.as_u16()) == Some(429),
+        _ => false,
+    }
+}
+
+impl<R: Rpc> Rpc for RateLimitedRpc<R> {
+    fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        self.call(|rpc| rpc.get_account(pubkey))
+    }
+
+    fn get_account_data(&self, pubkey: &Pubkey) -> Result<Vec<u8>> {
+        self.call(|rpc| rpc.get_account_data(pubkey))
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        self.call(|rpc| rpc.get_multiple_accounts(pubkeys))
+    }
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        self.call(|rpc| rpc.get_program_accounts_with_config(program_id, config.clone()))
+    }
+
+    fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
+        self.call(|rpc| rpc.get_balance(pubkey))
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+        self.call(|rpc| rpc.get_minimum_balance_for_rent_exemption(data_len))
+    }
+
+    fn get_latest_blockhash(&self) -> Result<Hash> {
+        self.call(|rpc| rpc.get_latest_blockhash())
+    }
+
+    fn get_genesis_hash(&self) -> Result<Hash> {
+        self.call(|rpc| rpc.get_genesis_hash())
+    }
+
+    fn get_slot(&self) -> Result<Slot> {
+        self.call(|rpc| rpc.get_slot())
+    }
+
+    /// Rate limited but never retried, as a throttled confirmation doesn't mean the transaction
+    /// wasn't sent.
+    fn send_and_confirm_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Signature> {
+        self.limiter.acquire();
+        self.inner.send_and_confirm_transaction(transaction)
+    }
+
+    fn simulate_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<RpcSimulateTransactionResult> {
+        self.call(|rpc| rpc.simulate_transaction(transaction))
+    }
+
+    fn get_asset(&self, id: &Pubkey) -> Result<DasAsset> {
+        self.call(|rpc| rpc.get_asset(id))
+    }
+
+    fn get_asset_proof(&self, id: &Pubkey) -> Result<DasAssetProof> {
+        self.call(|rpc| rpc.get_asset_proof(id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    use anyhow::anyhow;
+    use serde_json::Value;
+    use solana_client::{rpc_client::RpcClient, rpc_request::RpcRequest};
+
+    use super::*;
+    use crate::rpc::FixtureRpc;
+
+    /// The error `RpcClient` returns once a node keeps answering with HTTP 429, from a local server
+    /// that does nothing else.
+    fn throttled_error() -> Error {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                );
+            }
+        });
+
+        Error::from(
+            RpcClient::new(url)
+                .send::<Slot>(RpcRequest::GetSlot, Value::Null)
+                .unwrap_err(),
+        )
+    }
+
+    #[test]
+    fn test_bucket_allows_burst_then_waits() {
+        let limiter = RateLimiter::new(10, 3);
+
+        for _ in 0..3 {
+            assert!(limiter.try_acquire().is_ok());
+        }
+
+        let wait = limiter.try_acquire().unwrap_err();
+        assert!(wait <= Duration::from_millis(100));
+    }
+
+    struct Throttled {
+        inner: FixtureRpc,
+        failures: AtomicU32,
+    }
+
+    impl Throttled {
+        fn throttled(&self) -> bool {
+            self.failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok()
+        }
+    }
+
+    impl Rpc for Throttled {
+        fn get_account(&self, pubkey: &Pubkey) -> Result<Account> {
+            if self.throttled() {
+                return Err(throttled_error());
+            }
+            self.inner.get_account(pubkey)
+        }
+
+        fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+            self.inner.get_multiple_accounts(pubkeys)
+        }
+
+        fn get_program_accounts_with_config(
+            &self,
+            program_id: &Pubkey,
+            config: RpcProgramAccountsConfig,
+        ) -> Result<Vec<(Pubkey, Account)>> {
+            self.inner
+                .get_program_accounts_with_config(program_id, config)
+        }
+
+        fn get_balance(&self, pubkey: &Pubkey) -> Result<u64> {
+            self.inner.get_balance(pubkey)
+        }
+
+        fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> Result<u64> {
+            self.inner.get_minimum_balance_for_rent_exemption(data_len)
+        }
+
+        fn get_latest_blockhash(&self) -> Result<Hash> {
+            self.inner.get_latest_blockhash()
+        }
+
+        fn get_genesis_hash(&self) -> Result<Hash> {
+            self.inner.get_genesis_hash()
+        }
+
+        fn get_slot(&self) -> Result<Slot> {
+            self.inner.get_slot()
+        }
+
+        fn send_and_confirm_transaction(
+            &self,
+            transaction: &VersionedTransaction,
+        ) -> Result<Signature> {
+            if self.throttled() {
+                return Err(throttled_error());
+            }
+            self.inner.send_and_confirm_transaction(transaction)
+        }
+
+        fn simulate_transaction(
+            &self,
+            transaction: &VersionedTransaction,
+        ) -> Result<RpcSimulateTransactionResult> {
+            self.inner.simulate_transaction(transaction)
+        }
+    }
+
+    #[test]
+    fn test_retries_throttled_requests() {
+        let pubkey = Pubkey::new_unique();
+        let throttled = Throttled {
+            inner: FixtureRpc::new().with_account(pubkey, Account::default()),
+            failures: AtomicU32::new(1),
+        };
+
+        let rpc = RateLimitedRpc::new(throttled, RateLimiter::new(100, 100), 2);
+        assert!(rpc.get_account(&pubkey).is_ok());
+
+        // Gives up once out of retries.
+        rpc.inner.failures.store(3, Ordering::SeqCst);
+        let err = rpc.get_account(&pubkey).unwrap_err();
+        assert!(is_rate_limited(&err));
+    }
+
+    #[test]
+    fn test_only_http_429_is_rate_limited() {
+        assert!(is_rate_limited(&throttled_error()));
+
+        // Other errors aren't retried, whatever their message says.
+        assert!(!is_rate_limited(&anyhow!(
+            "AccountNotFound: pubkey=429oN3dKEMmkQ5dDb4KFxoDtR6JJfgJpbeT2b1iZWPsX"
+        )));
+        assert!(!is_rate_limited(&anyhow!(
+            "HTTP status client error (429 Too Many Requests)"
+        )));
+    }
+
+    #[test]
+    fn test_sends_are_not_retried() {
+        let throttled = Throttled {
+            inner: FixtureRpc::new(),
+            failures: AtomicU32::new(1),
+        };
+
+        let rpc = RateLimitedRpc::new(throttled, RateLimiter::new(100, 100), 2);
+        let err = rpc
+            .send_and_confirm_transaction(&VersionedTransaction::default())
+            .unwrap_err();
+
+        assert!(is_rate_limited(&err));
+        assert!(rpc.inner.inner.sent_transactions().is_empty());
+    }
+}
@@ -6,9 +6,28 @@ use solana_sdk::{
     clock::Slot,
     commitment_config::CommitmentConfig,
     hash::Hash,
+    pubkey::Pubkey,
     signature::{read_keypair_file, Keypair},
+    signer::Signer,
 };
-use std::{fs::File, path::PathBuf, str::FromStr};
+use std::{
+    collections::HashMap,
+    env,
+    fs::File,
+    path::PathBuf,
+    str::FromStr,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use crate::rpc::{RateLimitedRpc, RateLimiter, Rpc};
+
+/// Environment variable holding the name of the profile to use.
+pub const PROFILE_ENV: &str = "EIGEN_PROFILE";
+
+pub const DEFAULT_PROFILE: &str = "default";
+pub const DEFAULT_REQUESTS_PER_SECOND: u32 = 10;
+pub const DEFAULT_MAX_CONCURRENCY: usize = 8;
+pub const DEFAULT_MAX_RETRIES: u32 = 5;
 
 #[derive(Debug, Deserialize, Serialize)]
 struct SolanaConfig {
@@ -17,11 +36,70 @@ struct SolanaConfig {
     pub commitment: String,
 }
 
+/// Named connection settings from `~/.config/eigen/profiles.yml`. Unset values fall back to the
+/// Solana config file and the defaults.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct Profile {
+    pub rpc_url: Option<String>,
+    pub keypair_path: Option<PathBuf>,
+    pub commitment: Option<String>,
+    /// Requests per second allowed by the token bucket.
+    pub requests_per_second: Option<u32>,
+    /// Requests that can be made at once before the rate limit applies, defaults to
+    /// `requests_per_second`.
+    pub burst: Option<u32>,
+    /// Most requests in flight at once for bulk fetches.
+    pub max_concurrency: Option<usize>,
+    /// Retries of a request the node throttled with a 429, with exponential backoff.
+    pub max_retries: Option<u32>,
+}
+
+impl Profile {
+    /// Loads the named profile, or the one named by `EIGEN_PROFILE`, or else the `default` profile
+    /// if it exists.
+    pub fn load(name: Option<&str>) -> Result<Self> {
+        let name = name
+            .map(str::to_string)
+            .or_else(|| env::var(PROFILE_ENV).ok());
+
+        let mut profiles = parse_profiles()?;
+
+        match name {
+            Some(name) => profiles.remove(&name).ok_or_else(|| {
+                anyhow!(
+                    "Profile {} not found in {}",
+                    name,
+                    profiles_path().display()
+                )
+            }),
+            None => Ok(profiles.remove(DEFAULT_PROFILE).unwrap_or_default()),
+        }
+    }
+}
+
 pub struct CliConfig {
-    pub client: RpcClient,
+    pub client: Arc<dyn Rpc>,
     pub keypair: Keypair,
     pub recent_blockhash: Hash,
     pub recent_slot: Slot,
+    /// Commitment of the client, for requests that take their own config.
+    pub commitment: CommitmentConfig,
+    pub nonce: Option<NonceConfig>,
+    /// File to write the signed transactions to instead of sending them.
+    pub sign_only: Option<PathBuf>,
+    /// Whether a transaction was already written to the sign only file. Every transaction
+    /// advances the nonce, so only one can be signed ahead of time.
+    pub(crate) signed: AtomicBool,
+    /// Most requests in flight at once for bulk fetches.
+    pub max_concurrency: usize,
+}
+
+/// Durable nonce used in place of a recent blockhash so transactions don't expire while waiting
+/// on slow signers.
+pub struct NonceConfig {
+    pub account: Pubkey,
+    /// Nonce authority, when it's not the fee payer.
+    pub authority: Option<Keypair>,
 }
 
 #[derive(Debug, Default)]
@@ -29,6 +107,10 @@ pub struct CliConfigBuilder {
     pub json_rpc_url: Option<String>,
     pub keypair_path: Option<PathBuf>,
     pub commitment: Option<String>,
+    pub requests_per_second: Option<u32>,
+    pub burst: Option<u32>,
+    pub max_concurrency: Option<usize>,
+    pub max_retries: Option<u32>,
 }
 
 impl CliConfigBuilder {
@@ -37,6 +119,10 @@ impl CliConfigBuilder {
             json_rpc_url: None,
             keypair_path: None,
             commitment: None,
+            requests_per_second: None,
+            burst: None,
+            max_concurrency: None,
+            max_retries: None,
         }
     }
     pub fn rpc_url(mut self, json_rpc_url: String) -> Self {
@@ -51,6 +137,33 @@ impl CliConfigBuilder {
         self.commitment = Some(commitment);
         self
     }
+    pub fn requests_per_second(mut self, requests_per_second: u32) -> Self {
+        self.requests_per_second = Some(requests_per_second);
+        self
+    }
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = Some(burst);
+        self
+    }
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+    /// Applies the values set in a profile.
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.json_rpc_url = profile.rpc_url.or(self.json_rpc_url);
+        self.keypair_path = profile.keypair_path.or(self.keypair_path);
+        self.commitment = profile.commitment.or(self.commitment);
+        self.requests_per_second = profile.requests_per_second.or(self.requests_per_second);
+        self.burst = profile.burst.or(self.burst);
+        self.max_concurrency = profile.max_concurrency.or(self.max_concurrency);
+        self.max_retries = profile.max_retries.or(self.max_retries);
+        self
+    }
     pub fn build(&self) -> Result<CliConfig> {
         let rpc_url = self
             .json_rpc_url
@@ -62,7 +175,16 @@ impl CliConfigBuilder {
             None => CommitmentConfig::confirmed(),
         };
 
-        let client = RpcClient::new_with_commitment(rpc_url, commitment);
+        let requests_per_second = self
+            .requests_per_second
+            .unwrap_or(DEFAULT_REQUESTS_PER_SECOND);
+        let burst = self.burst.unwrap_or(requests_per_second);
+
+        let client = RateLimitedRpc::new(
+            RpcClient::new_with_commitment(rpc_url, commitment),
+            RateLimiter::new(requests_per_second, burst),
+            self.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+        );
 
         let keypair_path = self
             .keypair_path
@@ -72,20 +194,20 @@ impl CliConfigBuilder {
         let keypair =
             read_keypair_file(keypair_path).map_err(|_| anyhow!("Unable to read keypair file"))?;
 
-        let recent_blockhash = client.get_latest_blockhash()?;
-        let recent_slot = client.get_slot()?;
+        let mut config = CliConfig::with_client(Arc::new(client), keypair)?;
+        config.commitment = commitment;
+        config.max_concurrency = self.max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY);
 
-        Ok(CliConfig {
-            client,
-            keypair,
-            recent_blockhash,
-            recent_slot,
-        })
+        Ok(config)
     }
 }
 
 impl CliConfig {
-    pub fn new(keypair_path: Option<PathBuf>, rpc_url: Option<String>) -> Result<Self> {
+    pub fn new(
+        keypair_path: Option<PathBuf>,
+        rpc_url: Option<String>,
+        profile: Option<String>,
+    ) -> Result<Self> {
         let mut builder = CliConfigBuilder::new();
         let solana_config = parse_solana_config();
 
@@ -96,6 +218,8 @@ impl CliConfig {
                 .commitment(config.commitment);
         }
 
+        builder = builder.profile(Profile::load(profile.as_deref())?);
+
         if let Some(keypair_path) = keypair_path {
             builder = builder.keypair_path(keypair_path);
         }
@@ -109,6 +233,79 @@ impl CliConfig {
         Ok(config)
     }
 
+    /// Creates a config around an existing client, such as a [`crate::rpc::FixtureRpc`] in tests.
+    pub fn with_client(client: Arc<dyn Rpc>, keypair: Keypair) -> Result<Self> {
+        let recent_blockhash = client.get_latest_blockhash()?;
+        let recent_slot = client.get_slot()?;
+
+        Ok(CliConfig {
+            client,
+            keypair,
+            recent_blockhash,
+            recent_slot,
+            commitment: CommitmentConfig::confirmed(),
+            nonce: None,
+            sign_only: None,
+            signed: AtomicBool::new(false),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+        })
+    }
+
+    /// Uses a durable nonce account for transactions sent with this config. The nonce authority
+    /// defaults to the fee payer.
+    pub fn with_nonce(
+        mut self,
+        nonce: Option<Pubkey>,
+        nonce_authority_path: Option<PathBuf>,
+    ) -> Result<Self> {
+        let account = match nonce {
+            Some(account) => account,
+            None if nonce_authority_path.is_some() => {
+                return Err(anyhow!("A nonce authority requires a nonce account"))
+            }
+            None => return Ok(self),
+        };
+
+        let authority = nonce_authority_path
+            .map(|path| {
+                read_keypair_file(path).map_err(|_| anyhow!("Unable to read nonce authority file"))
+            })
+            .transpose()?;
+
+        self.nonce = Some(NonceConfig { account, authority });
+
+        let data = crate::transactions::fetch_nonce(&self, &account)?;
+        if data.authority != self.nonce_authority().unwrap().pubkey() {
+            return Err(anyhow!(
+                "Nonce account {} has authority {}, which is not the provided signer",
+                account,
+                data.authority
+            ));
+        }
+
+        Ok(self)
+    }
+
+    /// Writes transactions to `path` signed but not sent, to submit later with
+    /// `eigen nonce submit`. Requires a durable nonce, as a recent blockhash would expire
+    /// before then.
+    pub fn with_sign_only(mut self, path: Option<PathBuf>) -> Result<Self> {
+        if path.is_some() && self.nonce.is_none() {
+            return Err(anyhow!("Signing only requires a durable nonce"));
+        }
+
+        self.sign_only = path;
+
+        Ok(self)
+    }
+
+    /// Signer authorized to advance the nonce, if a nonce is configured.
+    pub fn nonce_authority(&self) -> Option<&Keypair> {
+        self.nonce
+            .as_ref()
+            .map(|nonce| nonce.authority.as_ref().unwrap_or(&self.keypair))
+    }
+
     #[allow(unused)]
     pub fn update_blocks(&mut self) -> Result<()> {
         self.recent_blockhash = self.client.get_latest_blockhash()?;
@@ -135,3 +332,21 @@ fn parse_solana_config() -> Option<SolanaConfig> {
     }
     None
 }
+
+fn profiles_path() -> PathBuf {
+    home_dir()
+        .expect("Couldn't find home dir")
+        .join(".config")
+        .join("eigen")
+        .join("profiles.yml")
+}
+
+fn parse_profiles() -> Result<HashMap<String, Profile>> {
+    let path = profiles_path();
+
+    match File::open(&path) {
+        Ok(file) => serde_yaml::from_reader(file)
+            .map_err(|e| anyhow!("Invalid profiles file {}: {}", path.display(), e)),
+        Err(_) => Ok(HashMap::new()),
+    }
+}
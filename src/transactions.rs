@@ -0,0 +1,379 @@
+use std::{path::Path, sync::atomic::Ordering};
+
+use anyhow::{anyhow, Result};
+use base64::{prelude::BASE64_STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use solana_client::nonce_utils::data_from_account;
+use solana_sdk::{
+    address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount},
+    hash::Hash,
+    instruction::Instruction,
+    message::{v0, Message, VersionedMessage},
+    nonce::state::Data as NonceData,
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction,
+    transaction::VersionedTransaction,
+};
+
+use crate::setup::CliConfig;
+
+/// Maximum size of a serialized transaction, in bytes.
+pub const MAX_TRANSACTION_SIZE: usize = PACKET_DATA_SIZE;
+
+/// Compiles the instructions into a message. Legacy messages are used when no lookup tables are
+/// provided so the output matches what the rest of the tooling expects, v0 messages otherwise.
+pub fn compile_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<VersionedMessage> {
+    if lookup_tables.is_empty() {
+        Ok(VersionedMessage::Legacy(Message::new_with_blockhash(
+            instructions,
+            Some(payer),
+            &blockhash,
+        )))
+    } else {
+        Ok(VersionedMessage::V0(v0::Message::try_compile(
+            payer,
+            instructions,
+            lookup_tables,
+            blockhash,
+        )?))
+    }
+}
+
+/// Serialized size in bytes of a signed transaction containing the instructions.
+pub fn transaction_size(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<usize> {
+    let message = compile_message(payer, instructions, lookup_tables, Hash::default())?;
+    let num_signatures = message.header().num_required_signatures as usize;
+
+    let tx = VersionedTransaction {
+        signatures: vec![Signature::default(); num_signatures],
+        message,
+    };
+
+    Ok(bincode::serialized_size(&tx)? as usize)
+}
+
+/// Packs instructions, in order, into as few transactions as possible based on their serialized
+/// size rather than a fixed instruction count.
+pub fn pack_instructions(
+    payer: &Pubkey,
+    instructions: Vec<Instruction>,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Vec<Vec<Instruction>>> {
+    pack_instructions_with_prefix(payer, &[], instructions, lookup_tables)
+}
+
+/// Same as [`pack_instructions`] but reserves room for instructions that are prepended to every
+/// transaction, such as advancing a durable nonce. The prefix is not included in the batches.
+pub fn pack_instructions_with_prefix(
+    payer: &Pubkey,
+    prefix: &[Instruction],
+    instructions: Vec<Instruction>,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Vec<Vec<Instruction>>> {
+    // Compiling can also fail when there are too many accounts for a single message,
+    // which is treated the same as going over the size limit.
+    let fits = |ixs: &[Instruction]| {
+        let with_prefix: Vec<Instruction> = prefix.iter().chain(ixs).cloned().collect();
+        transaction_size(payer, &with_prefix, lookup_tables)
+            .map(|size| size <= MAX_TRANSACTION_SIZE)
+            .unwrap_or(false)
+    };
+
+    let mut batches: Vec<Vec<Instruction>> = vec![];
+    let mut current: Vec<Instruction> = vec![];
+
+    for ix in instructions {
+        current.push(ix);
+
+        if fits(&current) {
+            continue;
+        }
+
+        let ix = current.pop().expect("just pushed");
+
+        if !current.is_empty() {
+            batches.push(std::mem::take(&mut current));
+        }
+
+        if !fits(std::slice::from_ref(&ix)) {
+            return Err(anyhow!(
+                "Instruction for program {} does not fit in a single transaction",
+                ix.program_id
+            ));
+        }
+
+        current.push(ix);
+    }
+
+    if !current.is_empty() {
+        batches.push(current);
+    }
+
+    Ok(batches)
+}
+
+/// Builds and signs a transaction, using only the signers the message requires.
+pub fn build_transaction(
+    payer: &Keypair,
+    instructions: &[Instruction],
+    extra_signers: &[&Keypair],
+    lookup_tables: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> Result<VersionedTransaction> {
+    let message = compile_message(&payer.pubkey(), instructions, lookup_tables, blockhash)?;
+
+    let num_signers = message.header().num_required_signatures as usize;
+    let required = &message.static_account_keys()[..num_signers];
+
+    let mut signers: Vec<&Keypair> = vec![payer];
+    signers.extend(
+        extra_signers
+            .iter()
+            .filter(|s| s.pubkey() != payer.pubkey() && required.contains(&s.pubkey())),
+    );
+
+    Ok(VersionedTransaction::try_new(message, &signers)?)
+}
+
+/// Packs the instructions by size and sends them as one or more transactions, returning the
+/// signatures in order.
+///
+/// When the config has a durable nonce, every transaction advances it as its first instruction
+/// and uses the stored nonce instead of a recent blockhash. With sign only set the transaction is
+/// written to that file instead of being sent.
+pub fn send_instructions(
+    config: &CliConfig,
+    instructions: Vec<Instruction>,
+    extra_signers: &[&Keypair],
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<Vec<Signature>> {
+    let mut signers = extra_signers.to_vec();
+    let mut prefix = vec![];
+
+    if let (Some(nonce), Some(authority)) = (&config.nonce, config.nonce_authority()) {
+        prefix.push(system_instruction::advance_nonce_account(
+            &nonce.account,
+            &authority.pubkey(),
+        ));
+        signers.push(authority);
+    }
+
+    let batches = pack_instructions_with_prefix(
+        &config.keypair.pubkey(),
+        &prefix,
+        instructions,
+        lookup_tables,
+    )?;
+
+    if config.sign_only.is_some() && (batches.len() > 1 || config.signed.load(Ordering::Relaxed)) {
+        return Err(anyhow!(
+            "Signing only covers a single transaction, as each one advances the nonce"
+        ));
+    }
+
+    let mut signatures = Vec::with_capacity(batches.len());
+
+    for batch in batches {
+        // The nonce changes after every transaction so it is fetched again for each batch.
+        let blockhash = match &config.nonce {
+            Some(nonce) => fetch_nonce(config, &nonce.account)?.blockhash(),
+            None => config.client.get_latest_blockhash()?,
+        };
+
+        let instructions: Vec<Instruction> = prefix.iter().chain(&batch).cloned().collect();
+
+        let tx = build_transaction(
+            &config.keypair,
+            &instructions,
+            &signers,
+            lookup_tables,
+            blockhash,
+        )?;
+
+        if let Some(path) = &config.sign_only {
+            write_signed_transactions(path, std::slice::from_ref(&tx))?;
+            config.signed.store(true, Ordering::Relaxed);
+            signatures.push(tx.signatures[0]);
+            continue;
+        }
+
+        signatures.push(config.client.send_and_confirm_transaction(&tx)?);
+    }
+
+    Ok(signatures)
+}
+
+/// Transactions signed ahead of time, as base64 encoded wire transactions.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SignedTransactionsFile {
+    pub transactions: Vec<String>,
+}
+
+pub fn write_signed_transactions(path: &Path, transactions: &[VersionedTransaction]) -> Result<()> {
+    let file = SignedTransactionsFile {
+        transactions: transactions
+            .iter()
+            .map(|tx| Ok(BASE64_STANDARD.encode(bincode::serialize(tx)?)))
+            .collect::<Result<_>>()?,
+    };
+
+    std::fs::write(path, serde_json::to_string_pretty(&file)?)
+        .map_err(|e| anyhow!("Failed to write transactions to {}: {}", path.display(), e))
+}
+
+pub fn read_signed_transactions(path: &Path) -> Result<Vec<VersionedTransaction>> {
+    let data =
+        std::fs::read(path).map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let file: SignedTransactionsFile = serde_json::from_slice(&data)
+        .map_err(|e| anyhow!("Invalid transactions file {}: {}", path.display(), e))?;
+
+    file.transactions
+        .iter()
+        .map(|encoded| {
+            let bytes = BASE64_STANDARD
+                .decode(encoded)
+                .map_err(|e| anyhow!("Invalid transaction in {}: {}", path.display(), e))?;
+
+            bincode::deserialize(&bytes)
+                .map_err(|e| anyhow!("Invalid transaction in {}: {}", path.display(), e))
+        })
+        .collect()
+}
+
+/// Signers a transaction still needs a signature from.
+pub fn missing_signers(tx: &VersionedTransaction) -> Vec<Pubkey> {
+    let num_signers = tx.message.header().num_required_signatures as usize;
+    let message = tx.message.serialize();
+
+    tx.message.static_account_keys()[..num_signers]
+        .iter()
+        .zip(&tx.signatures)
+        .filter(|(signer, signature)| !signature.verify(signer.as_ref(), &message))
+        .map(|(signer, _)| *signer)
+        .collect()
+}
+
+/// Fetches and decodes a durable nonce account.
+pub fn fetch_nonce(config: &CliConfig, address: &Pubkey) -> Result<NonceData> {
+    let account = config.client.get_account(address)?;
+
+    data_from_account(&account).map_err(|e| anyhow!("Invalid nonce account {}: {}", address, e))
+}
+
+/// Fetches and decodes address lookup table accounts so they can be used to compile v0 messages.
+pub fn fetch_lookup_tables(
+    config: &CliConfig,
+    addresses: &[Pubkey],
+) -> Result<Vec<AddressLookupTableAccount>> {
+    addresses
+        .iter()
+        .map(|address| {
+            let data = config.client.get_account_data(address)?;
+            let table = AddressLookupTable::deserialize(&data)
+                .map_err(|e| anyhow!("Invalid lookup table {}: {}", address, e))?;
+
+            Ok(AddressLookupTableAccount {
+                key: *address,
+                addresses: table.addresses.to_vec(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::system_instruction;
+
+    fn transfers(payer: &Pubkey, recipients: &[Pubkey]) -> Vec<Instruction> {
+        recipients
+            .iter()
+            .map(|r| system_instruction::transfer(payer, r, 1))
+            .collect()
+    }
+
+    #[test]
+    fn test_pack_legacy_by_size() {
+        let payer = Pubkey::new_unique();
+        let recipients: Vec<Pubkey> = (0..256).map(|_| Pubkey::new_unique()).collect();
+
+        let batches = pack_instructions(&payer, transfers(&payer, &recipients), &[]).unwrap();
+
+        // Every instruction is kept, in order.
+        let packed: Vec<Instruction> = batches.iter().flatten().cloned().collect();
+        assert_eq!(packed, transfers(&payer, &recipients));
+
+        // Each batch fits and is as full as it can be.
+        for (i, batch) in batches.iter().enumerate() {
+            assert!(transaction_size(&payer, batch, &[]).unwrap() <= MAX_TRANSACTION_SIZE);
+
+            if let Some(next) = batches.get(i + 1) {
+                let mut overfull = batch.clone();
+                overfull.push(next[0].clone());
+                assert!(
+                    transaction_size(&payer, &overfull, &[]).unwrap_or(usize::MAX)
+                        > MAX_TRANSACTION_SIZE
+                );
+            }
+        }
+
+        // Size-based packing beats the old fixed count of 15 per transaction.
+        assert!(batches[0].len() > 15);
+    }
+
+    #[test]
+    fn test_pack_with_lookup_table() {
+        let payer = Pubkey::new_unique();
+        let recipients: Vec<Pubkey> = (0..256).map(|_| Pubkey::new_unique()).collect();
+
+        let lookup_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: recipients.clone(),
+        };
+
+        let legacy = pack_instructions(&payer, transfers(&payer, &recipients), &[]).unwrap();
+        let v0 =
+            pack_instructions(&payer, transfers(&payer, &recipients), &[lookup_table]).unwrap();
+
+        assert!(v0.len() < legacy.len());
+    }
+
+    #[test]
+    fn test_pack_reserves_room_for_prefix() {
+        let payer = Pubkey::new_unique();
+        let recipients: Vec<Pubkey> = (0..256).map(|_| Pubkey::new_unique()).collect();
+        let prefix = [system_instruction::advance_nonce_account(
+            &Pubkey::new_unique(),
+            &payer,
+        )];
+
+        let batches =
+            pack_instructions_with_prefix(&payer, &prefix, transfers(&payer, &recipients), &[])
+                .unwrap();
+
+        for batch in batches {
+            let with_prefix: Vec<Instruction> = prefix.iter().chain(&batch).cloned().collect();
+            assert!(transaction_size(&payer, &with_prefix, &[]).unwrap() <= MAX_TRANSACTION_SIZE);
+        }
+    }
+
+    #[test]
+    fn test_oversized_instruction() {
+        let payer = Pubkey::new_unique();
+        let ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[0; 2000], vec![]);
+
+        assert!(pack_instructions(&payer, vec![ix], &[]).is_err());
+    }
+}
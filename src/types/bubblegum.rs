@@ -0,0 +1,72 @@
+use borsh::BorshSerialize;
+use solana_sdk::{keccak, pubkey::Pubkey};
+
+use crate::{
+    commands::BUBBLEGUM_ID,
+    types::metaplex::{Collection, Creator, TokenStandard, Uses},
+};
+
+// ----Bubblegum Structs----
+
+/// Size of the concurrent merkle tree header: account type, version, max buffer size, max depth,
+/// authority, creation slot and padding.
+const TREE_HEADER_LEN: usize = 2 + 4 + 4 + 32 + 8 + 6;
+const MAX_BUFFER_SIZE_OFFSET: usize = 2;
+const MAX_DEPTH_OFFSET: usize = 6;
+
+/// Bubblegum `MetadataArgs`, whose hash is part of a compressed NFT's leaf.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize)]
+pub struct MetadataArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    /// Bubblegum only knows the first four standards, which share their discriminants.
+    pub token_standard: Option<TokenStandard>,
+    pub collection: Option<Collection>,
+    pub uses: Option<Uses>,
+    /// Always `Original` (0) for Bubblegum v1 trees.
+    pub token_program_version: u8,
+    pub creators: Vec<Creator>,
+}
+
+impl MetadataArgs {
+    pub fn hash(&self) -> [u8; 32] {
+        keccak::hashv(&[&self.try_to_vec().unwrap()]).to_bytes()
+    }
+}
+
+/// Leaf data hash: the metadata hash followed by the seller fee basis points.
+pub fn data_hash(metadata_hash: &[u8; 32], seller_fee_basis_points: u16) -> [u8; 32] {
+    keccak::hashv(&[metadata_hash, &seller_fee_basis_points.to_le_bytes()]).to_bytes()
+}
+
+/// Tree authority (tree config) PDA of a Bubblegum merkle tree.
+pub fn find_tree_authority_pda(merkle_tree: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_ID)
+}
+
+/// Depth of the canopy a concurrent merkle tree account caches, so the proof nodes it holds can
+/// be left out of instructions. Returns `None` if the data is not a merkle tree.
+pub fn canopy_depth(data: &[u8]) -> Option<u32> {
+    let read_u32 = |offset: usize| {
+        data.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().expect("4 bytes")) as usize)
+    };
+    let max_buffer_size = read_u32(MAX_BUFFER_SIZE_OFFSET)?;
+    let max_depth = read_u32(MAX_DEPTH_OFFSET)?;
+
+    // Sequence number, active index and buffer size, then the change log buffer and the rightmost
+    // proof.
+    let change_log = 32 + 32 * max_depth + 8;
+    let rightmost_proof = 32 * max_depth + 32 + 8;
+    let tree_len = 24 + max_buffer_size * change_log + rightmost_proof;
+
+    let canopy_nodes = data.len().checked_sub(TREE_HEADER_LEN + tree_len)? / 32;
+
+    // A canopy of depth d holds 2^(d+1) - 2 nodes.
+    Some((canopy_nodes + 2).ilog2().saturating_sub(1))
+}
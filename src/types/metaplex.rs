@@ -0,0 +1,296 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::commands::TOKEN_METADATA_ID;
+
+// ----Token Metadata Structs----
+
+pub const METADATA_PREFIX: &str = "metadata";
+pub const EDITION_SEED: &str = "edition";
+pub const TOKEN_RECORD_SEED: &str = "token_record";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum TokenStandard {
+    NonFungible,
+    FungibleAsset,
+    Fungible,
+    NonFungibleEdition,
+    ProgrammableNonFungible,
+    ProgrammableNonFungibleEdition,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Creator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Data {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<Creator>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Collection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Uses {
+    pub use_method: u8,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum CollectionDetails {
+    V1 { size: u64 },
+    V2 { padding: [u8; 8] },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum ProgrammableConfig {
+    V1 { rule_set: Option<Pubkey> },
+}
+
+/// Token Metadata `Metadata` account, up to the fields eigen needs. Accounts are zero padded, so
+/// the trailing optional fields of older accounts decode as `None`.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Metadata {
+    pub key: u8,
+    pub update_authority: Pubkey,
+    pub mint: Pubkey,
+    pub data: Data,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<TokenStandard>,
+    pub collection: Option<Collection>,
+    pub uses: Option<Uses>,
+    pub collection_details: Option<CollectionDetails>,
+    pub programmable_config: Option<ProgrammableConfig>,
+}
+
+impl Metadata {
+    /// Deserializes the leading fields, ignoring the padding after them.
+    pub fn from_bytes(mut data: &[u8]) -> std::io::Result<Self> {
+        Self::deserialize(&mut data)
+    }
+
+    pub fn is_programmable(&self) -> bool {
+        matches!(
+            self.token_standard,
+            Some(TokenStandard::ProgrammableNonFungible)
+                | Some(TokenStandard::ProgrammableNonFungibleEdition)
+        )
+    }
+
+    pub fn rule_set(&self) -> Option<Pubkey> {
+        match &self.programmable_config {
+            Some(ProgrammableConfig::V1 { rule_set }) => *rule_set,
+            None => None,
+        }
+    }
+
+    pub fn find_pda(mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                METADATA_PREFIX.as_bytes(),
+                TOKEN_METADATA_ID.as_ref(),
+                mint.as_ref(),
+            ],
+            &TOKEN_METADATA_ID,
+        )
+    }
+}
+
+/// Master or print edition PDA of a mint.
+pub fn find_edition_pda(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            METADATA_PREFIX.as_bytes(),
+            TOKEN_METADATA_ID.as_ref(),
+            mint.as_ref(),
+            EDITION_SEED.as_bytes(),
+        ],
+        &TOKEN_METADATA_ID,
+    )
+}
+
+/// Token record PDA of a programmable NFT token account.
+pub fn find_token_record_pda(mint: &Pubkey, token_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            METADATA_PREFIX.as_bytes(),
+            TOKEN_METADATA_ID.as_ref(),
+            mint.as_ref(),
+            TOKEN_RECORD_SEED.as_bytes(),
+            token_account.as_ref(),
+        ],
+        &TOKEN_METADATA_ID,
+    )
+}
+
+// ----MPL Core Structs----
+
+pub const CORE_ASSET_V1_KEY: u8 = 1;
+pub const CORE_PLUGIN_HEADER_V1_KEY: u8 = 3;
+pub const CORE_COLLECTION_V1_KEY: u8 = 5;
+/// Plugin type, and `Plugin` variant, of the royalties plugin.
+pub const CORE_ROYALTIES_PLUGIN: u8 = 0;
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum UpdateAuthority {
+    None,
+    Address(Pubkey),
+    Collection(Pubkey),
+}
+
+/// MPL Core `AssetV1` account, up to the update authority. Plugins stored after the base asset
+/// are not decoded.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CoreAsset {
+    pub key: u8,
+    pub owner: Pubkey,
+    pub update_authority: UpdateAuthority,
+}
+
+impl CoreAsset {
+    pub fn from_bytes(mut data: &[u8]) -> std::io::Result<Self> {
+        let asset = Self::deserialize(&mut data)?;
+
+        if asset.key != CORE_ASSET_V1_KEY {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Not an MPL Core asset",
+            ));
+        }
+
+        Ok(asset)
+    }
+
+    pub fn collection(&self) -> Option<Pubkey> {
+        match self.update_authority {
+            UpdateAuthority::Collection(collection) => Some(collection),
+            _ => None,
+        }
+    }
+}
+
+/// Base of an MPL Core `AssetV1`, read only to find where the plugins start.
+#[derive(BorshDeserialize)]
+struct CoreAssetBase {
+    _key: u8,
+    _owner: Pubkey,
+    _update_authority: UpdateAuthority,
+    _name: String,
+    _uri: String,
+    _seq: Option<u64>,
+}
+
+/// Base of an MPL Core `CollectionV1`, read only to find where the plugins start.
+#[derive(BorshDeserialize)]
+struct CoreCollectionBase {
+    _key: u8,
+    _update_authority: Pubkey,
+    _name: String,
+    _uri: String,
+    _num_minted: u32,
+    _current_size: u32,
+}
+
+#[derive(BorshDeserialize)]
+struct PluginHeader {
+    key: u8,
+    plugin_registry_offset: u64,
+}
+
+#[derive(BorshDeserialize)]
+enum PluginAuthority {
+    None,
+    Owner,
+    UpdateAuthority,
+    Address { _address: Pubkey },
+}
+
+#[derive(BorshDeserialize)]
+struct RegistryRecord {
+    plugin_type: u8,
+    _authority: PluginAuthority,
+    offset: u64,
+}
+
+/// Plugin registry, up to the internal plugins. External plugins are not decoded.
+#[derive(BorshDeserialize)]
+struct PluginRegistry {
+    _key: u8,
+    registry: Vec<RegistryRecord>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CoreCreator {
+    pub address: Pubkey,
+    pub percentage: u8,
+}
+
+/// Royalties plugin of a Core asset or collection, up to the creators.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CoreRoyalties {
+    pub basis_points: u16,
+    pub creators: Vec<CoreCreator>,
+}
+
+/// Royalties plugin of a Core asset or collection account, `None` if it has none.
+pub fn core_royalties(data: &[u8]) -> std::io::Result<Option<CoreRoyalties>> {
+    let invalid = |message: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+
+    let mut plugins = data;
+    match data.first() {
+        Some(&CORE_ASSET_V1_KEY) => {
+            CoreAssetBase::deserialize(&mut plugins)?;
+        }
+        Some(&CORE_COLLECTION_V1_KEY) => {
+            CoreCollectionBase::deserialize(&mut plugins)?;
+        }
+        _ => return Err(invalid("Not an MPL Core asset or collection")),
+    }
+
+    // The plugin header directly follows the base account, if there are any plugins.
+    if plugins.is_empty() {
+        return Ok(None);
+    }
+    let header = PluginHeader::deserialize(&mut plugins)?;
+    if header.key != CORE_PLUGIN_HEADER_V1_KEY {
+        return Ok(None);
+    }
+
+    let mut registry = data
+        .get(header.plugin_registry_offset as usize..)
+        .ok_or_else(|| invalid("Plugin registry offset out of bounds"))?;
+    let registry = PluginRegistry::deserialize(&mut registry)?;
+
+    for record in registry.registry {
+        if record.plugin_type != CORE_ROYALTIES_PLUGIN {
+            continue;
+        }
+
+        let mut plugin = data
+            .get(record.offset as usize..)
+            .ok_or_else(|| invalid("Plugin offset out of bounds"))?;
+        if u8::deserialize(&mut plugin)? != CORE_ROYALTIES_PLUGIN {
+            return Err(invalid(
+                "Royalties record does not point at a royalties plugin",
+            ));
+        }
+
+        return Ok(Some(CoreRoyalties::deserialize(&mut plugin)?));
+    }
+
+    Ok(None)
+}
@@ -1,3 +1,6 @@
+pub mod bubblegum;
+pub mod metaplex;
 pub mod raydium_clmm;
 pub mod raydium_cp;
 pub mod raydium_v4;
+pub mod tensor_escrow;
@@ -0,0 +1,121 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use sha2::{Digest, Sha256};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+use crate::commands::{Discriminator, TENSOR_ESCROW_ID};
+
+// ----Tensor Escrow Structs----
+
+pub const MARGIN_SEED: &str = "margin";
+
+/// Shared escrow that pools and bids draw SOL from, called a margin account by the program.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct MarginAccount {
+    pub discriminator: [u8; 8],
+    pub owner: Pubkey,
+    pub name: [u8; 32],
+    pub nr: u16,
+    pub bump: [u8; 1],
+    pub pools_attached: u32,
+    pub reserved: [u8; 64],
+}
+
+impl Discriminator for MarginAccount {}
+
+impl MarginAccount {
+    pub const LEN: usize = 8 + 32 + 32 + 2 + 1 + 4 + 64;
+
+    /// Offset of the owner, for memcmp filters.
+    pub const OWNER_OFFSET: usize = 8;
+
+    pub fn find_pda(owner: &Pubkey, nr: u16) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                MARGIN_SEED.as_bytes(),
+                find_tswap_pda().0.as_ref(),
+                owner.as_ref(),
+                &nr.to_le_bytes(),
+            ],
+            &TENSOR_ESCROW_ID,
+        )
+    }
+
+    /// The name with its zero padding removed.
+    pub fn name(&self) -> String {
+        String::from_utf8_lossy(&self.name)
+            .trim_end_matches('\0')
+            .to_string()
+    }
+}
+
+/// Program singleton that margin accounts are derived from.
+pub fn find_tswap_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[], &TENSOR_ESCROW_ID)
+}
+
+// ----Tensor Escrow Instructions----
+
+/// Accounts shared by every margin account instruction.
+#[derive(Clone, Copy, Debug)]
+pub struct MarginAccountInstruction {
+    pub margin_account: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct InitMarginAccountInstructionArgs {
+    pub margin_nr: u16,
+    pub name: [u8; 32],
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct MarginLamportsInstructionArgs {
+    pub lamports: u64,
+}
+
+impl MarginAccountInstruction {
+    pub fn init(&self, args: InitMarginAccountInstructionArgs) -> Instruction {
+        self.instruction("init_margin_account", args.try_to_vec().unwrap())
+    }
+
+    pub fn deposit(&self, args: MarginLamportsInstructionArgs) -> Instruction {
+        self.instruction("deposit_margin_account", args.try_to_vec().unwrap())
+    }
+
+    pub fn withdraw(&self, args: MarginLamportsInstructionArgs) -> Instruction {
+        self.instruction("withdraw_margin_account", args.try_to_vec().unwrap())
+    }
+
+    pub fn close(&self) -> Instruction {
+        self.instruction("close_margin_account", vec![])
+    }
+
+    fn instruction(&self, name: &str, args: Vec<u8>) -> Instruction {
+        let mut data = instruction_discriminator(name).to_vec();
+        data.extend(args);
+
+        Instruction {
+            program_id: TENSOR_ESCROW_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(find_tswap_pda().0, false),
+                AccountMeta::new(self.margin_account, false),
+                AccountMeta::new(self.owner, true),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+            data,
+        }
+    }
+}
+
+/// Anchor instruction discriminator: the first 8 bytes of the hash of `global:<name>`.
+pub fn instruction_discriminator(name: &str) -> [u8; 8] {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("global:{name}"));
+
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hasher.finalize()[..8]);
+    discriminator
+}
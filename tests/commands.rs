@@ -0,0 +1,3106 @@
+use std::{path::PathBuf, str::FromStr, sync::Arc};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    nonce::{
+        state::{Data as NonceData, DurableNonce, Versions as NonceVersions},
+        State as NonceState,
+    },
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction, system_program,
+};
+use tensor_amm::{
+    accounts::{NftDepositReceipt, Pool},
+    instructions::{CreatePoolInstructionArgs, DepositSolInstructionArgs},
+    programs::TENSOR_AMM_ID,
+    types::{CurveType, PoolConfig, PoolStats, PoolType},
+    Currency, NullableAddress, NullableU16,
+};
+use tensor_eigen::{
+    args::PoolSort,
+    commands::{
+        apply_migration, apply_plan, associated_token_address, build_merkle_proofs,
+        buy_listing_with_config, cancel_bid_with_config, close_escrow_with_config,
+        close_expired_orders, close_order_with_config, close_pool_with_config, compare_on_chain,
+        create_escrow_with_config, create_order_with_config, create_pool_args,
+        create_pool_with_config, decode_account, delist_nft_with_config, diff_pool_edit,
+        download_account, edit_bid_with_config, edit_listing_with_config, edit_order_with_config,
+        edit_pool_with_config, fetch_bids, fetch_decoded_account, fetch_escrow_details,
+        fetch_listings, fetch_mint_proof, fetch_order_details, fetch_orders, fetch_pools,
+        fetch_shard_balances, fetch_whitelist_pairs, find_expired_orders, find_fee_vault_pda,
+        find_order_state_pda, find_order_vault_pda, fund_fee_shards, handle_merkle_build,
+        handle_merkle_proof, init_mint_proofs, list_nft_with_config, place_bid_with_config,
+        plan_migration, plan_mint_proofs, plan_pools, pool_report, quote_pool, quoted_price,
+        read_comparison_files, read_mints, simulate_settlement, sort_pools,
+        submit_signed_transactions, take_bid_with_config, transfer_escrow_sol, transfer_pool_nfts,
+        transfer_pool_sol, verify_merkle_proof, write_comparison_report, write_report_csv,
+        AssetStandard, BidFilters, BidTarget, BidTerms, BuyTerms, ComparisonResult, CurrencyFilter,
+        DecodedAccount, EditPoolArgs, EditPoolFlags, ListingFilters, ListingTerms, MarketBids,
+        MarketListings, MerkleBuildParams, MerkleProofParams, MigrationAction, Mismatch,
+        MissingWhitelistPair, OrderFilters, OrderTerms, PlanAction, PoolConfigFlags, PoolDirection,
+        PoolEntry, PoolFilters, PoolManifest, PriceLockOrders, QuoteStep, Royalties, TakeBidTerms,
+        ACCOUNT_COMPRESSION_ID, MPL_CORE_ID, TENSOR_ESCROW_ID, TENSOR_PRICE_LOCK_ID, TOKEN_2022_ID,
+        TOKEN_AUTH_RULES_ID, TOKEN_ID, TOKEN_METADATA_ID, TOKEN_PROGRAM_IDS,
+        WHITELIST_SIGNER_PUBKEY,
+    },
+    discriminators::Discriminator,
+    formatting::CustomFormat,
+    rpc::{DasAsset, DasAssetProof, FixtureRpc, Rpc},
+    setup::CliConfig,
+    transactions::{
+        missing_signers, read_signed_transactions, send_instructions, write_signed_transactions,
+    },
+    types::{
+        bubblegum::{data_hash, find_tree_authority_pda, MetadataArgs},
+        metaplex::{
+            find_token_record_pda, CoreAsset, Creator, Data, Metadata, ProgrammableConfig,
+            TokenStandard, UpdateAuthority,
+        },
+        tensor_escrow::{instruction_discriminator, MarginAccount},
+    },
+    FEE_SHARDS,
+};
+use tensor_marketplace::{
+    accounts::{BidState, ListState},
+    programs::TENSOR_MARKETPLACE_ID,
+    types::Target,
+};
+use tensor_price_lock::{
+    accounts::{OrderNftReceipt, OrderState},
+    instructions::UpsertOrderInstructionArgs,
+    types::OrderType,
+};
+use tensor_whitelist::{
+    accounts::{MintProofV2, Whitelist, WhitelistV2},
+    programs::TENSOR_WHITELIST_ID,
+    types::{Condition, Mode, State},
+};
+
+// Whitelist v1 and the v2 derived from it under the default namespace, see `tests/fixtures`.
+const FIXTURE_V1: &str = "4diKqPoh9PkJEGxWRL3aernYgcMXzBzfkVXCPD8b8Ge4";
+const FIXTURE_V2: &str = "6wfuVPPt2QiqrkhegyvDe8j1xKH8woDXZK71XWuYVSio";
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+}
+
+fn config(rpc: FixtureRpc) -> (Arc<FixtureRpc>, CliConfig) {
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), Keypair::new()).unwrap();
+
+    (rpc, config)
+}
+
+fn fixture_rpc() -> FixtureRpc {
+    let rpc = FixtureRpc::new();
+    rpc.load_dir(fixtures_dir()).unwrap();
+    rpc
+}
+
+fn program_account(owner: Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: 1_000_000,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn whitelist_v1(uuid: [u8; 32], voc: Option<Pubkey>, fvc: Option<Pubkey>) -> (Pubkey, Account) {
+    let whitelist = Whitelist {
+        discriminator: Whitelist::discriminator(),
+        version: 1,
+        bump: 255,
+        verified: false,
+        root_hash: [0; 32],
+        uuid,
+        name: [0; 32],
+        frozen: false,
+        voc,
+        fvc,
+        reserved: [0; 64],
+    };
+
+    let mut data = whitelist.try_to_vec().unwrap();
+    data.resize(Whitelist::LEN, 0);
+
+    (
+        Whitelist::find_pda(uuid).0,
+        program_account(TENSOR_WHITELIST_ID, data),
+    )
+}
+
+fn whitelist_v2(uuid: [u8; 32], conditions: Vec<Condition>) -> (Pubkey, Account) {
+    let whitelist = WhitelistV2 {
+        discriminator: WhitelistV2::discriminator(),
+        version: 1,
+        bump: 255,
+        uuid,
+        state: State::Unfrozen,
+        update_authority: WHITELIST_SIGNER_PUBKEY,
+        namespace: WHITELIST_SIGNER_PUBKEY,
+        freeze_authority: Pubkey::default(),
+        conditions,
+    };
+
+    (
+        WhitelistV2::find_pda(&WHITELIST_SIGNER_PUBKEY, uuid).0,
+        program_account(TENSOR_WHITELIST_ID, whitelist.try_to_vec().unwrap()),
+    )
+}
+
+fn pool(owner: Pubkey) -> Pool {
+    Pool {
+        discriminator: Pool::discriminator(),
+        version: 1,
+        bump: [255],
+        pool_id: [1; 32],
+        created_at: 0,
+        updated_at: 0,
+        expiry: 0,
+        owner,
+        whitelist: Pubkey::from_str(FIXTURE_V2).unwrap(),
+        rent_payer: owner,
+        currency: Currency::sol(),
+        amount: 0,
+        price_offset: 0,
+        nfts_held: 0,
+        stats: PoolStats::default(),
+        shared_escrow: NullableAddress::none(),
+        cosigner: NullableAddress::none(),
+        maker_broker: NullableAddress::none(),
+        max_taker_sell_count: 0,
+        config: PoolConfig {
+            pool_type: PoolType::Trade,
+            curve_type: CurveType::Linear,
+            starting_price: 1_000_000_000,
+            delta: 100_000_000,
+            mm_compound_fees: false,
+            mm_fee_bps: NullableU16::new(100),
+        },
+        reserved: [0; 100],
+    }
+}
+
+fn pool_account(pool: &Pool) -> Account {
+    let mut data = pool.try_to_vec().unwrap();
+    data.resize(Pool::LEN, 0);
+
+    program_account(TENSOR_AMM_ID, data)
+}
+
+/// Adds an NFT of the given standard owned by `owner` and returns its mint.
+fn nft(rpc: &FixtureRpc, owner: &Pubkey, standard: AssetStandard) -> Pubkey {
+    let mint = Pubkey::new_unique();
+
+    if standard == AssetStandard::Core {
+        let asset = CoreAsset {
+            key: 1,
+            owner: *owner,
+            update_authority: UpdateAuthority::Collection(Pubkey::new_unique()),
+        };
+        let mut data = asset.try_to_vec().unwrap();
+        // Name, uri and seq, without plugins.
+        data.extend(
+            ("Core".to_string(), String::new(), None::<u64>)
+                .try_to_vec()
+                .unwrap(),
+        );
+        rpc.insert(mint, program_account(MPL_CORE_ID, data));
+        return mint;
+    }
+
+    let token_program = if standard == AssetStandard::Token2022 {
+        TOKEN_2022_ID
+    } else {
+        TOKEN_ID
+    };
+    rpc.insert(mint, program_account(token_program, vec![0; 82]));
+
+    let metadata = Metadata {
+        key: 4,
+        update_authority: Pubkey::new_unique(),
+        mint,
+        data: Data {
+            name: "NFT".to_string(),
+            symbol: String::new(),
+            uri: String::new(),
+            seller_fee_basis_points: 500,
+            creators: None,
+        },
+        primary_sale_happened: true,
+        is_mutable: true,
+        edition_nonce: None,
+        token_standard: Some(match standard {
+            AssetStandard::Programmable => TokenStandard::ProgrammableNonFungible,
+            _ => TokenStandard::NonFungible,
+        }),
+        collection: None,
+        uses: None,
+        collection_details: None,
+        programmable_config: (standard == AssetStandard::Programmable).then_some(
+            ProgrammableConfig::V1 {
+                rule_set: Some(Pubkey::new_unique()),
+            },
+        ),
+    };
+    let mut data = metadata.try_to_vec().unwrap();
+    data.resize(679, 0);
+    rpc.insert(
+        Metadata::find_pda(&mint).0,
+        program_account(TOKEN_METADATA_ID, data),
+    );
+
+    // The owner's associated token account holds the NFT.
+    let mut token_account = vec![0; 165];
+    token_account[..32].copy_from_slice(mint.as_ref());
+    token_account[32..64].copy_from_slice(owner.as_ref());
+    token_account[64..72].copy_from_slice(&1u64.to_le_bytes());
+    rpc.insert(
+        associated_token_address(owner, &mint, &token_program),
+        program_account(token_program, token_account),
+    );
+
+    mint
+}
+
+#[test]
+fn test_decode_fixture_accounts() {
+    let (_, config) = config(fixture_rpc());
+
+    let v1 = Pubkey::from_str(FIXTURE_V1).unwrap();
+    match fetch_decoded_account(&config, &v1, false).unwrap() {
+        DecodedAccount::Whitelist(whitelist) => assert_eq!(whitelist.uuid, [7; 32]),
+        _ => panic!("expected a whitelist v1"),
+    }
+
+    let v2 = Pubkey::from_str(FIXTURE_V2).unwrap();
+    match fetch_decoded_account(&config, &v2, false).unwrap() {
+        DecodedAccount::WhitelistV2(whitelist) => {
+            assert_eq!(whitelist.conditions.len(), 1);
+            assert_eq!(whitelist.conditions[0].mode, Mode::VOC);
+        }
+        _ => panic!("expected a whitelist v2"),
+    }
+
+    match fetch_decoded_account(&config, &v2, true).unwrap() {
+        DecodedAccount::Raw(data) => assert_eq!(&data[..8], &WhitelistV2::discriminator()),
+        _ => panic!("expected raw data"),
+    }
+}
+
+#[test]
+fn test_decode_missing_account_as_empty_wallet() {
+    let (_, config) = config(FixtureRpc::new());
+
+    match fetch_decoded_account(&config, &Pubkey::new_unique(), false).unwrap() {
+        DecodedAccount::Wallet(entry) => {
+            assert_eq!(entry.account.lamports, 0);
+            assert_eq!(entry.account.owner, system_program::ID);
+        }
+        _ => panic!("expected an empty wallet"),
+    }
+}
+
+#[test]
+fn test_decode_token_and_unknown_accounts() {
+    // Mint account size, owned by the token program.
+    let mint = program_account(TOKEN_PROGRAM_IDS[0], vec![0; 82]);
+    assert!(matches!(
+        decode_account(Pubkey::new_unique(), mint).unwrap(),
+        DecodedAccount::Token(_)
+    ));
+
+    let unknown = program_account(Pubkey::new_unique(), vec![1; 16]);
+    assert!(matches!(
+        decode_account(Pubkey::new_unique(), unknown).unwrap(),
+        DecodedAccount::Unknown(_)
+    ));
+}
+
+#[test]
+fn test_download_writes_json() {
+    let (_, config) = config(fixture_rpc());
+
+    let output_dir = std::env::temp_dir().join(format!("eigen-download-{}", Pubkey::new_unique()));
+    std::fs::create_dir_all(&output_dir).unwrap();
+
+    let v2 = Pubkey::from_str(FIXTURE_V2).unwrap();
+    let downloaded = download_account(&config, &v2, Some(output_dir.clone())).unwrap();
+
+    assert_eq!(downloaded.path, output_dir.join("whitelist_v2.json"));
+
+    let written: serde_json::Value =
+        serde_json::from_reader(std::fs::File::open(&downloaded.path).unwrap()).unwrap();
+    assert_eq!(written, downloaded.data);
+    assert_eq!(
+        written["namespace"].as_str().unwrap(),
+        WHITELIST_SIGNER_PUBKEY.to_string()
+    );
+
+    std::fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_download_unsupported_account() {
+    let address = Pubkey::new_unique();
+    let rpc =
+        FixtureRpc::new().with_account(address, program_account(Pubkey::new_unique(), vec![9; 32]));
+    let (_, config) = config(rpc);
+
+    assert!(download_account(&config, &address, Some(std::env::temp_dir())).is_err());
+}
+
+#[test]
+fn test_compare_whitelists() {
+    let rpc = fixture_rpc();
+
+    // Expects an FVC condition but the v2 has a VOC one.
+    let fvc = Pubkey::new_unique();
+    let (v1, account) = whitelist_v1([8; 32], None, Some(fvc));
+    rpc.insert(v1, account);
+    let (v2, account) = whitelist_v2(
+        [8; 32],
+        vec![Condition {
+            mode: Mode::VOC,
+            value: fvc,
+        }],
+    );
+    rpc.insert(v2, account);
+
+    // No v2 at all.
+    let (missing_v1, account) = whitelist_v1([9; 32], Some(Pubkey::new_unique()), None);
+    rpc.insert(missing_v1, account);
+
+    let (_, config) = config(rpc);
+    let report = compare_on_chain(&config, None, None, None).unwrap();
+
+    assert_eq!(report.cluster, "unknown");
+    assert_eq!(report.v1_count, 3);
+    assert_eq!(report.v2_count, 2);
+    assert_eq!(report.missing.len(), 1);
+    assert_eq!(report.missing[0].v1_pubkey, missing_v1);
+    assert_eq!(report.results.len(), 2);
+
+    let mismatches: Vec<_> = report.mismatches().collect();
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].whitelist_v1, v1);
+    assert!(mismatches[0].has(Mismatch::Fvc));
+    assert!(mismatches[0].has(Mismatch::UnexpectedV2Conditions));
+
+    let fixture = report
+        .results
+        .iter()
+        .find(|result| result.whitelist_v1 == Pubkey::from_str(FIXTURE_V1).unwrap())
+        .unwrap();
+    assert!(fixture.is_match());
+    assert!(!report.is_clean());
+}
+
+#[test]
+fn test_compare_whitelists_from_list() {
+    let rpc = fixture_rpc();
+
+    // More v1s than fit in a single getMultipleAccounts request.
+    let uuids: Vec<[u8; 32]> = (0..150u8).map(|i| [i; 32]).collect();
+    for uuid in &uuids[1..] {
+        let voc = Pubkey::new_unique();
+        let (v1, account) = whitelist_v1(*uuid, Some(voc), None);
+        rpc.insert(v1, account);
+        let (v2, account) = whitelist_v2(
+            *uuid,
+            vec![Condition {
+                mode: Mode::VOC,
+                value: voc,
+            }],
+        );
+        rpc.insert(v2, account);
+    }
+
+    // The list holds UUIDs, the first of which has no whitelist at all.
+    let list = std::env::temp_dir().join(format!("eigen-list-{}.json", Pubkey::new_unique()));
+    let list_uuids: Vec<Pubkey> = uuids
+        .iter()
+        .map(|uuid| Pubkey::new_from_array(*uuid))
+        .collect();
+    std::fs::write(&list, serde_json::to_string(&list_uuids).unwrap()).unwrap();
+
+    let (_, config) = config(rpc);
+    let report = compare_on_chain(&config, Some(list.clone()), None, None).unwrap();
+    std::fs::remove_file(list).unwrap();
+
+    // The fixture v1 isn't listed.
+    assert_eq!(report.v1_count, 149);
+    assert_eq!(report.results.len(), 149);
+    assert!(report.is_clean());
+}
+
+#[test]
+fn test_compare_report() {
+    let rpc = fixture_rpc();
+
+    let fvc = Pubkey::new_unique();
+    let (v1, account) = whitelist_v1([8; 32], None, Some(fvc));
+    rpc.insert(v1, account);
+    let (_, account) = whitelist_v2(
+        [8; 32],
+        vec![Condition {
+            mode: Mode::VOC,
+            value: fvc,
+        }],
+    );
+    rpc.insert(
+        WhitelistV2::find_pda(&WHITELIST_SIGNER_PUBKEY, [8; 32]).0,
+        account,
+    );
+
+    let (missing_v1, account) = whitelist_v1([9; 32], Some(Pubkey::new_unique()), None);
+    rpc.insert(missing_v1, account);
+
+    let (_, config) = config(rpc);
+    let mut report = compare_on_chain(&config, None, None, None).unwrap();
+
+    let summary = report.summary();
+    assert_eq!(summary.missing, 1);
+    assert_eq!(summary.matched, 1);
+    assert_eq!(summary.mismatched, 1);
+    assert_eq!(summary.by_kind[&Mismatch::V2Missing], 1);
+    assert_eq!(summary.by_kind[&Mismatch::Fvc], 1);
+    assert_eq!(summary.by_kind[&Mismatch::UnexpectedV2Conditions], 1);
+
+    let dir = std::env::temp_dir().join(format!("eigen-report-{}", Pubkey::new_unique()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let files = write_comparison_report(&mut report, &dir, None).unwrap();
+    assert_eq!(files.len(), 3);
+    assert!(report.previous.is_none());
+
+    let csv = std::fs::read_to_string(dir.join("unknown_report.csv")).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "whitelist_v1,whitelist_v2,status,mismatch_count,mismatches"
+    );
+    assert_eq!(lines.len(), 4);
+    // Missing v2s first, then mismatches, with the details quoted since they hold commas.
+    assert!(lines[1].starts_with(&format!("{},", missing_v1)));
+    assert!(lines[2].starts_with(&format!("{},", v1)));
+    assert!(lines[2].contains(",mismatch,2,\"Fvc: expected FVC"));
+    assert!(lines[3].ends_with(",match,0,"));
+
+    let html = std::fs::read_to_string(dir.join("unknown_report.html")).unwrap();
+    assert!(html.contains(&format!(
+        "<a href=\"https://explorer.solana.com/address/{0}\"><code>{0}</code></a>",
+        v1
+    )));
+    assert!(html.contains("<tr><th>Fvc</th><td>1</td></tr>"));
+
+    // The next report in the same directory shows the progress since this one.
+    report.missing.clear();
+    write_comparison_report(&mut report, &dir, None).unwrap();
+    assert_eq!(report.previous.as_ref().unwrap().missing, 1);
+
+    let html = std::fs::read_to_string(dir.join("unknown_report.html")).unwrap();
+    assert!(html.contains("<tr><th>Missing v2s</th><td>0 (-1)</td></tr>"));
+    assert!(html.contains("<tr><th>V2Missing</th><td>0 (-1)</td></tr>"));
+    assert!(report.custom_format().contains("0 missing v2s (-1)"));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_shard_balances() {
+    let rpc = FixtureRpc::new();
+    let rent_exempt = rpc.get_minimum_balance_for_rent_exemption(0).unwrap();
+
+    for shard in &FEE_SHARDS[..200] {
+        rpc.insert(
+            Pubkey::from_str(shard).unwrap(),
+            Account {
+                lamports: rent_exempt,
+                data: vec![],
+                owner: system_program::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let (_, config) = config(rpc);
+    let balances = fetch_shard_balances(&config).unwrap();
+
+    assert_eq!(balances.balances.len(), FEE_SHARDS.len());
+    assert_eq!(balances.funded_count(), 200);
+}
+
+#[test]
+fn test_fund_shards() {
+    let rpc = FixtureRpc::new();
+    let rent_exempt = rpc.get_minimum_balance_for_rent_exemption(0).unwrap();
+
+    // Every shard but the last 40 is funded, one of those only partially.
+    for (i, shard) in FEE_SHARDS[..216].iter().enumerate() {
+        rpc.insert(
+            Pubkey::from_str(shard).unwrap(),
+            Account {
+                lamports: if i == 0 { rent_exempt - 1 } else { rent_exempt },
+                data: vec![],
+                owner: system_program::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let (rpc, config) = config(rpc);
+    let funded = fund_fee_shards(&config, &[]).unwrap();
+
+    assert_eq!(funded.funded.len(), 41);
+    assert_eq!(funded.funded[0], Pubkey::from_str(FEE_SHARDS[0]).unwrap());
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), funded.signatures.len());
+
+    let transfers: usize = sent.iter().map(|tx| tx.message.instructions().len()).sum();
+    assert_eq!(transfers, 41);
+
+    // Nothing left to fund once the shards hold the rent-exempt minimum.
+    for shard in &funded.funded {
+        rpc.insert(
+            *shard,
+            Account {
+                lamports: rent_exempt,
+                data: vec![],
+                owner: system_program::ID,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    let funded = fund_fee_shards(&config, &[]).unwrap();
+    assert!(funded.funded.is_empty());
+    assert!(funded.signatures.is_empty());
+}
+
+#[test]
+fn test_close_pool_refuses_with_blockers() {
+    let owner = Keypair::new();
+    let address = Pubkey::new_unique();
+
+    let mut pool = pool(owner.pubkey());
+    pool.nfts_held = 2;
+    pool.amount = 1_500_000_000;
+
+    // Someone else's pool.
+    let (rpc, other) = config(FixtureRpc::new().with_account(address, pool_account(&pool)));
+    let error = close_pool_with_config(&other, &address)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(&format!("owned by {}", pool.owner)));
+    assert!(rpc.sent_transactions().is_empty());
+
+    let rpc = Arc::new(FixtureRpc::new().with_account(address, pool_account(&pool)));
+    let config = CliConfig::with_client(rpc.clone(), owner).unwrap();
+
+    let error = close_pool_with_config(&config, &address)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("holds 2 NFTs"));
+    assert!(error.contains("holds 1.5 SOL"));
+    assert!(!error.contains("owned by"));
+    assert!(rpc.sent_transactions().is_empty());
+}
+
+#[test]
+fn test_close_pool() {
+    let owner = Keypair::new();
+    let address = Pubkey::new_unique();
+
+    // SOL held by a shared escrow pool lives in the escrow and doesn't block closing.
+    let escrow = Pubkey::new_unique();
+    let mut pool = pool(owner.pubkey());
+    pool.amount = 1_000_000_000;
+    pool.shared_escrow = NullableAddress::new(escrow);
+
+    let rpc = Arc::new(FixtureRpc::new().with_account(address, pool_account(&pool)));
+    let config = CliConfig::with_client(rpc.clone(), owner).unwrap();
+
+    let closed = close_pool_with_config(&config, &address).unwrap();
+    assert_eq!(closed.rent_reclaimed, 1_000_000);
+    assert_eq!(closed.rent_payer, pool.rent_payer);
+    assert_eq!(closed.shared_escrow, Some(escrow));
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(closed.signatures, vec![sent[0].signatures[0]]);
+
+    let keys = sent[0].message.static_account_keys();
+    let instructions = sent[0].message.instructions();
+    assert_eq!(instructions.len(), 1);
+    assert_eq!(
+        keys[instructions[0].program_id_index as usize],
+        TENSOR_AMM_ID
+    );
+    assert!(keys.contains(&address));
+}
+
+#[test]
+fn test_close_shared_escrow_pool() {
+    let owner = Keypair::new();
+    let address = Pubkey::new_unique();
+
+    let (escrow, escrow_account) = margin_account(owner.pubkey(), 0, 1, 3_000_000_000);
+    let mut pool = pool(owner.pubkey());
+    pool.shared_escrow = NullableAddress::new(escrow);
+
+    let rpc = Arc::new(
+        FixtureRpc::new()
+            .with_account(address, pool_account(&pool))
+            .with_account(escrow, escrow_account),
+    );
+    let config = CliConfig::with_client(rpc.clone(), owner).unwrap();
+
+    // The escrow keeps the SOL, so the output names it and how to withdraw from it.
+    let closed = close_pool_with_config(&config, &address).unwrap();
+    assert_eq!(closed.shared_escrow, Some(escrow));
+    assert!(closed
+        .escrow_available
+        .is_some_and(|available| available > 0));
+
+    let output = closed.custom_format();
+    assert!(output.contains(&format!("shared escrow {}", escrow)));
+    assert!(output.contains(&format!("eigen escrow withdraw {} <AMOUNT>", escrow)));
+    assert_eq!(rpc.sent_transactions().len(), 1);
+}
+
+#[test]
+fn test_deposit_sol() {
+    let owner = Keypair::new();
+    let address = Pubkey::new_unique();
+    let pool = pool(owner.pubkey());
+
+    let rpc = Arc::new(FixtureRpc::new().with_account(address, pool_account(&pool)));
+    let config = CliConfig::with_client(rpc.clone(), owner).unwrap();
+
+    let deposited =
+        transfer_pool_sol(&config, &address, PoolDirection::Deposit, 2_000_000_000).unwrap();
+    assert_eq!(deposited.lamports, 2_000_000_000);
+    assert_eq!(deposited.balance_before, 0);
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 1);
+
+    let keys = sent[0].message.static_account_keys();
+    let ix = &sent[0].message.instructions()[0];
+    assert_eq!(keys[ix.program_id_index as usize], TENSOR_AMM_ID);
+    // The instruction data ends with the borsh encoded args.
+    assert!(ix.data.ends_with(
+        &DepositSolInstructionArgs {
+            lamports: 2_000_000_000
+        }
+        .try_to_vec()
+        .unwrap()
+    ));
+}
+
+#[test]
+fn test_pool_sol_validation() {
+    let owner = Keypair::new();
+    let address = Pubkey::new_unique();
+
+    let mut nft_pool = pool(owner.pubkey());
+    nft_pool.config.pool_type = PoolType::NFT;
+
+    let mut trade_pool = pool(owner.pubkey());
+    trade_pool.amount = 1_000_000_000;
+
+    let rpc = Arc::new(FixtureRpc::new().with_account(address, pool_account(&nft_pool)));
+    let config = CliConfig::with_client(rpc.clone(), owner.insecure_clone()).unwrap();
+
+    let error = transfer_pool_sol(&config, &address, PoolDirection::Deposit, 1)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("NFT pools"));
+
+    rpc.insert(address, pool_account(&trade_pool));
+
+    let error = transfer_pool_sol(&config, &address, PoolDirection::Withdraw, 1_000_000_001)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("only holds 1 SOL"));
+    assert!(rpc.sent_transactions().is_empty());
+
+    let withdrawn =
+        transfer_pool_sol(&config, &address, PoolDirection::Withdraw, 1_000_000_000).unwrap();
+    assert_eq!(withdrawn.balance_before, 1_000_000_000);
+    assert_eq!(rpc.sent_transactions().len(), 1);
+}
+
+#[test]
+fn test_deposit_nfts_across_standards() {
+    let owner = Keypair::new();
+    let address = Pubkey::new_unique();
+    let rpc = fixture_rpc();
+    rpc.insert(address, pool_account(&pool(owner.pubkey())));
+
+    let standards = [
+        AssetStandard::Legacy,
+        AssetStandard::Programmable,
+        AssetStandard::Token2022,
+        AssetStandard::Core,
+    ];
+    let mints: Vec<Pubkey> = standards
+        .iter()
+        .map(|standard| nft(&rpc, &owner.pubkey(), *standard))
+        .collect();
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), owner.insecure_clone()).unwrap();
+
+    let deposited =
+        transfer_pool_nfts(&config, &address, PoolDirection::Deposit, &mints, &[]).unwrap();
+
+    let detected: Vec<AssetStandard> = deposited.assets.iter().map(|a| a.standard).collect();
+    assert_eq!(detected, standards);
+    assert!(deposited.assets[1].rule_set.is_some());
+    assert!(deposited.assets[3].collection.is_some());
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), deposited.signatures.len());
+
+    let instructions: Vec<_> = sent
+        .iter()
+        .flat_map(|tx| {
+            let keys = tx.message.static_account_keys().to_vec();
+            tx.message.instructions().iter().map(move |ix| {
+                let accounts: Vec<Pubkey> = ix.accounts.iter().map(|i| keys[*i as usize]).collect();
+                (keys[ix.program_id_index as usize], accounts)
+            })
+        })
+        .collect();
+    assert_eq!(instructions.len(), 4);
+
+    for (mint, (program, accounts)) in mints.iter().zip(&instructions) {
+        assert_eq!(*program, TENSOR_AMM_ID);
+        assert!(accounts.contains(mint));
+        assert!(accounts.contains(&NftDepositReceipt::find_pda(mint, &address).0));
+    }
+
+    // The pNFT moves through token records with its authorization rules.
+    let pnft = &deposited.assets[1];
+    let owner_ta = associated_token_address(&owner.pubkey(), &pnft.mint, &TOKEN_ID);
+    assert!(instructions[1]
+        .1
+        .contains(&find_token_record_pda(&pnft.mint, &owner_ta).0));
+    assert!(instructions[1].1.contains(&pnft.rule_set.unwrap()));
+    assert!(instructions[1].1.contains(&TOKEN_AUTH_RULES_ID));
+
+    // Token-2022 uses its own token program and Core passes the collection.
+    assert!(instructions[2].1.contains(&TOKEN_2022_ID));
+    assert!(instructions[3]
+        .1
+        .contains(&deposited.assets[3].collection.unwrap()));
+}
+
+#[test]
+fn test_pool_nft_preflight() {
+    let owner = Keypair::new();
+    let address = Pubkey::new_unique();
+    let rpc = fixture_rpc();
+    rpc.insert(address, pool_account(&pool(owner.pubkey())));
+
+    // Held by someone else.
+    let others = nft(&rpc, &Pubkey::new_unique(), AssetStandard::Legacy);
+    let others_core = nft(&rpc, &Pubkey::new_unique(), AssetStandard::Core);
+    let owned = nft(&rpc, &owner.pubkey(), AssetStandard::Legacy);
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), owner.insecure_clone()).unwrap();
+
+    let error = transfer_pool_nfts(
+        &config,
+        &address,
+        PoolDirection::Deposit,
+        &[others, others_core, owned],
+        &[],
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains(&format!(
+        "{} is not in the associated token account",
+        others
+    )));
+    assert!(error.contains(&format!("{} is not owned by", others_core)));
+    assert!(!error.contains(&owned.to_string()));
+
+    // Never deposited.
+    let error = transfer_pool_nfts(&config, &address, PoolDirection::Withdraw, &[owned], &[])
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(&format!("{} is not deposited in this pool", owned)));
+
+    let mut token_pool = pool(owner.pubkey());
+    token_pool.config.pool_type = PoolType::Token;
+    rpc.insert(address, pool_account(&token_pool));
+    assert!(transfer_pool_nfts(&config, &address, PoolDirection::Deposit, &[owned], &[]).is_err());
+
+    assert!(rpc.sent_transactions().is_empty());
+}
+
+#[test]
+fn test_withdraw_nfts_in_bulk() {
+    let owner = Keypair::new();
+    let address = Pubkey::new_unique();
+    let rpc = fixture_rpc();
+
+    let mut pool = pool(owner.pubkey());
+    pool.nfts_held = 20;
+    rpc.insert(address, pool_account(&pool));
+
+    let mints: Vec<Pubkey> = (0..20)
+        .map(|_| {
+            let mint = nft(&rpc, &owner.pubkey(), AssetStandard::Token2022);
+            rpc.insert(
+                NftDepositReceipt::find_pda(&mint, &address).0,
+                program_account(TENSOR_AMM_ID, vec![0; 8]),
+            );
+            mint
+        })
+        .collect();
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), owner).unwrap();
+
+    let withdrawn =
+        transfer_pool_nfts(&config, &address, PoolDirection::Withdraw, &mints, &[]).unwrap();
+
+    let sent = rpc.sent_transactions();
+    assert!(sent.len() > 1);
+    assert_eq!(withdrawn.signatures.len(), sent.len());
+
+    let count: usize = sent.iter().map(|tx| tx.message.instructions().len()).sum();
+    assert_eq!(count, 20);
+}
+
+#[test]
+fn test_list_pools() {
+    let rpc = fixture_rpc();
+
+    let owner = Pubkey::new_unique();
+    let whitelist = Pubkey::new_unique();
+    let cosigner = Pubkey::new_unique();
+    let escrow = Pubkey::new_unique();
+
+    let mut pools = vec![];
+    for i in 0..4u64 {
+        let mut pool = pool(owner);
+        pool.pool_id = [i as u8; 32];
+        pool.amount = i * 1_000_000_000;
+        pool.config.starting_price = (4 - i) * 100_000_000;
+        pool.expiry = i64::MAX;
+        pools.push(pool);
+    }
+    pools[0].whitelist = whitelist;
+    pools[1].cosigner = NullableAddress::new(cosigner);
+    pools[2].shared_escrow = NullableAddress::new(escrow);
+    pools[3].config.pool_type = PoolType::Token;
+    pools[3].config.curve_type = CurveType::Exponential;
+    pools[3].expiry = 0;
+
+    // Someone else's pool on the same whitelist.
+    let mut other = pool(Pubkey::new_unique());
+    other.whitelist = whitelist;
+    pools.push(other);
+
+    for pool in &pools {
+        rpc.insert(
+            Pool::find_pda(&pool.owner, pool.pool_id).0,
+            pool_account(pool),
+        );
+    }
+    let (_, config) = config(rpc);
+
+    let list = |filters: PoolFilters| fetch_pools(&config, &filters).unwrap();
+
+    assert_eq!(list(PoolFilters::default()).len(), 5);
+
+    let owned = list(PoolFilters {
+        owner: Some(owner),
+        ..Default::default()
+    });
+    assert_eq!(owned.len(), 4);
+    assert!(owned.iter().all(|entry| entry.pool.owner == owner));
+
+    assert_eq!(
+        list(PoolFilters {
+            whitelist: Some(whitelist),
+            ..Default::default()
+        })
+        .len(),
+        2
+    );
+
+    let cosigned = list(PoolFilters {
+        cosigner: Some(cosigner),
+        ..Default::default()
+    });
+    assert_eq!(cosigned.len(), 1);
+    assert_eq!(cosigned[0].pool.pool_id, [1; 32]);
+
+    let shared = list(PoolFilters {
+        owner: Some(owner),
+        shared_escrow: Some(escrow),
+        ..Default::default()
+    });
+    assert_eq!(shared.len(), 1);
+    assert_eq!(shared[0].pool.pool_id, [2; 32]);
+
+    // Local filters.
+    let token = list(PoolFilters {
+        pool_type: Some(PoolType::Token),
+        curve_type: Some(CurveType::Exponential),
+        ..Default::default()
+    });
+    assert_eq!(token.len(), 1);
+    // Token pools quote the price they pay.
+    assert_eq!(token[0].price, Some(100_000_000));
+
+    let mut active = list(PoolFilters {
+        owner: Some(owner),
+        active: true,
+        ..Default::default()
+    });
+    assert_eq!(active.len(), 3);
+
+    sort_pools(&mut active, PoolSort::Amount, true);
+    let amounts: Vec<u64> = active.iter().map(|entry| entry.pool.amount).collect();
+    assert_eq!(amounts, vec![2_000_000_000, 1_000_000_000, 0]);
+
+    sort_pools(&mut active, PoolSort::Price, false);
+    let prices: Vec<Option<u64>> = active.iter().map(|entry| entry.price).collect();
+    assert_eq!(
+        prices,
+        vec![Some(200_000_000), Some(300_000_000), Some(400_000_000)]
+    );
+}
+
+#[test]
+fn test_quote_pool_ladder() {
+    let mut pool = pool(Pubkey::new_unique());
+    pool.nfts_held = 2;
+    pool.amount = 1_500_000_000;
+
+    let royalties = Royalties { bps: 500, pct: 100 };
+    let quote = quote_pool(Some(Pubkey::new_unique()), pool, 5, royalties).unwrap();
+
+    // 2% taker fee, 5% royalties and the 1% MM fee taken out of the price on sells.
+    assert_eq!(
+        quote.buys[0],
+        QuoteStep {
+            price: 1_000_000_000,
+            taker_fee: 20_000_000,
+            maker_broker_fee: 8_000_000,
+            royalty: 50_000_000,
+            mm_fee: 10_000_000,
+            total: 1_070_000_000,
+        }
+    );
+    assert_eq!(
+        quote.sells[0],
+        QuoteStep {
+            price: 900_000_000,
+            taker_fee: 18_000_000,
+            maker_broker_fee: 7_200_000,
+            royalty: 45_000_000,
+            mm_fee: 9_000_000,
+            total: 828_000_000,
+        }
+    );
+
+    // Buys stop once the pool runs out of NFTs, sells once it runs out of SOL.
+    let buys: Vec<_> = quote.buys.iter().map(|step| step.price).collect();
+    assert_eq!(buys, vec![1_000_000_000, 1_100_000_000]);
+    assert_eq!(quote.buys_stopped.as_deref(), Some("Pool has no NFTs left"));
+
+    let sells: Vec<_> = quote.sells.iter().map(|step| step.price).collect();
+    assert_eq!(sells, vec![900_000_000]);
+    assert_eq!(
+        quote.sells_stopped.as_deref(),
+        Some("Pool only holds 0.6 SOL")
+    );
+}
+
+#[test]
+fn test_quote_pool_config() {
+    let mut pool = pool(Pubkey::new_unique());
+    pool.config.curve_type = CurveType::Exponential;
+    pool.config.starting_price = 1_000_000_001;
+    pool.config.delta = 333;
+
+    // Local configs aren't bounded by the pool's NFTs or SOL.
+    let quote = quote_pool(None, pool.clone(), 3, Royalties::none()).unwrap();
+
+    // Exponential prices round up on buys and down on sells, like the program.
+    let buys: Vec<_> = quote.buys.iter().map(|step| step.price).collect();
+    assert_eq!(buys, vec![1_000_000_001, 1_033_300_002, 1_067_708_892]);
+
+    let sells: Vec<_> = quote.sells.iter().map(|step| step.price).collect();
+    assert_eq!(sells, vec![967_773_154, 936_584_878, 906_401_701]);
+    assert!(quote.sells_stopped.is_none());
+
+    // Token pools only buy NFTs, at the current offset and without an MM fee.
+    pool.config.pool_type = PoolType::Token;
+    let quote = quote_pool(None, pool, 1, Royalties::none()).unwrap();
+
+    assert!(quote.buys.is_empty());
+    assert_eq!(quote.sells[0].price, 1_000_000_001);
+    assert_eq!(quote.sells[0].mm_fee, 0);
+}
+
+#[test]
+fn test_pool_report() {
+    let owner = Pubkey::new_unique();
+
+    // A trade pool that sold two NFTs more than it bought, and a token pool that bought three.
+    let mut trade = pool(owner);
+    trade.price_offset = 2;
+    trade.nfts_held = 4;
+    trade.amount = 2_000_000_000;
+    trade.expiry = 1_000 + 2 * 86_400;
+    trade.stats = PoolStats {
+        taker_sell_count: 1,
+        taker_buy_count: 3,
+        accumulated_mm_profit: 42_000_000,
+    };
+
+    let mut token = pool(owner);
+    token.pool_id = [2; 32];
+    token.config.pool_type = PoolType::Token;
+    token.price_offset = -3;
+    token.amount = 500_000_000;
+    token.stats.taker_sell_count = 3;
+
+    let entries = [trade, token]
+        .into_iter()
+        .map(|pool| PoolEntry {
+            address: Pool::find_pda(&pool.owner, pool.pool_id).0,
+            price: quoted_price(&pool),
+            pool,
+        })
+        .collect();
+
+    let report = pool_report(entries, 1_000);
+
+    let trade = &report.rows[0];
+    assert_eq!(trade.price, Some(1_200_000_000));
+    assert_eq!(trade.inventory_value, 4_800_000_000);
+    assert_eq!(trade.drift_pct.map(|drift| drift.round()), Some(20.0));
+    assert_eq!(trade.expires_in, 2 * 86_400);
+
+    let token = &report.rows[1];
+    assert_eq!(token.price, Some(700_000_000));
+    assert_eq!(token.inventory_value, 0);
+    assert_eq!(token.drift_pct.map(|drift| drift.round()), Some(-30.0));
+    assert!(token.expires_in < 0);
+
+    assert_eq!(report.trades(), 7);
+    assert_eq!(report.mm_profit(), 42_000_000);
+    assert_eq!(report.amount(), 2_500_000_000);
+
+    let path = std::env::temp_dir().join(format!("eigen-report-{}.csv", Pubkey::new_unique()));
+    write_report_csv(&report, &path).unwrap();
+
+    let csv = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("address,type,curve,buys,sells,mm_profit_sol"));
+    assert!(lines[1].starts_with(&format!(
+        "{},Trade,Linear,3,1,0.042,2,4,1.2,4.8,2,20.00,",
+        report.rows[0].address
+    )));
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_create_pool_from_flags() {
+    let owner = Keypair::new();
+    let whitelist = Pubkey::from_str(FIXTURE_V2).unwrap();
+
+    let flags = PoolConfigFlags {
+        pool_type: Some(PoolType::Trade),
+        curve_type: Some(CurveType::Exponential),
+        start: Some("1.5SOL".to_string()),
+        delta: Some("5%".to_string()),
+        fee_bps: Some(200),
+        expire: Some("30d".to_string()),
+        ..Default::default()
+    };
+
+    let args = create_pool_args(&flags).unwrap();
+    assert_eq!(args.config.starting_price, 1_500_000_000);
+    assert_eq!(args.config.delta, 500);
+    assert_eq!(args.config.mm_fee_bps, NullableU16::new(200));
+    assert_eq!(args.expire_in_sec, Some(30 * 86_400));
+    // Pool ids are random unless given.
+    assert_ne!(args.pool_id, create_pool_args(&flags).unwrap().pool_id);
+
+    let rpc = Arc::new(FixtureRpc::new());
+    let config = CliConfig::with_client(rpc.clone(), owner.insecure_clone()).unwrap();
+
+    let created = create_pool_with_config(&config, &whitelist, None, args.clone()).unwrap();
+    assert_eq!(
+        created.pool,
+        Pool::find_pda(&owner.pubkey(), args.pool_id).0
+    );
+    assert_eq!(rpc.sent_transactions().len(), 1);
+
+    // Every combination the program rejects is reported before sending.
+    let rejected = create_pool_args(&PoolConfigFlags {
+        pool_type: Some(PoolType::Token),
+        delta: Some("10000bps".to_string()),
+        expire: Some("400d".to_string()),
+        ..flags
+    })
+    .unwrap();
+
+    let err = create_pool_with_config(&config, &whitelist, None, rejected)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("MM fees are only allowed on trade pools"));
+    assert!(err.contains("Exponential delta of 10000 bps"));
+    assert!(err.contains("Expiry can't be more than 365 days"));
+    assert_eq!(rpc.sent_transactions().len(), 1);
+}
+
+#[test]
+fn test_apply_pool_manifest() {
+    let owner = Keypair::new();
+    let rpc = fixture_rpc();
+
+    // Two pools from the manifest, one of them out of date, and one that isn't listed.
+    let mut stale = pool(owner.pubkey());
+    stale.amount = 1_000_000_000;
+    let mut current = pool(owner.pubkey());
+    current.pool_id = [2; 32];
+    let mut unlisted = pool(owner.pubkey());
+    unlisted.pool_id = [3; 32];
+
+    for pool in [&stale, &current, &unlisted] {
+        rpc.insert(
+            Pool::find_pda(&pool.owner, pool.pool_id).0,
+            pool_account(pool),
+        );
+    }
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), owner.insecure_clone()).unwrap();
+
+    let manifest = |pools: &str| -> PoolManifest {
+        serde_yaml::from_str(&format!(
+            "close_unlisted: true\npools:\n{}",
+            pools
+                .replace("{ONE}", &"01".repeat(32))
+                .replace("{TWO}", &"02".repeat(32))
+                .replace("{FOUR}", &"04".repeat(32))
+                .replace("{WHITELIST}", FIXTURE_V2)
+        ))
+        .unwrap()
+    };
+
+    let pools = "
+  - { pool_id: '{ONE}', whitelist: {WHITELIST}, type: trade, curve: linear, start: 1.2SOL, delta: 0.1SOL, fee_bps: 100, sol: 3SOL }
+  - { pool_id: '{TWO}', whitelist: {WHITELIST}, type: trade, curve: linear, start: 1SOL, delta: 0.1SOL, fee_bps: 100 }
+  - { pool_id: '{FOUR}', whitelist: {WHITELIST}, type: token, curve: exponential, start: 2SOL, delta: 5%, sol: 10SOL }
+";
+    let plan = plan_pools(&config, &manifest(pools)).unwrap();
+    assert_eq!(plan.unchanged, 1);
+
+    let address = |id: u8| Pool::find_pda(&owner.pubkey(), [id; 32]).0;
+    let actions: Vec<_> = plan
+        .actions
+        .iter()
+        .map(|action| match action {
+            PlanAction::Create { pool, .. } => ("create", *pool, 0),
+            PlanAction::Edit { pool, changes, .. } => ("edit", *pool, changes.len() as u64),
+            PlanAction::DepositSol { pool, lamports } => ("deposit", *pool, *lamports),
+            PlanAction::DepositNfts { pool, .. } => ("deposit nfts", *pool, 0),
+            PlanAction::Close { pool, .. } => ("close", *pool, 0),
+        })
+        .collect();
+    assert_eq!(
+        actions,
+        vec![
+            ("edit", address(1), 1),
+            ("deposit", address(1), 2_000_000_000),
+            ("create", address(4), 0),
+            ("deposit", address(4), 10_000_000_000),
+            ("close", address(3), 0),
+        ]
+    );
+
+    let signatures = apply_plan(&config, &plan).unwrap();
+    let sent = rpc.sent_transactions();
+    assert!(!sent.is_empty());
+    assert_eq!(signatures.len(), sent.len());
+
+    // Changes the program can't make are refused before anything is planned.
+    let err = plan_pools(
+        &config,
+        &manifest(
+            "
+  - { pool_id: '{ONE}', whitelist: {WHITELIST}, type: nft, curve: linear, start: 1SOL }
+  - { pool_id: '{TWO}', whitelist: {WHITELIST}, type: trade, curve: linear, start: 1SOL, fee_bps: 100 }
+",
+        ),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("pool type can't change from Trade to NFT"));
+}
+
+#[test]
+fn test_partial_pool_edit() {
+    let owner = Keypair::new();
+    let address = Pubkey::new_unique();
+    let mut pool = pool(owner.pubkey());
+    pool.price_offset = 3;
+
+    let flags = EditPoolFlags {
+        start: Some("1.2SOL".to_string()),
+        fee_bps: Some(150),
+        reset_price_offset: true,
+        ..Default::default()
+    };
+    let edit = diff_pool_edit(
+        &pool,
+        &EditPoolArgs::from_flags(&flags, &pool.config).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        edit.changes,
+        vec![
+            "starting_price: 1000000000 -> 1200000000",
+            "mm_fee_bps: 100 -> 150",
+            "price_offset: 3 -> 0",
+        ]
+    );
+    // Fields that weren't edited keep their on-chain values.
+    assert_eq!(
+        edit.args.new_config,
+        Some(PoolConfig {
+            starting_price: 1_200_000_000,
+            mm_fee_bps: NullableU16::new(150),
+            ..pool.config.clone()
+        })
+    );
+    assert!(edit.args.reset_price_offset);
+    assert!(edit.args.cosigner.is_none());
+
+    // Config files can be partial too, and values matching the pool aren't changes.
+    let partial: EditPoolArgs =
+        serde_json::from_str(r#"{"new_config": {"delta": 100000000}}"#).unwrap();
+    let unchanged = diff_pool_edit(&pool, &partial).unwrap();
+    assert!(unchanged.changes.is_empty());
+    assert!(unchanged.args.new_config.is_none());
+
+    // Edits the program would reject fail before sending.
+    pool.config.pool_type = PoolType::Token;
+    pool.config.mm_fee_bps = NullableU16::none();
+    let err = diff_pool_edit(
+        &pool,
+        &EditPoolArgs::from_flags(&flags, &pool.config).unwrap(),
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("MM fees are only allowed on trade pools"));
+
+    let rpc = Arc::new(FixtureRpc::new());
+    let config = CliConfig::with_client(rpc.clone(), owner).unwrap();
+
+    let signatures = edit_pool_with_config(&config, &address, &edit).unwrap();
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(rpc.sent_transactions().len(), 1);
+}
+
+fn margin_account(owner: Pubkey, nr: u16, pools_attached: u32, lamports: u64) -> (Pubkey, Account) {
+    let escrow = MarginAccount {
+        discriminator: MarginAccount::discriminator(),
+        owner,
+        name: [0; 32],
+        nr,
+        bump: [255],
+        pools_attached,
+        reserved: [0; 64],
+    };
+
+    (
+        MarginAccount::find_pda(&owner, nr).0,
+        Account {
+            lamports,
+            ..program_account(TENSOR_ESCROW_ID, escrow.try_to_vec().unwrap())
+        },
+    )
+}
+
+fn bid_account(owner: Pubkey, margin: Option<Pubkey>) -> Account {
+    let bid = BidState {
+        discriminator: BidState::discriminator(),
+        version: 1,
+        bump: [255],
+        owner,
+        bid_id: Pubkey::new_unique(),
+        target: Target::Whitelist,
+        target_id: Pubkey::new_unique(),
+        field: None,
+        field_id: None,
+        quantity: 2,
+        filled_quantity: 0,
+        amount: 500_000_000,
+        currency: None,
+        expiry: i64::MAX,
+        private_taker: None,
+        maker_broker: None,
+        margin,
+        updated_at: 0,
+        cosigner: tensor_marketplace::NullableAddress::none(),
+        rent_payer: tensor_marketplace::NullableAddress::none(),
+        reserved: [0; 8],
+        reserved1: [0; 16],
+        reserved2: [0; 32],
+    };
+
+    program_account(TENSOR_MARKETPLACE_ID, bid.try_to_vec().unwrap())
+}
+
+#[test]
+fn test_shared_escrow() {
+    let owner = Keypair::new();
+    let rent = solana_sdk::rent::Rent::default().minimum_balance(MarginAccount::LEN);
+
+    let (escrow, escrow_account) = margin_account(owner.pubkey(), 0, 1, rent + 2_000_000_000);
+    let (other, other_account) = margin_account(owner.pubkey(), 2, 0, rent);
+
+    let rpc = Arc::new(
+        FixtureRpc::new()
+            .with_account(escrow, escrow_account.clone())
+            .with_account(other, other_account),
+    );
+    let config = CliConfig::with_client(rpc.clone(), owner.insecure_clone()).unwrap();
+
+    // The escrow decodes from its discriminator.
+    let decoded = decode_account(escrow, escrow_account).unwrap();
+    assert!(
+        matches!(decoded, DecodedAccount::MarginAccount(account) if account.pools_attached == 1)
+    );
+
+    // Numbers 0 and 2 are taken, so the next escrow gets 1.
+    let created = create_escrow_with_config(&config, None, "desk").unwrap();
+    assert_eq!(created.nr, 1);
+    assert_eq!(
+        created.address,
+        MarginAccount::find_pda(&owner.pubkey(), 1).0
+    );
+    assert!(create_escrow_with_config(&config, Some(2), "").is_err());
+    assert!(create_escrow_with_config(&config, None, &"x".repeat(33)).is_err());
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    let ix = &sent[0].message.instructions()[0];
+    assert_eq!(
+        sent[0].message.static_account_keys()[ix.program_id_index as usize],
+        TENSOR_ESCROW_ID
+    );
+    assert!(ix
+        .data
+        .starts_with(&instruction_discriminator("init_margin_account")));
+
+    // Withdrawals can't dip into the rent exemption.
+    let error = transfer_escrow_sol(&config, &escrow, PoolDirection::Withdraw, 2_000_000_001)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("only holds 2 SOL"));
+
+    let withdrawn =
+        transfer_escrow_sol(&config, &escrow, PoolDirection::Withdraw, 2_000_000_000).unwrap();
+    assert_eq!(withdrawn.balance_before, 2_000_000_000);
+    assert_eq!(rpc.sent_transactions().len(), 2);
+
+    // Pools and bids drawing from the escrow are listed, other bids of the owner aren't.
+    let mut attached = pool(owner.pubkey());
+    attached.shared_escrow = NullableAddress::new(escrow);
+    let attached_address = Pool::find_pda(&owner.pubkey(), attached.pool_id).0;
+    rpc.insert(attached_address, pool_account(&attached));
+
+    let bid = Pubkey::new_unique();
+    rpc.insert(bid, bid_account(owner.pubkey(), Some(escrow)));
+    rpc.insert(Pubkey::new_unique(), bid_account(owner.pubkey(), None));
+
+    let details = fetch_escrow_details(&config, &escrow).unwrap();
+    assert_eq!(details.escrow.available, 2_000_000_000);
+    assert_eq!(details.pools.len(), 1);
+    assert_eq!(details.pools[0].address, attached_address);
+    assert_eq!(details.bids.len(), 1);
+    assert_eq!(details.bids[0].0, bid);
+
+    let error = close_escrow_with_config(&config, &escrow)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("1 pools are attached"));
+    assert!(error.contains(&attached_address.to_string()));
+    assert!(error.contains("1 bids draw from it"));
+    assert_eq!(rpc.sent_transactions().len(), 2);
+
+    // New pools attach to the escrow when they're created.
+    let args = create_pool_args(&PoolConfigFlags {
+        pool_type: Some(PoolType::Token),
+        curve_type: Some(CurveType::Linear),
+        start: Some("1".to_string()),
+        ..Default::default()
+    })
+    .unwrap();
+    let created =
+        create_pool_with_config(&config, &Pubkey::new_unique(), Some(escrow), args.clone())
+            .unwrap();
+    assert_eq!(created.shared_escrow, Some(escrow));
+    let sent = rpc.sent_transactions();
+    assert!(sent[2].message.static_account_keys().contains(&escrow));
+
+    let mut nft_args = args;
+    nft_args.config.pool_type = PoolType::NFT;
+    let error = create_pool_with_config(&config, &Pubkey::new_unique(), Some(escrow), nft_args)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("NFT pools don't hold SOL"));
+    assert_eq!(rpc.sent_transactions().len(), 3);
+}
+
+fn listing_account(owner: Pubkey, mint: Pubkey, amount: u64, currency: Option<Pubkey>) -> Account {
+    let listing = ListState {
+        discriminator: ListState::discriminator(),
+        version: 1,
+        bump: [255],
+        owner,
+        asset_id: mint,
+        amount,
+        currency,
+        expiry: i64::MAX,
+        private_taker: None,
+        maker_broker: None,
+        rent_payer: tensor_marketplace::NullableAddress::none(),
+        cosigner: tensor_marketplace::NullableAddress::none(),
+        reserved1: [0; 64],
+    };
+
+    program_account(TENSOR_MARKETPLACE_ID, listing.try_to_vec().unwrap())
+}
+
+/// SPL mint with the given decimals, and a token account of it for `wallet` holding `amount`.
+fn currency_mint(rpc: &FixtureRpc, decimals: u8, wallet: &Pubkey, amount: u64) -> Pubkey {
+    let mint = Pubkey::new_unique();
+    let mut data = vec![0; 82];
+    data[44] = decimals;
+    rpc.insert(mint, program_account(TOKEN_ID, data));
+
+    let mut token_account = vec![0; 165];
+    token_account[..32].copy_from_slice(mint.as_ref());
+    token_account[32..64].copy_from_slice(wallet.as_ref());
+    token_account[64..72].copy_from_slice(&amount.to_le_bytes());
+    rpc.insert(
+        associated_token_address(wallet, &mint, &TOKEN_ID),
+        program_account(TOKEN_ID, token_account),
+    );
+
+    mint
+}
+
+/// Core asset with a royalties plugin paying `creator`, laid out as the Core program writes it:
+/// the base asset, the plugin header, the plugin and then the registry pointing at it.
+fn core_asset_with_royalties(rpc: &FixtureRpc, owner: &Pubkey, creator: &Pubkey) -> Pubkey {
+    let mint = Pubkey::new_unique();
+
+    let mut data = CoreAsset {
+        key: 1,
+        owner: *owner,
+        update_authority: UpdateAuthority::Address(Pubkey::new_unique()),
+    }
+    .try_to_vec()
+    .unwrap();
+    data.extend(
+        ("Core".to_string(), String::new(), None::<u64>)
+            .try_to_vec()
+            .unwrap(),
+    );
+
+    let header = data.len();
+    let plugin = header + 9;
+    let royalties = (0u8, 500u16, vec![(*creator, 100u8)], 0u8);
+    let registry = plugin + royalties.try_to_vec().unwrap().len();
+
+    data.extend((3u8, registry as u64).try_to_vec().unwrap());
+    data.extend(royalties.try_to_vec().unwrap());
+    // One royalties record with update authority, and no external plugins.
+    data.extend(
+        (4u8, vec![(0u8, 2u8, plugin as u64)], Vec::<u8>::new())
+            .try_to_vec()
+            .unwrap(),
+    );
+
+    rpc.insert(mint, program_account(MPL_CORE_ID, data));
+    mint
+}
+
+fn instruction_data(
+    tx: &solana_sdk::transaction::VersionedTransaction,
+) -> (Pubkey, Vec<Pubkey>, Vec<u8>) {
+    let keys = tx.message.static_account_keys();
+    let ix = &tx.message.instructions()[0];
+
+    (
+        keys[ix.program_id_index as usize],
+        ix.accounts.iter().map(|i| keys[*i as usize]).collect(),
+        ix.data.clone(),
+    )
+}
+
+#[test]
+fn test_list_and_delist_across_standards() {
+    let owner = Keypair::new();
+    let rpc = fixture_rpc();
+
+    let standards = [
+        (AssetStandard::Legacy, "list_legacy"),
+        (AssetStandard::Programmable, "list_legacy"),
+        (AssetStandard::Token2022, "list_t22"),
+        (AssetStandard::Core, "list_core"),
+    ];
+    let mints: Vec<Pubkey> = standards
+        .iter()
+        .map(|(standard, _)| nft(&rpc, &owner.pubkey(), *standard))
+        .collect();
+    let others = nft(&rpc, &Pubkey::new_unique(), AssetStandard::Legacy);
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), owner.insecure_clone()).unwrap();
+
+    let terms = ListingTerms {
+        expire: Some("7d".to_string()),
+        ..Default::default()
+    };
+
+    for (mint, (standard, name)) in mints.iter().zip(standards) {
+        let listed = list_nft_with_config(&config, mint, "1.5", &terms, None).unwrap();
+        assert_eq!(listed.amount, 1_500_000_000);
+        assert_eq!(listed.standard, Some(standard.to_string()));
+        assert_eq!(listed.list_state, ListState::find_pda(mint).0);
+
+        let (program, accounts, data) = instruction_data(rpc.sent_transactions().last().unwrap());
+        assert_eq!(program, TENSOR_MARKETPLACE_ID);
+        assert!(data.starts_with(&instruction_discriminator(name)));
+        assert!(accounts.contains(&listed.list_state));
+    }
+
+    // The pNFT moves through token records into the listing's token account.
+    let (_, accounts, _) = instruction_data(&rpc.sent_transactions()[1]);
+    let list_ta = associated_token_address(&ListState::find_pda(&mints[1]).0, &mints[1], &TOKEN_ID);
+    assert!(accounts.contains(&find_token_record_pda(&mints[1], &list_ta).0));
+    assert!(accounts.contains(&TOKEN_AUTH_RULES_ID));
+
+    // Expiry beyond a year, assets held by someone else and listings that exist are refused.
+    let long = ListingTerms {
+        expire: Some("400d".to_string()),
+        ..Default::default()
+    };
+    assert!(list_nft_with_config(&config, &mints[0], "1", &long, None).is_err());
+
+    let error = list_nft_with_config(&config, &others, "1", &ListingTerms::default(), None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("is not in the associated token account"));
+
+    let list_state = ListState::find_pda(&mints[0]).0;
+    rpc.insert(
+        list_state,
+        listing_account(owner.pubkey(), mints[0], 1, None),
+    );
+    let error = list_nft_with_config(&config, &mints[0], "1", &ListingTerms::default(), None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("already listed"));
+    assert_eq!(rpc.sent_transactions().len(), 4);
+
+    // Edits keep what isn't given, but a new currency needs a new price.
+    let edited =
+        edit_listing_with_config(&config, &mints[0], None, &ListingTerms::default()).unwrap();
+    assert_eq!(edited.amount, 1);
+    let (_, _, data) = instruction_data(rpc.sent_transactions().last().unwrap());
+    assert!(data.starts_with(&instruction_discriminator("edit")));
+
+    let usdc = currency_mint(&rpc, 6, &owner.pubkey(), 0);
+    let new_currency = ListingTerms {
+        currency: Some(CurrencyFilter::Spl(usdc)),
+        ..Default::default()
+    };
+    assert!(edit_listing_with_config(&config, &mints[0], None, &new_currency).is_err());
+    let edited = edit_listing_with_config(&config, &mints[0], Some("2.5"), &new_currency).unwrap();
+    assert_eq!(edited.amount, 2_500_000);
+
+    // Terms can be cleared, and a listing in a token moved back to SOL.
+    let mut listing = listing_account(owner.pubkey(), mints[0], 2_500_000, Some(usdc));
+    let mut state = ListState::try_from_slice(&listing.data).unwrap();
+    state.private_taker = Some(Pubkey::new_unique());
+    state.maker_broker = Some(Pubkey::new_unique());
+    listing.data = state.try_to_vec().unwrap();
+    rpc.insert(list_state, listing);
+
+    let cleared = ListingTerms {
+        currency: Some(CurrencyFilter::Sol),
+        clear_private_taker: true,
+        ..Default::default()
+    };
+    let edited = edit_listing_with_config(&config, &mints[0], Some("2"), &cleared).unwrap();
+    assert_eq!(edited.currency.mint, None);
+    assert_eq!(edited.amount, 2_000_000_000);
+    assert_eq!(edited.private_taker, None);
+    assert_eq!(edited.maker_broker, state.maker_broker);
+
+    // An expired listing isn't edited without a new expiry.
+    let mut expired = state.clone();
+    expired.expiry = chrono::Utc::now().timestamp() - 60;
+    rpc.insert(
+        list_state,
+        program_account(TENSOR_MARKETPLACE_ID, expired.try_to_vec().unwrap()),
+    );
+    let sent = rpc.sent_transactions().len();
+    let error = edit_listing_with_config(&config, &mints[0], None, &ListingTerms::default())
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("it has expired, give a new --expire"));
+    assert_eq!(rpc.sent_transactions().len(), sent);
+
+    let renewed = ListingTerms {
+        expire: Some("7d".to_string()),
+        ..Default::default()
+    };
+    let edited = edit_listing_with_config(&config, &mints[0], None, &renewed).unwrap();
+    assert_eq!(edited.expire_in_sec, Some(7 * 86_400));
+
+    // Delisting returns the NFT and rent to the owner.
+    let delisted = delist_nft_with_config(&config, &mints[0]).unwrap();
+    assert_eq!(delisted.list_state, list_state);
+    let (_, accounts, data) = instruction_data(rpc.sent_transactions().last().unwrap());
+    assert!(data.starts_with(&instruction_discriminator("delist_legacy")));
+    assert!(accounts.contains(&associated_token_address(
+        &owner.pubkey(),
+        &mints[0],
+        &TOKEN_ID
+    )));
+
+    rpc.insert(
+        list_state,
+        listing_account(Pubkey::new_unique(), mints[0], 1, None),
+    );
+    let error = delist_nft_with_config(&config, &mints[0])
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("it is listed by"));
+    assert!(delist_nft_with_config(&config, &mints[1])
+        .unwrap_err()
+        .to_string()
+        .contains("is not listed"));
+}
+
+#[test]
+fn test_buy_listing() {
+    let buyer = Keypair::new();
+    let seller = Pubkey::new_unique();
+    let creator = Pubkey::new_unique();
+    let rpc = FixtureRpc::new();
+
+    let usdc = currency_mint(&rpc, 6, &buyer.pubkey(), 1_000_000);
+    let asset = Pubkey::new_unique();
+    let list_state = ListState::find_pda(&asset).0;
+    let asset = {
+        let mint = core_asset_with_royalties(&rpc, &list_state, &creator);
+        let account = rpc.get_account(&mint).unwrap();
+        rpc.insert(asset, account);
+        asset
+    };
+    rpc.insert(
+        list_state,
+        listing_account(seller, asset, 2_500_000, Some(usdc)),
+    );
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), buyer.insecure_clone()).unwrap();
+
+    // Too little balance and a max price below the listing are reported together.
+    let terms = BuyTerms {
+        max_price: Some("2".to_string()),
+        ..Default::default()
+    };
+    let error = buy_listing_with_config(&config, &asset, &terms, None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(&format!(
+        "it is listed at 2.5 {}, above the max price of 2",
+        usdc
+    )));
+    assert!(error.contains(&format!("only holds 1 {}", usdc)));
+    assert!(rpc.sent_transactions().is_empty());
+
+    let buyer_ta = associated_token_address(&buyer.pubkey(), &usdc, &TOKEN_ID);
+    let mut funded = rpc.get_account(&buyer_ta).unwrap();
+    funded.data[64..72].copy_from_slice(&5_000_000u64.to_le_bytes());
+    rpc.insert(buyer_ta, funded);
+
+    let bought = buy_listing_with_config(&config, &asset, &BuyTerms::default(), None).unwrap();
+    assert_eq!(bought.seller, seller);
+    assert_eq!(bought.max_amount, 2_500_000);
+    assert_eq!(bought.standard, "Core");
+
+    // Royalties go to the Core plugin's creators, in the listing currency.
+    let (program, accounts, data) = instruction_data(&rpc.sent_transactions()[0]);
+    assert_eq!(program, TENSOR_MARKETPLACE_ID);
+    assert!(data.starts_with(&instruction_discriminator("buy_core_spl")));
+    assert!(accounts.contains(&find_fee_vault_pda(&list_state)));
+    assert!(accounts.contains(&creator));
+    assert!(accounts.contains(&associated_token_address(&creator, &usdc, &TOKEN_ID)));
+    assert!(accounts.contains(&associated_token_address(&seller, &usdc, &TOKEN_ID)));
+
+    // Cosigned and private listings need the right signers.
+    let cosigner = Keypair::new();
+    let mut listing = ListState::from_bytes(&rpc.get_account(&list_state).unwrap().data).unwrap();
+    listing.cosigner = tensor_marketplace::NullableAddress::new(cosigner.pubkey());
+    listing.private_taker = Some(Pubkey::new_unique());
+    rpc.insert(
+        list_state,
+        program_account(TENSOR_MARKETPLACE_ID, listing.try_to_vec().unwrap()),
+    );
+
+    let error = buy_listing_with_config(&config, &asset, &BuyTerms::default(), None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("it is a private listing"));
+    assert!(error.contains(&format!("it needs cosigner {}", cosigner.pubkey())));
+
+    listing.private_taker = None;
+    rpc.insert(
+        list_state,
+        program_account(TENSOR_MARKETPLACE_ID, listing.try_to_vec().unwrap()),
+    );
+    buy_listing_with_config(&config, &asset, &BuyTerms::default(), Some(&cosigner)).unwrap();
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 2);
+    assert!(sent[1]
+        .message
+        .static_account_keys()
+        .contains(&cosigner.pubkey()));
+}
+
+#[test]
+fn test_list_compressed_asset() {
+    let owner = Keypair::new();
+    let creator = Pubkey::new_unique();
+    let rpc = FixtureRpc::new();
+
+    // A depth 3 tree with a canopy of depth 1, so the top proof node is left out.
+    let tree = Pubkey::new_unique();
+    let mut tree_data = vec![0; 56 + 24 + 8 * (40 + 32 * 3) + 32 * 3 + 40 + 64];
+    tree_data[2..6].copy_from_slice(&8u32.to_le_bytes());
+    tree_data[6..10].copy_from_slice(&3u32.to_le_bytes());
+    rpc.insert(tree, program_account(ACCOUNT_COMPRESSION_ID, tree_data));
+
+    let metadata = MetadataArgs {
+        name: "cNFT".to_string(),
+        symbol: String::new(),
+        uri: "https://example.com/0.json".to_string(),
+        seller_fee_basis_points: 500,
+        primary_sale_happened: false,
+        is_mutable: true,
+        edition_nonce: None,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection: None,
+        uses: None,
+        token_program_version: 0,
+        creators: vec![Creator {
+            address: creator,
+            verified: true,
+            share: 100,
+        }],
+    };
+    let leaf_hash = Pubkey::new_from_array(data_hash(&metadata.hash(), 500));
+
+    let id = Pubkey::new_unique();
+    let asset: DasAsset = serde_json::from_value(serde_json::json!({
+        "id": id.to_string(),
+        "content": {
+            "json_uri": metadata.uri,
+            "metadata": { "name": "cNFT", "symbol": "", "token_standard": "NonFungible" }
+        },
+        "compression": {
+            "compressed": true,
+            "data_hash": leaf_hash.to_string(),
+            "creator_hash": Pubkey::new_unique().to_string(),
+            "tree": tree.to_string(),
+            "leaf_id": 7
+        },
+        "royalty": { "basis_points": 500, "primary_sale_happened": false },
+        "creators": [{ "address": creator.to_string(), "share": 100, "verified": true }],
+        "ownership": { "owner": owner.pubkey().to_string(), "delegate": null },
+        "mutable": true
+    }))
+    .unwrap();
+    let proof = DasAssetProof {
+        root: Pubkey::new_unique(),
+        proof: vec![
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ],
+        node_index: 15,
+        tree_id: tree,
+    };
+    rpc.insert_asset(asset.clone(), proof.clone());
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), owner.insecure_clone()).unwrap();
+
+    let listed = list_nft_with_config(&config, &id, "3", &ListingTerms::default(), None).unwrap();
+    assert_eq!(listed.standard, Some("Compressed".to_string()));
+
+    let (_, accounts, data) = instruction_data(&rpc.sent_transactions()[0]);
+    assert!(data.starts_with(&instruction_discriminator("list")));
+    assert!(accounts.contains(&find_tree_authority_pda(&tree).0));
+    assert_eq!(&accounts[accounts.len() - 2..], &proof.proof[..2]);
+    // Nonce and index of leaf 7, then the root.
+    assert_eq!(&data[8..16], &7u64.to_le_bytes());
+    assert_eq!(&data[16..20], &7u32.to_le_bytes());
+    assert_eq!(&data[20..52], proof.root.as_ref());
+
+    // A stale indexer fails before anything is sent.
+    let mut stale = asset;
+    stale.royalty.basis_points = 250;
+    rpc.insert_asset(stale, proof);
+    let error = list_nft_with_config(&config, &id, "3", &ListingTerms::default(), None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("doesn't match its leaf"));
+
+    let missing = Pubkey::new_unique();
+    let error = list_nft_with_config(&config, &missing, "3", &ListingTerms::default(), None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(&format!("Mint {} not found", missing)));
+    assert_eq!(rpc.sent_transactions().len(), 1);
+}
+
+fn wallet(rpc: &FixtureRpc, lamports: u64) -> Keypair {
+    let wallet = Keypair::new();
+    rpc.insert(
+        wallet.pubkey(),
+        Account {
+            lamports,
+            ..program_account(system_program::id(), vec![])
+        },
+    );
+    wallet
+}
+
+#[test]
+fn test_place_edit_cancel_bid() {
+    let rpc = FixtureRpc::new();
+    let owner = wallet(&rpc, 3_000_000_000);
+    let (whitelist, account) = whitelist_v2(
+        [7; 32],
+        vec![Condition {
+            mode: Mode::VOC,
+            value: Pubkey::new_unique(),
+        }],
+    );
+    rpc.insert(whitelist, account);
+    let (escrow, account) = margin_account(owner.pubkey(), 0, 0, 10_000_000_000);
+    rpc.insert(escrow, account);
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), owner.insecure_clone()).unwrap();
+
+    // Mint bids use the mint as their bid id.
+    let mint = Pubkey::new_unique();
+    let bid = place_bid_with_config(
+        &config,
+        &BidTarget::Mint(mint),
+        "1",
+        &BidTerms::default(),
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(bid.bid_state, BidState::find_pda(&owner.pubkey(), &mint).0);
+    assert_eq!((bid.amount, bid.quantity), (1_000_000_000, 1));
+
+    let (program, accounts, data) = instruction_data(&rpc.sent_transactions()[0]);
+    assert_eq!(program, TENSOR_MARKETPLACE_ID);
+    assert!(data.starts_with(&instruction_discriminator("bid")));
+    assert_eq!(&data[8..40], mint.as_ref());
+    // Without a margin the owner stands in for the shared escrow.
+    assert_eq!(accounts[3], owner.pubkey());
+    assert_eq!(accounts[4], owner.pubkey());
+
+    // Quantity bids without a margin hold the SOL for every fill.
+    let five = BidTerms {
+        quantity: Some(5),
+        ..Default::default()
+    };
+    let target = BidTarget::Whitelist {
+        whitelist,
+        trait_id: None,
+    };
+    let error = place_bid_with_config(&config, &target, "1", &five, None, None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(&format!(
+        "it needs 5 SOL but {} only holds 3 SOL",
+        owner.pubkey()
+    )));
+
+    let margin = BidTerms {
+        margin: Some(escrow),
+        ..five
+    };
+    let bid = place_bid_with_config(&config, &target, "1", &margin, None, None).unwrap();
+    assert_eq!(bid.margin, Some(escrow));
+    let (_, accounts, _) = instruction_data(rpc.sent_transactions().last().unwrap());
+    assert_eq!(accounts[4], escrow);
+
+    // Trait bids rely on a cosigner, which signs the bid.
+    let trait_target = BidTarget::Whitelist {
+        whitelist,
+        trait_id: Some(Pubkey::new_unique()),
+    };
+    let error = place_bid_with_config(&config, &trait_target, "1", &margin, None, None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("need a cosigner"));
+
+    let cosigner = Keypair::new();
+    place_bid_with_config(&config, &trait_target, "1", &margin, None, Some(&cosigner)).unwrap();
+    let (_, accounts, _) = instruction_data(rpc.sent_transactions().last().unwrap());
+    assert!(accounts.contains(&cosigner.pubkey()));
+    assert_eq!(rpc.sent_transactions().len(), 3);
+
+    // Edits keep the bid id and target, and existing bids can't be placed again.
+    let bid_state = Pubkey::new_unique();
+    let account = bid_account(owner.pubkey(), None);
+    let existing = BidState::from_bytes(&account.data).unwrap();
+    rpc.insert(bid_state, account);
+
+    let edited = edit_bid_with_config(
+        &config,
+        &bid_state,
+        Some("1"),
+        &BidTerms {
+            quantity: Some(3),
+            ..Default::default()
+        },
+        None,
+    )
+    .unwrap();
+    assert!(edited.edited);
+    assert_eq!((edited.amount, edited.quantity), (1_000_000_000, 3));
+    let (_, accounts, data) = instruction_data(rpc.sent_transactions().last().unwrap());
+    assert_eq!(accounts[2], bid_state);
+    assert_eq!(&data[8..40], existing.bid_id.as_ref());
+    assert_eq!(&data[41..73], existing.target_id.as_ref());
+
+    rpc.insert(
+        BidState::find_pda(&owner.pubkey(), &existing.bid_id).0,
+        bid_account(owner.pubkey(), None),
+    );
+    let error = place_bid_with_config(
+        &config,
+        &BidTarget::Mint(mint),
+        "1",
+        &BidTerms::default(),
+        Some(existing.bid_id),
+        None,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("use edit-bid"));
+
+    // Only the owner can edit or cancel.
+    let other = Pubkey::new_unique();
+    rpc.insert(other, bid_account(Pubkey::new_unique(), None));
+    let error = edit_bid_with_config(&config, &other, Some("2"), &BidTerms::default(), None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("it is owned by"));
+    assert!(cancel_bid_with_config(&config, &other).is_err());
+
+    let cancelled = cancel_bid_with_config(&config, &bid_state).unwrap();
+    assert_eq!(cancelled.refund, 1_000_000);
+    let (_, accounts, data) = instruction_data(rpc.sent_transactions().last().unwrap());
+    assert!(data.starts_with(&instruction_discriminator("cancel_bid")));
+    assert_eq!(accounts[0], bid_state);
+
+    assert!(cancel_bid_with_config(&config, &Pubkey::new_unique())
+        .unwrap_err()
+        .to_string()
+        .contains("not found"));
+}
+
+#[test]
+fn test_take_bid() {
+    let rpc = FixtureRpc::new();
+    let seller = wallet(&rpc, 1_000_000_000);
+    let bidder = Pubkey::new_unique();
+    let mint = nft(&rpc, &seller.pubkey(), AssetStandard::Legacy);
+    let asset = nft(&rpc, &seller.pubkey(), AssetStandard::Core);
+
+    let set_bid = |rpc: &FixtureRpc, address: Pubkey, edit: &dyn Fn(&mut BidState)| {
+        let mut bid = BidState::from_bytes(&bid_account(bidder, None).data).unwrap();
+        edit(&mut bid);
+        rpc.insert(
+            address,
+            program_account(TENSOR_MARKETPLACE_ID, bid.try_to_vec().unwrap()),
+        );
+    };
+
+    let mint_bid = Pubkey::new_unique();
+    set_bid(&rpc, mint_bid, &|bid| {
+        bid.target = Target::AssetId;
+        bid.target_id = mint;
+    });
+
+    // Merkle tree whitelists need a mint proof for the asset.
+    let (whitelist, account) = whitelist_v2(
+        [8; 32],
+        vec![Condition {
+            mode: Mode::MerkleTree,
+            value: Pubkey::new_unique(),
+        }],
+    );
+    rpc.insert(whitelist, account);
+    let whitelist_bid = Pubkey::new_unique();
+    set_bid(&rpc, whitelist_bid, &|bid| bid.target_id = whitelist);
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), seller.insecure_clone()).unwrap();
+
+    let sold =
+        take_bid_with_config(&config, &mint_bid, &mint, &TakeBidTerms::default(), None).unwrap();
+    assert_eq!(sold.bidder, bidder);
+    assert_eq!((sold.amount, sold.min_amount), (500_000_000, 500_000_000));
+
+    let (program, accounts, data) = instruction_data(&rpc.sent_transactions()[0]);
+    assert_eq!(program, TENSOR_MARKETPLACE_ID);
+    assert!(data.starts_with(&instruction_discriminator("take_bid_legacy")));
+    assert!(accounts.contains(&find_fee_vault_pda(&mint_bid)));
+    assert!(accounts.contains(&associated_token_address(&bidder, &mint, &TOKEN_ID)));
+
+    // Taking the wrong asset below the min price is refused with every reason.
+    let terms = TakeBidTerms {
+        min_price: Some("1".to_string()),
+        ..Default::default()
+    };
+    let error = take_bid_with_config(&config, &mint_bid, &asset, &terms, None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains(&format!("it is a bid for {}", mint)));
+    assert!(error.contains("it bids 0.5 SOL, below the min price of 1 SOL"));
+
+    let error = take_bid_with_config(
+        &config,
+        &whitelist_bid,
+        &asset,
+        &TakeBidTerms::default(),
+        None,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains(&format!(
+        "{} has no mint proof for whitelist {}",
+        asset, whitelist
+    )));
+    assert_eq!(rpc.sent_transactions().len(), 1);
+
+    let mint_proof = MintProofV2::find_pda(&asset, &whitelist).0;
+    rpc.insert(mint_proof, program_account(TENSOR_WHITELIST_ID, vec![0; 8]));
+    take_bid_with_config(
+        &config,
+        &whitelist_bid,
+        &asset,
+        &TakeBidTerms::default(),
+        None,
+    )
+    .unwrap();
+    let (_, accounts, data) = instruction_data(rpc.sent_transactions().last().unwrap());
+    assert!(data.starts_with(&instruction_discriminator("take_bid_core")));
+    assert!(accounts.contains(&mint_proof));
+    assert!(accounts.contains(&whitelist));
+
+    // Filled, expired and margin bids that can't pay are refused.
+    let (escrow, account) = margin_account(bidder, 0, 0, 1_000_000);
+    rpc.insert(escrow, account);
+    set_bid(&rpc, mint_bid, &|bid| {
+        bid.target = Target::AssetId;
+        bid.target_id = mint;
+        bid.filled_quantity = bid.quantity;
+        bid.expiry = 0;
+        bid.margin = Some(escrow);
+    });
+    let error = take_bid_with_config(&config, &mint_bid, &mint, &TakeBidTerms::default(), None)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("all 2 of the bid are filled"));
+    assert!(error.contains("the bid has expired"));
+    assert!(error.contains(&format!("its shared escrow {} only holds", escrow)));
+    assert_eq!(rpc.sent_transactions().len(), 2);
+}
+
+#[test]
+fn test_market_listings_filters() {
+    let rpc = FixtureRpc::new();
+    let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let usdc = currency_mint(&rpc, 6, &alice, 0);
+
+    let (cheap, spl, expired) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    rpc.insert(
+        ListState::find_pda(&cheap).0,
+        listing_account(alice, cheap, 1_000_000_000, None),
+    );
+    rpc.insert(
+        ListState::find_pda(&spl).0,
+        listing_account(alice, spl, 2_500_000, Some(usdc)),
+    );
+    let mut listing =
+        ListState::from_bytes(&listing_account(bob, expired, 500_000_000, None).data).unwrap();
+    listing.expiry = 0;
+    rpc.insert(
+        ListState::find_pda(&expired).0,
+        program_account(TENSOR_MARKETPLACE_ID, listing.try_to_vec().unwrap()),
+    );
+
+    let (_, config) = config(rpc);
+
+    let listings = |filters: ListingFilters| -> Vec<Pubkey> {
+        fetch_listings(&config, &filters)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.listing.asset_id)
+            .collect()
+    };
+
+    assert_eq!(listings(ListingFilters::default()).len(), 3);
+    let by_alice = listings(ListingFilters {
+        owner: Some(alice),
+        ..Default::default()
+    });
+    assert_eq!(by_alice.len(), 2);
+    assert!(!by_alice.contains(&expired));
+
+    assert_eq!(
+        listings(ListingFilters {
+            mint: Some(spl),
+            ..Default::default()
+        }),
+        vec![spl]
+    );
+    assert_eq!(
+        listings(ListingFilters {
+            currency: Some(CurrencyFilter::Spl(usdc)),
+            ..Default::default()
+        }),
+        vec![spl]
+    );
+    let mut sol = listings(ListingFilters {
+        currency: Some(CurrencyFilter::Sol),
+        active: true,
+        ..Default::default()
+    });
+    sol.sort();
+    assert_eq!(sol, vec![cheap]);
+
+    // SPL prices are shown in the currency's decimals, and expired listings are flagged.
+    let output = MarketListings {
+        listings: fetch_listings(&config, &ListingFilters::default()).unwrap(),
+    }
+    .custom_format();
+    assert!(output.contains(&format!("2.5 {}", usdc)));
+    assert!(output.contains("1 SOL"));
+    assert!(output.contains("(expired)"));
+    assert!(output.ends_with("3 listings"));
+}
+
+#[test]
+fn test_market_bids_filters() {
+    let rpc = FixtureRpc::new();
+    let owner = Pubkey::new_unique();
+    let escrow = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+
+    let whitelist_bid = Pubkey::new_unique();
+    rpc.insert(whitelist_bid, bid_account(owner, None));
+
+    let margin_bid = Pubkey::new_unique();
+    rpc.insert(margin_bid, bid_account(owner, Some(escrow)));
+
+    let mint_bid = Pubkey::new_unique();
+    let mut bid = BidState::from_bytes(&bid_account(Pubkey::new_unique(), None).data).unwrap();
+    bid.target = Target::AssetId;
+    bid.target_id = mint;
+    bid.filled_quantity = 1;
+    bid.expiry = 0;
+    rpc.insert(
+        mint_bid,
+        program_account(TENSOR_MARKETPLACE_ID, bid.try_to_vec().unwrap()),
+    );
+
+    let (_, config) = config(rpc);
+
+    let bids = |filters: BidFilters| -> Vec<Pubkey> {
+        let mut bids: Vec<Pubkey> = fetch_bids(&config, &filters)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.address)
+            .collect();
+        bids.sort();
+        bids
+    };
+    let sorted = |mut bids: Vec<Pubkey>| {
+        bids.sort();
+        bids
+    };
+
+    assert_eq!(bids(BidFilters::default()).len(), 3);
+    assert_eq!(
+        bids(BidFilters {
+            owner: Some(owner),
+            ..Default::default()
+        }),
+        sorted(vec![whitelist_bid, margin_bid])
+    );
+    assert_eq!(
+        bids(BidFilters {
+            target: Some(Target::AssetId),
+            ..Default::default()
+        }),
+        vec![mint_bid]
+    );
+    assert_eq!(
+        bids(BidFilters {
+            target_id: Some(mint),
+            ..Default::default()
+        }),
+        vec![mint_bid]
+    );
+    assert_eq!(
+        bids(BidFilters {
+            margin: Some(escrow),
+            ..Default::default()
+        }),
+        vec![margin_bid]
+    );
+    assert_eq!(
+        bids(BidFilters {
+            currency: Some(CurrencyFilter::Sol),
+            active: true,
+            ..Default::default()
+        }),
+        sorted(vec![whitelist_bid, margin_bid])
+    );
+
+    let output = MarketBids {
+        bids: fetch_bids(&config, &BidFilters::default()).unwrap(),
+    }
+    .custom_format();
+    assert!(output.contains("1/2"));
+    assert!(output.contains("(expired)"));
+    assert!(output.contains("Whitelist"));
+    assert!(output.ends_with("3 bids"));
+}
+
+#[test]
+fn test_market_cleanup() {
+    let rpc = FixtureRpc::new();
+    let owner = wallet(&rpc, 1_000_000_000);
+    let cosigner = Pubkey::new_unique();
+
+    let expired_listing = |mint: Pubkey| {
+        let mut listing =
+            ListState::from_bytes(&listing_account(owner.pubkey(), mint, 1, None).data).unwrap();
+        listing.expiry = 0;
+        program_account(TENSOR_MARKETPLACE_ID, listing.try_to_vec().unwrap())
+    };
+    let expired_bid = |owner: Pubkey, cosigner: Option<Pubkey>, lamports: u64| {
+        let mut bid = BidState::from_bytes(&bid_account(owner, None).data).unwrap();
+        bid.expiry = 0;
+        if let Some(cosigner) = cosigner {
+            bid.cosigner = tensor_marketplace::NullableAddress::new(cosigner);
+        }
+        Account {
+            lamports,
+            ..program_account(TENSOR_MARKETPLACE_ID, bid.try_to_vec().unwrap())
+        }
+    };
+
+    let core = nft(&rpc, &owner.pubkey(), AssetStandard::Core);
+    let core_listing = ListState::find_pda(&core).0;
+    rpc.insert(core_listing, expired_listing(core));
+
+    // No account and no DAS entry, so the listing can't be closed.
+    let missing = Pubkey::new_unique();
+    let missing_listing = ListState::find_pda(&missing).0;
+    rpc.insert(missing_listing, expired_listing(missing));
+
+    let active = Pubkey::new_unique();
+    rpc.insert(
+        ListState::find_pda(&active).0,
+        listing_account(owner.pubkey(), active, 1, None),
+    );
+
+    let rent = solana_sdk::rent::Rent::default()
+        .minimum_balance(bid_account(owner.pubkey(), None).data.len());
+
+    let bid = Pubkey::new_unique();
+    rpc.insert(bid, expired_bid(owner.pubkey(), None, rent + 500_000_000));
+    rpc.insert(Pubkey::new_unique(), bid_account(owner.pubkey(), None));
+
+    let cosigned_bid = Pubkey::new_unique();
+    rpc.insert(
+        cosigned_bid,
+        expired_bid(Pubkey::new_unique(), Some(cosigner), rent),
+    );
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), owner).unwrap();
+
+    let plan = find_expired_orders(&config, None).unwrap();
+    let mut found: Vec<Pubkey> = plan.orders.iter().map(|order| order.address).collect();
+    found.sort();
+    let mut expected = vec![core_listing, missing_listing, bid];
+    expected.sort();
+    assert_eq!(found, expected);
+    assert_eq!(plan.escrowed(), 500_000_000);
+    assert!(plan.custom_format().contains("3 expired orders"));
+
+    // A cosigner sweeps the orders it cosigned, whoever owns them.
+    let cosigned = find_expired_orders(&config, Some(cosigner)).unwrap();
+    assert_eq!(cosigned.orders.len(), 1);
+    assert_eq!(cosigned.orders[0].address, cosigned_bid);
+    assert_eq!(cosigned.rent(), rent);
+
+    // The orders that can be closed go out together, the others are reported for a rerun.
+    let cleanup = close_expired_orders(&config, plan).unwrap();
+    assert_eq!(cleanup.closed.len(), 2);
+    assert!(cleanup.closed.contains(&core_listing));
+    assert!(cleanup.closed.contains(&bid));
+    assert_eq!(cleanup.failed.len(), 1);
+    assert_eq!(cleanup.failed[0].0, missing_listing);
+    assert_eq!(cleanup.signatures.len(), 1);
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].message.instructions().len(), 2);
+
+    let output = cleanup.custom_format();
+    assert!(output.contains("Closed 2 of 3 expired orders"));
+    assert!(output.contains("0.5 SOL escrowed"));
+    assert!(output.contains(&format!(
+        "run cleanup again to retry:\n  {}",
+        missing_listing
+    )));
+}
+
+fn order_state(maker: Pubkey, whitelist: Pubkey, order_type: OrderType) -> OrderState {
+    OrderState {
+        discriminator: OrderState::discriminator(),
+        version: 1,
+        bump: [255],
+        order_id: Pubkey::new_unique().to_bytes(),
+        order_type,
+        nonce: 0,
+        maker,
+        price: 1_000_000_000,
+        currency: None,
+        apr_bps: 1_000,
+        duration_sec: 365 * 86_400,
+        whitelist,
+        maker_broker: None,
+        margin: None,
+        expiry: i64::MAX,
+        created_at: 0,
+        updated_at: 0,
+        nfts_held: 0,
+        vault_balance: 0,
+        locked_at: 0,
+        locked_until: 0,
+        taker: None,
+        collateral_returned: false,
+        last_exercised_at: 0,
+        exercise_count: 0,
+        accumulated_profit: 0,
+        taker_withdrawn_nfts: 0,
+        taker_withdrawn_funds: 0,
+        early_close: false,
+        reserved0: [0; 31],
+        reserved1: [0; 32],
+        reserved2: [0; 64],
+    }
+}
+
+fn order_account(order: &OrderState) -> Account {
+    program_account(TENSOR_PRICE_LOCK_ID, order.try_to_vec().unwrap())
+}
+
+#[test]
+fn test_price_lock_orders() {
+    let rpc = FixtureRpc::new();
+    let maker = Pubkey::new_unique();
+    let taker = Pubkey::new_unique();
+    let whitelist = Pubkey::new_unique();
+
+    let open = Pubkey::new_unique();
+    rpc.insert(
+        open,
+        order_account(&order_state(maker, whitelist, OrderType::NFT)),
+    );
+
+    let locked = Pubkey::new_unique();
+    let mut locked_order = order_state(maker, Pubkey::new_unique(), OrderType::Token);
+    locked_order.taker = Some(taker);
+    locked_order.locked_until = i64::MAX;
+    locked_order.created_at = 1;
+    rpc.insert(locked, order_account(&locked_order));
+
+    let other = Pubkey::new_unique();
+    rpc.insert(
+        other,
+        order_account(&order_state(
+            Pubkey::new_unique(),
+            whitelist,
+            OrderType::NFT,
+        )),
+    );
+
+    let core = nft(&rpc, &Pubkey::new_unique(), AssetStandard::Core);
+    let missing = Pubkey::new_unique();
+    for asset in [core, missing] {
+        let receipt = OrderNftReceipt {
+            discriminator: OrderNftReceipt::discriminator(),
+            bump: 255,
+            asset,
+            order_state: open,
+        };
+        rpc.insert(
+            Pubkey::new_unique(),
+            program_account(TENSOR_PRICE_LOCK_ID, receipt.try_to_vec().unwrap()),
+        );
+    }
+
+    let (_, config) = config(rpc);
+
+    let addresses = |filters: OrderFilters| {
+        let mut addresses: Vec<Pubkey> = fetch_orders(&config, &filters)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.address)
+            .collect();
+        addresses.sort();
+        addresses
+    };
+    let sorted = |mut addresses: Vec<Pubkey>| {
+        addresses.sort();
+        addresses
+    };
+
+    assert_eq!(addresses(OrderFilters::default()).len(), 3);
+    assert_eq!(
+        addresses(OrderFilters {
+            maker: Some(maker),
+            ..Default::default()
+        }),
+        sorted(vec![open, locked])
+    );
+    assert_eq!(
+        addresses(OrderFilters {
+            taker: Some(taker),
+            ..Default::default()
+        }),
+        vec![locked]
+    );
+    assert_eq!(
+        addresses(OrderFilters {
+            whitelist: Some(whitelist),
+            ..Default::default()
+        }),
+        sorted(vec![open, other])
+    );
+
+    let orders = PriceLockOrders {
+        orders: fetch_orders(&config, &OrderFilters::default()).unwrap(),
+    };
+    let output = orders.custom_format();
+    assert!(output.contains("Locked"));
+    assert!(output.contains("3 orders"));
+
+    let details = fetch_order_details(&config, &open).unwrap();
+    assert_eq!(details.receipts.len(), 2);
+    let standard = |asset: Pubkey| {
+        details
+            .receipts
+            .iter()
+            .find(|receipt| receipt.receipt.asset == asset)
+            .unwrap()
+            .standard
+            .clone()
+    };
+    assert_eq!(standard(core), Some("Core".to_string()));
+    assert_eq!(standard(missing), None);
+
+    // 10% APR over a year on 1 SOL, without the fees the SDK has no schedule for.
+    assert_eq!(details.settlement.premium, 100_000_000);
+    assert_eq!(
+        details.settlement,
+        simulate_settlement(1_000_000_000, 1_000, 365 * 86_400).unwrap()
+    );
+    let output = details.custom_format();
+    assert!(output.contains("Settlement:"));
+    assert!(!output.contains("fee:"));
+}
+
+#[test]
+fn test_create_edit_close_price_lock_order() {
+    let rpc = FixtureRpc::new();
+    let maker = wallet(&rpc, 1_000_000_000);
+    let maker_pubkey = maker.pubkey();
+
+    let (whitelist, whitelist_account) = whitelist_v2([7; 32], vec![]);
+    rpc.insert(whitelist, whitelist_account);
+
+    let order = Pubkey::new_unique();
+    let mut state = order_state(maker_pubkey, whitelist, OrderType::NFT);
+    rpc.insert(order, order_account(&state));
+
+    let locked = Pubkey::new_unique();
+    state.taker = Some(Pubkey::new_unique());
+    state.locked_until = i64::MAX;
+    state.nfts_held = 2;
+    rpc.insert(locked, order_account(&state));
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), maker).unwrap();
+
+    let error = create_order_with_config(
+        &config,
+        OrderType::Token,
+        &Pubkey::new_unique(),
+        &OrderTerms::default(),
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("it needs a --price"));
+    assert!(error.contains("doesn't exist"));
+
+    let terms = OrderTerms {
+        price: Some("2".to_string()),
+        apr_bps: Some(500),
+        duration: Some("7d".to_string()),
+        ..Default::default()
+    };
+    let created = create_order_with_config(&config, OrderType::Token, &whitelist, &terms).unwrap();
+    assert_eq!(created.price, 2_000_000_000);
+    assert_eq!(created.duration_sec, 7 * 86_400);
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    let (program, _, data) = instruction_data(&sent[0]);
+    assert_eq!(program, TENSOR_PRICE_LOCK_ID);
+    let args = UpsertOrderInstructionArgs::try_from_slice(&data[8..]).unwrap();
+    assert_eq!(
+        find_order_state_pda(&maker_pubkey, &args.order_id),
+        created.order_state
+    );
+
+    // Terms that aren't given keep the order's.
+    let edited = edit_order_with_config(
+        &config,
+        &order,
+        &OrderTerms {
+            price: Some("1.5".to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert!(edited.edited);
+    assert_eq!(edited.order_state, order);
+    assert_eq!(edited.price, 1_500_000_000);
+    assert_eq!(edited.apr_bps, 1_000);
+    assert_eq!(edited.duration_sec, 365 * 86_400);
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 2);
+    let (_, _, data) = instruction_data(&sent[1]);
+    let args = UpsertOrderInstructionArgs::try_from_slice(&data[8..]).unwrap();
+    assert_eq!(args.order_type, OrderType::NFT);
+    assert_eq!(args.price, 1_500_000_000);
+
+    let error = edit_order_with_config(&config, &locked, &OrderTerms::default())
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("it is locked by"));
+
+    let error = close_order_with_config(&config, &locked)
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("it is locked by"));
+    assert!(error.contains("it holds 2 NFTs"));
+
+    rpc.insert(
+        find_order_vault_pda(&order),
+        Account {
+            lamports: 300_000_000,
+            ..program_account(system_program::id(), vec![])
+        },
+    );
+    let closed = close_order_with_config(&config, &order).unwrap();
+    assert_eq!(closed.vault_balance, 300_000_000);
+    assert_eq!(rpc.sent_transactions().len(), 3);
+    assert!(closed.custom_format().contains("Order closed"));
+}
+
+#[test]
+fn test_whitelist_migrate() {
+    let rpc = FixtureRpc::new();
+    let signer = wallet(&rpc, 1_000_000_000);
+    let namespace = signer.pubkey();
+
+    let v2_under_signer = |uuid: [u8; 32], update_authority: Pubkey, condition: Condition| {
+        let whitelist = WhitelistV2 {
+            discriminator: WhitelistV2::discriminator(),
+            version: 1,
+            bump: 255,
+            uuid,
+            state: State::Unfrozen,
+            update_authority,
+            namespace,
+            freeze_authority: Pubkey::default(),
+            conditions: vec![condition],
+        };
+        rpc.insert(
+            WhitelistV2::find_pda(&namespace, uuid).0,
+            program_account(TENSOR_WHITELIST_ID, whitelist.try_to_vec().unwrap()),
+        );
+    };
+
+    // No v2 yet.
+    let voc = Pubkey::new_unique();
+    let (missing, account) = whitelist_v1([20; 32], Some(voc), None);
+    rpc.insert(missing, account);
+
+    // A v2 with the wrong condition the signer can update.
+    let fvc = Pubkey::new_unique();
+    let (mismatched, account) = whitelist_v1([21; 32], None, Some(fvc));
+    rpc.insert(mismatched, account);
+    v2_under_signer(
+        [21; 32],
+        namespace,
+        Condition {
+            mode: Mode::VOC,
+            value: fvc,
+        },
+    );
+
+    // Nothing to migrate.
+    let (empty, account) = whitelist_v1([22; 32], None, None);
+    rpc.insert(empty, account);
+
+    // A mismatched v2 someone else updates.
+    let (foreign, account) = whitelist_v1([23; 32], Some(voc), None);
+    rpc.insert(foreign, account);
+    v2_under_signer(
+        [23; 32],
+        Pubkey::new_unique(),
+        Condition {
+            mode: Mode::FVC,
+            value: voc,
+        },
+    );
+
+    // Already migrated, say by an earlier run.
+    let (migrated, account) = whitelist_v1([24; 32], Some(voc), None);
+    rpc.insert(migrated, account);
+    v2_under_signer(
+        [24; 32],
+        namespace,
+        Condition {
+            mode: Mode::VOC,
+            value: voc,
+        },
+    );
+
+    // The compare files, with the missing v1 in both.
+    let dir = std::env::temp_dir();
+    let missing_path = dir.join(format!("eigen-missing-{}.json", Pubkey::new_unique()));
+    let mismatches_path = dir.join(format!("eigen-mismatches-{}.json", Pubkey::new_unique()));
+    let missing_pairs: Vec<MissingWhitelistPair> = [missing, empty]
+        .into_iter()
+        .map(|v1_pubkey| MissingWhitelistPair {
+            v1_pubkey,
+            v2_pubkey: Pubkey::new_unique(),
+        })
+        .collect();
+    let mismatches: Vec<ComparisonResult> = [mismatched, foreign, migrated, missing]
+        .into_iter()
+        .map(|whitelist_v1| ComparisonResult {
+            whitelist_v1,
+            whitelist_v2: Pubkey::new_unique(),
+            mismatches: vec![],
+        })
+        .collect();
+    std::fs::write(
+        &missing_path,
+        serde_json::to_string(&missing_pairs).unwrap(),
+    )
+    .unwrap();
+    std::fs::write(
+        &mismatches_path,
+        serde_json::to_string(&mismatches).unwrap(),
+    )
+    .unwrap();
+
+    let v1s =
+        read_comparison_files(Some(missing_path.clone()), Some(mismatches_path.clone())).unwrap();
+    std::fs::remove_file(missing_path).unwrap();
+    std::fs::remove_file(mismatches_path).unwrap();
+    assert_eq!(v1s.len(), 5);
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), signer.insecure_clone()).unwrap();
+
+    let pairs = fetch_whitelist_pairs(&config, &v1s, &namespace).unwrap();
+    let plan = plan_migration(&pairs, &namespace);
+
+    assert_eq!(plan.unchanged, 1);
+    assert_eq!(plan.actions.len(), 2);
+    assert!(matches!(
+        &plan.actions[0],
+        MigrationAction::Create { v1, condition, .. }
+            if *v1 == missing && condition.mode == Mode::VOC && condition.value == voc
+    ));
+    assert!(matches!(
+        &plan.actions[1],
+        MigrationAction::Update { v1, mismatches, condition, .. }
+            if *v1 == mismatched && mismatches.contains(&Mismatch::Fvc) && condition.mode == Mode::FVC && condition.value == fvc
+    ));
+    let skipped: Vec<Pubkey> = plan.skipped.iter().map(|(v1, _)| *v1).collect();
+    assert_eq!(skipped, vec![empty, foreign]);
+    assert!(plan.custom_format().contains("Skipped 2 whitelists"));
+
+    let migration = apply_migration(&config, &signer, plan).unwrap();
+    assert_eq!(migration.migrated.len(), 2);
+    assert!(migration.failed.is_empty());
+
+    let sent = rpc.sent_transactions();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].message.instructions().len(), 2);
+    let (program, _, _) = instruction_data(&sent[0]);
+    assert_eq!(program, TENSOR_WHITELIST_ID);
+    assert!(migration
+        .custom_format()
+        .contains("Migrated 2 of 2 whitelists"));
+}
+
+#[test]
+fn test_whitelist_merkle_build_and_proof() {
+    let dir = std::env::temp_dir();
+    let id = Pubkey::new_unique();
+    let mints_path = dir.join(format!("eigen-mints-{}.json", id));
+    let proofs_path = dir.join(format!("eigen-proofs-{}.json", id));
+
+    let mints: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+    let addresses: Vec<String> = mints.iter().map(|mint| mint.to_string()).collect();
+    std::fs::write(&mints_path, serde_json::to_string(&addresses).unwrap()).unwrap();
+
+    let built = handle_merkle_build(MerkleBuildParams {
+        mints_path: mints_path.clone(),
+        out: proofs_path.clone(),
+        sort_leaves: false,
+    })
+    .unwrap();
+    assert_eq!(built.proofs.proofs.len(), 5);
+    assert!(built
+        .custom_format()
+        .contains(&format!("Merkle root: {}", built.proofs.root)));
+
+    for mint in &mints {
+        let proof = handle_merkle_proof(MerkleProofParams {
+            proofs_path: proofs_path.clone(),
+            mint: *mint,
+        })
+        .unwrap();
+        assert_eq!(proof.root, built.proofs.root);
+        assert!(verify_merkle_proof(
+            mint,
+            &proof.proof,
+            &built.proofs.root.to_bytes()
+        ));
+    }
+
+    assert!(handle_merkle_proof(MerkleProofParams {
+        proofs_path: proofs_path.clone(),
+        mint: Pubkey::new_unique(),
+    })
+    .is_err());
+
+    std::fs::remove_file(mints_path).unwrap();
+    std::fs::remove_file(proofs_path).unwrap();
+}
+
+#[test]
+fn test_init_and_show_mint_proofs() {
+    let rpc = FixtureRpc::new();
+    let payer = wallet(&rpc, 1_000_000_000);
+
+    let mints: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+    let proofs = build_merkle_proofs(&mints, false).unwrap();
+    let (whitelist, account) = whitelist_v2(
+        [30; 32],
+        vec![Condition {
+            mode: Mode::MerkleTree,
+            value: proofs.root,
+        }],
+    );
+    rpc.insert(whitelist, account);
+
+    let stored = |mint: &Pubkey, proof: Vec<[u8; 32]>| {
+        let mut padded = [[0; 32]; 28];
+        padded[..proof.len()].copy_from_slice(&proof);
+        let mint_proof = MintProofV2 {
+            discriminator: MintProofV2::discriminator(),
+            proof_len: proof.len() as u8,
+            proof: padded,
+            creation_slot: 42,
+            payer: payer.pubkey(),
+        };
+        rpc.insert(
+            MintProofV2::find_pda(mint, &whitelist).0,
+            program_account(TENSOR_WHITELIST_ID, mint_proof.try_to_vec().unwrap()),
+        );
+    };
+
+    // Already created, say by an earlier run.
+    stored(&mints[0], proofs.proof(&mints[0]).unwrap());
+    // Created with the proof of an older tree.
+    stored(&mints[1], vec![[9; 32]]);
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), payer.insecure_clone()).unwrap();
+
+    let plan = plan_mint_proofs(&config, &whitelist, &proofs, &mints).unwrap();
+    assert_eq!(plan.unchanged, 1);
+    assert_eq!(plan.actions.len(), 3);
+    assert!(plan.actions.iter().all(|action| action.mint != mints[0]));
+    assert!(plan
+        .actions
+        .iter()
+        .all(|action| action.exists == (action.mint == mints[1])));
+    assert!(plan
+        .custom_format()
+        .contains("3 mint proofs to send, 1 up to date"));
+
+    let sent = init_mint_proofs(&config, plan).unwrap();
+    assert_eq!(sent.initialized.len(), 3);
+    assert!(sent.failed.is_empty());
+
+    let transactions = rpc.sent_transactions();
+    assert_eq!(
+        transactions
+            .iter()
+            .map(|tx| tx.message.instructions().len())
+            .sum::<usize>(),
+        3
+    );
+    let (program, accounts, _) = instruction_data(&transactions[0]);
+    assert_eq!(program, TENSOR_WHITELIST_ID);
+    assert_eq!(accounts[2], whitelist);
+
+    // Proofs of another tree are refused.
+    let other = build_merkle_proofs(&[Pubkey::new_unique()], false).unwrap();
+    assert!(plan_mint_proofs(&config, &whitelist, &other, &mints).is_err());
+
+    let details = fetch_mint_proof(&config, &whitelist, &mints[0]).unwrap();
+    assert!(details.valid);
+    assert_eq!(details.root, Some(proofs.root));
+    assert_eq!(details.proof, proofs.proof(&mints[0]).unwrap());
+    assert_eq!(details.creation_slot, 42);
+    assert!(details.custom_format().contains("Status: Valid"));
+
+    let outdated = fetch_mint_proof(&config, &whitelist, &mints[1]).unwrap();
+    assert!(!outdated.valid);
+    assert!(outdated.custom_format().contains("Status: Invalid"));
+
+    assert!(fetch_mint_proof(&config, &whitelist, &mints[2]).is_err());
+}
+
+#[test]
+fn test_mint_proofs_recomputed_for_another_builders_root() {
+    let rpc = FixtureRpc::new();
+    let payer = wallet(&rpc, 1_000_000_000);
+
+    // Root of these mints in this order from a separate implementation of the tree, not from
+    // `build_merkle_proofs`. Their leaves sort into the reverse order.
+    let root = Pubkey::from_str("Dc5FKvaLCktjKFTrFcXkBNoJjZHKLenEjeiHP3cNQbpr").unwrap();
+    let addresses = [
+        "So11111111111111111111111111111111111111112",
+        "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+        "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+    ];
+    let mints: Vec<Pubkey> = addresses
+        .iter()
+        .map(|address| Pubkey::from_str(address).unwrap())
+        .collect();
+
+    let (whitelist, account) = whitelist_v2(
+        [31; 32],
+        vec![Condition {
+            mode: Mode::MerkleTree,
+            value: root,
+        }],
+    );
+    rpc.insert(whitelist, account);
+
+    let rpc = Arc::new(rpc);
+    let config = CliConfig::with_client(rpc.clone(), payer.insecure_clone()).unwrap();
+
+    // What `mint-proof init --mints` recomputes from the mints file.
+    let mints_path = std::env::temp_dir().join(format!("eigen-mints-{}.json", whitelist));
+    std::fs::write(&mints_path, serde_json::to_string(&addresses).unwrap()).unwrap();
+    let read = read_mints(&mints_path).unwrap();
+    std::fs::remove_file(mints_path).unwrap();
+
+    let proofs = build_merkle_proofs(&read, false).unwrap();
+    let plan = plan_mint_proofs(&config, &whitelist, &proofs, &mints).unwrap();
+    assert_eq!(plan.root, root);
+    assert_eq!(plan.actions.len(), 3);
+    assert!(plan.actions.iter().all(|action| verify_merkle_proof(
+        &action.mint,
+        &action.proof,
+        &root.to_bytes()
+    )));
+
+    // Sorting the leaves builds another tree.
+    let sorted = build_merkle_proofs(&read, true).unwrap();
+    assert!(plan_mint_proofs(&config, &whitelist, &sorted, &mints).is_err());
+}
+
+#[test]
+fn test_sign_only_nonce_transaction_round_trip() {
+    let rpc = FixtureRpc::new();
+    let payer = wallet(&rpc, 1_000_000_000);
+
+    let nonce = Pubkey::new_unique();
+    let durable_nonce = DurableNonce::from_blockhash(&Hash::new_unique());
+    let nonce_data = NonceVersions::new(NonceState::Initialized(NonceData::new(
+        payer.pubkey(),
+        durable_nonce,
+        5_000,
+    )));
+    rpc.insert(
+        nonce,
+        Account {
+            lamports: 1_500_000,
+            ..program_account(
+                system_program::id(),
+                bincode::serialize(&nonce_data).unwrap(),
+            )
+        },
+    );
+
+    let rpc = Arc::new(rpc);
+    let path = std::env::temp_dir().join(format!("eigen-signed-{}.json", Pubkey::new_unique()));
+
+    // Signing only without a nonce would expire, so it's refused.
+    assert!(CliConfig::with_client(rpc.clone(), payer.insecure_clone())
+        .unwrap()
+        .with_sign_only(Some(path.clone()))
+        .is_err());
+
+    let config = CliConfig::with_client(rpc.clone(), payer.insecure_clone())
+        .unwrap()
+        .with_nonce(Some(nonce), None)
+        .unwrap()
+        .with_sign_only(Some(path.clone()))
+        .unwrap();
+
+    let recipient = Pubkey::new_unique();
+    let transfer = system_instruction::transfer(&payer.pubkey(), &recipient, 1_000);
+    let signatures = send_instructions(&config, vec![transfer.clone()], &[], &[]).unwrap();
+
+    // Nothing is sent, and the nonce only covers the one transaction.
+    assert!(rpc.sent_transactions().is_empty());
+    assert!(send_instructions(&config, vec![transfer], &[], &[]).is_err());
+
+    let signed = read_signed_transactions(&path).unwrap();
+    assert_eq!(signed.len(), 1);
+    assert_eq!(signed[0].signatures[0], signatures[0]);
+    assert_eq!(
+        *signed[0].message.recent_blockhash(),
+        durable_nonce.as_hash().to_owned()
+    );
+    assert!(missing_signers(&signed[0]).is_empty());
+    let (program, accounts, _) = instruction_data(&signed[0]);
+    assert_eq!(program, system_program::id());
+    assert_eq!(accounts[0], nonce);
+
+    // Submitted later with a config that sends.
+    let config = CliConfig::with_client(rpc.clone(), Keypair::new()).unwrap();
+    let submitted = submit_signed_transactions(&config, &path).unwrap();
+    assert_eq!(submitted.signatures, signatures);
+    assert_eq!(rpc.sent_transactions(), signed);
+
+    // A transaction missing a signature isn't submitted.
+    let mut unsigned = signed[0].clone();
+    unsigned.signatures[0] = Signature::default();
+    write_signed_transactions(&path, &[unsigned]).unwrap();
+    assert!(submit_signed_transactions(&config, &path).is_err());
+    assert_eq!(rpc.sent_transactions().len(), 1);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_example_configs_parse() {
+    let examples = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let read = |name: &str| std::fs::File::open(examples.join(name)).unwrap();
+
+    let create: CreatePoolInstructionArgs =
+        serde_json::from_reader(read("pool_config.json")).unwrap();
+    assert_eq!(create.config.pool_type, PoolType::Trade);
+
+    let edit: EditPoolArgs = serde_json::from_reader(read("edit_pool_config.json")).unwrap();
+    assert_eq!(
+        edit.new_config.unwrap().curve_type,
+        Some(CurveType::Exponential)
+    );
+
+    let whitelist: serde_json::Value =
+        serde_json::from_reader(read("whitelist_config.json")).unwrap();
+    let conditions: Vec<Condition> =
+        serde_json::from_value(whitelist["conditions"].clone()).unwrap();
+    assert_eq!(conditions[0].mode, Mode::VOC);
+}